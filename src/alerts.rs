@@ -0,0 +1,285 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use humantime::{format_duration, parse_duration};
+
+use crate::watchlist::Status;
+
+// How often an alert that's still firing is logged again, until it's
+// acknowledged or silenced
+pub const REPEAT: Duration = Duration::from_secs(5 * 60);
+// Alerts the report keeps, past which the oldest go
+const HISTORY: usize = 1000;
+// The health index's rule, as `ack` and `silence` name it
+pub const HEALTH: &str = "health";
+
+// What to log about an alert after a check
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notice {
+    // it just started firing, got worse, or a silence over it ran out
+    Fired,
+    // still firing, REPEAT after it was last logged
+    Repeat,
+    Resolved,
+    // it would have fired, but a maintenance window holds it
+    Suppressed,
+}
+
+// Why a firing alert isn't being logged again
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hushed {
+    Acked,
+    Silenced,
+    Maintenance,
+}
+
+struct Firing {
+    status: Status,
+    labels: Vec<(&'static str, String)>,
+    // when it was last logged; None while silenced or held from the start
+    logged: Option<Duration>,
+    since: Duration,
+    acked: bool,
+}
+
+// What a silence covers: one rule (a watched metric or `health`), or every
+// alert carrying a label: `scope=cloud`, `severity=warn`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    Rule(String),
+    Label(String, String),
+}
+
+#[derive(Clone, Debug)]
+pub struct Silence {
+    pub id: u32,
+    pub target: Target,
+    pub until: Duration,
+}
+
+impl Target {
+    fn covers(&self, rule: &str, labels: &[(&str, String)]) -> bool {
+        match self {
+            Target::Rule(r) => r == rule,
+            Target::Label(key, value) => labels.iter().any(|(k, v)| k == key && v == value),
+        }
+    }
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::Rule(rule) => write!(f, "{rule}"),
+            Target::Label(key, value) => write!(f, "{key}={value}"),
+        }
+    }
+}
+
+// An alert that fired, as it was logged, kept for the report after the log
+// buffer has been cleared or moved on
+pub struct Record {
+    pub rule: String,
+    pub at: Duration,
+    pub message: String,
+    // the maintenance window that kept it out of the logs
+    pub held: Option<String>,
+    // when it stopped firing, or stopped being watched
+    pub ended: Option<Duration>,
+}
+
+// Firing alerts by rule, the silences over them, and every alert that fired
+// this session. Times are app uptime.
+#[derive(Default)]
+pub struct Alerts {
+    firing: BTreeMap<String, Firing>,
+    pub history: Vec<Record>,
+    pub silences: Vec<Silence>,
+    // the maintenance window in force, which holds every alert
+    pub maintenance: Option<String>,
+    next_id: u32,
+}
+
+impl Alerts {
+    // Where `rule` stands now, and what to log about it, if anything.
+    // Acknowledging lasts until the alert resolves or gets worse.
+    pub fn check(
+        &mut self,
+        rule: &str,
+        labels: Vec<(&'static str, String)>,
+        status: Status,
+        now: Duration,
+    ) -> Option<Notice> {
+        let silenced = self.silenced(rule, &labels);
+        let held = !silenced && self.maintenance.is_some();
+        if status < Status::Warn {
+            let was = self.firing.remove(rule)?;
+            self.end(rule, now);
+            let quiet = was.logged.is_none() || silenced;
+            return (!quiet).then_some(Notice::Resolved);
+        }
+        let Some(firing) = self.firing.get_mut(rule) else {
+            self.firing.insert(
+                rule.to_string(),
+                Firing {
+                    status,
+                    labels,
+                    logged: (!silenced && !held).then_some(now),
+                    since: now,
+                    acked: false,
+                },
+            );
+            return match (silenced, held) {
+                (true, _) => None,
+                (_, true) => Some(Notice::Suppressed),
+                _ => Some(Notice::Fired),
+            };
+        };
+        let worse = status > firing.status;
+        firing.status = status;
+        firing.labels = labels;
+        if worse {
+            firing.acked = false;
+        }
+        if silenced {
+            return None;
+        }
+        if held {
+            return worse.then_some(Notice::Suppressed);
+        }
+        let notice = match firing.logged {
+            None => Notice::Fired,
+            Some(_) if worse => Notice::Fired,
+            Some(_) if firing.acked => return None,
+            Some(at) if now.saturating_sub(at) >= REPEAT => Notice::Repeat,
+            Some(_) => return None,
+        };
+        firing.logged = Some(now);
+        Some(notice)
+    }
+
+    // Drops an alert whose rule is gone, without a resolved line
+    pub fn forget(&mut self, rule: &str, now: Duration) {
+        self.firing.remove(rule);
+        self.end(rule, now);
+    }
+
+    // A fired alert, logged or held by the maintenance window in force
+    pub fn record(&mut self, rule: &str, message: String, at: Duration) {
+        if self.history.len() == HISTORY {
+            self.history.remove(0);
+        }
+        self.history.push(Record {
+            rule: rule.to_string(),
+            at,
+            message,
+            held: self.maintenance.clone(),
+            ended: None,
+        });
+    }
+
+    fn end(&mut self, rule: &str, now: Duration) {
+        for record in self.history.iter_mut().filter(|r| r.rule == rule) {
+            record.ended.get_or_insert(now);
+        }
+    }
+
+    // How long `rule` has been firing
+    pub fn firing_for(&self, rule: &str, now: Duration) -> Option<Duration> {
+        self.firing.get(rule).map(|f| now.saturating_sub(f.since))
+    }
+
+    // Every firing rule, how bad, for how long, and whether it's hushed
+    pub fn firing(&self, now: Duration) -> Vec<(&str, Status, Duration, Option<Hushed>)> {
+        self.firing
+            .iter()
+            .map(|(rule, f)| {
+                let since = now.saturating_sub(f.since);
+                (rule.as_str(), f.status, since, self.hushed(rule))
+            })
+            .collect()
+    }
+
+    // Stops `rule`'s repeats; false if it isn't firing
+    pub fn ack(&mut self, rule: &str) -> bool {
+        self.firing.get_mut(rule).map(|f| f.acked = true).is_some()
+    }
+
+    // Why a firing `rule` isn't logged again, silences first
+    pub fn hushed(&self, rule: &str) -> Option<Hushed> {
+        let firing = self.firing.get(rule)?;
+        if self.silenced(rule, &firing.labels) {
+            Some(Hushed::Silenced)
+        } else if self.maintenance.is_some() {
+            Some(Hushed::Maintenance)
+        } else if firing.acked {
+            Some(Hushed::Acked)
+        } else {
+            None
+        }
+    }
+
+    fn silenced(&self, rule: &str, labels: &[(&str, String)]) -> bool {
+        self.silences.iter().any(|s| s.target.covers(rule, labels))
+    }
+
+    pub fn silence(&mut self, target: Target, until: Duration) -> u32 {
+        self.next_id += 1;
+        self.silences.push(Silence {
+            id: self.next_id,
+            target,
+            until,
+        });
+        self.next_id
+    }
+
+    // Cancels silence `id`, or every one with `None`; how many went
+    pub fn unsilence(&mut self, id: Option<u32>) -> usize {
+        let before = self.silences.len();
+        self.silences.retain(|s| id.is_some_and(|id| s.id != id));
+        before - self.silences.len()
+    }
+
+    // Silences that ran out by `now`, removed
+    pub fn expire(&mut self, now: Duration) -> Vec<Silence> {
+        let (gone, kept) = std::mem::take(&mut self.silences)
+            .into_iter()
+            .partition(|s| s.until <= now);
+        self.silences = kept;
+        gone
+    }
+}
+
+// The labels a silence can match: the rule's scope (a mode, `system`, or a
+// host, as its `SCOPE:` prefix names it) and how bad it is
+pub fn labels(rule: &str, status: Status) -> Vec<(&'static str, String)> {
+    let mut labels = Vec::new();
+    if let Some((scope, _)) = rule.split_once(':') {
+        labels.push(("scope", scope.to_string()));
+    }
+    let severity = match status {
+        Status::Crit => "crit",
+        _ => "warn",
+    };
+    labels.push(("severity", severity.to_string()));
+    labels
+}
+
+// `silence TARGET DURATION`: the duration is the last word, in one piece:
+// `silence cloud:latency p95 30m`, `silence severity=warn 2h`
+pub fn parse_silence(args: &str) -> Result<(String, Duration), String> {
+    let usage = "usage: silence RULE|LABEL=VALUE DURATION, e.g. silence scope=cloud 30m";
+    let (target, duration) = args.trim().rsplit_once(' ').ok_or(usage)?;
+    let duration = parse_duration(duration)
+        .map_err(|_| format!("bad duration `{duration}`; e.g. 90s, 30m, 2h"))?;
+    if duration.is_zero() {
+        return Err("a silence needs a duration longer than zero".to_string());
+    }
+    match target.trim() {
+        "" => Err(usage.to_string()),
+        target => Ok((target.to_string(), duration)),
+    }
+}
+
+// A duration to the second, for the log
+pub fn span(d: Duration) -> String {
+    format_duration(Duration::from_secs(d.as_secs())).to_string()
+}
@@ -0,0 +1,2655 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    time::Duration,
+};
+
+use clap::ValueEnum;
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind};
+use humantime::format_duration;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use ratatui::{layout::Rect, prelude::*};
+
+use crate::{
+    alerts::{self, Alerts, Hushed, Notice, HEALTH},
+    applog,
+    availability::Availability,
+    bench::Bench,
+    caps::Caps,
+    capture::{self, Capture},
+    chrome::{PanelsSpec, Styles},
+    cli::Cli,
+    compare::{self, Comparison},
+    config::{Config, ConfigError},
+    control::{self, Request},
+    datasource::{DataSource, Feed, METRICS},
+    derived::Formula,
+    detail::{self, Detail, Panel, Selection},
+    dirs::{self, AppDirs},
+    docker::{self, DockerStats},
+    explain::Catalog,
+    focus::Focus,
+    follow,
+    glyphs::GlyphSet,
+    gpu::{self, GpuProcesses},
+    health::{self, HealthSpec, Part},
+    history::History,
+    hosts::{Fleet, HostView},
+    idle::{Idle, IDLE_TICK},
+    ingest::IngestQueue,
+    input::LineInput,
+    integrity::{self, Integrity},
+    journal,
+    kafka::{Kafka, KafkaSpec},
+    kube::Pods,
+    layout::{fit, sanitize, sanitize_lines, MIN_H, MIN_W},
+    listen,
+    logs::{Dedup, Level, LogBuffer, LogEntry, SourceId},
+    maintenance::{Change, Maintenance},
+    motion::Steady,
+    mqtt::{MqttSpec, Telemetry},
+    notify::{self, Alert, Notifier},
+    otlp::{self, Otlp},
+    payload::{self, Payload, Popup},
+    pins::{self, Pin, Scope},
+    profile::Profile,
+    programs::Programs,
+    prometheus::{Prometheus, PrometheusSpec},
+    push::Push,
+    reconnect::Links,
+    redact::{self, RedactSpec, Rule},
+    report::{self, Incident, MetricSummary, Report, Uptime},
+    ros2::{Ros2, Ros2Spec},
+    sampling,
+    scaling::Scaling,
+    scenario::{Action, Scenario},
+    serial::Port,
+    sim::{self, Synthetic},
+    socket::{self, ControlSocket},
+    statsd::{self, Statsd},
+    stdin, syslog,
+    sysmetrics::{self, Machine},
+    timebase::Timebase,
+    timeline,
+    tokens::{self, TokenStreams},
+    ui::ai_rows,
+    views::Views,
+    watchdog::{Degrade, FrameWatchdog},
+    watchlist::{Order, Status, Watchlist},
+    websocket::{WebSocket, WebSocketSpec},
+    wizard::{Choices, Outcome, Wizard},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Mode {
+    #[value(name = "ai")]
+    AiObservability,
+    Robotics,
+    Cloud,
+    #[value(name = "forensics")]
+    DataForensics,
+    Sandbox,
+}
+
+impl Mode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::AiObservability => "AI observability",
+            Mode::Robotics => "Robotics",
+            Mode::Cloud => "Cloud",
+            Mode::DataForensics => "Data forensics",
+            Mode::Sandbox => "Sandbox",
+        }
+    }
+
+    pub fn from_digit(c: char) -> Option<Mode> {
+        match c {
+            '1' => Some(Mode::AiObservability),
+            '2' => Some(Mode::Robotics),
+            '3' => Some(Mode::Cloud),
+            '4' => Some(Mode::DataForensics),
+            '5' => Some(Mode::Sandbox),
+            _ => None,
+        }
+    }
+
+    pub fn short(&self) -> &'static str {
+        match self {
+            Mode::AiObservability => "AI",
+            Mode::Robotics => "ROB",
+            Mode::Cloud => "CLD",
+            Mode::DataForensics => "DFX",
+            Mode::Sandbox => "SBX",
+        }
+    }
+}
+
+pub struct AppState {
+    pub(crate) timebase: Timebase,
+    pub(crate) mode: Mode,
+    // when each mode was switched to, for the session report
+    pub(crate) mode_log: Vec<(Duration, Mode)>,
+    pub(crate) logs: LogBuffer,
+    // [redact]: masks every line gets on its way into `logs`
+    pub(crate) redact: Vec<Rule>,
+    pub(crate) cmd_input: LineInput,
+    pub(crate) cmd_active: bool,
+    // lines run from the bar, for Up/Down
+    pub(crate) cmd_history: History,
+    pub(crate) rng: StdRng,
+    // set whenever the next frame would differ from the last one drawn
+    pub(crate) dirty: bool,
+    // lines from producer threads, drained on tick
+    pub(crate) ingest: IngestQueue,
+    // what backs each mode's panel, indexed by `Mode as usize`
+    pub(crate) feeds: Vec<Feed>,
+    // connection state of network-backed sources, and their up/down history
+    pub(crate) links: Links,
+    pub(crate) availability: Availability,
+    // --host agents, and the multi-host view when it's open
+    pub(crate) fleet: Fleet,
+    pub(crate) host_view: Option<HostView>,
+    // --bench load generator, and lines moved into the log buffer so far
+    pub(crate) bench: Option<Bench>,
+    pub(crate) ingested: u64,
+    // per-source "dropped N lines" counters, refreshed on tick
+    pub(crate) dropped: Vec<(Arc<str>, u64)>,
+    pub(crate) show_diag: bool,
+    // sheds rendering work when frames run over budget
+    pub(crate) watchdog: FrameWatchdog,
+    // active `grep` filter over the log pane
+    pub(crate) search: Option<String>,
+    pub(crate) caps: Caps,
+    // [panels]: each panel's borders and title alignment
+    pub(crate) chrome: Styles,
+    // first-run setup, shown over the dashboard until finished or skipped
+    pub(crate) wizard: Option<Wizard>,
+    // --scenario: scripted kiosk steps, replayed on loop
+    pub(crate) scenario: Option<Scenario>,
+    // --educator: metric explanations, and whether they're showing (`e`)
+    pub(crate) catalog: Option<Catalog>,
+    pub(crate) explain: bool,
+    // --deltas: change over the last minute beside each value (`d`)
+    pub(crate) deltas: bool,
+    // --attention: a panel with a critical watched metric comes first
+    pub(crate) attention: bool,
+    // --reduced-motion: values held until they change materially
+    pub(crate) steady: Option<Steady>,
+    // --idle-after: ambient screensaver
+    pub(crate) idle: Idle,
+    // --read-only: commands that change data, sources, or saved state are
+    // refused
+    pub(crate) read_only: bool,
+    // whether mouse capture should be on; the main loop applies changes
+    pub(crate) mouse_capture: bool,
+    // where config and session data live
+    pub(crate) dirs: AppDirs,
+    pub(crate) profile: Profile,
+    // data advances on tick_rate; input is polled on its own, much finer cadence
+    pub(crate) tick_rate: Duration,
+    // whether tick() writes synthetic log lines
+    pub(crate) synthetic: bool,
+    // files streamed into the logs by --follow and `tail`
+    pub(crate) follows: Vec<PathBuf>,
+    // whether lines piped into stdin feed the logs
+    pub(crate) piped: bool,
+    // whether --journal streams the systemd journal into them
+    pub(crate) journal: bool,
+    // whether --listen, --syslog, --http, or a subscription take log lines
+    // from elsewhere
+    pub(crate) listening: bool,
+    // --integrity: hashed files and their recent changes
+    pub(crate) integrity: Option<Integrity>,
+    // --capture: the interface's traffic over the last minute
+    pub(crate) capture: Option<Capture>,
+    // --gpu-processes: who's using the GPUs, for the AI observability strip
+    pub(crate) gpu: Option<GpuProcesses>,
+    // --docker: running containers, in place of cloud mode's system panel
+    pub(crate) docker: Option<DockerStats>,
+    // --kube-events: scaling events for cloud mode's timeline
+    pub(crate) scaling: Option<Scaling>,
+    // --llm-proxy: streamed responses for the AI observability strip
+    pub(crate) tokens: Option<TokenStreams>,
+    // `serial open`: the port, and the robotics feed it took over
+    pub(crate) serial: Option<(Port, Feed)>,
+    // [ros2]: joint states and diagnostics for the robotics strip
+    pub(crate) ros2: Option<Ros2>,
+    // [kafka]: the topic's partitions and their lag, for the Cloud strip
+    pub(crate) kafka: Option<Kafka>,
+    // [kube]: a namespace's pods, in place of cloud mode's system panel
+    pub(crate) pods: Option<Pods>,
+    // the request picked in the `requests` viewer, while it's open
+    pub(crate) request_pick: Option<usize>,
+    // [health]: user-defined composite in place of the trust score, and
+    // whether the breakdown popup is open
+    pub(crate) health: Option<HealthSpec>,
+    pub(crate) show_health: bool,
+    // [derived]: formulas over metrics from any source, by name
+    pub(crate) derived: BTreeMap<String, Formula>,
+    // [control]: where robotics commands go, and a setpoint waiting on y/n
+    pub(crate) control: Option<control::Control>,
+    pub(crate) confirm: Option<Request>,
+    // [[notify]]: where alerts go besides the logs
+    pub(crate) notifiers: Vec<Notifier>,
+    // panel with the keys (Tab, Ctrl+arrows, `l` for the logs); command
+    // mode stands in for the command bar's focus
+    pub(crate) focus: Option<Focus>,
+    // the focused log pane's number keys show one source only
+    pub(crate) log_source: Option<SourceId>,
+    // JSON and multi-line entries: the one ↑/↓ picked (0 = newest), and the
+    // popup Enter opened on it
+    pub(crate) log_pick: Option<usize>,
+    pub(crate) log_popup: Option<Popup>,
+    // metric row picked with Tab, expanded in place with Enter
+    pub(crate) selected: Option<Selection>,
+    // named moments set with `mark` (or over HTTP with --mark-listen), and
+    // the open `compare` chart
+    pub(crate) markers: Vec<(String, Duration)>,
+    // labels from /mark, over --mark-listen or --http
+    pub(crate) mark_tx: Sender<String>,
+    mark_rx: Receiver<String>,
+    // --socket: commands from other processes, and what the one running now
+    // has logged, for its reply
+    pub(crate) socket: Option<ControlSocket>,
+    pub(crate) replying: Option<Vec<(Level, String)>>,
+    // this machine behind the system panel; None under --simulate
+    pub(crate) machine: Option<Machine>,
+    pub(crate) comparison: Option<Comparison>,
+    // favorites strip under the banner, kept across sessions
+    pub(crate) pins: Vec<Pin>,
+    // triage list beside the logs, kept across sessions
+    pub(crate) watchlist: Watchlist,
+    // `view save`d investigations by name, likewise
+    pub(crate) views: Views,
+    // what's firing among the watchlist and the health index, acknowledged
+    // or not, and the silences over it
+    pub(crate) alerts: Alerts,
+    // [[maintenance]]: the windows, each one that began (name, start, end),
+    // and the alerts they held (when, window, message) for the report
+    pub(crate) maintenance: Maintenance,
+    pub(crate) maintenance_log: Vec<(String, Duration, Option<Duration>)>,
+    // time travel: the moment being viewed instead of now, and where the
+    // scrubber was last drawn (for clicks and drags)
+    pub(crate) scrub: Option<Duration>,
+    pub(crate) timeline: Rect,
+    // top-left of the visible window when the terminal is below the minimum size
+    pub(crate) pan: (u16, u16),
+    // virtual time added per tick in deterministic simulation (--sim-step)
+    pub(crate) sim_step: Option<Duration>,
+    // held with 1–5 to switch modes while typing in the command bar
+    pub(crate) mode_modifier: KeyModifiers,
+}
+
+impl AppState {
+    pub fn new(cli: &Cli) -> Self {
+        let caps = Caps::detect(cli);
+        let ingest = IngestQueue::default();
+        let links = Links::default();
+        let programs = Programs::new(cli.programs.clone().unwrap_or_default());
+        let fleet = Fleet::start(&cli.host, &programs, &links, &ingest);
+        let bench = cli.bench.map(|rate| Bench::start(rate, &ingest));
+        let (mark_tx, mark_rx) = mpsc::channel();
+        let mut app = Self {
+            timebase: match cli.sim_step {
+                Some(_) => Timebase::simulated(),
+                None => Timebase::new(),
+            },
+            mode: cli.mode.unwrap_or(cli.profile.mode()),
+            mode_log: Vec::new(),
+            logs: LogBuffer::new(
+                cli.log_capacity,
+                cli.log_budget,
+                Dedup {
+                    window: Duration::from_millis(cli.dedup_window),
+                    // a config file can say anything
+                    similarity: cli.dedup_similarity.clamp(0.0, 1.0),
+                },
+            ),
+            redact: cli.redact.as_ref().map_or_else(Vec::new, RedactSpec::rules),
+            cmd_input: LineInput::default(),
+            cmd_active: false,
+            cmd_history: History::default(),
+            rng: match cli.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+            dirty: true,
+            ingest,
+            feeds: Mode::value_variants()
+                .iter()
+                .map(|&mode| Feed::new(Synthetic::new(mode)))
+                .collect(),
+            links,
+            availability: Availability::default(),
+            fleet,
+            host_view: None,
+            bench,
+            ingested: 0,
+            dropped: Vec::new(),
+            show_diag: false,
+            watchdog: FrameWatchdog::new(Duration::from_millis(cli.frame_budget)),
+            search: None,
+            caps,
+            chrome: cli
+                .panels
+                .as_ref()
+                .map(PanelsSpec::resolve)
+                .unwrap_or_default(),
+            dirs: AppDirs::resolve(cli),
+            profile: cli.profile,
+            tick_rate: cli.profile.tick_rate(),
+            synthetic: cli.profile.synthetic(),
+            follows: Vec::new(),
+            piped: false,
+            listening: false,
+            journal: false,
+            integrity: None,
+            capture: None,
+            gpu: None,
+            docker: None,
+            scaling: None,
+            tokens: None,
+            serial: None,
+            ros2: None,
+            kafka: None,
+            pods: None,
+            request_pick: None,
+            wizard: None,
+            scenario: None,
+            idle: Idle::new(Duration::from_secs(cli.idle_after)),
+            catalog: None,
+            steady: cli.reduced_motion.then(Steady::default),
+            explain: cli.educator,
+            deltas: cli.deltas,
+            attention: cli.attention,
+            read_only: cli.read_only,
+            mouse_capture: caps.mouse,
+            health: None,
+            show_health: false,
+            derived: BTreeMap::new(),
+            control: None,
+            notifiers: Vec::new(),
+            confirm: None,
+            focus: None,
+            log_source: None,
+            log_pick: None,
+            log_popup: None,
+            selected: None,
+            markers: Vec::new(),
+            mark_tx,
+            mark_rx,
+            socket: None,
+            replying: None,
+            machine: None,
+            comparison: None,
+            pins: Vec::new(),
+            watchlist: Watchlist::default(),
+            views: Views::default(),
+            alerts: Alerts::default(),
+            maintenance: Maintenance::new(cli.maintenance.clone().unwrap_or_default()),
+            maintenance_log: Vec::new(),
+            scrub: None,
+            timeline: Rect::default(),
+            pan: (0, 0),
+            sim_step: cli.sim_step.map(Duration::from_millis),
+            mode_modifier: cli.mode_modifier.key_modifiers(),
+        };
+        app.push_log(format!(
+            "ai-intui v0.9 — 1–5 to switch modes ({}+1–5 while typing), : for command mode",
+            cli.mode_modifier.label()
+        ));
+        app.push_log(
+            "commands: help / ?, clear, diag, set mode <ai|robotics|cloud|forensics|sandbox>",
+        );
+        let summary = app.caps.summary();
+        app.push_log(summary);
+        if let Some(spec) = cli.redact.as_ref().filter(|_| !app.redact.is_empty()) {
+            app.push_log(format!("redacting log lines: {}", spec.describe()));
+        }
+        if !app.maintenance.is_empty() {
+            app.push_log(format!(
+                "maintenance windows: {}",
+                app.maintenance.describe()
+            ));
+        }
+        match app.dirs.ensure() {
+            Ok(true) => {
+                let config = dirs::display(&app.dirs.config);
+                app.push_log(format!(
+                    "first run: created {config} (`paths` lists locations)"
+                ));
+            }
+            Ok(false) => {}
+            Err(e) => app.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                format!("can't create app directories: {e}"),
+            ),
+        }
+        for path in &cli.follow {
+            if let Err(e) = app.follow(path.clone()) {
+                app.push_entry(Level::Warn, SourceId::APP, e);
+            }
+        }
+        if let Some(units) = &cli.journal {
+            match journal::start(units, &programs, &app.links, &app.ingest) {
+                Ok(()) => {
+                    let units = match units.as_str() {
+                        journal::ALL => "every unit".to_string(),
+                        units => units.replace(',', ", "),
+                    };
+                    app.push_log(format!("reading the systemd journal: {units}"));
+                    app.journal = true;
+                    app.synthetic = false;
+                }
+                Err(e) => app.push_entry(Level::Warn, SourceId::APP, format!("--journal: {e}")),
+            }
+        }
+        if let Some(addr) = cli.listen {
+            app.listen(addr);
+        }
+        if let Some(addr) = cli.syslog {
+            app.receive_syslog(addr);
+        }
+        if let Some(path) = &cli.socket {
+            app.open_socket(path);
+        }
+        if !cli.integrity.is_empty() {
+            let tx = app.ingest.sender(integrity::NAME, integrity::QUEUE);
+            app.integrity = Some(Integrity::start(cli.integrity.clone(), tx));
+        }
+        if let Some(interface) = &cli.capture {
+            let tx = app.ingest.sender(capture::NAME, capture::QUEUE);
+            match Capture::start(interface, tx) {
+                Ok(capture) => app.capture = Some(capture),
+                Err(e) => app.push_entry(
+                    Level::Warn,
+                    SourceId::APP,
+                    format!("--capture {interface}: {e}"),
+                ),
+            }
+        }
+        if cli.gpu_processes {
+            app.gpu = Some(GpuProcesses::start(
+                app.ingest.sender(gpu::NAME, gpu::QUEUE),
+                &programs,
+            ));
+        }
+        if cli.docker {
+            app.docker = Some(DockerStats::start(
+                app.ingest.sender(docker::NAME, docker::QUEUE),
+                &programs,
+            ));
+        }
+        if let Some(namespace) = &cli.kube_events {
+            app.scaling = Some(Scaling::start(
+                namespace,
+                &programs,
+                &app.links,
+                &app.ingest,
+            ));
+        }
+        if let Some(spec) = &cli.llm_proxy {
+            let tx = app.ingest.sender(tokens::NAME, tokens::QUEUE);
+            let mut sampling = cli.llm_requests.clone().unwrap_or_default();
+            // [redact] holds for prompts and responses too, before their own rules
+            sampling.redact.splice(0..0, app.redact.iter().cloned());
+            match TokenStreams::start(spec, sampling, tx) {
+                Ok(tokens) => app.tokens = Some(tokens),
+                Err(e) => app.push_entry(
+                    Level::Warn,
+                    SourceId::APP,
+                    format!("--llm-proxy {spec}: {e}"),
+                ),
+            }
+        }
+        app.mode_log.push((Duration::ZERO, app.mode));
+        if let Some(spec) = &cli.health {
+            app.load_health(spec.clone());
+        }
+        if let Some(formulas) = &cli.derived {
+            app.load_derived(formulas.clone());
+        }
+        if let Some(spec) = &cli.prometheus {
+            app.scrape(spec);
+        }
+        if let Some(spec) = &cli.websocket {
+            app.subscribe(spec);
+        }
+        if let Some(spec) = &cli.mqtt {
+            app.telemetry(spec);
+        }
+        if let Some(spec) = &cli.ros2 {
+            app.bridge(spec);
+        }
+        if let Some(spec) = &cli.kafka {
+            app.consume(spec);
+        }
+        if let Some(spec) = &cli.kube {
+            match Pods::start(spec, &programs, &app.links, &app.ingest) {
+                Ok(pods) => {
+                    app.push_log(format!("cloud pods ← namespace `{}`", spec.namespace));
+                    app.pods = Some(pods);
+                }
+                Err(e) => app.push_entry(Level::Warn, SourceId::APP, format!("[kube]: {e}")),
+            }
+        }
+        if let Some(spec) = &cli.control {
+            match control::Control::new(spec.clone(), &app.ingest) {
+                Ok(control) => {
+                    app.push_log(format!("robotics commands → {}", control.spec.url));
+                    app.control = Some(control);
+                }
+                Err(e) => app.push_entry(
+                    Level::Warn,
+                    SourceId::APP,
+                    format!("[control]: {e}; robotics commands are off"),
+                ),
+            }
+        }
+        for spec in cli.notify.iter().flatten() {
+            match Notifier::start(spec, &app.ingest) {
+                Ok(notifier) => {
+                    app.push_log(format!("alerts → {}", notifier.describe()));
+                    app.notifiers.push(notifier);
+                }
+                Err(e) => app.push_entry(
+                    Level::Warn,
+                    SourceId::APP,
+                    format!("[[notify]] {}: {e}", notify::site(&spec.url)),
+                ),
+            }
+        }
+        if let Some(addr) = cli.http {
+            let source = Push::listen(addr, app.ingest.clone(), app.mark_tx.clone());
+            // its log lines take the place of the synthetic ones, as --listen's do
+            if source.is_ok() {
+                app.listening = true;
+                app.synthetic = false;
+            }
+            app.receive("--http", addr, source);
+        }
+        if let Some(addr) = cli.otlp {
+            let source = Otlp::listen(addr, app.ingest.sender(otlp::NAME, otlp::QUEUE));
+            app.receive("--otlp", addr, source);
+        }
+        if let Some(addr) = cli.statsd {
+            let source = Statsd::listen(addr, app.ingest.sender(statsd::NAME, statsd::QUEUE));
+            app.receive("--statsd", addr, source);
+        }
+        app.pins = pins::load(&app.dirs.pins_file());
+        app.cmd_history = History::load(&app.dirs.history_file());
+        match Watchlist::load(&app.dirs.watchlist_file()) {
+            Ok(watchlist) => app.watchlist = watchlist,
+            Err(e) => app.warn_unloaded(&e, "the watchlist starts empty and isn't saved over it"),
+        }
+        match Views::load(&app.dirs.views_file()) {
+            Ok(views) => app.views = views,
+            Err(e) => app.warn_unloaded(&e, "no saved views, and none are saved over it"),
+        }
+        if cli.educator {
+            let catalog = Catalog::load(&app.dirs.explain_file()).unwrap_or_else(|e| {
+                app.warn_unloaded(&e, "using the bundled explanations");
+                Catalog::bundled()
+            });
+            app.catalog = Some(catalog);
+            app.push_log("educator mode: `e` shows or hides metric explanations");
+        }
+        app
+    }
+
+    pub(crate) fn uptime(&self) -> Duration {
+        self.timebase.elapsed()
+    }
+
+    // What metrics and logs are drawn for: now, or the scrubbed-to moment
+    pub(crate) fn view_time(&self) -> Duration {
+        self.scrub.unwrap_or_else(|| self.uptime())
+    }
+
+    // Time travel reaches back as far as the oldest retained log line
+    pub(crate) fn history_start(&self) -> Duration {
+        self.logs.iter().next().map_or(Duration::ZERO, |e| e.at)
+    }
+
+    // Keeps the `[health]` weights that name a metric we have; with none left
+    // the built-in trust score stays
+    fn load_health(&mut self, mut spec: HealthSpec) {
+        let mode = self.mode;
+        let unknown: Vec<String> = spec
+            .weights
+            .keys()
+            .filter(|metric| self.health_row(metric, mode, 0.0).is_none())
+            .cloned()
+            .collect();
+        for metric in unknown {
+            self.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                format!("[health]: no mode or system metric `{metric}`; left out"),
+            );
+            spec.weights.remove(&metric);
+        }
+        if spec
+            .alert_below
+            .is_some_and(|below| !(0.0..=1.0).contains(&below))
+        {
+            self.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                "[health]: alert-below is a fraction between 0 and 1 (0.6 for 60%); ignored",
+            );
+            spec.alert_below = None;
+        }
+        if spec.weights.values().all(|w| *w == 0.0) {
+            self.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                "[health] has no usable weights; showing the trust score",
+            );
+            return;
+        }
+        self.health = Some(spec);
+    }
+
+    // Keeps the `[derived]` formulas whose every metric we have
+    fn load_derived(&mut self, formulas: BTreeMap<String, Formula>) {
+        for (name, formula) in formulas {
+            let unknown = formula
+                .operands()
+                .into_iter()
+                .find_map(|pin| self.resolve_pin(&pin.to_string()).err());
+            match unknown {
+                Some(e) => self.push_entry(
+                    Level::Warn,
+                    SourceId::APP,
+                    format!("[derived] `{name}`: {e}; left out"),
+                ),
+                None => {
+                    self.derived.insert(name, formula);
+                }
+            }
+        }
+    }
+
+    // A `[health]` weight's metric: `mode:label` or `system:label`, or a bare
+    // label from `mode`'s panel or the system one. Host metrics have no history,
+    // so they can't take part.
+    fn health_row(&self, spec: &str, mode: Mode, t: f32) -> Option<MetricRow> {
+        let find =
+            |rows: &[MetricRow], label: &str| rows.iter().find(|row| row.0 == label).copied();
+        match Pin::parse(spec) {
+            Some(Pin {
+                scope: Scope::Mode(mode),
+                label,
+            }) => find(&self.mode_rows(mode, t), &label),
+            Some(Pin {
+                scope: Scope::System,
+                label,
+            }) => find(&self.system_rows(t), &label),
+            Some(_) => None,
+            None => {
+                find(&self.mode_rows(mode, t), spec).or_else(|| find(&self.system_rows(t), spec))
+            }
+        }
+    }
+
+    // The composite's parts for `mode` at `t`, when `[health]` is configured
+    pub(crate) fn health_parts(&self, mode: Mode, t: f32) -> Option<Vec<Part>> {
+        let spec = self.health.as_ref()?;
+        let parts = spec
+            .weights
+            .iter()
+            .filter_map(|(metric, &weight)| {
+                let (_, value, fmt, fill, _) = self.health_row(metric, mode, t)?;
+                Some(Part::new(metric.clone(), fmt(value), weight, fill))
+            })
+            .collect();
+        Some(parts)
+    }
+
+    // `mode`'s rows as its data source had them at `t`; latency from a
+    // histogram is whichever quantile the panel reads
+    fn mode_rows(&self, mode: Mode, t: f32) -> [MetricRow; 7] {
+        let feed = &self.feeds[mode as usize];
+        let mut rows = ai_rows(feed.values(t));
+        if feed.quantiled(METRICS[0], t) {
+            rows[0].0 = feed.quantile.latency_label();
+        }
+        rows
+    }
+
+    // The AI panel's rows, with the configured health index in place of the
+    // trust score
+    pub(crate) fn panel_rows(&self, mode: Mode, t: f32) -> [MetricRow; 7] {
+        let mut rows = self.mode_rows(mode, t);
+        if let Some(parts) = self.health_parts(mode, t) {
+            let score = health::combine(&parts);
+            rows[6] = (
+                health::LABEL,
+                score,
+                |v| format!("{:.0}%", v * 100.0),
+                score,
+                Color::Green,
+            );
+        }
+        rows
+    }
+
+    // How long `mode`'s row `index` had gone without a sample at `t`, once
+    // that makes it stale; the health index is worked out here, so never
+    pub(crate) fn stale(&self, mode: Mode, index: usize, t: f32) -> Option<Duration> {
+        if index == 6 && self.health.is_some() {
+            return None;
+        }
+        self.feeds[mode as usize].stale(METRICS.get(index)?, t)
+    }
+
+    // The system panel's rows at time `t`: this machine's reading then, or
+    // the synthetic ones under --simulate
+    pub(crate) fn system_rows(&self, t: f32) -> [MetricRow; 4] {
+        match &self.machine {
+            Some(machine) => sysmetrics::rows(machine.at(Duration::from_secs_f32(t.max(0.0)))),
+            None => sim::system_rows(t),
+        }
+    }
+
+    // An expanded row: the last minute sampled once per sparkline column
+    fn row_detail(&self, panel: Panel, index: usize, width: usize) -> Option<Detail> {
+        let row_at = |at: Duration| {
+            let t = at.as_secs_f32();
+            match panel {
+                Panel::Ai => self.panel_rows(self.mode, t).get(index).copied(),
+                Panel::System => self.system_rows(t).get(index).copied(),
+            }
+        };
+        let end = self.view_time();
+        let start = end.saturating_sub(detail::WINDOW);
+        let (label, _, fmt, _, _) = row_at(end)?;
+        let last = width.saturating_sub(1).max(1);
+        // columns where the source had gone quiet are gaps, not its last value
+        let columns: Vec<Option<(f32, f32)>> = (0..width)
+            .map(|i| {
+                let at = start + (end - start).mul_f64(i as f64 / last as f64);
+                let gap =
+                    panel == Panel::Ai && self.stale(self.mode, index, at.as_secs_f32()).is_some();
+                row_at(at).filter(|_| !gap).map(|row| (row.1, row.3))
+            })
+            .collect();
+        let samples: Vec<(f32, f32)> = columns.iter().flatten().copied().collect();
+        let mut min = samples.iter().map(|s| s.0).fold(f32::MAX, f32::min);
+        let mut max = samples.iter().map(|s| s.0).fold(f32::MIN, f32::max);
+        let health = self.health.as_ref().filter(|_| label == health::LABEL);
+        // readings a tick's mean smoothed over still count, so a spike
+        // between ticks shows here even though no bar ever drew it
+        let feed = &self.feeds[self.mode as usize];
+        let metric = METRICS.get(index).copied().unwrap_or_default();
+        if panel == Panel::Ai && health.is_none() {
+            let (start, end) = (start.as_secs_f32(), end.as_secs_f32());
+            if let Some((lo, hi)) = feed.extremes(metric, start, end) {
+                (min, max) = (min.min(lo), max.max(hi));
+            }
+        }
+
+        // the value a full bar stands for, from any sample the bar didn't clip
+        let mut thresholds = samples
+            .iter()
+            .find(|s| s.1 > 0.0 && s.1 < 1.0)
+            .map_or("bar scale unknown".to_string(), |s| {
+                format!("bar full at {}", fmt(s.0 / s.1))
+            });
+        if let Some(below) = health.and_then(|h| h.alert_below) {
+            thresholds += &format!(", alerts below {:.0}%", below * 100.0);
+        }
+        let source = match (panel, health) {
+            (Panel::Ai, Some(spec)) => {
+                let metrics: Vec<&str> = spec.weights.keys().map(String::as_str).collect();
+                format!("[health] over {}", metrics.join(", "))
+            }
+            (Panel::Ai, None) => {
+                let mut source = feed.describe();
+                if feed.quantiled(metric, end.as_secs_f32()) {
+                    source += &format!(", {} of its histogram buckets", feed.quantile.name());
+                } else if let Some(every) = feed.interval().filter(|&i| i < self.tick_interval()) {
+                    source += &format!(
+                        ", sampled every {} ms and averaged per tick",
+                        every.as_millis()
+                    );
+                }
+                source
+            }
+            (Panel::System, _) if self.machine.is_some() => {
+                "this machine, sampled every second".to_string()
+            }
+            (Panel::System, _) => "synthetic system sampler (--simulate)".to_string(),
+        };
+        // cloud latency also gets a rule where --kube-events saw it scale
+        let scaled = self
+            .scaling
+            .iter()
+            .filter(|_| self.mode == Mode::Cloud && panel == Panel::Ai && index == 0)
+            .flat_map(|scaling| scaling.between(start, end))
+            .filter(|event| event.kind.scales())
+            .map(|event| event.at);
+        let marks = self
+            .markers
+            .iter()
+            .map(|(_, at)| *at)
+            .filter(|at| (start..=end).contains(at))
+            .chain(scaled)
+            .map(|at| {
+                let frac =
+                    (at - start).as_secs_f64() / (end - start).as_secs_f64().max(f64::EPSILON);
+                (frac * last as f64).round() as usize
+            })
+            .collect();
+        // nothing at all came in over the window
+        let (min, max) = match min <= max {
+            true => (fmt(min), fmt(max)),
+            false => ("-".to_string(), "-".to_string()),
+        };
+        Some(Detail {
+            fills: columns.iter().map(|s| s.map(|s| s.1)).collect(),
+            marks,
+            min,
+            max,
+            thresholds,
+            source,
+        })
+    }
+
+    // `▲12 ms`: how far `now` moved from `before`, padded to TREND_W; a change
+    // too small to show in the metric's own format reads as flat
+    pub(crate) fn trend(&self, now: f32, before: f32, fmt: fn(f32) -> String) -> Span<'static> {
+        let ascii = self.caps.glyphs == GlyphSet::Ascii;
+        let change = fmt((now - before).abs());
+        let (arrow, color, amount) = if change == fmt(0.0) {
+            (
+                if ascii { "=" } else { "·" },
+                Color::DarkGray,
+                String::new(),
+            )
+        } else if now > before {
+            (if ascii { "^" } else { "▲" }, Color::Gray, change)
+        } else {
+            (if ascii { "v" } else { "▼" }, Color::Gray, change)
+        };
+        let (text, pad) = fit(&amount, TREND_W - 2);
+        Span::styled(format!(" {arrow}{text}{pad}"), Style::default().fg(color))
+    }
+
+    // --attention: the first critical watched metric on screen, as an
+    // expanded row; cleared as soon as it recovers
+    pub(crate) fn emphasis(&self) -> Option<Selection> {
+        if !self.attention || self.host_view.is_some() {
+            return None;
+        }
+        let t = self.view_time().as_secs_f32();
+        self.watchlist.items.iter().find_map(|item| {
+            let pin = Pin::parse(&item.metric)?;
+            let (panel, row) = match pin.scope {
+                Scope::Mode(mode) if mode == self.mode => (
+                    Panel::Ai,
+                    self.panel_rows(mode, t)
+                        .iter()
+                        .position(|row| row.0 == pin.label)?,
+                ),
+                Scope::System => (
+                    Panel::System,
+                    self.system_rows(t)
+                        .iter()
+                        .position(|row| row.0 == pin.label)?,
+                ),
+                _ => return None,
+            };
+            (item.status(self.reading(&pin).map(|r| r.0)) == Status::Crit).then_some(Selection {
+                panel,
+                row,
+                expanded: true,
+            })
+        })
+    }
+
+    // A row the user expanded wins over one attention mode brought forward
+    fn shown_selection(&self) -> Option<Selection> {
+        match self.selected {
+            Some(sel) if sel.expanded => Some(sel),
+            selected => self.emphasis().or(selected),
+        }
+    }
+
+    // Lines under row `index` of `panel` when it's the expanded one
+    pub(crate) fn expansion(
+        &self,
+        panel: Panel,
+        index: usize,
+        area: Rect,
+        color: Color,
+    ) -> Vec<Line<'static>> {
+        match self.shown_selection() {
+            Some(sel) if sel.expanded && sel.panel == panel && sel.row == index => {
+                let width = area.width.saturating_sub(4) as usize;
+                self.row_detail(panel, index, width)
+                    .map(|d| d.lines(self.bar_glyphs(), color))
+                    .unwrap_or_default()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // Reversed label on the row Tab picked
+    // An expanded row scrolls its panel so the row and its detail stay in view
+    pub(crate) fn first_row(&self, panel: Panel) -> usize {
+        match self.shown_selection() {
+            Some(sel) if sel.expanded && sel.panel == panel => sel.row,
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn highlight(&self, panel: Panel, index: usize, line: &mut Line) {
+        if self
+            .shown_selection()
+            .is_some_and(|sel| sel.panel == panel && sel.row == index)
+        {
+            if let Some(label) = line.spans.first_mut() {
+                label.style = label.style.add_modifier(Modifier::REVERSED);
+            }
+        }
+    }
+
+    // One alert when the index drops below `alert-below`, repeated while it
+    // stays there unless acknowledged, and one note when it recovers
+    fn check_health(&mut self) {
+        let Some(below) = self.health.as_ref().and_then(|h| h.alert_below) else {
+            return;
+        };
+        let now = self.uptime();
+        let parts = self
+            .health_parts(self.mode, now.as_secs_f32())
+            .unwrap_or_default();
+        let score = health::combine(&parts);
+        let status = if score < below {
+            Status::Crit
+        } else {
+            Status::Ok
+        };
+        let labels = Self::health_labels(self.mode, status);
+        let since = self.alerts.firing_for(HEALTH, now).unwrap_or_default();
+        let Some(notice) = self.alerts.check(HEALTH, labels, status, now) else {
+            return;
+        };
+        let message = match notice {
+            Notice::Fired | Notice::Suppressed => {
+                let weakest = parts
+                    .iter()
+                    .filter(|p| p.weight != 0.0)
+                    .min_by(|a, b| a.score.total_cmp(&b.score))
+                    .map(|p| format!("; weakest: {} {}", p.metric, p.value))
+                    .unwrap_or_default();
+                let message = format!(
+                    "{} {:.0}% is below {:.0}%{weakest}",
+                    health::LABEL,
+                    score * 100.0,
+                    below * 100.0
+                );
+                self.raise(notice, HEALTH, status, message.clone());
+                message
+            }
+            Notice::Repeat => {
+                let message = format!(
+                    "{} still {:.0}%, below {:.0}% for {}; `ack {HEALTH}` stops these",
+                    health::LABEL,
+                    score * 100.0,
+                    below * 100.0,
+                    alerts::span(since)
+                );
+                self.push_entry(Level::Error, SourceId::APP, message.clone());
+                message
+            }
+            Notice::Resolved => {
+                let message = format!("{} back to {:.0}%", health::LABEL, score * 100.0);
+                self.push_log(message.clone());
+                message
+            }
+        };
+        if let Some(event) = notify::Event::of(notice) {
+            self.notify(Alert {
+                rule: HEALTH.to_string(),
+                name: health::LABEL.to_string(),
+                event,
+                status,
+                value: format!("{:.0}%", score * 100.0),
+                limit: Some(format!("{:.0}%", below * 100.0)),
+                since,
+                mode: self.mode.name(),
+                message,
+            });
+        }
+    }
+
+    // Watched metrics past a threshold: logged when they cross it, again
+    // every few minutes while they stay past it unless acknowledged or
+    // silenced, and once more when they're back
+    fn check_watchlist(&mut self) {
+        let now = self.uptime();
+        let t = now.as_secs_f32();
+        let mut notices = Vec::new();
+        for item in &self.watchlist.items {
+            let reading = Pin::parse(&item.metric).and_then(|pin| self.reading_at(&pin, t));
+            let status = item.status(reading.as_ref().map(|r| r.0));
+            let labels = alerts::labels(&item.metric, status);
+            let since = self
+                .alerts
+                .firing_for(&item.metric, now)
+                .unwrap_or_default();
+            if let Some(notice) = self.alerts.check(&item.metric, labels, status, now) {
+                let shown = reading.map_or_else(|| "—".to_string(), |r| r.1);
+                notices.push((notice, item.clone(), status, shown, since));
+            }
+        }
+        // unwatched since it fired
+        let gone: Vec<String> = self
+            .alerts
+            .firing(now)
+            .into_iter()
+            .map(|(rule, ..)| rule)
+            .filter(|rule| {
+                *rule != HEALTH && !self.watchlist.items.iter().any(|i| i.metric == *rule)
+            })
+            .map(str::to_string)
+            .collect();
+        for rule in gone {
+            self.alerts.forget(&rule, now);
+        }
+        for (notice, item, status, shown, since) in notices {
+            let name = item.display_name();
+            let (level, severity, limit) = match status {
+                Status::Crit => (Level::Error, "crit", item.crit),
+                _ => (Level::Warn, "warn", item.warn),
+            };
+            let written = limit.map(|l| format!(" {l}")).unwrap_or_default();
+            let message = match notice {
+                Notice::Fired | Notice::Suppressed => {
+                    let message = format!("{name} {shown} is past {severity}{written}");
+                    self.raise(notice, &item.metric, status, message.clone());
+                    message
+                }
+                Notice::Repeat => {
+                    let message = format!(
+                        "{name} still {shown}, past {severity}{written} for {}; `ack {}` stops these",
+                        alerts::span(since),
+                        item.metric
+                    );
+                    self.push_entry(level, SourceId::APP, message.clone());
+                    message
+                }
+                Notice::Resolved => {
+                    let message = format!("{name} back to {shown}");
+                    self.push_log(message.clone());
+                    message
+                }
+            };
+            if let Some(event) = notify::Event::of(notice) {
+                self.notify(Alert {
+                    rule: item.metric.clone(),
+                    name: name.to_string(),
+                    event,
+                    status,
+                    value: shown,
+                    limit: limit
+                        .filter(|_| notice != Notice::Resolved)
+                        .map(|l| l.to_string()),
+                    since,
+                    mode: self.mode.name(),
+                    message,
+                });
+            }
+        }
+    }
+
+    // [[notify]]: an alert to every notifier that wants it, with the
+    // metrics its templates name as they stand now
+    fn notify(&self, alert: Alert) {
+        let t = self.uptime().as_secs_f32();
+        for notifier in &self.notifiers {
+            notifier.send(&alert, &|pin| self.reading_at(pin, t).map(|r| r.1));
+        }
+    }
+
+    // A new alert is logged, unless a maintenance window holds it; the
+    // report keeps it either way
+    fn raise(&mut self, notice: Notice, rule: &str, status: Status, message: String) {
+        let now = self.uptime();
+        self.alerts.record(rule, message.clone(), now);
+        if notice != Notice::Suppressed {
+            let level = match status {
+                Status::Crit => Level::Error,
+                _ => Level::Warn,
+            };
+            self.push_entry(level, SourceId::APP, message);
+        }
+    }
+
+    // A maintenance window beginning or ending on the local clock
+    fn check_maintenance(&mut self) {
+        let now = self.uptime();
+        let local = self.timebase.local_at(now).naive_local();
+        match self.maintenance.check(local) {
+            Some(Change::Began(name, end)) => {
+                self.push_log(format!(
+                    "maintenance `{name}` until {}; alerts are held",
+                    end.format("%H:%M")
+                ));
+                self.alerts.maintenance = Some(name.clone());
+                self.maintenance_log.push((name, now, None));
+            }
+            Some(Change::Ended(name)) => {
+                self.push_log(format!("maintenance `{name}` is over; alerts are back on"));
+                self.alerts.maintenance = None;
+                if let Some(last) = self.maintenance_log.last_mut() {
+                    last.2 = Some(now);
+                }
+            }
+            None => {}
+        }
+    }
+
+    // Silences that ran out since the last tick
+    fn expire_silences(&mut self) {
+        for silence in self.alerts.expire(self.uptime()) {
+            self.push_log(format!(
+                "silence {} on {} ended",
+                silence.id, silence.target
+            ));
+        }
+    }
+
+    // The health index alerts for one mode at a time
+    fn health_labels(mode: Mode, status: Status) -> Vec<(&'static str, String)> {
+        let mode = mode.to_possible_value().expect("no skipped modes");
+        alerts::labels(&format!("{}:{HEALTH}", mode.get_name()), status)
+    }
+
+    // `pin LABEL` pins from the current mode (or the system panel);
+    // `pin SCOPE:LABEL` names a mode, `system`, or a --host explicitly
+    pub(crate) fn resolve_pin(&self, spec: &str) -> Result<Pin, String> {
+        let ai = self.panel_rows(self.mode, 0.0).map(|row| row.0);
+        let system = self.system_rows(0.0).map(|row| row.0);
+        let pin = match Pin::parse(spec) {
+            Some(pin) => pin,
+            None if ai.contains(&spec) => Pin {
+                scope: Scope::Mode(self.mode),
+                label: spec.to_string(),
+            },
+            None => Pin {
+                scope: Scope::System,
+                label: spec.to_string(),
+            },
+        };
+        let known = match &pin.scope {
+            Scope::Mode(_) => ai.contains(&pin.label.as_str()),
+            Scope::System => system.contains(&pin.label.as_str()),
+            // agents report whatever they like, possibly not yet
+            Scope::Host(host) => {
+                if !self.fleet.hosts.iter().any(|h| &*h.name == host) {
+                    return Err(format!("no mode, `system`, or host called `{host}`"));
+                }
+                true
+            }
+            Scope::Derived => {
+                if !self.derived.contains_key(&pin.label) {
+                    let names: Vec<&str> = self.derived.keys().map(String::as_str).collect();
+                    return Err(match names.is_empty() {
+                        true => "no derived metrics; define them in [derived]".to_string(),
+                        false => format!(
+                            "no derived metric `{}`. try: {}",
+                            pin.label,
+                            names.join(", ")
+                        ),
+                    });
+                }
+                true
+            }
+        };
+        if !known {
+            return Err(format!(
+                "unknown metric `{}`. try: {}, {}",
+                pin.label,
+                ai.join(", "),
+                system.join(", ")
+            ));
+        }
+        Ok(pin)
+    }
+
+    // `report PATH`: metric spread per mode while it was on screen, plus the
+    // warnings and errors still in the log buffer
+    pub(crate) fn session_report(&self) -> Report {
+        let now = self.uptime();
+        let clock = |at: Duration| self.timebase.local_at(at).format("%H:%M:%S").to_string();
+        let summarize = |rows: &dyn Fn(f32) -> Vec<MetricRow>, spans: &[(Duration, Duration)]| {
+            rows(0.0)
+                .iter()
+                .map(|&(label, _, fmt, _, _)| {
+                    let points = report::sample(spans, |at| {
+                        let rows = rows(at.as_secs_f32());
+                        rows.iter()
+                            .find(|row| row.0 == label)
+                            .map_or(0.0, |row| row.1)
+                    });
+                    let stats = compare::stats(&points);
+                    MetricSummary {
+                        label,
+                        min: fmt(stats.min as f32),
+                        avg: fmt(stats.mean as f32),
+                        max: fmt(stats.max as f32),
+                    }
+                })
+                .collect()
+        };
+
+        let mut panels = Vec::new();
+        let mut seen = Vec::new();
+        for &(_, mode) in &self.mode_log {
+            if seen.contains(&mode) {
+                continue;
+            }
+            seen.push(mode);
+            let spans: Vec<_> = self
+                .mode_log
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, m))| *m == mode)
+                .map(|(i, &(start, _))| {
+                    let end = self.mode_log.get(i + 1).map_or(now, |next| next.0);
+                    (start, end)
+                })
+                .collect();
+            let time: Duration = spans.iter().map(|(start, end)| *end - *start).sum();
+            let heading = format!(
+                "{} ({} on screen)",
+                mode.name(),
+                format_duration(Duration::from_secs(time.as_secs()))
+            );
+            panels.push((
+                heading,
+                summarize(&|t| self.panel_rows(mode, t).to_vec(), &spans),
+            ));
+        }
+        panels.push((
+            "System".to_string(),
+            summarize(&|t| self.system_rows(t).to_vec(), &[(Duration::ZERO, now)]),
+        ));
+
+        // a collapsed storm keeps its size and end in the report
+        let repeats = |e: &LogEntry| match e.repeats {
+            0 | 1 => String::new(),
+            n => format!(" (×{n}, last at {})", clock(e.last)),
+        };
+        // every alert that fired, from the alerts' own record, so `clear`
+        // and a full buffer don't take any out of the report
+        let alerts = self
+            .alerts
+            .history
+            .iter()
+            .map(|r| {
+                let held = r
+                    .held
+                    .as_ref()
+                    .map(|name| format!(" (suppressed: maintenance `{name}`)"))
+                    .unwrap_or_default();
+                let ended = r.ended.map_or("still firing".to_string(), |end| {
+                    format!("over at {}", clock(end))
+                });
+                Incident {
+                    at: clock(r.at),
+                    source: r.rule.clone(),
+                    message: format!("{}{held}; {ended}", r.message),
+                }
+            })
+            .collect();
+        // error and warning lines from sources, as far as the buffer still
+        // holds them; those during a maintenance window say so
+        let window_at = |at: Duration| {
+            self.maintenance_log
+                .iter()
+                .find(|(_, start, end)| *start <= at && !end.is_some_and(|end| at >= end))
+                .map(|(name, ..)| name.as_str())
+        };
+        let anomalies = self
+            .logs
+            .iter()
+            .filter(|e| e.source != SourceId::APP && matches!(e.level, Level::Warn | Level::Error))
+            .map(|e| {
+                let during = window_at(e.at)
+                    .map(|name| format!(" (during maintenance `{name}`)"))
+                    .unwrap_or_default();
+                Incident {
+                    at: clock(e.at),
+                    source: self.logs.source_name(e.source).to_string(),
+                    message: format!("{}{}{during}", e.message, repeats(e)),
+                }
+            })
+            .collect();
+        let mut excerpt: Vec<String> = self
+            .logs
+            .iter()
+            .rev()
+            .filter(|e| e.source != SourceId::APP)
+            .take(20)
+            .map(|e| format!("{} {}{}", clock(e.at), self.logs.render(e), repeats(e)))
+            .collect();
+        excerpt.reverse();
+
+        Report {
+            started: self.timebase.local_at(Duration::ZERO),
+            ended: self.timebase.local_at(now),
+            duration: now,
+            panels,
+            markers: self
+                .markers
+                .iter()
+                .map(|(name, at)| (name.clone(), clock(*at)))
+                .collect(),
+            availability: self
+                .availability
+                .summaries(now)
+                .into_iter()
+                .map(|s| Uptime {
+                    name: s.name.to_string(),
+                    uptime: format!("{:.2}%", s.uptime * 100.0),
+                    outages: s
+                        .outages
+                        .iter()
+                        .map(|&(start, length)| {
+                            (
+                                clock(start),
+                                format_duration(Duration::from_secs(length.as_secs())).to_string(),
+                            )
+                        })
+                        .collect(),
+                    still_down: !s.up_now,
+                })
+                .collect(),
+            maintenance: self
+                .maintenance_log
+                .iter()
+                .map(|(name, start, end)| (name.clone(), clock(*start), end.map(clock)))
+                .collect(),
+            alerts,
+            anomalies,
+            excerpt,
+        }
+    }
+
+    // Err is for the command that changed them to report
+    pub(crate) fn save_pins(&self) -> Result<(), String> {
+        let path = self.dirs.pins_file();
+        pins::save(&path, &self.pins).map_err(|e| format!("can't write {}: {e}", path.display()))
+    }
+
+    // Current (or scrubbed-to) value of a pin, raw and formatted like its
+    // panel does
+    fn reading(&self, pin: &Pin) -> Option<(f64, String)> {
+        self.reading_at(pin, self.view_time().as_secs_f32())
+    }
+
+    fn reading_at(&self, pin: &Pin, t: f32) -> Option<(f64, String)> {
+        let row = |rows: &[MetricRow]| {
+            rows.iter()
+                .find(|row| row.0 == pin.label)
+                .map(|&(_, value, fmt, _, _)| (f64::from(value), fmt(value)))
+        };
+        match &pin.scope {
+            Scope::Mode(mode) => row(&self.panel_rows(*mode, t)),
+            Scope::System => row(&self.system_rows(t)),
+            Scope::Host(host) => {
+                let host = self.fleet.hosts.iter().find(|h| &*h.name == host)?;
+                let (_, value) = host.metrics().into_iter().find(|(n, _)| *n == pin.label)?;
+                Some((value, plain(value)))
+            }
+            Scope::Derived => {
+                let formula = self.derived.get(&pin.label)?;
+                let value = formula.eval(&|pin| self.operand_at(pin, t))?;
+                Some((value, plain(value)))
+            }
+        }
+    }
+
+    // A derived metric's operand at `t` on the shared timebase: the latest
+    // sample by then, held until its row goes stale. None while its source
+    // has sent nothing, or nothing lately, so a gap doesn't read as zero.
+    // Hosts keep only their latest report.
+    pub(crate) fn operand_at(&self, pin: &Pin, t: f32) -> Option<f64> {
+        let Scope::Mode(mode) = pin.scope else {
+            return self.reading_at(pin, t).map(|(value, _)| value);
+        };
+        let rows = self.panel_rows(mode, t);
+        let i = rows.iter().position(|row| row.0 == pin.label)?;
+        // the health index is worked out here, so it's always there
+        let feed = &self.feeds[mode as usize];
+        let fresh = rows[i].0 == health::LABEL
+            || (feed.age(METRICS[i], t).is_some() && feed.stale(METRICS[i], t).is_none());
+        fresh.then_some(f64::from(rows[i].1))
+    }
+
+    pub(crate) fn pin_value(&self, pin: &Pin) -> Option<String> {
+        self.reading(pin).map(|(_, shown)| shown)
+    }
+
+    // A mode's banner badge: its worst watchlist status and how many alerts
+    // are firing there (watched metrics past a threshold, and the health index
+    // under `alert-below`) that nobody acknowledged or silenced, so trouble in
+    // another mode shows without switching
+    pub(crate) fn mode_status(&self, mode: Mode) -> (Status, usize) {
+        let mut worst = Status::Unknown;
+        let mut firing = 0;
+        for item in &self.watchlist.items {
+            let Some(pin) = Pin::parse(&item.metric).filter(|p| p.scope == Scope::Mode(mode))
+            else {
+                continue;
+            };
+            let status = item.status(self.reading(&pin).map(|r| r.0));
+            worst = worst.max(status);
+            let hushed = self.alerts.hushed(&item.metric).is_some();
+            firing += usize::from(status >= Status::Warn && !hushed);
+        }
+        let below = self.health.as_ref().and_then(|h| h.alert_below);
+        if let Some(below) = below {
+            let t = self.view_time().as_secs_f32();
+            let score = health::combine(&self.health_parts(mode, t).unwrap_or_default());
+            if score < below {
+                worst = Status::Crit;
+                firing += usize::from(self.alerts.hushed(HEALTH).is_none());
+            } else {
+                worst = worst.max(Status::Ok);
+            }
+        }
+        (worst, firing)
+    }
+
+    // (name, value, status, acknowledged or silenced) per watchlist item, in
+    // display order
+    pub(crate) fn watch_rows(&self) -> Vec<(String, String, Status, Option<Hushed>)> {
+        let mut rows: Vec<_> = self
+            .watchlist
+            .items
+            .iter()
+            .map(|item| {
+                let reading = Pin::parse(&item.metric).and_then(|pin| self.reading(&pin));
+                let status = item.status(reading.as_ref().map(|r| r.0));
+                let shown = reading.map_or_else(|| "—".to_string(), |r| r.1);
+                let hushed = (status >= Status::Warn)
+                    .then(|| self.alerts.hushed(&item.metric))
+                    .flatten();
+                (item.display_name().to_string(), shown, status, hushed)
+            })
+            .collect();
+        if self.watchlist.sort == Order::Worst {
+            // stable, so equal statuses keep the order they were added in
+            rows.sort_by_key(|row| std::cmp::Reverse(row.2));
+        }
+        rows
+    }
+
+    pub(crate) fn save_watchlist(&self) -> Result<(), String> {
+        let path = self.dirs.watchlist_file();
+        self.watchlist
+            .save(&path)
+            .map_err(|e| format!("can't write {}: {e}", path.display()))
+    }
+
+    fn toggle_time_travel(&mut self) {
+        if self.scrub.take().is_some() {
+            self.push_log("back to live");
+            return;
+        }
+        self.scrub = Some(self.uptime());
+        self.push_log(
+            "time travel: ←/→ 1s, PgUp/PgDn 1m, Home oldest, drag the timeline; End or Esc for live",
+        );
+    }
+
+    pub(crate) fn scrub_to(&mut self, at: Duration) {
+        self.scrub = Some(at.clamp(self.history_start(), self.uptime()));
+    }
+
+    // Writes the first-run settings file; skipping saves the defaults so the
+    // wizard doesn't come back
+    fn finish_setup(&mut self, choices: Option<Choices>) {
+        self.wizard = None;
+        let mut config = Config::default();
+        if let Some(choices) = choices {
+            self.set_profile(choices.profile);
+            self.set_mode(choices.mode);
+            config.profile = Some(choices.profile);
+            config.mode = Some(choices.mode);
+            let panels = PanelsSpec {
+                borders: Some(choices.borders),
+                ..PanelsSpec::default()
+            };
+            self.chrome = panels.resolve();
+            config.panels = Some(panels);
+            // the system panel switches now, as the rest does
+            if choices.simulate {
+                self.machine = None;
+            } else if self.machine.is_none() {
+                self.machine = Some(Machine::start());
+            }
+            config.simulate = Some(choices.simulate);
+            if choices.data_dir != self.dirs.data {
+                self.push_log("new data directory is used from the next launch");
+                config.data_dir = Some(choices.data_dir);
+            }
+        }
+        let path = self.dirs.config_file();
+        let header =
+            "# ai-intui settings; `ai-intui config show --resolved` prints the effective values";
+        match config.save(&path, header) {
+            Ok(()) => self.push_log(format!("settings saved to {}", dirs::display(&path))),
+            Err(e) => self.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                format!("can't write {}: {e}", path.display()),
+            ),
+        }
+    }
+
+    // Whether a command that changes data or sources may run; Err says why not
+    pub(crate) fn allow_mutation(&self, command: &str) -> Result<(), String> {
+        match self.read_only {
+            true => Err(format!("`{command}` is disabled in read-only mode")),
+            false => Ok(()),
+        }
+    }
+
+    // Releasing capture hands selection back to the terminal
+    pub(crate) fn set_mouse_capture(&mut self, on: bool) {
+        self.mouse_capture = on;
+        self.push_log(if on {
+            "mouse capture on"
+        } else {
+            "mouse capture off: select text with the mouse, `m` to restore"
+        });
+    }
+
+    // Bars lose their eighth-cell partials while the watchdog is shedding work,
+    // and under --reduced-motion, where their creeping edge is motion too
+    pub(crate) fn bar_glyphs(&self) -> GlyphSet {
+        match (self.caps.glyphs, self.watchdog.level()) {
+            (GlyphSet::Unicode, Degrade::Reduced | Degrade::Minimal) => GlyphSet::Blocks,
+            (GlyphSet::Unicode, _) if self.steady.is_some() => GlyphSet::Blocks,
+            (glyphs, _) => glyphs,
+        }
+    }
+
+    // What a metric row shows: its live value, or the held one under --reduced-motion
+    pub(crate) fn shown(&self, label: &'static str, value: f32, norm: f32) -> (f32, f32) {
+        match &self.steady {
+            Some(steady) => steady.hold(label, value, norm),
+            None => (value, norm),
+        }
+    }
+
+    pub(crate) fn frame_drawn(&mut self, took: Duration) {
+        if let Some(bench) = &mut self.bench {
+            bench.frame(took);
+        }
+        let Some(level) = self.watchdog.observe(took) else {
+            return;
+        };
+        let budget = self.watchdog.budget().unwrap_or_default();
+        if level == Degrade::Full {
+            self.push_log("frames back under budget; full rendering restored");
+        } else {
+            self.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                format!(
+                    "frames over {}ms budget; rendering {}",
+                    budget.as_millis(),
+                    level.label()
+                ),
+            );
+        }
+    }
+
+    // The catalog, while explanations are showing
+    pub(crate) fn explanations(&self) -> Option<&Catalog> {
+        self.catalog.as_ref().filter(|_| self.explain)
+    }
+
+    fn toggle_host_view(&mut self) {
+        if self.fleet.hosts.is_empty() {
+            self.push_log("no hosts to show; add them with --host NAME=ssh://HOST");
+            return;
+        }
+        self.host_view = match self.host_view {
+            Some(_) => None,
+            None => Some(HostView::default()),
+        };
+    }
+
+    // A marker now: a highlighted log line, and a line on every chart
+    pub(crate) fn mark(&mut self, label: String) {
+        self.push_entry(Level::Info, SourceId::MARK, label.clone());
+        self.markers.push((label, self.uptime()));
+    }
+
+    pub(crate) fn focused(&self, panel: Focus) -> bool {
+        match panel {
+            Focus::Command => self.cmd_active,
+            _ => !self.cmd_active && self.focus == Some(panel),
+        }
+    }
+
+    fn move_focus(&mut self, back: bool) {
+        let current = if self.cmd_active {
+            Some(Focus::Command)
+        } else {
+            self.focus
+        };
+        let next = Focus::step(current, back);
+        self.cmd_active = next == Focus::Command;
+        self.focus = (next != Focus::Command).then_some(next);
+        self.log_pick = None;
+    }
+
+    // `0` in the focused log pane shows every source again, `1`–`9` just one
+    fn filter_source(&mut self, digit: u32) {
+        if digit == 0 {
+            self.log_source = None;
+            return;
+        }
+        if let Some(&id) = self.logs.active_sources().get(digit as usize - 1) {
+            self.log_source = Some(id);
+        }
+    }
+
+    // Collapsed payload entries the log pane is showing, newest first
+    pub(crate) fn payload_entries(&self) -> Vec<&LogEntry> {
+        let shown = match self.search.as_deref() {
+            Some(q) => self.logs.search(q),
+            None => self.logs.iter().collect(),
+        };
+        shown
+            .into_iter()
+            .rev()
+            .filter(|e| self.log_source.is_none() || self.log_source == Some(e.source))
+            .filter(|e| Payload::detect(&e.message).is_some())
+            .collect()
+    }
+
+    fn open_payload(&mut self) {
+        let pick = self.log_pick.unwrap_or(0);
+        let Some(entry) = self.payload_entries().get(pick).copied() else {
+            return;
+        };
+        let stamp = self.timebase.local_at(entry.at).format("%H:%M:%S");
+        self.log_popup = Some(Popup {
+            title: format!(
+                "{} {stamp} • ↑↓ scroll • Esc closes",
+                self.logs.source_name(entry.source)
+            ),
+            lines: payload::expanded(&entry.message),
+            scroll: 0,
+        });
+    }
+
+    fn open_request(&mut self) {
+        let pick = self.request_pick.unwrap_or(0);
+        let Some(exchange) = self.tokens.as_ref().and_then(|t| t.requests.get(pick)) else {
+            return;
+        };
+        self.log_popup = Some(sampling::popup(exchange));
+    }
+
+    // The ambient view slows everything down; input is still polled as usual
+    pub fn tick_interval(&self) -> Duration {
+        if self.idle.active() {
+            self.tick_rate.max(IDLE_TICK)
+        } else {
+            self.tick_rate
+        }
+    }
+
+    // How often sources that asked to be read between ticks are sampled;
+    // None when none did, or the tick is already that fast
+    pub fn sample_interval(&self) -> Option<Duration> {
+        self.feeds
+            .iter()
+            .filter_map(Feed::interval)
+            .min()
+            .filter(|&every| every < self.tick_interval())
+    }
+
+    // Readings from fast sources between ticks, folded into the next one
+    pub fn sample(&mut self) {
+        for feed in &mut self.feeds {
+            if feed.interval().is_some() {
+                feed.sample();
+            }
+        }
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    // Keeps the pan offset inside the part of the dashboard that doesn't fit
+    pub(crate) fn clamp_pan(&mut self, area: Rect) {
+        self.pan.0 = self.pan.0.min(MIN_W.saturating_sub(area.width));
+        self.pan.1 = self.pan.1.min(MIN_H.saturating_sub(area.height));
+    }
+
+    // A file that didn't load, and what's done instead. toml's report
+    // spans several lines; this keeps where and what.
+    fn warn_unloaded(&mut self, e: &ConfigError, instead: &str) {
+        let report = e.to_string();
+        let mut lines = report.lines();
+        let (first, last) = (lines.next().unwrap_or_default(), lines.last());
+        let what = last.map(|l| format!(": {l}")).unwrap_or_default();
+        self.push_entry(
+            Level::Warn,
+            SourceId::APP,
+            format!("{first}{what}; {instead}"),
+        );
+    }
+
+    pub(crate) fn push_log<S: Into<String>>(&mut self, line: S) {
+        self.push_entry(Level::Info, SourceId::APP, line);
+    }
+
+    pub(crate) fn push_entry<S: Into<String>>(
+        &mut self,
+        level: Level,
+        source: SourceId,
+        message: S,
+    ) {
+        self.dirty = true;
+        let at = self.uptime();
+        let mut message = message.into();
+        if let Cow::Owned(masked) = redact::apply(&self.redact, &message) {
+            message = masked;
+        }
+        let entry = LogEntry {
+            at,
+            level,
+            source,
+            message: Arc::from(message),
+            repeats: 1,
+            last: at,
+        };
+        if source == SourceId::APP {
+            applog::write(level, "app", &entry.message);
+            if let Some(said) = &mut self.replying {
+                said.push((level, entry.message.to_string()));
+            }
+        }
+        self.logs.push(entry);
+    }
+
+    pub(crate) fn set_mode(&mut self, mode: Mode) {
+        if self.mode != mode {
+            self.mode = mode;
+            self.mode_log.push((self.uptime(), mode));
+            if let Some(steady) = &self.steady {
+                steady.reset();
+            }
+            self.push_log(format!("mode set → {}", self.mode.name()));
+        }
+    }
+
+    // `estop` and `set NAME VALUE`: an e-stop goes out at once, while a
+    // setpoint waits for y/n. Requests, refusals, and replies all land in the
+    // audit trail; a refusal is Err too, so a batch stops there.
+    pub(crate) fn control_command(&mut self, line: &str) -> Result<(), String> {
+        let Some(control) = &self.control else {
+            return Err(format!("`{line}` needs a [control] table in config.toml"));
+        };
+        let request = match control.check(line) {
+            Ok(request) if !self.read_only => request,
+            Ok(_) => {
+                control.audit(Level::Warn, format!("refused {line}: read-only mode"));
+                return Err(format!("`{line}` was refused"));
+            }
+            Err(e) => {
+                control.audit(Level::Warn, format!("refused {line}: {e}"));
+                return Err(format!("`{line}` was refused"));
+            }
+        };
+        match request {
+            Request::Estop => control.send(&request),
+            Request::Set { .. } => {
+                control.audit(
+                    Level::Info,
+                    format!("{} awaiting confirmation", request.label()),
+                );
+                self.confirm = Some(request);
+            }
+        }
+        Ok(())
+    }
+
+    // y/n on the pending setpoint
+    pub(crate) fn answer_confirm(&mut self, send: bool) {
+        let (Some(request), Some(control)) = (self.confirm.take(), &self.control) else {
+            return;
+        };
+        match send {
+            true => control.send(&request),
+            false => control.audit(Level::Info, format!("{} cancelled", request.label())),
+        }
+    }
+
+    // The command a `[control.keys]` binding runs; robotics mode only
+    pub(crate) fn control_key(&self, c: char) -> Option<String> {
+        let control = self
+            .control
+            .as_ref()
+            .filter(|_| self.mode == Mode::Robotics)?;
+        control.key(c).map(str::to_string)
+    }
+
+    // `[prometheus]`: the configured panel reads the scraped metrics instead
+    // of the synthetic ones
+    fn scrape(&mut self, spec: &PrometheusSpec) {
+        let mode = spec.mode.unwrap_or(Mode::AiObservability);
+        match Prometheus::start(spec, &self.links, &self.ingest) {
+            Ok(source) => {
+                let feed = Feed::new(source);
+                self.push_log(format!("{} panel ← {}", mode.name(), feed.describe()));
+                self.feeds[mode as usize] = feed;
+            }
+            Err(e) => self.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                format!(
+                    "[prometheus]: {e}; the {} panel stays synthetic",
+                    mode.name()
+                ),
+            ),
+        }
+    }
+
+    // Streams a gateway's events in: log lines in place of the synthetic
+    // ones, and metric events into the AI observability panel
+    fn subscribe(&mut self, spec: &WebSocketSpec) {
+        let mode = Mode::AiObservability;
+        match WebSocket::start(spec, &self.links, &self.ingest) {
+            Ok(source) => {
+                let feed = Feed::new(source);
+                self.push_log(format!("{} panel ← {}", mode.name(), feed.describe()));
+                self.feeds[mode as usize] = feed;
+                self.listening = true;
+                self.synthetic = false;
+            }
+            Err(e) => self.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                format!(
+                    "[websocket]: {e}; the {} panel stays synthetic",
+                    mode.name()
+                ),
+            ),
+        }
+    }
+
+    // Robot telemetry from an MQTT broker: mapped topics fill the robotics
+    // panel's rows, and other topics' payloads are logged
+    fn telemetry(&mut self, spec: &MqttSpec) {
+        let mode = Mode::Robotics;
+        match Telemetry::start(spec, &self.links, &self.ingest) {
+            Ok(source) => {
+                let feed = Feed::new(source);
+                self.push_log(format!("{} panel ← {}", mode.name(), feed.describe()));
+                self.feeds[mode as usize] = feed;
+                if !spec.topics.is_empty() {
+                    self.listening = true;
+                    self.synthetic = false;
+                }
+            }
+            Err(e) => self.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                format!("[mqtt]: {e}; the {} panel stays synthetic", mode.name()),
+            ),
+        }
+    }
+
+    // ROS 2 through rosbridge: joint states and diagnostics for the strip,
+    // mapped topics into the robotics panel's rows, and the rest logged.
+    // Without mapped topics the panel keeps its feed, [mqtt]'s or not.
+    fn bridge(&mut self, spec: &Ros2Spec) {
+        let mode = Mode::Robotics;
+        match Ros2::start(spec, &self.links, &self.ingest) {
+            Ok((ros2, source)) => {
+                if !spec.metrics.is_empty() {
+                    let feed = Feed::new(source);
+                    self.push_log(format!("{} panel ← {}", mode.name(), feed.describe()));
+                    self.feeds[mode as usize] = feed;
+                } else {
+                    self.push_log(format!("robotics strip ← rosbridge {}", ros2.url));
+                }
+                self.ros2 = Some(ros2);
+                self.listening = true;
+                self.synthetic = false;
+            }
+            Err(e) => self.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                format!("[ros2]: {e}; the {} panel stays synthetic", mode.name()),
+            ),
+        }
+    }
+
+    // A Kafka topic's records in the logs, in place of the synthetic ones,
+    // and its partitions' lag in the Cloud strip
+    fn consume(&mut self, spec: &KafkaSpec) {
+        match Kafka::start(spec, &self.links, &self.ingest) {
+            Ok(kafka) => {
+                let group = match &spec.group {
+                    Some(group) => format!(" for group `{group}`"),
+                    None => String::new(),
+                };
+                self.push_log(format!(
+                    "cloud strip ← kafka `{}`{group} at {}",
+                    spec.topic,
+                    spec.brokers.join(", ")
+                ));
+                self.kafka = Some(kafka);
+                self.listening = true;
+                self.synthetic = false;
+            }
+            Err(e) => self.push_entry(Level::Warn, SourceId::APP, format!("[kafka]: {e}")),
+        }
+    }
+
+    // Fills the AI observability panel from what a listener on `addr`
+    // receives: OTLP exports or StatsD lines
+    fn receive(
+        &mut self,
+        flag: &str,
+        addr: SocketAddr,
+        source: io::Result<impl DataSource + 'static>,
+    ) {
+        let mode = Mode::AiObservability;
+        match source {
+            Ok(source) => {
+                let feed = Feed::new(source);
+                self.push_log(format!("{} panel ← {}", mode.name(), feed.describe()));
+                self.feeds[mode as usize] = feed;
+            }
+            Err(e) => self.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                format!(
+                    "{flag} {addr}: {e}; the {} panel stays synthetic",
+                    mode.name()
+                ),
+            ),
+        }
+    }
+
+    // Streams lines appended to `path` into the logs, under the file's name,
+    // in place of the synthetic ones
+    pub(crate) fn follow(&mut self, path: PathBuf) -> Result<(), String> {
+        if self.follows.contains(&path) {
+            return Err(format!("already following {}", path.display()));
+        }
+        let name = follow::source_name(&path);
+        follow::spawn(path.clone(), self.ingest.sender(&name, follow::QUEUE));
+        self.push_log(format!("following {} as `{name}`", path.display()));
+        self.follows.push(path);
+        self.synthetic = false;
+        Ok(())
+    }
+
+    // Streams a serial port's lines into the logs, in place of the synthetic
+    // ones, and its `key=value` numbers into the robotics panel
+    pub(crate) fn open_serial(&mut self, path: PathBuf, baud: u32) -> Result<(), String> {
+        if let Some((port, _)) = &self.serial {
+            return Err(format!(
+                "{} is open; `serial close` it first",
+                port.path.display()
+            ));
+        }
+        let mode = Mode::Robotics;
+        match Port::open(&path, baud, &self.links, &self.ingest) {
+            Ok((port, source)) => {
+                let feed = Feed::new(source);
+                self.push_log(format!("{} panel ← {}", mode.name(), feed.describe()));
+                self.push_log(format!("reading {} as `{}`", path.display(), port.name));
+                let previous = std::mem::replace(&mut self.feeds[mode as usize], feed);
+                self.serial = Some((port, previous));
+                self.synthetic = false;
+                Ok(())
+            }
+            Err(e) => Err(format!("serial: {e}")),
+        }
+    }
+
+    // Gives the robotics panel back to what fed it before
+    pub(crate) fn close_serial(&mut self) {
+        let Some((port, previous)) = self.serial.take() else {
+            self.push_log("no serial port open. `serial open PATH [BAUD]`");
+            return;
+        };
+        port.close(&self.links);
+        self.feeds[Mode::Robotics as usize] = previous;
+        self.synthetic = self.synthetic_logs();
+        self.push_log(format!("closed {}", port.path.display()));
+    }
+
+    // Streams lines piped into stdin into the logs, in place of the synthetic ones
+    pub(crate) fn read_stdin(&mut self) {
+        stdin::spawn(self.ingest.sender(stdin::NAME, stdin::QUEUE));
+        self.push_log("reading log lines from stdin");
+        self.piped = true;
+        self.synthetic = false;
+    }
+
+    // Takes log lines over TCP, each connection under the sender's address,
+    // in place of the synthetic ones
+    fn listen(&mut self, addr: SocketAddr) {
+        match listen::spawn(addr, self.ingest.clone()) {
+            Ok(bound) => {
+                self.push_log(format!("listening for log lines on tcp://{bound}"));
+                self.listening = true;
+                self.synthetic = false;
+            }
+            Err(e) => self.push_entry(Level::Warn, SourceId::APP, format!("--listen {addr}: {e}")),
+        }
+    }
+
+    // Takes syslog from devices and containers over UDP and TCP, in place of
+    // the synthetic lines
+    fn receive_syslog(&mut self, addr: SocketAddr) {
+        match syslog::spawn(addr, self.ingest.clone()) {
+            Ok(bound) => {
+                self.push_log(format!(
+                    "receiving syslog on udp://{bound} and tcp://{bound}"
+                ));
+                self.listening = true;
+                self.synthetic = false;
+            }
+            Err(e) => self.push_entry(Level::Warn, SourceId::APP, format!("--syslog {addr}: {e}")),
+        }
+    }
+
+    // An empty PATH is the default place, in the runtime directory
+    fn open_socket(&mut self, path: &Path) {
+        let path = match path.as_os_str().is_empty() {
+            true => socket::default_path(&self.dirs.state),
+            false => path.to_path_buf(),
+        };
+        match ControlSocket::bind(path.clone()) {
+            Ok(socket) => {
+                self.push_log(format!("taking commands on {}", path.display()));
+                self.socket = Some(socket);
+            }
+            Err(e) => self.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                format!("--socket {}: {e}", path.display()),
+            ),
+        }
+    }
+
+    // Runs what came in over --socket, like typed commands, and answers each
+    // with what it logged past its own echo
+    fn answer_socket(&mut self) {
+        let Some(socket) = &self.socket else {
+            return;
+        };
+        let requests: Vec<_> = socket.requests().collect();
+        for request in requests {
+            self.replying = Some(Vec::new());
+            self.execute(&request.line);
+            let mut said = self.replying.take().unwrap_or_default();
+            if said
+                .first()
+                .is_some_and(|(_, line)| line.starts_with(":> "))
+            {
+                said.remove(0);
+            }
+            request.answer(said);
+        }
+    }
+
+    // Real log lines outrank made-up ones
+    fn synthetic_logs(&self) -> bool {
+        self.profile.synthetic()
+            && self.follows.is_empty()
+            && !self.piped
+            && !self.listening
+            && !self.journal
+            && self.serial.is_none()
+    }
+
+    pub(crate) fn set_profile(&mut self, profile: Profile) {
+        self.profile = profile;
+        self.tick_rate = profile.tick_rate();
+        self.synthetic = self.synthetic_logs();
+        self.push_log(format!(
+            "profile set → {} ({} ms tick)",
+            profile.name(),
+            self.tick_rate.as_millis()
+        ));
+        self.set_mode(profile.mode());
+    }
+
+    pub fn tick(&mut self) {
+        if let Some(step) = self.sim_step {
+            self.timebase.advance(step);
+        }
+
+        // metrics and uptime are derived from the clock, so every tick is a new frame
+        self.mark_dirty();
+
+        self.drain_ingest();
+        let now = self.uptime();
+        if let Some(machine) = &mut self.machine {
+            machine.drain(now);
+        }
+        if let Some(integrity) = &mut self.integrity {
+            integrity.drain(now);
+        }
+        if let Some(capture) = &mut self.capture {
+            capture.drain();
+        }
+        if let Some(gpu) = &mut self.gpu {
+            gpu.drain();
+        }
+        if let Some(docker) = &mut self.docker {
+            docker.drain();
+        }
+        if let Some(scaling) = &mut self.scaling {
+            scaling.drain(now);
+        }
+        if let Some(tokens) = &mut self.tokens {
+            tokens.drain(now);
+        }
+        if let Some(ros2) = &mut self.ros2 {
+            ros2.drain();
+        }
+        if let Some(kafka) = &mut self.kafka {
+            kafka.drain();
+        }
+        if let Some(pods) = &mut self.pods {
+            pods.drain();
+        }
+        for feed in &mut self.feeds {
+            feed.poll(now);
+        }
+        self.availability
+            .observe(self.uptime(), &self.links.states());
+        while let Ok(label) = self.mark_rx.try_recv() {
+            self.mark(sanitize(&label).into_owned());
+        }
+        self.run_scenario();
+        self.answer_socket();
+        self.idle.check();
+        self.expire_silences();
+        self.check_maintenance();
+        self.check_health();
+        self.check_watchlist();
+
+        if let Some((jump, forward)) = self.timebase.check_drift() {
+            let dir = if forward { "forward" } else { "back" };
+            self.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                format!(
+                    "wall clock jumped {dir} by {}; timestamps rebased",
+                    format_duration(Duration::from_secs(jump.as_secs()))
+                ),
+            );
+        }
+
+        // Occasionally emit a synthetic log line depending on mode
+        if self.synthetic && self.rng.gen_bool(0.12) {
+            let (prefix, msg) = sim::log_line(self.mode, self.uptime().as_secs_f32());
+            let source = self.logs.source(prefix);
+            self.push_entry(Level::Info, source, msg);
+        }
+    }
+
+    // Moves queued source lines into the log buffer; capped per tick so a flood
+    // of input can't stall the frame
+    pub(crate) fn drain_ingest(&mut self) {
+        for line in self.ingest.drain(2048) {
+            // a source named after its peer's address gets masked like a line
+            let name = sanitize(&line.source);
+            let source = self.logs.source(&redact::apply(&self.redact, &name));
+            let message = match sanitize_lines(&line.message) {
+                Cow::Borrowed(_) => line.message,
+                Cow::Owned(clean) => clean,
+            };
+            self.push_entry(line.level, source, message);
+            self.ingested += 1;
+        }
+        self.dropped = self.ingest.dropped();
+    }
+
+    // Steps that came due since the last tick, on the app clock so --sim-step
+    // replays a scenario deterministically
+    fn run_scenario(&mut self) {
+        let now = self.uptime();
+        while let Some(action) = self.scenario.as_mut().and_then(|s| s.poll(now)) {
+            match action {
+                Action::Mode(mode) => self.set_mode(mode),
+                Action::Run(command) => self.execute(&command),
+                Action::Log {
+                    level,
+                    source,
+                    message,
+                } => {
+                    let source = self.logs.source(&source);
+                    self.push_entry(level, source, message);
+                }
+            }
+        }
+    }
+}
+
+// columns moved per Left/Right when panning
+pub(crate) const PAN_STEP: u16 = 4;
+// --deltas: how far back the change is measured, and its column's width
+pub(crate) const TREND_SECS: f32 = 60.0;
+pub(crate) const TREND_W: usize = 9;
+
+// label, raw value, value formatter, bar fill (0..1), bar color
+pub type MetricRow = (&'static str, f32, fn(f32) -> String, f32, Color);
+
+// A host or derived value, which has no panel row to format it
+fn plain(value: f64) -> String {
+    if value.abs() < 10.0 {
+        format!("{value:.2}")
+    } else {
+        format!("{value:.0}")
+    }
+}
+
+// What the main loop does after an event
+pub enum Control {
+    Continue,
+    Quit,
+    Suspend,
+}
+
+// Applies one terminal event to the app
+pub fn handle_event(app: &mut AppState, ev: Event) -> Control {
+    // any input counts as activity; whatever wakes the ambient view is swallowed
+    if matches!(ev, Event::Key(_) | Event::Mouse(_) | Event::Paste(_)) && app.idle.touch() {
+        app.mark_dirty();
+        return Control::Continue;
+    }
+    match ev {
+        Event::Key(key) if key.kind == KeyEventKind::Press => {
+            // IMPORTANT: only act on actual key presses
+            app.mark_dirty();
+            let quit =
+                key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            if let (Some(wizard), false) = (&mut app.wizard, quit) {
+                match wizard.handle_key(key) {
+                    Outcome::Continue => {}
+                    Outcome::Done(choices) => app.finish_setup(Some(choices)),
+                    Outcome::Skip => app.finish_setup(None),
+                }
+                return Control::Continue;
+            }
+            // a setpoint waiting on y/n takes the keys, but an e-stop key
+            // still stops the robot
+            if app.confirm.is_some() && !quit {
+                match key.code {
+                    KeyCode::Char(c)
+                        if app
+                            .control_key(c)
+                            .is_some_and(|line| Request::parse(&line) == Ok(Request::Estop)) =>
+                    {
+                        app.answer_confirm(false);
+                        app.execute("estop");
+                    }
+                    KeyCode::Char('y') | KeyCode::Enter => app.answer_confirm(true),
+                    KeyCode::Char('n') | KeyCode::Esc => app.answer_confirm(false),
+                    _ => {}
+                }
+                return Control::Continue;
+            }
+            match key.code {
+                // Ctrl-Z suspends from anywhere, like other terminal apps (Unix only)
+                KeyCode::Char('z')
+                    if cfg!(unix) && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    return Control::Suspend
+                }
+
+                // global exits (not in command mode)
+                KeyCode::Char('q') if !app.cmd_active => return Control::Quit,
+                KeyCode::Char('c')
+                    if !app.cmd_active && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    return Control::Quit
+                }
+
+                // an open log line: arrows scroll, Esc closes
+                KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown
+                    if !app.cmd_active && app.log_popup.is_some() =>
+                {
+                    if let Some(popup) = &mut app.log_popup {
+                        let last = popup.lines.len().saturating_sub(1);
+                        popup.scroll = match key.code {
+                            KeyCode::Up => popup.scroll.saturating_sub(1),
+                            KeyCode::PageUp => popup.scroll.saturating_sub(10),
+                            KeyCode::Down => (popup.scroll + 1).min(last),
+                            _ => (popup.scroll + 10).min(last),
+                        };
+                    }
+                }
+                KeyCode::Esc if !app.cmd_active && app.log_popup.is_some() => {
+                    app.log_popup = None;
+                }
+
+                // the `requests` viewer: ↑/↓ pick, Enter opens, Esc closes
+                KeyCode::Up | KeyCode::Down if !app.cmd_active && app.request_pick.is_some() => {
+                    let n = app.tokens.as_ref().map_or(0, |t| t.requests.len());
+                    if let Some(pick) = &mut app.request_pick {
+                        *pick = match key.code {
+                            KeyCode::Up => pick.saturating_sub(1),
+                            _ => (*pick + 1).min(n.saturating_sub(1)),
+                        };
+                    }
+                }
+                KeyCode::Enter if !app.cmd_active && app.request_pick.is_some() => {
+                    app.open_request();
+                }
+                KeyCode::Esc if !app.cmd_active && app.request_pick.is_some() => {
+                    app.request_pick = None;
+                }
+
+                // panel focus: Tab/Shift+Tab or Ctrl+arrows walk banner, metrics,
+                // system, logs, and command bar; `l` jumps to the logs
+                KeyCode::Tab | KeyCode::BackTab if app.host_view.is_none() => {
+                    app.move_focus(key.code == KeyCode::BackTab);
+                }
+                KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down
+                    if key.modifiers.contains(KeyModifiers::CONTROL) && app.host_view.is_none() =>
+                {
+                    app.move_focus(matches!(key.code, KeyCode::Left | KeyCode::Up));
+                }
+                KeyCode::Char('l') if !app.cmd_active => {
+                    app.focus = (!app.focused(Focus::Logs)).then_some(Focus::Logs);
+                    app.log_pick = None;
+                }
+
+                // focused log pane: digits filter by source, ↑/↓ pick a JSON or
+                // multi-line entry, Enter opens it
+                KeyCode::Up | KeyCode::Down
+                    if app.focused(Focus::Logs) && app.host_view.is_none() =>
+                {
+                    let n = app.payload_entries().len();
+                    app.log_pick = match (key.code, app.log_pick) {
+                        (_, _) if n == 0 => None,
+                        (KeyCode::Up, None) => Some(0),
+                        (KeyCode::Up, Some(p)) => Some((p + 1).min(n - 1)),
+                        // past the newest: back to following the tail
+                        (_, Some(0) | None) => None,
+                        (_, Some(p)) => Some(p.min(n) - 1),
+                    };
+                }
+                KeyCode::Enter if app.focused(Focus::Logs) && app.host_view.is_none() => {
+                    app.open_payload();
+                }
+                KeyCode::Char(c @ '0'..='9')
+                    if app.focused(Focus::Logs) && !key.modifiers.contains(app.mode_modifier) =>
+                {
+                    app.filter_source(c.to_digit(10).unwrap_or_default());
+                }
+
+                // focused metrics panels: ↑/↓ pick a row, Enter expands it
+                KeyCode::Up | KeyCode::Down
+                    if (app.focused(Focus::Metrics) || app.focused(Focus::System))
+                        && app.host_view.is_none() =>
+                {
+                    let (panel, rows) = if app.focused(Focus::Metrics) {
+                        (Panel::Ai, METRICS.len())
+                    } else {
+                        (Panel::System, app.system_rows(0.0).len())
+                    };
+                    let back = key.code == KeyCode::Up;
+                    app.selected = Selection::step(app.selected, panel, rows, back);
+                }
+                KeyCode::Enter
+                    if (app.focused(Focus::Metrics) || app.focused(Focus::System))
+                        && app.host_view.is_none() =>
+                {
+                    let panel = if app.focused(Focus::Metrics) {
+                        Panel::Ai
+                    } else {
+                        Panel::System
+                    };
+                    app.selected = match app.selected {
+                        Some(sel) if sel.panel == panel => Some(Selection {
+                            expanded: !sel.expanded,
+                            ..sel
+                        }),
+                        // nothing picked yet: open the first row
+                        _ => Some(Selection {
+                            panel,
+                            row: 0,
+                            expanded: true,
+                        }),
+                    };
+                }
+
+                // focused banner: ←/→ step through the modes
+                KeyCode::Left | KeyCode::Right
+                    if app.focused(Focus::Banner) && app.scrub.is_none() =>
+                {
+                    let modes = Mode::value_variants();
+                    let at = modes.iter().position(|m| *m == app.mode).unwrap_or(0);
+                    let next = if key.code == KeyCode::Left {
+                        (at + modes.len() - 1) % modes.len()
+                    } else {
+                        (at + 1) % modes.len()
+                    };
+                    app.set_mode(modes[next]);
+                }
+
+                // mode switching: plain 1–5 outside the command bar; with the
+                // mode modifier (Alt by default) from anywhere, so digits can be typed
+                KeyCode::Char(c @ '1'..='5')
+                    if !app.cmd_active || key.modifiers.contains(app.mode_modifier) =>
+                {
+                    if let Some(mode) = Mode::from_digit(c) {
+                        app.set_mode(mode);
+                    }
+                }
+
+                KeyCode::Char('d') if !app.cmd_active => app.deltas = !app.deltas,
+
+                // educator mode: show/hide the explanation under each metric
+                KeyCode::Char('e') if !app.cmd_active && app.catalog.is_some() => {
+                    app.explain = !app.explain;
+                }
+
+                KeyCode::Esc if !app.cmd_active && app.comparison.is_some() => {
+                    app.comparison = None;
+                }
+                KeyCode::Esc if !app.cmd_active && app.show_health => app.show_health = false,
+
+                // Esc collapses the picked row, then deselects it, then unfocuses
+                KeyCode::Esc
+                    if !app.cmd_active
+                        && app.host_view.is_none()
+                        && app.selected.is_some_and(|sel| {
+                            app.focus.is_none() || app.focus == Some(sel.panel.focus())
+                        }) =>
+                {
+                    app.selected = app
+                        .selected
+                        .filter(|sel| sel.expanded)
+                        .map(|sel| Selection {
+                            expanded: false,
+                            ..sel
+                        });
+                }
+                KeyCode::Esc if !app.cmd_active && app.focus.is_some() => {
+                    app.focus = None;
+                    app.log_pick = None;
+                }
+
+                // time travel through retained history
+                KeyCode::Char('t') if !app.cmd_active => app.toggle_time_travel(),
+                KeyCode::End | KeyCode::Esc if !app.cmd_active && app.scrub.is_some() => {
+                    app.toggle_time_travel();
+                }
+                KeyCode::Left
+                | KeyCode::Right
+                | KeyCode::PageUp
+                | KeyCode::PageDown
+                | KeyCode::Home
+                    if !app.cmd_active && app.scrub.is_some() =>
+                {
+                    let at = app.view_time();
+                    let at = match key.code {
+                        KeyCode::Left => at.saturating_sub(Duration::from_secs(1)),
+                        KeyCode::Right => at + Duration::from_secs(1),
+                        KeyCode::PageUp => at.saturating_sub(Duration::from_secs(60)),
+                        KeyCode::PageDown => at + Duration::from_secs(60),
+                        _ => Duration::ZERO,
+                    };
+                    app.scrub_to(at);
+                }
+
+                // multi-host view (--host)
+                KeyCode::Char('h') if !app.cmd_active => app.toggle_host_view(),
+
+                // release/restore mouse capture for native text selection
+                KeyCode::Char('m') if !app.cmd_active => app.set_mouse_capture(!app.mouse_capture),
+                KeyCode::Char(c) if !app.cmd_active && app.control_key(c).is_some() => {
+                    if let Some(line) = app.control_key(c) {
+                        app.execute(&line);
+                    }
+                }
+
+                // enter command mode with :
+                KeyCode::Char(':') => {
+                    if app.cmd_active {
+                        // already in command mode: treat ':' as input
+                        app.cmd_input.insert_char(':');
+                    } else {
+                        app.cmd_active = true;
+                        app.cmd_input.clear();
+                    }
+                }
+
+                // command-mode controls
+                KeyCode::Esc if app.cmd_active => {
+                    app.cmd_input.clear();
+                    app.cmd_history.reset();
+                    app.cmd_active = false;
+                }
+                KeyCode::Enter if app.cmd_active => {
+                    app.process_command();
+                    app.cmd_active = false;
+                }
+                KeyCode::Backspace if app.cmd_active => app.cmd_input.backspace(),
+                KeyCode::Delete if app.cmd_active => app.cmd_input.delete(),
+                KeyCode::Left if app.cmd_active => app.cmd_input.left(),
+                KeyCode::Right if app.cmd_active => app.cmd_input.right(),
+                KeyCode::Home if app.cmd_active => app.cmd_input.home(),
+                KeyCode::End if app.cmd_active => app.cmd_input.end(),
+                KeyCode::Up | KeyCode::Down if app.cmd_active => {
+                    let line = match key.code {
+                        KeyCode::Up => app.cmd_history.older(),
+                        _ => app.cmd_history.newer(),
+                    };
+                    if let Some(line) = line {
+                        let line = line.to_string();
+                        app.cmd_input.clear();
+                        app.cmd_input.insert_str(&line);
+                    }
+                }
+                KeyCode::Char(c) if app.cmd_active => {
+                    // generic character input only in command mode
+                    app.cmd_input.insert_char(c);
+                }
+
+                // multi-host view: move between tiles, open one, back out
+                KeyCode::Left | KeyCode::Up | KeyCode::Right | KeyCode::Down
+                    if app.host_view.is_some_and(|v| !v.detail) =>
+                {
+                    let n = app.fleet.hosts.len();
+                    if let Some(view) = &mut app.host_view {
+                        view.focus = match key.code {
+                            KeyCode::Left | KeyCode::Up => (view.focus + n - 1) % n,
+                            _ => (view.focus + 1) % n,
+                        };
+                    }
+                }
+                KeyCode::Enter if app.host_view.is_some() => {
+                    if let Some(view) = &mut app.host_view {
+                        view.detail = true;
+                    }
+                }
+                KeyCode::Esc if app.host_view.is_some() => {
+                    app.host_view = app
+                        .host_view
+                        .filter(|v| v.detail)
+                        .map(|v| HostView { detail: false, ..v });
+                }
+
+                // panning an undersized terminal; clamped to the real overflow after the draw
+                KeyCode::Left => app.pan.0 = app.pan.0.saturating_sub(PAN_STEP),
+                KeyCode::Right => app.pan.0 = app.pan.0.saturating_add(PAN_STEP),
+                KeyCode::Up => app.pan.1 = app.pan.1.saturating_sub(1),
+                KeyCode::Down => app.pan.1 = app.pan.1.saturating_add(1),
+
+                _ => {}
+            }
+        }
+        // bracketed paste (and IME commits on some terminals) arrive as one string
+        Event::Paste(text) if app.wizard.is_some() => {
+            if let Some(wizard) = &mut app.wizard {
+                wizard.paste(&text);
+            }
+            app.mark_dirty();
+        }
+        Event::Paste(text) if app.cmd_active => {
+            app.cmd_input.insert_str(&text);
+            app.mark_dirty();
+        }
+        // click or drag along the timeline while time travelling; the
+        // scrubber rect is in dashboard coordinates, which pan shifts
+        Event::Mouse(m)
+            if app.scrub.is_some()
+                && matches!(
+                    m.kind,
+                    MouseEventKind::Down(MouseButton::Left)
+                        | MouseEventKind::Drag(MouseButton::Left)
+                ) =>
+        {
+            let (x, y) = (m.column + app.pan.0, m.row + app.pan.1);
+            if y == app.timeline.y {
+                let (start, end) = (app.history_start(), app.uptime());
+                if let Some(at) = timeline::position(app.timeline, x, start, end) {
+                    app.scrub_to(at);
+                    app.mark_dirty();
+                }
+            }
+        }
+        // panels recompute (and collapse below their minimums) on the next draw
+        Event::Resize(_, _) => app.mark_dirty(),
+        _ => {}
+    }
+    Control::Continue
+}
@@ -0,0 +1,53 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, LineWriter, Write},
+    panic,
+    path::Path,
+    sync::{Mutex, OnceLock},
+};
+
+use chrono::Local;
+
+use crate::logs::Level;
+
+// ai-intui's own diagnostics (its messages, source reconnects, panics) mirrored
+// to --log-file, apart from ingested data, so a report can come with logs.
+// Everything here is a no-op until `init` succeeds.
+static FILE: OnceLock<Mutex<LineWriter<File>>> = OnceLock::new();
+
+pub fn init(path: &Path) -> io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let _ = FILE.set(Mutex::new(LineWriter::new(file)));
+    write(
+        Level::Info,
+        "app",
+        &format!("ai-intui {} started", env!("CARGO_PKG_VERSION")),
+    );
+
+    // record panics before the default hook prints them to the (raw) terminal
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        write(Level::Error, "panic", &info.to_string());
+        default_hook(info);
+    }));
+    Ok(())
+}
+
+pub fn write(level: Level, source: &str, message: &str) {
+    let Some(file) = FILE.get() else {
+        return;
+    };
+    // a poisoned lock only means another thread panicked mid-line; keep logging
+    let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+    let level = match level {
+        Level::Debug => "DEBUG",
+        Level::Info => "INFO",
+        Level::Warn => "WARN",
+        Level::Error => "ERROR",
+    };
+    let _ = writeln!(
+        file,
+        "{} {level:<5} {source}: {message}",
+        Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z")
+    );
+}
@@ -0,0 +1,126 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+// `auth` under a source's table in config.toml: what a locked-down endpoint
+// wants to see, e.g. `[prometheus.auth]` with `bearer = "..."`. Checked as
+// it's read. The certificate paths are for TLS URLs only, and read when the
+// source starts.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct AuthSpec {
+    // `Authorization: Bearer TOKEN`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bearer: Option<String>,
+    // `Authorization: Basic`, or the user name and password MQTT's CONNECT
+    // carries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    // sent as written with every request, e.g. `X-Api-Key`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    // a client certificate and its private key, PEM, for a server that asks
+    // for one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<PathBuf>,
+    // CA certificates, PEM, trusted instead of the public roots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca: Option<PathBuf>,
+}
+
+// `auth` for a source that speaks HTTP: everything but MQTT
+pub fn http<'de, D: Deserializer<'de>>(d: D) -> Result<AuthSpec, D::Error> {
+    let auth = AuthSpec::deserialize(d)?;
+    auth.check().map_err(de::Error::custom)?;
+    Ok(auth)
+}
+
+// `auth` for an MQTT broker, which has no headers to send
+pub fn mqtt<'de, D: Deserializer<'de>>(d: D) -> Result<AuthSpec, D::Error> {
+    let auth = http(d)?;
+    if auth.bearer.is_some() || !auth.headers.is_empty() {
+        return Err(de::Error::custom(
+            "MQTT has no headers to send; use `username` and `password`",
+        ));
+    }
+    Ok(auth)
+}
+
+impl AuthSpec {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // Whether it names files that only a TLS connection uses
+    pub fn wants_tls(&self) -> bool {
+        self.cert.is_some() || self.key.is_some() || self.ca.is_some()
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if self.bearer.is_some() && self.username.is_some() {
+            return Err(
+                "`bearer` and `username` both set the Authorization header; pick one".into(),
+            );
+        }
+        if self.password.is_some() && self.username.is_none() {
+            return Err("`password` needs a `username`".into());
+        }
+        if self.cert.is_some() != self.key.is_some() {
+            return Err("`cert` and `key` go together".into());
+        }
+        for (name, value) in &self.headers {
+            let token = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+            if name.is_empty() || !name.chars().all(token) {
+                return Err(format!("`{name}` isn't a header name"));
+            }
+            if value.chars().any(|c| c.is_control() && c != '\t') {
+                return Err(format!("`{name}`'s value can't hold control characters"));
+            }
+        }
+        Ok(())
+    }
+
+    // Request header lines, each ending in CRLF, for `bearer`, `username`,
+    // and `headers`
+    pub fn header_lines(&self) -> String {
+        let mut lines = String::new();
+        if let Some(token) = &self.bearer {
+            lines.push_str(&format!("Authorization: Bearer {token}\r\n"));
+        }
+        if let Some(username) = &self.username {
+            let credentials = format!("{username}:{}", self.password.as_deref().unwrap_or(""));
+            lines.push_str(&format!(
+                "Authorization: Basic {}\r\n",
+                base64(credentials.as_bytes())
+            ));
+        }
+        for (name, value) in &self.headers {
+            lines.push_str(&format!("{name}: {value}\r\n"));
+        }
+        lines
+    }
+}
+
+// Standard base64, padded: basic credentials, and the WebSocket handshake's
+// key
+pub fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            out.push(match i <= chunk.len() {
+                true => ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char,
+                false => '=',
+            });
+        }
+    }
+    out
+}
@@ -0,0 +1,139 @@
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use ratatui::prelude::*;
+
+use crate::{glyphs::GlyphSet, reconnect::LinkState};
+
+// Up/down history of every network-backed source, sampled from its link state
+// each tick. A source only counts as up while connected; tracking starts once
+// its first connection attempt has settled, so the startup handshake isn't
+// booked as an outage.
+#[derive(Default)]
+pub struct Availability {
+    // state changes on the app clock, oldest first; the first is when
+    // tracking started
+    targets: BTreeMap<Arc<str>, Vec<(Duration, bool)>>,
+}
+
+// One target over the session, for `source` and the report
+pub struct Summary {
+    pub name: Arc<str>,
+    // 0..1
+    pub uptime: f64,
+    pub up_now: bool,
+    // (start, length), oldest first; an ongoing outage runs to `now`
+    pub outages: Vec<(Duration, Duration)>,
+}
+
+impl Availability {
+    pub fn observe(&mut self, now: Duration, states: &[(Arc<str>, LinkState)]) {
+        for (name, state) in states {
+            let up = *state == LinkState::Connected;
+            match self.targets.get_mut(name) {
+                Some(changes) if changes.last().is_some_and(|&(_, was)| was != up) => {
+                    changes.push((now, up));
+                }
+                Some(_) => {}
+                None if *state != LinkState::Connecting => {
+                    self.targets.insert(name.clone(), vec![(now, up)]);
+                }
+                None => {}
+            }
+        }
+    }
+
+    // (start, end, up) spans from when tracking started until `now`
+    fn spans(changes: &[(Duration, bool)], now: Duration) -> Vec<(Duration, Duration, bool)> {
+        changes
+            .iter()
+            .enumerate()
+            .map(|(i, &(start, up))| {
+                let end = changes.get(i + 1).map_or(now, |next| next.0);
+                (start, end.max(start), up)
+            })
+            .collect()
+    }
+
+    pub fn summary(&self, name: &str, now: Duration) -> Option<Summary> {
+        let (name, changes) = self.targets.get_key_value(name)?;
+        let spans = Self::spans(changes, now);
+        let total: Duration = spans.iter().map(|(start, end, _)| *end - *start).sum();
+        let up: Duration = spans
+            .iter()
+            .filter(|s| s.2)
+            .map(|(start, end, _)| *end - *start)
+            .sum();
+        let uptime = if total.is_zero() {
+            // just started tracking: whatever it is right now
+            f64::from(u8::from(spans.last().is_some_and(|s| s.2)))
+        } else {
+            up.as_secs_f64() / total.as_secs_f64()
+        };
+        Some(Summary {
+            name: name.clone(),
+            uptime,
+            up_now: spans.last().is_some_and(|s| s.2),
+            outages: spans
+                .iter()
+                .filter(|s| !s.2)
+                .map(|(start, end, _)| (*start, *end - *start))
+                .collect(),
+        })
+    }
+
+    pub fn summaries(&self, now: Duration) -> Vec<Summary> {
+        self.targets
+            .keys()
+            .filter_map(|name| self.summary(name, now))
+            .collect()
+    }
+
+    // The tracked session squeezed into `width` cells: green while up, red for
+    // any cell that saw an outage
+    pub fn strip(
+        &self,
+        name: &str,
+        now: Duration,
+        width: usize,
+        glyphs: GlyphSet,
+    ) -> Option<Line<'static>> {
+        let changes = self.targets.get(name)?;
+        let spans = Self::spans(changes, now);
+        let start = changes.first()?.0;
+        let total = now.saturating_sub(start);
+        let (up, down) = match glyphs {
+            GlyphSet::Unicode => ('▇', '▁'),
+            GlyphSet::Blocks => ('█', '_'),
+            GlyphSet::Ascii => ('#', '_'),
+        };
+        let cells = (0..width).map(|c| {
+            let from = start + total.mul_f64(c as f64 / width as f64);
+            let to = start + total.mul_f64((c + 1) as f64 / width as f64);
+            let outage = spans
+                .iter()
+                .any(|&(s, e, ok)| !ok && s <= to && (e > from || e == now));
+            if outage {
+                Span::styled(down.to_string(), Style::default().fg(Color::Red))
+            } else {
+                Span::styled(up.to_string(), Style::default().fg(Color::Green))
+            }
+        });
+        Some(Line::from(cells.collect::<Vec<_>>()))
+    }
+
+    // `up 99.2% ▇▇▇▁▇▇`: the session's uptime, then the strip across the rest
+    pub fn line(
+        &self,
+        name: &str,
+        now: Duration,
+        width: usize,
+        glyphs: GlyphSet,
+    ) -> Option<Line<'static>> {
+        let summary = self.summary(name, now)?;
+        let label = format!("up {:.1}% ", summary.uptime * 100.0);
+        let strip = self.strip(name, now, width.saturating_sub(label.len()), glyphs)?;
+        let mut spans = vec![Span::styled(label, Style::default().fg(Color::Gray))];
+        spans.extend(strip.spans);
+        Some(Line::from(spans))
+    }
+}
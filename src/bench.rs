@@ -0,0 +1,114 @@
+use std::{
+    fmt::Write,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{ingest::IngestQueue, logs::Level};
+
+// Producer wakes this often and sends whatever the rate says is owed
+const BATCH_EVERY: Duration = Duration::from_millis(10);
+
+const SERVICES: [&str; 4] = ["gateway", "scheduler", "trainer", "storage"];
+
+// --bench: floods the ingest queue from its own thread at a fixed line rate,
+// like a very busy source would, and keeps the numbers for the exit report
+pub struct Bench {
+    rate: u64,
+    started: Instant,
+    produced: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    frames: u64,
+    frame_total: Duration,
+    frame_max: Duration,
+}
+
+impl Bench {
+    pub fn start(rate: u64, ingest: &IngestQueue) -> Self {
+        // one second of backlog before the oldest lines go
+        let tx = ingest.sender("bench", usize::try_from(rate).unwrap_or(usize::MAX));
+        let produced = Arc::new(AtomicU64::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let started = Instant::now();
+        {
+            let (produced, stop) = (produced.clone(), stop.clone());
+            thread::spawn(move || {
+                let mut rng = StdRng::seed_from_u64(rate);
+                let mut sent = 0u64;
+                while !stop.load(Ordering::Relaxed) {
+                    let due = (started.elapsed().as_secs_f64() * rate as f64) as u64;
+                    let batch: Vec<(Level, String)> = (sent..due)
+                        .map(|n| {
+                            let level = match rng.gen_range(0..100) {
+                                0 => Level::Error,
+                                1..=4 => Level::Warn,
+                                _ => Level::Info,
+                            };
+                            let service = SERVICES[n as usize % SERVICES.len()];
+                            let latency = rng.gen_range(2.0..400.0f32);
+                            (level, format!("{service} req={n} latency={latency:.1}ms"))
+                        })
+                        .collect();
+                    sent = due;
+                    tx.send_batch(batch);
+                    produced.store(sent, Ordering::Relaxed);
+                    thread::sleep(BATCH_EVERY);
+                }
+            });
+        }
+        Self {
+            rate,
+            started,
+            produced,
+            stop,
+            frames: 0,
+            frame_total: Duration::ZERO,
+            frame_max: Duration::ZERO,
+        }
+    }
+
+    pub fn frame(&mut self, took: Duration) {
+        self.frames += 1;
+        self.frame_total += took;
+        self.frame_max = self.frame_max.max(took);
+    }
+
+    // Stops the producer; `ingested` is how many lines reached the log buffer
+    pub fn report(&self, ingested: u64, dropped: u64) -> String {
+        self.stop.store(true, Ordering::Relaxed);
+        let secs = self.started.elapsed().as_secs_f64().max(f64::EPSILON);
+        let produced = self.produced.load(Ordering::Relaxed);
+        let pct = |n: u64| 100.0 * n as f64 / produced.max(1) as f64;
+        let mut out = String::new();
+        let _ = writeln!(out, "bench: {secs:.1}s at {} lines/s target", self.rate);
+        let _ = writeln!(
+            out,
+            "  generated {produced} lines ({:.0}/s)",
+            produced as f64 / secs
+        );
+        let _ = writeln!(
+            out,
+            "  ingested  {ingested} lines ({:.0}/s, {:.1}%)",
+            ingested as f64 / secs,
+            pct(ingested)
+        );
+        let _ = writeln!(out, "  dropped   {dropped} lines ({:.1}%)", pct(dropped));
+        // still waiting in the queue when we quit
+        let queued = produced.saturating_sub(ingested + dropped);
+        let _ = writeln!(out, "  queued    {queued} lines ({:.1}%)", pct(queued));
+        let avg = self.frame_total.as_secs_f64() * 1000.0 / self.frames.max(1) as f64;
+        let _ = write!(
+            out,
+            "  frames    {} ({avg:.2} ms avg, {:.2} ms max)",
+            self.frames,
+            self.frame_max.as_secs_f64() * 1000.0
+        );
+        out
+    }
+}
@@ -0,0 +1,176 @@
+use std::env;
+
+use clap::ValueEnum;
+use ratatui::{buffer::Buffer, style::Color};
+
+use crate::{cli::Cli, glyphs::GlyphSet};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum ColorDepth {
+    /// No colors (also selected by NO_COLOR)
+    #[value(name = "none")]
+    Mono,
+    /// The 16 basic ANSI colors
+    #[value(name = "16")]
+    Ansi16,
+    /// xterm 256-color palette
+    #[value(name = "256")]
+    Ansi256,
+    /// 24-bit RGB
+    #[value(name = "truecolor")]
+    TrueColor,
+}
+
+impl ColorDepth {
+    pub fn label(self) -> &'static str {
+        match self {
+            ColorDepth::Mono => "no color",
+            ColorDepth::Ansi16 => "16 colors",
+            ColorDepth::Ansi256 => "256 colors",
+            ColorDepth::TrueColor => "truecolor",
+        }
+    }
+}
+
+// What the terminal can do, detected once at startup from the environment
+// and overridable from the CLI
+#[derive(Clone, Copy, Debug)]
+pub struct Caps {
+    pub color: ColorDepth,
+    pub glyphs: GlyphSet,
+    pub mouse: bool,
+}
+
+fn term() -> String {
+    env::var("TERM").unwrap_or_default()
+}
+
+fn detect_color() -> ColorDepth {
+    if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return ColorDepth::Mono;
+    }
+    let colorterm = env::var("COLORTERM").unwrap_or_default();
+    if colorterm == "truecolor" || colorterm == "24bit" || env::var_os("WT_SESSION").is_some() {
+        return ColorDepth::TrueColor;
+    }
+    let term = term();
+    if term == "dumb" {
+        ColorDepth::Mono
+    } else if term.contains("256color") {
+        ColorDepth::Ansi256
+    } else {
+        ColorDepth::Ansi16
+    }
+}
+
+// dumb terminals and the Linux console don't report mouse events to us
+fn detect_mouse() -> bool {
+    !matches!(term().as_str(), "dumb" | "linux")
+}
+
+impl Caps {
+    pub fn detect(cli: &Cli) -> Self {
+        Self {
+            color: cli.color.unwrap_or_else(detect_color),
+            glyphs: cli.glyphs.unwrap_or_else(GlyphSet::detect),
+            mouse: !cli.no_mouse && detect_mouse(),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "terminal: {}, {:?} glyphs, mouse {}",
+            self.color.label(),
+            self.glyphs,
+            if self.mouse { "on" } else { "off" }
+        )
+        .to_ascii_lowercase()
+    }
+
+    // Rewrites every cell's colors and line drawing to what the terminal can
+    // show, so draw code can keep using whatever palette and widgets it likes
+    pub fn degrade(&self, buf: &mut Buffer) {
+        if self.color == ColorDepth::TrueColor && self.glyphs == GlyphSet::Unicode {
+            return;
+        }
+        let area = buf.area;
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buf.get_mut(x, y);
+                cell.fg = downsample(cell.fg, self.color);
+                cell.bg = downsample(cell.bg, self.color);
+                if let Some(c) = self.glyphs.plain(cell.symbol()) {
+                    cell.set_char(c);
+                }
+            }
+        }
+    }
+}
+
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+fn indexed_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..=15 => ANSI16[i as usize].1,
+        16..=231 => {
+            let i = i - 16;
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            (level(i / 36), level((i / 6) % 6), level(i % 6))
+        }
+        _ => {
+            let v = 8 + (i - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+fn nearest16(rgb: (u8, u8, u8)) -> Color {
+    let dist = |c: (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(c.0, rgb.0) + d(c.1, rgb.1) + d(c.2, rgb.2)
+    };
+    ANSI16
+        .iter()
+        .min_by_key(|(_, c)| dist(*c))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+fn to_cube(v: u8) -> u8 {
+    if v < 48 {
+        0
+    } else if v < 115 {
+        1
+    } else {
+        (v - 35) / 40
+    }
+}
+
+fn downsample(color: Color, depth: ColorDepth) -> Color {
+    match (color, depth) {
+        (Color::Reset, _) => Color::Reset,
+        (_, ColorDepth::Mono) => Color::Reset,
+        (Color::Rgb(r, g, b), ColorDepth::Ansi256) => {
+            Color::Indexed(16 + 36 * to_cube(r) + 6 * to_cube(g) + to_cube(b))
+        }
+        (Color::Rgb(r, g, b), ColorDepth::Ansi16) => nearest16((r, g, b)),
+        (Color::Indexed(i), ColorDepth::Ansi16) if i > 15 => nearest16(indexed_rgb(i)),
+        (c, _) => c,
+    }
+}
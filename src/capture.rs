@@ -0,0 +1,467 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use ratatui::{prelude::*, widgets::Paragraph};
+
+use crate::{
+    chrome::Chrome,
+    ingest::SourceSender,
+    layout::{fit, pad},
+    logs::Level,
+};
+
+// What new destinations are logged under
+pub const NAME: &str = "capture";
+pub const QUEUE: usize = 256;
+// The capture thread hands over a tally this often
+const FLUSH: Duration = Duration::from_secs(1);
+// Tallies summed for the table
+const WINDOW: usize = 60;
+// Destinations seen this soon after starting are the baseline, not news
+const LEARN: Duration = Duration::from_secs(10);
+// External destinations remembered; past this, new ones are no longer reported
+const MAX_SEEN: usize = 100_000;
+// Bytes of each frame read: enough for the link, IP, and port headers, never
+// the payload
+const SNAPLEN: usize = 128;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Proto {
+    Tcp,
+    Udp,
+    Icmp,
+    Other,
+}
+
+const PROTOS: [(Proto, &str); 4] = [
+    (Proto::Tcp, "tcp"),
+    (Proto::Udp, "udp"),
+    (Proto::Icmp, "icmp"),
+    (Proto::Other, "other"),
+];
+
+// What one frame's headers say
+struct Packet {
+    proto: Proto,
+    // None for frames that aren't IP (ARP, LLDP, ...)
+    hosts: Option<(IpAddr, IpAddr)>,
+    dst_port: Option<u16>,
+}
+
+// Traffic seen during one FLUSH
+#[derive(Default)]
+struct Tally {
+    bytes: u64,
+    // bytes per protocol, in PROTOS order
+    protocols: [u64; 4],
+    // bytes each address sent or received
+    hosts: HashMap<IpAddr, u64>,
+    new_destinations: usize,
+}
+
+// --capture: a live summary of an interface's traffic for data forensics
+// mode. Only headers are read; counts are all that's kept.
+pub struct Capture {
+    pub interface: String,
+    rx: Receiver<Tally>,
+    tallies: VecDeque<Tally>,
+    pub new_destinations: usize,
+}
+
+impl Capture {
+    // Opens the interface now, so a missing one or missing privileges are
+    // reported at startup
+    pub fn start(interface: &str, log: SourceSender) -> io::Result<Self> {
+        let socket = sys::open(interface)?;
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || run(socket, &tx, &log));
+        Ok(Self {
+            interface: interface.to_string(),
+            rx,
+            tallies: VecDeque::new(),
+            new_destinations: 0,
+        })
+    }
+
+    pub fn drain(&mut self) {
+        while let Ok(tally) = self.rx.try_recv() {
+            self.new_destinations += tally.new_destinations;
+            if self.tallies.len() == WINDOW {
+                self.tallies.pop_front();
+            }
+            self.tallies.push_back(tally);
+        }
+    }
+}
+
+fn run(socket: sys::Socket, tx: &Sender<Tally>, log: &SourceSender) {
+    let started = Instant::now();
+    let mut seen: HashSet<IpAddr> = HashSet::new();
+    let mut tally = Tally::default();
+    let mut flushed = Instant::now();
+    let mut frame = [0; SNAPLEN];
+    loop {
+        match socket.recv(&mut frame) {
+            Ok(Some((len, ethernet))) => {
+                let packet = parse(&frame[..len.min(SNAPLEN)], ethernet);
+                let i = PROTOS.iter().position(|p| p.0 == packet.proto).unwrap();
+                tally.bytes += len as u64;
+                tally.protocols[i] += len as u64;
+                if let Some((src, dst)) = packet.hosts {
+                    *tally.hosts.entry(src).or_default() += len as u64;
+                    *tally.hosts.entry(dst).or_default() += len as u64;
+                    // learned destinations are remembered too, so they
+                    // don't show up as new later
+                    let new = external(dst) && seen.len() < MAX_SEEN && seen.insert(dst);
+                    if new && started.elapsed() >= LEARN {
+                        tally.new_destinations += 1;
+                        let port = packet.dst_port.map(|p| format!("/{p}")).unwrap_or_default();
+                        log.send(
+                            Level::Warn,
+                            format!(
+                                "new external destination {dst} ({}{port} from {src})",
+                                PROTOS[i].1
+                            ),
+                        );
+                    }
+                }
+            }
+            // our own copy of a looped-back frame, or the read timed out
+            Ok(None) => {}
+            Err(e) => {
+                log.send(Level::Error, format!("capture stopped: {e}"));
+                return;
+            }
+        }
+        if flushed.elapsed() >= FLUSH {
+            flushed = Instant::now();
+            if tx.send(std::mem::take(&mut tally)).is_err() {
+                // the app is gone
+                return;
+            }
+        }
+    }
+}
+
+// Link, network, and transport headers; anything cut short counts as other
+fn parse(frame: &[u8], ethernet: bool) -> Packet {
+    let other = Packet {
+        proto: Proto::Other,
+        hosts: None,
+        dst_port: None,
+    };
+    let mut ip = frame;
+    if ethernet {
+        let Some(mut ethertype) = frame.get(12..14).map(|b| u16::from_be_bytes([b[0], b[1]]))
+        else {
+            return other;
+        };
+        let mut start = 14;
+        // 802.1Q and 802.1ad tags
+        while ethertype == 0x8100 || ethertype == 0x88a8 {
+            let Some(b) = frame.get(start + 2..start + 4) else {
+                return other;
+            };
+            ethertype = u16::from_be_bytes([b[0], b[1]]);
+            start += 4;
+        }
+        if ethertype != 0x0800 && ethertype != 0x86dd {
+            return other;
+        }
+        ip = &frame[start.min(frame.len())..];
+    }
+    let (proto, hosts, transport) = match ip.first().map(|b| b >> 4) {
+        Some(4) if ip.len() >= 20 => {
+            let header = usize::from(ip[0] & 0x0f) * 4;
+            let src = Ipv4Addr::new(ip[12], ip[13], ip[14], ip[15]);
+            let dst = Ipv4Addr::new(ip[16], ip[17], ip[18], ip[19]);
+            (ip[9], (src.into(), dst.into()), ip.get(header..))
+        }
+        Some(6) if ip.len() >= 40 => {
+            let addr = |at: usize| {
+                let mut octets = [0; 16];
+                octets.copy_from_slice(&ip[at..at + 16]);
+                IpAddr::from(Ipv6Addr::from(octets))
+            };
+            (ip[6], (addr(8), addr(24)), ip.get(40..))
+        }
+        _ => return other,
+    };
+    let proto = match proto {
+        6 => Proto::Tcp,
+        17 => Proto::Udp,
+        1 | 58 => Proto::Icmp,
+        _ => Proto::Other,
+    };
+    let dst_port = match proto {
+        Proto::Tcp | Proto::Udp => transport
+            .and_then(|t| t.get(2..4))
+            .map(|b| u16::from_be_bytes([b[0], b[1]])),
+        _ => None,
+    };
+    Packet {
+        proto,
+        hosts: Some(hosts),
+        dst_port,
+    }
+}
+
+// Whether an address is outside this network: not private, loopback,
+// link-local, multicast, or unspecified
+fn external(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(v4) => {
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_multicast()
+                || v4.is_broadcast()
+                || v4.is_unspecified())
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            // fc00::/7 unique local, fe80::/10 link-local
+            !(v6.is_loopback()
+                || v6.is_multicast()
+                || v6.is_unspecified()
+                || first & 0xfe00 == 0xfc00
+                || first & 0xffc0 == 0xfe80)
+        }
+    }
+}
+
+// `1.2 MB`, `840 KB`, `12 B`
+fn bytes(n: f64) -> String {
+    match n {
+        n if n >= 1e9 => format!("{:.1} GB", n / 1e9),
+        n if n >= 1e6 => format!("{:.1} MB", n / 1e6),
+        n if n >= 1e3 => format!("{:.0} KB", n / 1e3),
+        n => format!("{n:.0} B"),
+    }
+}
+
+// The data forensics capture table: throughput, protocol mix, and the top
+// talkers over the last minute
+pub fn draw(buf: &mut Buffer, area: Rect, capture: &Capture, chrome: Chrome) {
+    let inner = area.width.saturating_sub(2) as usize;
+    let total: u64 = capture.tallies.iter().map(|t| t.bytes).sum();
+    let mut hosts: HashMap<IpAddr, u64> = HashMap::new();
+    let mut protocols = [0; 4];
+    for tally in &capture.tallies {
+        for (host, n) in &tally.hosts {
+            *hosts.entry(*host).or_default() += n;
+        }
+        for (sum, n) in protocols.iter_mut().zip(tally.protocols) {
+            *sum += n;
+        }
+    }
+
+    let mut lines = Vec::new();
+    if total == 0 {
+        lines.push(Line::styled(
+            "no traffic yet",
+            Style::default().fg(Color::DarkGray),
+        ));
+    } else {
+        let mix: Vec<String> = PROTOS
+            .iter()
+            .zip(protocols)
+            .filter(|(_, n)| *n > 0)
+            .map(|((_, name), n)| format!("{name} {:.0}%", n as f64 * 100.0 / total as f64))
+            .collect();
+        lines.push(Line::styled(
+            fit(&mix.join(" · "), inner).0.to_string(),
+            Style::default().fg(Color::Gray),
+        ));
+        let mut top: Vec<(IpAddr, u64)> = hosts.into_iter().collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let rows = area.height.saturating_sub(3) as usize;
+        for (host, n) in top.into_iter().take(rows) {
+            let size = bytes(n as f64);
+            let host_w = inner.saturating_sub(size.len() + 1);
+            let host_name = host.to_string();
+            let (name, name_pad) = fit(&host_name, host_w);
+            let color = match external(host) {
+                true => Color::Yellow,
+                false => Color::Gray,
+            };
+            lines.push(Line::from(vec![
+                Span::styled(name.to_string(), Style::default().fg(color)),
+                Span::raw(name_pad),
+                Span::raw(pad(1)),
+                Span::styled(size, Style::default().add_modifier(Modifier::BOLD)),
+            ]));
+        }
+    }
+
+    let secs = capture.tallies.len().max(1) as f64 * FLUSH.as_secs_f64();
+    let mut title = format!(
+        "capture • {} • {}/s",
+        capture.interface,
+        bytes(total as f64 / secs)
+    );
+    if capture.new_destinations > 0 {
+        title.push_str(&format!(" • {} new", capture.new_destinations));
+    }
+    Paragraph::new(lines)
+        .block(
+            chrome
+                .block()
+                .border_style(Style::default().fg(Color::DarkGray))
+                .title(Span::styled(
+                    title,
+                    Style::default()
+                        .fg(Color::LightGreen)
+                        .add_modifier(Modifier::BOLD),
+                )),
+        )
+        .render(area, buf);
+}
+
+// A raw AF_PACKET socket bound to one interface
+#[cfg(all(feature = "capture", target_os = "linux"))]
+mod sys {
+    use std::{
+        ffi::CString,
+        io, mem,
+        os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    };
+
+    pub struct Socket {
+        fd: OwnedFd,
+    }
+
+    pub fn open(interface: &str) -> io::Result<Socket> {
+        let name = CString::new(interface)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "bad interface name"))?;
+        // SAFETY: plain libc calls on a NUL-terminated name
+        let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if index == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no interface `{interface}`"),
+            ));
+        }
+        let all = (libc::ETH_P_ALL as u16).to_be();
+        // SAFETY: socket() has no pointer arguments; the fd is owned from here on
+        let fd = unsafe { libc::socket(libc::AF_PACKET, libc::SOCK_RAW, i32::from(all)) };
+        if fd < 0 {
+            let e = io::Error::last_os_error();
+            return Err(match e.kind() {
+                io::ErrorKind::PermissionDenied => io::Error::new(
+                    e.kind(),
+                    format!("{e} (capturing needs root or CAP_NET_RAW)"),
+                ),
+                _ => e,
+            });
+        }
+        // SAFETY: fd is a fresh socket nobody else owns
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        // SAFETY: sockaddr_ll is plain data, valid zeroed
+        let mut addr: libc::sockaddr_ll = unsafe { mem::zeroed() };
+        addr.sll_family = libc::AF_PACKET as u16;
+        addr.sll_protocol = all;
+        addr.sll_ifindex = index as i32;
+        // SAFETY: addr outlives the call and the length matches its type
+        let bound = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const libc::sockaddr_ll as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t,
+            )
+        };
+        if bound < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // time out reads so tallies are handed over on a quiet link too
+        let timeout = libc::timeval {
+            tv_sec: 0,
+            tv_usec: 250_000,
+        };
+        // SAFETY: timeout outlives the call and the length matches its type
+        let set = unsafe {
+            libc::setsockopt(
+                fd.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                &timeout as *const libc::timeval as *const libc::c_void,
+                mem::size_of::<libc::timeval>() as libc::socklen_t,
+            )
+        };
+        if set < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Socket { fd })
+    }
+
+    impl Socket {
+        // The next frame's full length (only `frame.len()` of it is copied)
+        // and whether it starts with an Ethernet header; None on a timeout,
+        // or for the outgoing copy of a frame on loopback, which is also
+        // seen coming in
+        pub fn recv(&self, frame: &mut [u8]) -> io::Result<Option<(usize, bool)>> {
+            // SAFETY: as in open()
+            let mut from: libc::sockaddr_ll = unsafe { mem::zeroed() };
+            let mut len = mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t;
+            // SAFETY: frame and from outlive the call, with their real lengths
+            let n = unsafe {
+                libc::recvfrom(
+                    self.fd.as_raw_fd(),
+                    frame.as_mut_ptr() as *mut libc::c_void,
+                    frame.len(),
+                    libc::MSG_TRUNC,
+                    &mut from as *mut libc::sockaddr_ll as *mut libc::sockaddr,
+                    &mut len,
+                )
+            };
+            if n < 0 {
+                let e = io::Error::last_os_error();
+                return match e.kind() {
+                    io::ErrorKind::WouldBlock
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::Interrupted => Ok(None),
+                    _ => Err(e),
+                };
+            }
+            let loopback = from.sll_hatype == libc::ARPHRD_LOOPBACK;
+            if loopback && from.sll_pkttype == libc::PACKET_OUTGOING {
+                return Ok(None);
+            }
+            // tun devices and the like carry bare IP packets
+            let ethernet = from.sll_hatype != libc::ARPHRD_NONE;
+            Ok(Some((n as usize, ethernet)))
+        }
+    }
+}
+
+#[cfg(not(all(feature = "capture", target_os = "linux")))]
+mod sys {
+    use std::io;
+
+    pub enum Socket {}
+
+    pub fn open(_interface: &str) -> io::Result<Socket> {
+        let why = match cfg!(target_os = "linux") {
+            true => "this build doesn't include it; rebuild with `--features capture`",
+            false => "it's only supported on Linux",
+        };
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("no packet capture: {why}"),
+        ))
+    }
+
+    impl Socket {
+        pub fn recv(&self, _frame: &mut [u8]) -> io::Result<Option<(usize, bool)>> {
+            match *self {}
+        }
+    }
+}
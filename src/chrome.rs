@@ -0,0 +1,137 @@
+use ratatui::{
+    layout::Alignment,
+    widgets::{Block, BorderType, Borders},
+};
+use serde::{Deserialize, Serialize};
+
+// `[panels]` in config.toml: how each panel is framed. `borders` and `title`
+// at the top apply to every panel; a panel's own table overrides them.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct PanelsSpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub borders: Option<BorderStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<TitleAlign>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banner: Option<PanelStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<PanelStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<PanelStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logs: Option<PanelStyle>,
+    // the mode's strip over the logs: integrity, capture, scaling, and so on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strip: Option<PanelStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watchlist: Option<PanelStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<PanelStyle>,
+}
+
+// One panel's table
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PanelStyle {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub borders: Option<BorderStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<TitleAlign>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BorderStyle {
+    #[default]
+    Plain,
+    Rounded,
+    Double,
+    Thick,
+    // no frame; the title keeps its own row
+    #[serde(rename = "none")]
+    Hidden,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+// A panel's resolved style
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Chrome {
+    pub borders: BorderStyle,
+    pub title: TitleAlign,
+}
+
+impl Chrome {
+    // An empty block in this style, for the panel to add its title and
+    // border color to
+    pub fn block<'a>(self) -> Block<'a> {
+        let block = Block::default().title_alignment(match self.title {
+            TitleAlign::Left => Alignment::Left,
+            TitleAlign::Center => Alignment::Center,
+            TitleAlign::Right => Alignment::Right,
+        });
+        match self.border_type() {
+            Some(kind) => block.borders(Borders::ALL).border_type(kind),
+            None => block,
+        }
+    }
+
+    // The banner's rule under it, in this style's line
+    pub fn rule<'a>(self) -> Block<'a> {
+        match self.border_type() {
+            Some(kind) => Block::default().borders(Borders::BOTTOM).border_type(kind),
+            None => Block::default(),
+        }
+    }
+
+    fn border_type(self) -> Option<BorderType> {
+        match self.borders {
+            BorderStyle::Plain => Some(BorderType::Plain),
+            BorderStyle::Rounded => Some(BorderType::Rounded),
+            BorderStyle::Double => Some(BorderType::Double),
+            BorderStyle::Thick => Some(BorderType::Thick),
+            BorderStyle::Hidden => None,
+        }
+    }
+}
+
+// Every panel's chrome, resolved once at startup
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Styles {
+    pub banner: Chrome,
+    pub metrics: Chrome,
+    pub system: Chrome,
+    pub logs: Chrome,
+    pub strip: Chrome,
+    pub watchlist: Chrome,
+    pub command: Chrome,
+}
+
+impl PanelsSpec {
+    pub fn resolve(&self) -> Styles {
+        let each = |own: Option<PanelStyle>| {
+            let own = own.unwrap_or_default();
+            Chrome {
+                borders: own.borders.or(self.borders).unwrap_or_default(),
+                title: own.title.or(self.title).unwrap_or_default(),
+            }
+        };
+        Styles {
+            banner: each(self.banner),
+            metrics: each(self.metrics),
+            system: each(self.system),
+            logs: each(self.logs),
+            strip: each(self.strip),
+            watchlist: each(self.watchlist),
+            command: each(self.command),
+        }
+    }
+}
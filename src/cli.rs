@@ -0,0 +1,353 @@
+use std::{collections::BTreeMap, net::SocketAddr, path::PathBuf};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use crossterm::event::KeyModifiers;
+
+use crate::{
+    app::Mode, caps::ColorDepth, chrome::PanelsSpec, control::ControlSpec, derived::Formula,
+    glyphs::GlyphSet, health::HealthSpec, hosts::HostSpec, kafka::KafkaSpec, kube::KubeSpec,
+    maintenance::Window, mqtt::MqttSpec, notify::NotifierSpec, profile::Profile,
+    prometheus::PrometheusSpec, redact::RedactSpec, ros2::Ros2Spec, sampling::SamplingSpec,
+    tokens::ProxySpec, websocket::WebSocketSpec,
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "ai-intui", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// User settings file to read (default: config.toml in the config directory)
+    #[arg(long, value_name = "FILE", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Preset for start mode, tick rate, and whether synthetic logs run; it picks no data sources
+    #[arg(long, value_enum, value_name = "NAME", default_value = "demo")]
+    pub profile: Profile,
+
+    /// Mode to start in (default: the profile's)
+    #[arg(long, value_enum, value_name = "MODE")]
+    pub mode: Option<Mode>,
+
+    /// Maximum number of log lines kept in memory
+    #[arg(long, value_name = "LINES", default_value_t = 512)]
+    pub log_capacity: usize,
+
+    /// Approximate memory budget for retained logs and their search index (e.g. 512K, 64M); 0 disables it
+    #[arg(long, value_name = "SIZE", default_value = "0", value_parser = parse_bytes)]
+    pub log_budget: usize,
+
+    /// Collapse a log line into the one before it when it repeats within this many ms (0 disables)
+    #[arg(long, value_name = "MS", default_value_t = 5000)]
+    pub dedup_window: u64,
+
+    /// How alike two lines must be to collapse, as the share of words they agree on (1 = identical only)
+    #[arg(long, value_name = "0..1", default_value_t = 0.75, value_parser = parse_similarity)]
+    pub dedup_similarity: f32,
+
+    /// Directory for config, themes, and layouts (default: platform config dir, e.g. ~/.config/ai-intui)
+    #[arg(long, value_name = "DIR")]
+    pub config_dir: Option<PathBuf>,
+
+    /// Directory for history and recordings (default: platform data dir, e.g. ~/.local/share/ai-intui)
+    #[arg(long, value_name = "DIR")]
+    pub data_dir: Option<PathBuf>,
+
+    /// Append ai-intui's own diagnostics (its messages, source reconnects, panics) to FILE
+    #[arg(long, value_name = "FILE")]
+    pub log_file: Option<PathBuf>,
+
+    /// Record per-panel draw and event-handling timings, written to FILE on exit
+    #[arg(long, value_name = "FILE")]
+    pub profile_frames: Option<PathBuf>,
+
+    /// Frame time budget in ms; frames that keep exceeding it make the UI shed work (0 disables)
+    #[arg(long, value_name = "MS", default_value_t = 50)]
+    pub frame_budget: u64,
+
+    /// Render inline in the normal terminal buffer (ROWS high, default 24) instead of the alternate screen
+    #[arg(long, value_name = "ROWS", num_args = 0..=1, default_missing_value = "24")]
+    pub inline: Option<u16>,
+
+    /// Bar glyphs to draw with; blocks and ascii also draw borders and charts in ASCII (default: detected from the terminal)
+    #[arg(long, value_enum, value_name = "SET")]
+    pub glyphs: Option<GlyphSet>,
+
+    /// Color depth to render with (default: detected from COLORTERM/TERM/NO_COLOR)
+    #[arg(long, value_enum, value_name = "DEPTH")]
+    pub color: Option<ColorDepth>,
+
+    /// Hold metric values until they change materially, and draw bars in whole cells
+    #[arg(long)]
+    pub reduced_motion: bool,
+
+    /// Educator mode: explain each metric on the line under it (`e` toggles)
+    #[arg(long)]
+    pub educator: bool,
+
+    /// Show each metric's change over the last minute next to its value (`d` toggles)
+    #[arg(long)]
+    pub deltas: bool,
+
+    /// Attention mode: while a watched metric is critical, move its panel first and expand its row
+    #[arg(long)]
+    pub attention: bool,
+
+    /// Seconds without input before switching to the ambient screensaver view (0 disables)
+    #[arg(long, value_name = "SECS", default_value_t = 0)]
+    pub idle_after: u64,
+
+    /// Don't capture the mouse
+    #[arg(long)]
+    pub no_mouse: bool,
+
+    /// Disable commands that change data, sources, or saved state (for shared terminals and wall displays)
+    #[arg(long)]
+    pub read_only: bool,
+
+    /// Watch a remote agent in the multi-host view (`h`); repeat for each host
+    #[arg(long, value_name = "NAME=URL")]
+    pub host: Vec<HostSpec>,
+
+    /// Stream lines appended to FILE into the logs pane, like `tail -F`; repeat for each file
+    #[arg(long, value_name = "FILE")]
+    pub follow: Vec<PathBuf>,
+
+    /// Stream the systemd journal into the logs pane through journalctl (Linux), its priorities as levels; every unit unless UNIT (comma-separated) is given
+    #[arg(long, value_name = "UNIT", num_args = 0..=1, default_missing_value = "all")]
+    pub journal: Option<String>,
+
+    /// Hash files under PATH and alert when they change, with a table in data forensics mode; repeat for each path
+    #[arg(long, value_name = "PATH")]
+    pub integrity: Vec<PathBuf>,
+
+    /// Summarize live traffic on IFACE in data forensics mode (needs the `capture` feature, Linux, and CAP_NET_RAW)
+    #[arg(long, value_name = "IFACE")]
+    pub capture: Option<String>,
+
+    /// List the processes using NVIDIA GPUs, with their memory and SM share, in AI observability mode (reads NVML through nvidia-smi)
+    #[arg(long)]
+    pub gpu_processes: bool,
+
+    /// List the running Docker containers with live CPU, memory, and network and disk IO in cloud mode, in place of the system panel (reads `docker stats`)
+    #[arg(long)]
+    pub docker: bool,
+
+    /// Watch Kubernetes scaling and scheduling events through kubectl, on a timeline in cloud mode; all namespaces unless NAMESPACE is given
+    #[arg(long, value_name = "NAMESPACE", num_args = 0..=1, default_missing_value = "all")]
+    pub kube_events: Option<String>,
+
+    /// Proxy an OpenAI-compatible server (vLLM, llama.cpp, ...) for clients on PORT, charting each streamed response's token rate, TTFT, and stalls in AI observability mode
+    #[arg(long, value_name = "PORT=URL")]
+    pub llm_proxy: Option<ProxySpec>,
+
+    /// Play a kiosk scenario (timed mode switches, commands, and log lines) on loop
+    #[arg(long, value_name = "FILE")]
+    pub scenario: Option<PathBuf>,
+
+    /// Accept markers over HTTP on ADDR: `POST /mark` with the label as the body (e.g. 127.0.0.1:7171)
+    #[arg(long, value_name = "ADDR")]
+    pub mark_listen: Option<SocketAddr>,
+
+    /// Take newline-delimited log lines (plain or JSON) over TCP on ADDR, each connection under the sender's address (e.g. 127.0.0.1:7777)
+    #[arg(long, value_name = "ADDR")]
+    pub listen: Option<SocketAddr>,
+
+    /// Receive syslog (RFC 3164 or 5424) over UDP and TCP on ADDR, or 127.0.0.1:PORT given a port, each device's messages under its host name
+    #[arg(long, value_name = "ADDR|PORT", value_parser = parse_http_addr)]
+    pub syslog: Option<SocketAddr>,
+
+    /// Take log lines and metric samples pushed over HTTP on ADDR, or 127.0.0.1:PORT given a port: `POST /log`, `POST /metric`, and `POST /mark`
+    #[arg(long, value_name = "ADDR|PORT", value_parser = parse_http_addr)]
+    pub http: Option<SocketAddr>,
+
+    /// Take `:` prompt commands from other processes over a Unix socket at PATH (default $XDG_RUNTIME_DIR/ai-intui.sock)
+    #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = "")]
+    pub socket: Option<PathBuf>,
+
+    /// Receive OTLP/HTTP traces and metrics on ADDR into the AI observability panel (e.g. 127.0.0.1:4318)
+    #[arg(long, value_name = "ADDR")]
+    pub otlp: Option<SocketAddr>,
+
+    /// Receive StatsD counters, gauges, and timers over UDP on ADDR into the AI observability panel (e.g. 127.0.0.1:8125)
+    #[arg(long, value_name = "ADDR")]
+    pub statsd: Option<SocketAddr>,
+
+    /// Stress test: flood the pipeline with LINES per second (default 50000) and report throughput on exit
+    #[arg(long, value_name = "LINES", num_args = 0..=1, default_missing_value = "50000")]
+    pub bench: Option<u64>,
+
+    /// Show synthetic system metrics instead of this machine's CPU, memory, disk, and network
+    #[arg(long)]
+    pub simulate: bool,
+
+    /// Seed the synthetic generator for reproducible sessions
+    #[arg(long, value_name = "N")]
+    pub seed: Option<u64>,
+
+    /// Advance synthetic data on a virtual clock by MS per tick, independent of real time
+    #[arg(long, value_name = "MS")]
+    pub sim_step: Option<u64>,
+
+    /// Modifier held with 1–5 to switch modes while the command bar is active
+    #[arg(long, value_enum, value_name = "KEY", default_value = "alt")]
+    pub mode_modifier: ModeModifier,
+
+    // `[health]` from the config file; there's no flag for it
+    #[arg(skip)]
+    pub health: Option<HealthSpec>,
+
+    // `[prometheus]` from the config file, likewise
+    #[arg(skip)]
+    pub prometheus: Option<PrometheusSpec>,
+
+    // `[control]` from the config file, likewise
+    #[arg(skip)]
+    pub control: Option<ControlSpec>,
+
+    // `[websocket]` from the config file, likewise
+    #[arg(skip)]
+    pub websocket: Option<WebSocketSpec>,
+
+    // `[mqtt]` from the config file, likewise
+    #[arg(skip)]
+    pub mqtt: Option<MqttSpec>,
+
+    // `[ros2]` from the config file, likewise
+    #[arg(skip)]
+    pub ros2: Option<Ros2Spec>,
+
+    // `[kafka]` from the config file, likewise
+    #[arg(skip)]
+    pub kafka: Option<KafkaSpec>,
+
+    // `[kube]` from the config file, likewise
+    #[arg(skip)]
+    pub kube: Option<KubeSpec>,
+
+    // `[derived]` from the config file, likewise
+    #[arg(skip)]
+    pub derived: Option<BTreeMap<String, Formula>>,
+
+    // `[programs]` from the config file, likewise
+    #[arg(skip)]
+    pub programs: Option<BTreeMap<String, PathBuf>>,
+
+    // `[panels]` from the config file, likewise
+    #[arg(skip)]
+    pub panels: Option<PanelsSpec>,
+
+    // `[llm-requests]` from the config file, likewise
+    #[arg(skip)]
+    pub llm_requests: Option<SamplingSpec>,
+
+    // `[redact]` from the config file, likewise
+    #[arg(skip)]
+    pub redact: Option<RedactSpec>,
+
+    // `[[maintenance]]` from the config file, likewise
+    #[arg(skip)]
+    pub maintenance: Option<Vec<Window>>,
+
+    // `[[notify]]` from the config file, likewise
+    #[arg(skip)]
+    pub notify: Option<Vec<NotifierSpec>>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Print a shell completion script to stdout
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print the man page (roff) to stdout
+    Man,
+    /// Stream this machine's metrics as JSON lines, for another ai-intui's --host
+    Agent {
+        /// Milliseconds between samples
+        #[arg(long, value_name = "MS", default_value_t = 1000)]
+        interval: u64,
+    },
+    /// Validate the settings files without starting the dashboard
+    Check,
+    /// Inspect the settings files
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Run an input script against an in-memory terminal (end-to-end tests)
+    #[cfg(feature = "e2e")]
+    #[command(hide = true)]
+    Drive { script: PathBuf },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// List the settings files in precedence order
+    Show {
+        /// Print the effective settings after merging every file and flag instead
+        #[arg(long)]
+        resolved: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ModeModifier {
+    Alt,
+    Ctrl,
+}
+
+impl ModeModifier {
+    pub fn key_modifiers(self) -> KeyModifiers {
+        match self {
+            ModeModifier::Alt => KeyModifiers::ALT,
+            ModeModifier::Ctrl => KeyModifiers::CONTROL,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ModeModifier::Alt => "Alt",
+            ModeModifier::Ctrl => "Ctrl",
+        }
+    }
+}
+
+pub fn parse_similarity(raw: &str) -> Result<f32, String> {
+    raw.trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|s| (0.0..=1.0).contains(s))
+        .ok_or_else(|| format!("invalid similarity `{raw}` (expected 0 to 1, e.g. 0.75)"))
+}
+
+// A bare port listens on localhost only, since there's no auth
+pub fn parse_http_addr(raw: &str) -> Result<SocketAddr, String> {
+    match raw.trim().parse::<u16>() {
+        Ok(port) => Ok(([127, 0, 0, 1], port).into()),
+        Err(_) => raw
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid address `{raw}` (expected e.g. 8080 or 0.0.0.0:8080)")),
+    }
+}
+
+// Accepts plain bytes or a K/M/G suffix (powers of 1024)
+pub fn parse_bytes(raw: &str) -> Result<usize, String> {
+    let s = raw.trim().to_ascii_uppercase();
+    let s = s.strip_suffix('B').unwrap_or(&s);
+    let (digits, mult) = match s.chars().last() {
+        Some('K') => (&s[..s.len() - 1], 1usize << 10),
+        Some('M') => (&s[..s.len() - 1], 1 << 20),
+        Some('G') => (&s[..s.len() - 1], 1 << 30),
+        _ => (s, 1),
+    };
+    digits
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(mult))
+        .ok_or_else(|| format!("invalid size `{raw}` (expected e.g. 4096, 512K, 64M)"))
+}
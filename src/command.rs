@@ -0,0 +1,924 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::DateTime;
+
+use crate::{
+    alerts::{self, Hushed, Target, HEALTH},
+    app::{AppState, Mode},
+    compare::{self, Comparison, Window},
+    datasource::METRICS,
+    detail::{Panel, Selection},
+    dirs,
+    histogram::Quantile,
+    layout::sanitize,
+    logs::{Level, SourceId},
+    parse::level_from_name,
+    pins::{Pin, Scope},
+    profile::Profile,
+    reconnect::LinkState,
+    views::View,
+    watchlist::{self, Order, Status},
+};
+
+// What `push-log` lines show under
+const PUSHED: &str = "user";
+
+// `set mode cloud; mark incident` as its commands; `\;` is a `;` of a
+// command's own, as in `push-log rolled back\; watching`
+fn batch(line: &str) -> Vec<String> {
+    let mut commands = vec![String::new()];
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&';') => {
+                commands.last_mut().expect("never empty").push(';');
+                chars.next();
+            }
+            ';' => commands.push(String::new()),
+            c => commands.last_mut().expect("never empty").push(c),
+        }
+    }
+    commands
+}
+
+// The command bar: one line, typed or from a scenario, at a time
+impl AppState {
+    // A typed line, which may be a `;`-separated batch: its commands run in
+    // order before the next frame, up to the first that fails, and Up brings
+    // the whole line back
+    pub(crate) fn process_command(&mut self) {
+        let raw = self.cmd_input.as_str().to_string();
+        self.cmd_input.clear();
+        self.cmd_history.push(&raw);
+        let path = self.dirs.history_file();
+        if let Err(e) = self.cmd_history.save(&path) {
+            self.push_entry(
+                Level::Warn,
+                SourceId::APP,
+                format!("can't write {}: {e}", path.display()),
+            );
+        }
+        let commands = batch(&raw);
+        for (i, command) in commands.iter().enumerate() {
+            if let Err(e) = self.run(command) {
+                self.push_entry(Level::Warn, SourceId::APP, e);
+                let rest: Vec<&str> = commands[i + 1..]
+                    .iter()
+                    .map(|c| c.trim())
+                    .filter(|c| !c.is_empty())
+                    .collect();
+                if !rest.is_empty() {
+                    self.push_entry(
+                        Level::Warn,
+                        SourceId::APP,
+                        format!("batch stopped; not run: {}", rest.join("; ")),
+                    );
+                }
+                break;
+            }
+        }
+    }
+
+    // Runs one command line, typed or scripted; if it fails, the logs say why
+    pub fn execute(&mut self, raw: &str) {
+        if let Err(e) = self.run(raw) {
+            self.push_entry(Level::Warn, SourceId::APP, e);
+        }
+    }
+
+    // One command line; Err says why it failed, for the caller to log
+    fn run(&mut self, raw: &str) -> Result<(), String> {
+        let raw = raw.trim().to_string();
+        if raw.is_empty() {
+            return Ok(());
+        }
+
+        // Echo command first
+        self.push_log(format!(":> {}", raw));
+
+        let lower = raw.to_ascii_lowercase();
+
+        if lower == "help" || lower == "?" || lower == ":help" {
+            self.push_log(
+                "commands: \
+set mode <ai|robotics|cloud|forensics|sandbox>, \
+estop, set <setpoint> <value>, control, help / ?, clear, diag, grep <text>, mouse [on|off], source [retry <name>], tail [path], integrity, paths, profile [demo|ops|dev], \
+pin [scope:]<metric>, unpin <metric|all>, mark <name>, push-log [level] <text>, compare <metric> <window> <window>, report <path>, health, requests, serial [open <path> [baud]|close], watch <metric> [as <name>] [warn N] [crit N], unwatch <metric|all>, alerts, ack <rule|all>, silence [<rule|label=value> <duration>], unsilence <id|all>, quantile [p50|p95|p99], view [save|load|delete <name>]",
+            );
+        } else if lower == "mode" || lower == ":mode" {
+            self.push_log(format!("current mode → {}", self.mode.name()));
+        } else if lower.starts_with("set mode ") || lower.starts_with(":set mode ") {
+            let rest = lower
+                .trim_start_matches(':')
+                .trim_start_matches("set mode ")
+                .trim();
+
+            let target = match rest {
+                "ai" | "ai-observability" => Some(Mode::AiObservability),
+                "robotics" | "rob" => Some(Mode::Robotics),
+                "cloud" | "cld" => Some(Mode::Cloud),
+                "forensics" | "dfx" | "data" => Some(Mode::DataForensics),
+                "sandbox" | "sbx" => Some(Mode::Sandbox),
+                _ => None,
+            };
+
+            if let Some(m) = target {
+                self.set_mode(m);
+            } else {
+                return Err(
+                    "unknown mode. try: ai, robotics, cloud, forensics, sandbox".to_string()
+                );
+            }
+        } else if let Some(rest) = ["grep", "search"].iter().find_map(|c| {
+            raw.trim_start_matches(':')
+                .strip_prefix(c)
+                .filter(|r| r.is_empty() || r.starts_with(' '))
+        }) {
+            let query = rest.trim();
+            if query.is_empty() {
+                self.search = None;
+                self.push_log("search cleared");
+            } else {
+                let n = self.logs.search(query).len();
+                self.search = Some(query.to_string());
+                self.push_log(format!("grep \"{query}\" → {n} matches"));
+            }
+        } else if let Some(rest) = lower.trim_start_matches(':').strip_prefix("mouse") {
+            match rest.trim() {
+                "" => self.set_mouse_capture(!self.mouse_capture),
+                "on" => self.set_mouse_capture(true),
+                "off" => self.set_mouse_capture(false),
+                _ => return Err("usage: mouse [on|off]".to_string()),
+            }
+        } else if lower == "source" || lower == ":source" {
+            let states = self.links.states();
+            if states.is_empty() {
+                self.push_log("no network sources");
+            }
+            let now = self.uptime();
+            for (name, state) in states {
+                let uptime = match self.availability.summary(&name, now) {
+                    Some(s) => format!(
+                        " • up {:.1}%, {} outage{}",
+                        s.uptime * 100.0,
+                        s.outages.len(),
+                        if s.outages.len() == 1 { "" } else { "s" }
+                    ),
+                    None => String::new(),
+                };
+                self.push_log(format!("{name}: {}{uptime}", state.label()));
+            }
+        } else if let Some(name) = raw.trim_start_matches(':').strip_prefix("source retry ") {
+            let name = name.trim();
+            self.allow_mutation("source retry")?;
+            match self.links.retry(name) {
+                Some(LinkState::Retrying { .. }) => self.push_log(format!("{name}: retrying now")),
+                Some(state) => self.push_log(format!("{name}: already {}", state.label())),
+                None => return Err(format!("unknown source `{name}`. `source` lists them")),
+            }
+        } else if lower == "tail" || lower == ":tail" {
+            if self.follows.is_empty() {
+                self.push_log("no files followed. `tail PATH` or --follow FILE");
+            }
+            for path in self.follows.clone() {
+                self.push_log(format!("following {}", path.display()));
+            }
+        } else if lower == "integrity" || lower == ":integrity" {
+            let summary = match &self.integrity {
+                None => "no paths hashed. --integrity PATH".to_string(),
+                Some(integrity) => {
+                    let paths: Vec<String> = integrity
+                        .paths
+                        .iter()
+                        .map(|p| p.display().to_string())
+                        .collect();
+                    let files = integrity.files.map_or_else(
+                        || "hashing".to_string(),
+                        |n| format!("{n} file{}", if n == 1 { "" } else { "s" }),
+                    );
+                    format!(
+                        "integrity: {files} under {}; {} changes since the baseline",
+                        paths.join(", "),
+                        integrity.changes
+                    )
+                }
+            };
+            self.push_log(summary);
+        } else if let Some(path) = raw.trim_start_matches(':').strip_prefix("tail ") {
+            self.allow_mutation("tail")?;
+            let path = PathBuf::from(path.trim());
+            // --follow waits for a file that isn't there yet, but a typed path
+            // that can't be read is more likely a typo, and stops a batch
+            match fs::metadata(&path) {
+                Err(e) => return Err(format!("can't read {}: {e}", path.display())),
+                Ok(meta) if meta.is_dir() => {
+                    return Err(format!("{} is a directory", path.display()))
+                }
+                Ok(_) => self.follow(path)?,
+            }
+        } else if lower == "serial" || lower == ":serial" {
+            match &self.serial {
+                None => self.push_log("no serial port open. `serial open PATH [BAUD]`"),
+                Some((port, _)) => {
+                    let state = self
+                        .links
+                        .states()
+                        .into_iter()
+                        .find(|(name, _)| **name == *port.name)
+                        .map_or("closed".to_string(), |(_, state)| state.label());
+                    self.push_log(format!(
+                        "serial: {} at {} baud as `{}`, {state}",
+                        port.path.display(),
+                        port.baud,
+                        port.name
+                    ));
+                }
+            }
+        } else if lower == "serial close" || lower == ":serial close" {
+            self.allow_mutation("serial")?;
+            self.close_serial();
+        } else if let Some(args) = raw.trim_start_matches(':').strip_prefix("serial open ") {
+            let args: Vec<&str> = args.split_whitespace().collect();
+            let baud = match args.get(1) {
+                None => Some(115_200),
+                Some(baud) => baud.parse().ok(),
+            };
+            match (args.first(), baud) {
+                (Some(path), Some(baud)) if args.len() <= 2 => {
+                    self.allow_mutation("serial")?;
+                    self.open_serial(PathBuf::from(path), baud)?;
+                }
+                _ => {
+                    return Err(
+                        "usage: serial open PATH [BAUD], e.g. serial open /dev/ttyUSB0 115200"
+                            .to_string(),
+                    )
+                }
+            }
+        } else if lower == "profile" || lower == ":profile" {
+            self.push_log(format!("current profile → {}", self.profile.name()));
+        } else if let Some(name) = lower.trim_start_matches(':').strip_prefix("profile ") {
+            self.allow_mutation("profile")?;
+            match Profile::from_name(name.trim()) {
+                Some(profile) => self.set_profile(profile),
+                None => return Err("unknown profile. try: demo, ops, dev".to_string()),
+            }
+        } else if lower == "pin" || lower == ":pin" {
+            if self.pins.is_empty() {
+                self.push_log(
+                    "nothing pinned. `pin LABEL` or `pin SCOPE:LABEL`, e.g. pin cloud:latency p95",
+                );
+            }
+            for pin in self.pins.clone() {
+                self.push_log(format!("pinned {pin}"));
+            }
+        } else if let Some(spec) = raw.trim_start_matches(':').strip_prefix("pin ") {
+            self.allow_mutation("pin")?;
+            match self.resolve_pin(spec.trim()) {
+                Ok(pin) if self.pins.contains(&pin) => {
+                    self.push_log(format!("{pin} is already pinned"))
+                }
+                Ok(pin) => {
+                    self.pins.push(pin.clone());
+                    self.save_pins()?;
+                    self.push_log(format!("pinned {pin}"));
+                }
+                Err(e) => return Err(e),
+            }
+        } else if let Some(spec) = raw.trim_start_matches(':').strip_prefix("unpin ") {
+            self.allow_mutation("unpin")?;
+            let spec = spec.trim();
+            let before = self.pins.len();
+            if spec == "all" {
+                self.pins.clear();
+            } else if let Ok(pin) = self.resolve_pin(spec) {
+                self.pins.retain(|p| *p != pin);
+            }
+            if self.pins.len() == before {
+                return Err(format!("`{spec}` isn't pinned. `pin` lists the pins"));
+            } else {
+                self.save_pins()?;
+                self.push_log(format!("unpinned {}", before - self.pins.len()));
+            }
+        } else if let Some(name) = raw.trim_start_matches(':').strip_prefix("mark ") {
+            self.allow_mutation("mark")?;
+            self.mark(name.trim().to_string());
+        } else if let Some(text) = raw.trim_start_matches(':').strip_prefix("push-log ") {
+            self.allow_mutation("push-log")?;
+            // a leading level word sets the level, as in `push-log warn disk 91%`
+            let text = text.trim();
+            let (level, text) = match text.split_once(' ') {
+                Some((word, rest))
+                    if ["debug", "info", "warn", "error"]
+                        .contains(&word.to_ascii_lowercase().as_str()) =>
+                {
+                    (level_from_name(word), rest.trim())
+                }
+                _ => (Level::Info, text),
+            };
+            let source = self.logs.source(PUSHED);
+            self.push_entry(level, source, sanitize(text).into_owned());
+        } else if let Some(args) = raw.trim_start_matches(':').strip_prefix("compare ") {
+            self.compare(args)?;
+        } else if lower == "compare" || lower == ":compare" {
+            self.comparison = None;
+        } else if let Some(path) = raw.trim_start_matches(':').strip_prefix("report ") {
+            self.allow_mutation("report")?;
+            let path = Path::new(path.trim());
+            match self.session_report().save(path) {
+                Ok(()) => self.push_log(format!("report written to {}", path.display())),
+                Err(e) => return Err(format!("can't write {}: {e}", path.display())),
+            }
+        } else if lower == "health" || lower == ":health" {
+            if self.health.is_some() {
+                self.show_health = !self.show_health;
+            } else {
+                self.push_log(
+                    "the trust score is synthetic; define a health index with [health.weights] in config.toml",
+                );
+            }
+        } else if lower == "derived" || lower == ":derived" {
+            self.list_derived();
+        } else if lower == "requests" || lower == ":requests" {
+            self.request_pick = match (&self.tokens, self.request_pick) {
+                (None, _) => {
+                    self.push_log("no LLM requests to show; proxy them with --llm-proxy PORT=URL");
+                    None
+                }
+                (Some(_), Some(_)) => None,
+                (Some(_), None) => Some(0),
+            };
+        } else if lower == "watch" || lower == ":watch" {
+            if self.watchlist.is_empty() {
+                self.push_log(
+                    "watchlist is empty. e.g. watch cloud:latency p95 as edge p95 warn 300 crit 350",
+                );
+            }
+            for item in self.watchlist.items.clone() {
+                let limits: Vec<String> = [("warn", item.warn), ("crit", item.crit)]
+                    .iter()
+                    .filter_map(|(what, v)| v.map(|v| format!("{what} {v}")))
+                    .collect();
+                let name = item.name.map(|n| format!(" as {n}")).unwrap_or_default();
+                self.push_log(format!(
+                    "watching {}{name} {}",
+                    item.metric,
+                    limits.join(" ")
+                ));
+            }
+        } else if let Some(args) = raw.trim_start_matches(':').strip_prefix("watch ") {
+            self.allow_mutation("watch")?;
+            self.watch(args.trim())?;
+        } else if let Some(what) = raw.trim_start_matches(':').strip_prefix("unwatch ") {
+            self.allow_mutation("unwatch")?;
+            let what = what.trim();
+            let removed = if what == "all" {
+                std::mem::take(&mut self.watchlist.items).len()
+            } else {
+                // as typed (a display name), or resolved like `pin` does
+                let spec = self.resolve_pin(what).map(|pin| pin.to_string());
+                self.watchlist.remove(spec.as_deref().unwrap_or(what)) + self.watchlist.remove(what)
+            };
+            if removed == 0 {
+                return Err(format!("`{what}` isn't on the watchlist. `watch` lists it"));
+            } else {
+                self.save_watchlist()?;
+                self.push_log(format!("unwatched {removed}"));
+            }
+        } else if lower == "alerts" || lower == ":alerts" {
+            let now = self.uptime();
+            let firing: Vec<String> = self
+                .alerts
+                .firing(now)
+                .into_iter()
+                .map(|(rule, status, since, hushed)| {
+                    let severity = if status == Status::Crit {
+                        "crit"
+                    } else {
+                        "warn"
+                    };
+                    let hushed = match hushed {
+                        Some(Hushed::Acked) => ", acknowledged",
+                        Some(Hushed::Silenced) => ", silenced",
+                        Some(Hushed::Maintenance) => ", held for maintenance",
+                        None => "",
+                    };
+                    format!("{rule}: {severity} for {}{hushed}", alerts::span(since))
+                })
+                .collect();
+            if firing.is_empty() {
+                self.push_log("no alerts firing");
+            }
+            for line in firing {
+                self.push_log(line);
+            }
+        } else if let Some(what) = raw.trim_start_matches(':').strip_prefix("ack ") {
+            self.allow_mutation("ack")?;
+            self.ack(what.trim())?;
+        } else if lower == "silence" || lower == ":silence" {
+            if self.alerts.silences.is_empty() {
+                self.push_log(
+                    "no silences. e.g. silence cloud:latency p95 30m, silence scope=cloud 1h",
+                );
+            }
+            let now = self.uptime();
+            let silences: Vec<String> = self
+                .alerts
+                .silences
+                .iter()
+                .map(|s| {
+                    let left = alerts::span(s.until.saturating_sub(now));
+                    format!("silence {}: {}, {left} left", s.id, s.target)
+                })
+                .collect();
+            for line in silences {
+                self.push_log(line);
+            }
+        } else if let Some(args) = raw.trim_start_matches(':').strip_prefix("silence ") {
+            self.allow_mutation("silence")?;
+            self.silence(args)?;
+        } else if let Some(what) = raw.trim_start_matches(':').strip_prefix("unsilence ") {
+            let what = what.trim();
+            let id = match what {
+                "all" => Some(None),
+                id => id.parse().ok().map(Some),
+            };
+            let id = id.ok_or("usage: unsilence ID|all")?;
+            self.allow_mutation("unsilence")?;
+            match self.alerts.unsilence(id) {
+                0 => return Err(format!("no silence `{what}`. `silence` lists them")),
+                n => self.push_log(format!(
+                    "cancelled {n} silence{}",
+                    if n == 1 { "" } else { "s" }
+                )),
+            }
+        } else if let Some(arg) = lower.trim_start_matches(':').strip_prefix("quantile") {
+            self.quantile(arg.trim())?;
+        } else if lower == "view" || lower == ":view" {
+            self.list_views();
+        } else if let Some(args) = raw.trim_start_matches(':').strip_prefix("view ") {
+            let (verb, name) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+            match (verb, name.trim()) {
+                (_, "") => return Err("usage: view save|load|delete NAME".to_string()),
+                ("save", name) => {
+                    self.allow_mutation("view save")?;
+                    self.save_view(name)?
+                }
+                ("load", name) => self.load_view(name)?,
+                ("delete", name) => {
+                    self.allow_mutation("view delete")?;
+                    self.delete_view(name)?
+                }
+                (verb, _) => {
+                    return Err(format!("no `view {verb}`; it's view save|load|delete NAME"))
+                }
+            }
+        } else if lower == "paths" || lower == ":paths" {
+            for (what, path) in self.dirs.listing() {
+                self.push_log(format!("{what:<10} {}", dirs::display(&path)));
+            }
+        } else if lower == "estop" || lower == ":estop" {
+            self.control_command("estop")?;
+        } else if lower.trim_start_matches(':').starts_with("set ") {
+            self.control_command(raw.trim_start_matches(':'))?;
+        } else if lower == "control" || lower == ":control" {
+            let summary = match &self.control {
+                None => "no robotics commands. add a [control] table to config.toml".to_string(),
+                Some(control) => {
+                    let spec = &control.spec;
+                    let setpoints: Vec<String> = spec
+                        .setpoints
+                        .iter()
+                        .map(|(name, [min, max])| format!("{name} {min}..{max}"))
+                        .collect();
+                    let keys: Vec<String> = spec
+                        .keys
+                        .iter()
+                        .map(|(key, line)| format!("{key} {line}"))
+                        .collect();
+                    format!(
+                        "control → {} • setpoints: {} • keys: {}",
+                        spec.url,
+                        if setpoints.is_empty() {
+                            "none".to_string()
+                        } else {
+                            setpoints.join(", ")
+                        },
+                        if keys.is_empty() {
+                            "none".to_string()
+                        } else {
+                            keys.join(", ")
+                        },
+                    )
+                }
+            };
+            self.push_log(summary);
+        } else if lower == "diag" || lower == ":diag" {
+            self.show_diag = !self.show_diag;
+        } else if lower == "clear" || lower == ":clear" {
+            self.allow_mutation("clear")?;
+            self.logs.clear();
+            self.push_log("logs cleared");
+        } else {
+            return Err("unrecognized command. type `help` or `?`".to_string());
+        }
+        Ok(())
+    }
+
+    // `compare METRIC WINDOW-A WINDOW-B`: samples the metric over both windows,
+    // logs the delta statistics, and opens the overlay chart
+    fn compare(&mut self, args: &str) -> Result<(), String> {
+        let words: Vec<&str> = args.split_whitespace().collect();
+        let [metric @ .., a, b] = words.as_slice() else {
+            return Err(
+                "usage: compare METRIC WINDOW-A WINDOW-B, e.g. compare latency p95 5m 5m@deploy"
+                    .to_string(),
+            );
+        };
+        if metric.is_empty() {
+            return Err("usage: compare METRIC WINDOW-A WINDOW-B".to_string());
+        }
+        let pin = self.resolve_pin(&metric.join(" "))?;
+        let rows = |t: f32| match pin.scope {
+            Scope::Mode(mode) => self.panel_rows(mode, t).to_vec(),
+            _ => self.system_rows(t).to_vec(),
+        };
+        match pin.scope {
+            Scope::Host(_) => return Err("host metrics keep no history to compare".to_string()),
+            Scope::Derived => {
+                return Err("compare the metrics a derived one is made of".to_string())
+            }
+            _ => {}
+        }
+        let (_, _, fmt, _, _) = *rows(0.0)
+            .iter()
+            .find(|row| row.0 == pin.label)
+            .expect("resolve_pin checked the label");
+        // a gap where the mode's source had gone quiet
+        let value = |at: Duration| {
+            let t = at.as_secs_f32();
+            let rows = rows(t);
+            let i = rows.iter().position(|row| row.0 == pin.label)?;
+            match pin.scope {
+                Scope::Mode(mode) if self.stale(mode, i, t).is_some() => None,
+                _ => Some(rows[i].1),
+            }
+        };
+
+        let now = self.uptime();
+        let mut series = Vec::new();
+        let mut marks = Vec::new();
+        for (name, spec) in [("A", a), ("B", b)] {
+            let window: Window = spec.parse()?;
+            let (start, end) = window.resolve(now, &self.markers)?;
+            series.push(compare::sample(start, end, value));
+            for (label, at) in self.markers.iter().filter(|m| (start..=end).contains(&m.1)) {
+                marks.push((format!("{label} ({name})"), (*at - start).as_secs_f64()));
+            }
+        }
+        let [sa, sb] = [compare::stats(&series[0]), compare::stats(&series[1])];
+        let show = |v: f64| fmt(v as f32);
+        let delta = |x: f64, y: f64| {
+            let sign = if y >= x { "+" } else { "" };
+            let pct = if x != 0.0 {
+                format!(" ({sign}{:.1}%)", (y - x) / x.abs() * 100.0)
+            } else {
+                String::new()
+            };
+            format!("{sign}{}{pct}", show(y - x))
+        };
+        for (name, spec, st) in [("A", a, &sa), ("B", b, &sb)] {
+            self.push_log(format!(
+                "{pin} {name} {spec}: mean {}, p95 {}, min {}, max {}",
+                show(st.mean),
+                show(st.p95),
+                show(st.min),
+                show(st.max)
+            ));
+        }
+        self.push_log(format!(
+            "{pin} B vs A: mean {}, p95 {}",
+            delta(sa.mean, sb.mean),
+            delta(sa.p95, sb.p95)
+        ));
+        let mut series = series.into_iter();
+        self.comparison = Some(Comparison {
+            args: args.trim().to_string(),
+            metric: pin.to_string(),
+            labels: [format!("A {a}"), format!("B {b}")],
+            series: [
+                series.next().unwrap_or_default(),
+                series.next().unwrap_or_default(),
+            ],
+            marks,
+        });
+        Ok(())
+    }
+
+    // `view`: the saved views and what each one holds
+    fn list_views(&mut self) {
+        if self.views.0.is_empty() {
+            self.push_log("no saved views; `view save NAME` keeps this one");
+        }
+        let lines: Vec<String> = self
+            .views
+            .0
+            .iter()
+            .map(|(name, view)| format!("view {name}: {}", view.summary()))
+            .collect();
+        for line in lines {
+            self.push_log(line);
+        }
+    }
+
+    // `view save NAME`: what's on screen now, kept under NAME
+    fn save_view(&mut self, name: &str) -> Result<(), String> {
+        let t = self.view_time().as_secs_f32();
+        let metric = self.selected.and_then(|sel| {
+            let (scope, label) = match sel.panel {
+                Panel::Ai => (
+                    Scope::Mode(self.mode),
+                    self.panel_rows(self.mode, t).get(sel.row)?.0,
+                ),
+                Panel::System => (Scope::System, self.system_rows(t).get(sel.row)?.0),
+            };
+            Some(Pin {
+                scope,
+                label: label.to_string(),
+            })
+        });
+        let view = View {
+            mode: Some(self.mode),
+            grep: self.search.clone(),
+            source: self
+                .log_source
+                .map(|id| self.logs.source_name(id).to_string()),
+            metric: metric.map(|pin| pin.to_string()),
+            expanded: self.selected.is_some_and(|sel| sel.expanded),
+            compare: self.comparison.as_ref().map(|c| c.args.clone()),
+            at: self.scrub.map(|at| self.timebase.local_at(at).to_rfc3339()),
+            pan: Some(self.pan).filter(|pan| *pan != (0, 0)),
+        };
+        let summary = view.summary();
+        self.views.0.insert(name.to_string(), view);
+        self.save_views()?;
+        self.push_log(format!("view `{name}` saved: {summary}"));
+        Ok(())
+    }
+
+    // `view load NAME`: back to what was on screen when it was saved. The
+    // chart is drawn again over the same windows back from now; a moment
+    // time travel can no longer reach is the oldest one it can.
+    fn load_view(&mut self, name: &str) -> Result<(), String> {
+        let view = self
+            .views
+            .0
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("no view `{name}`. `view` lists them"))?;
+        if let Some(mode) = view.mode {
+            self.set_mode(mode);
+        }
+        self.search = view.grep.clone();
+        self.log_source = view.source.as_deref().map(|name| self.logs.source(name));
+        self.log_pick = None;
+        self.selected = None;
+        if let Some(spec) = &view.metric {
+            match self.view_row(spec) {
+                Some((panel, row)) => {
+                    self.selected = Some(Selection {
+                        panel,
+                        row,
+                        expanded: view.expanded,
+                    });
+                    self.focus = Some(panel.focus());
+                    self.cmd_active = false;
+                }
+                None => self.push_entry(
+                    Level::Warn,
+                    SourceId::APP,
+                    format!("view `{name}`: `{spec}` isn't a row on screen in this mode"),
+                ),
+            }
+        }
+        self.comparison = None;
+        if let Some(args) = &view.compare {
+            if let Err(e) = self.compare(args) {
+                self.push_entry(Level::Warn, SourceId::APP, format!("view `{name}`: {e}"));
+            }
+        }
+        self.pan = view.pan.unwrap_or_default();
+        self.scrub = None;
+        if let Some(at) = &view.at {
+            match DateTime::parse_from_rfc3339(at) {
+                Ok(at) => {
+                    let now = self.uptime();
+                    let behind = (self.timebase.local_at(now) - at)
+                        .to_std()
+                        .unwrap_or_default();
+                    self.scrub_to(now.saturating_sub(behind));
+                }
+                Err(e) => self.push_entry(
+                    Level::Warn,
+                    SourceId::APP,
+                    format!("view `{name}`: bad time `{at}`: {e}"),
+                ),
+            }
+        }
+        self.push_log(format!("view `{name}` loaded: {}", view.summary()));
+        Ok(())
+    }
+
+    // (panel, row) of a metric named like `pin` names it, if it's in the
+    // current mode's panel or the system one
+    fn view_row(&self, spec: &str) -> Option<(Panel, usize)> {
+        let pin = Pin::parse(spec)?;
+        let t = self.view_time().as_secs_f32();
+        match pin.scope {
+            Scope::Mode(mode) if mode == self.mode => Some((
+                Panel::Ai,
+                self.panel_rows(mode, t)
+                    .iter()
+                    .position(|row| row.0 == pin.label)?,
+            )),
+            Scope::System => Some((
+                Panel::System,
+                self.system_rows(t)
+                    .iter()
+                    .position(|row| row.0 == pin.label)?,
+            )),
+            _ => None,
+        }
+    }
+
+    fn delete_view(&mut self, name: &str) -> Result<(), String> {
+        if self.views.0.remove(name).is_none() {
+            return Err(format!("no view `{name}`. `view` lists them"));
+        }
+        self.save_views()?;
+        self.push_log(format!("view `{name}` deleted"));
+        Ok(())
+    }
+
+    fn save_views(&self) -> Result<(), String> {
+        let path = self.dirs.views_file();
+        self.views
+            .save(&path)
+            .map_err(|e| format!("can't write {}: {e}", path.display()))
+    }
+
+    // `derived`: each formula's value now (or at the scrubbed-to moment),
+    // and which of its metrics it's waiting on when it has none
+    fn list_derived(&mut self) {
+        if self.derived.is_empty() {
+            self.push_log("no derived metrics; define them in [derived] in config.toml");
+        }
+        let t = self.view_time().as_secs_f32();
+        for (name, formula) in self.derived.clone() {
+            let pin = Pin {
+                scope: Scope::Derived,
+                label: name,
+            };
+            let value = self.pin_value(&pin).unwrap_or_else(|| "-".to_string());
+            let waiting: Vec<String> = formula
+                .operands()
+                .into_iter()
+                .filter(|operand| self.operand_at(operand, t).is_none())
+                .map(|operand| operand.to_string())
+                .collect();
+            let note = match waiting.is_empty() {
+                true => String::new(),
+                false => format!(" (no recent {})", waiting.join(", ")),
+            };
+            self.push_log(format!("{pin} = {value} ← {formula}{note}"));
+        }
+    }
+
+    // `quantile [p50|p95|p99]`: what the panel on screen reads out of
+    // histograms, the focused host in the host view or else the mode's panel
+    fn quantile(&mut self, arg: &str) -> Result<(), String> {
+        let quantile = match arg {
+            "" => None,
+            arg => Some(Quantile::parse(arg).ok_or("usage: quantile [p50|p95|p99]")?),
+        };
+        let t = self.view_time().as_secs_f32();
+        let (panel, now, histograms) = match self.host_view {
+            Some(view) => {
+                let host = self.fleet.hosts.get_mut(view.focus).ok_or("no hosts")?;
+                host.quantile = quantile.unwrap_or(host.quantile);
+                (host.name.to_string(), host.quantile, host.has_histograms())
+            }
+            None => {
+                let feed = &mut self.feeds[self.mode as usize];
+                feed.quantile = quantile.unwrap_or(feed.quantile);
+                let histograms = feed.quantiled(METRICS[0], t);
+                (self.mode.name().to_string(), feed.quantile, histograms)
+            }
+        };
+        let note = if histograms {
+            ""
+        } else {
+            " (its source sends no histograms, so nothing changes)"
+        };
+        self.push_log(format!("quantile for {panel} → {}{note}", now.name()));
+        Ok(())
+    }
+
+    // An alert rule as `ack` and `silence` take it: `health`, or a watched
+    // metric as typed, by its display name, or resolved like `pin` does
+    fn alert_rule(&self, what: &str) -> Result<String, String> {
+        if what == HEALTH {
+            return Ok(HEALTH.to_string());
+        }
+        let spec = self.resolve_pin(what).map(|pin| pin.to_string()).ok();
+        self.watchlist
+            .items
+            .iter()
+            .find(|i| {
+                i.metric == what
+                    || i.name.as_deref() == Some(what)
+                    || spec.as_ref() == Some(&i.metric)
+            })
+            .map(|i| i.metric.clone())
+            .ok_or_else(|| {
+                format!("`{what}` isn't on the watchlist or `{HEALTH}`. `watch` lists it")
+            })
+    }
+
+    // `ack RULE|all`: a firing alert stops repeating until it clears
+    fn ack(&mut self, what: &str) -> Result<(), String> {
+        let rules = match what {
+            "all" => self
+                .alerts
+                .firing(self.uptime())
+                .into_iter()
+                .filter(|(.., hushed)| hushed.is_none())
+                .map(|(rule, ..)| rule.to_string())
+                .collect(),
+            what => vec![self.alert_rule(what)?],
+        };
+        if rules.is_empty() {
+            return Err("nothing to acknowledge. `alerts` lists what's firing".to_string());
+        }
+        for rule in rules {
+            if !self.alerts.ack(&rule) {
+                return Err(format!("{rule} isn't firing. `alerts` lists what is"));
+            }
+            self.push_log(format!("acknowledged {rule}; no repeats until it clears"));
+        }
+        Ok(())
+    }
+
+    // `silence RULE|LABEL=VALUE DURATION`
+    fn silence(&mut self, args: &str) -> Result<(), String> {
+        let (target, duration) = alerts::parse_silence(args)?;
+        let target = match target.split_once('=') {
+            Some((key, value)) => {
+                let (key, value) = (key.trim(), value.trim());
+                if !["scope", "severity"].contains(&key) || value.is_empty() {
+                    return Err(format!(
+                        "labels are scope=MODE|system|HOST and severity=warn|crit, not `{target}`"
+                    ));
+                }
+                Target::Label(key.to_string(), value.to_string())
+            }
+            None => Target::Rule(self.alert_rule(&target)?),
+        };
+        let id = self
+            .alerts
+            .silence(target.clone(), self.uptime() + duration);
+        self.push_log(format!(
+            "silence {id}: {target} for {}",
+            alerts::span(duration)
+        ));
+        Ok(())
+    }
+
+    // `watch METRIC [as NAME] [warn N] [crit N]`, `watch sort worst|added`
+    fn watch(&mut self, args: &str) -> Result<(), String> {
+        if let Some(order) = args.strip_prefix("sort ") {
+            self.watchlist.sort = match order.trim() {
+                "worst" => Order::Worst,
+                "added" => Order::Added,
+                _ => return Err("usage: watch sort worst|added".to_string()),
+            };
+            self.save_watchlist()?;
+            self.push_log(format!("watchlist sorted by {}", order.trim()));
+            return Ok(());
+        }
+        let mut item = watchlist::parse(args)?;
+        item.metric = self.resolve_pin(&item.metric)?.to_string();
+        let verb = if self.watchlist.upsert(item.clone()) {
+            "watching"
+        } else {
+            "updated"
+        };
+        self.save_watchlist()?;
+        self.push_log(format!("{verb} {}", item.metric));
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use std::{str::FromStr, time::Duration};
+
+use humantime::{format_duration, parse_duration};
+use ratatui::{
+    prelude::*,
+    symbols::Marker,
+    widgets::{Axis, Block, Borders, Chart, Clear, Dataset, GraphType},
+};
+
+use crate::ui::centered;
+
+// Points per window; plenty for a terminal-width chart
+const SAMPLES: usize = 120;
+
+// Where a window ends: some time ago (0 = now), or at a `mark`
+#[derive(Clone, Debug)]
+pub enum End {
+    Ago(Duration),
+    Marker(String),
+}
+
+// `LEN`, `LEN@-AGO`, or `LEN@MARKER`: e.g. `5m`, `5m@-1h`, `5m@deploy`
+#[derive(Clone, Debug)]
+pub struct Window {
+    len: Duration,
+    end: End,
+}
+
+impl FromStr for Window {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (len, end) = s.split_once('@').unwrap_or((s, "-0s"));
+        let len = parse_duration(len).map_err(|e| format!("bad window length `{len}`: {e}"))?;
+        if len.is_zero() {
+            return Err(format!("window `{s}` is empty"));
+        }
+        let end = match end.strip_prefix('-') {
+            Some(ago) => {
+                End::Ago(parse_duration(ago).map_err(|e| format!("bad offset `{ago}`: {e}"))?)
+            }
+            None => End::Marker(end.to_string()),
+        };
+        Ok(Self { len, end })
+    }
+}
+
+impl Window {
+    // (start, end) on the app clock
+    pub fn resolve(
+        &self,
+        now: Duration,
+        markers: &[(String, Duration)],
+    ) -> Result<(Duration, Duration), String> {
+        let end = match &self.end {
+            End::Ago(ago) => now
+                .checked_sub(*ago)
+                .ok_or_else(|| format!("-{} is before startup", format_duration(*ago)))?,
+            End::Marker(name) => markers
+                .iter()
+                .rev()
+                .find(|(n, _)| n == name)
+                .map(|(_, at)| *at)
+                .ok_or_else(|| format!("no marker `{name}`. `mark NAME` sets one"))?,
+        };
+        let start = end.checked_sub(self.len).ok_or_else(|| {
+            format!(
+                "{} window reaches back before startup",
+                format_duration(self.len)
+            )
+        })?;
+        Ok((start, end))
+    }
+}
+
+// Evenly spaced (seconds into the window, value) pairs; NaN where `value`
+// has none because the source had gone quiet, which stays a gap
+pub fn sample(
+    start: Duration,
+    end: Duration,
+    value: impl Fn(Duration) -> Option<f32>,
+) -> Vec<(f64, f64)> {
+    let len = end - start;
+    (0..SAMPLES)
+        .map(|i| {
+            let offset = len.mul_f64(i as f64 / (SAMPLES - 1) as f64);
+            let value = value(start + offset).map_or(f64::NAN, f64::from);
+            (offset.as_secs_f64(), value)
+        })
+        .collect()
+}
+
+pub struct Stats {
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    pub p95: f64,
+}
+
+pub fn stats(points: &[(f64, f64)]) -> Stats {
+    let mut values: Vec<f64> = points.iter().map(|p| p.1).filter(|v| !v.is_nan()).collect();
+    values.sort_by(f64::total_cmp);
+    let n = values.len().max(1);
+    Stats {
+        mean: values.iter().sum::<f64>() / n as f64,
+        min: values.first().copied().unwrap_or_default(),
+        max: values.last().copied().unwrap_or_default(),
+        p95: values
+            .get((n * 95).div_ceil(100).saturating_sub(1))
+            .copied()
+            .unwrap_or_default(),
+    }
+}
+
+fn number(v: f64) -> String {
+    if v.abs() < 10.0 {
+        format!("{v:.2}")
+    } else {
+        format!("{v:.0}")
+    }
+}
+
+// Both windows overlaid on a shared time axis, as an overlay like `diag`
+pub struct Comparison {
+    // what `compare` was given, for `view save`
+    pub args: String,
+    pub metric: String,
+    pub labels: [String; 2],
+    pub series: [Vec<(f64, f64)>; 2],
+    // `mark`s inside either window: (legend label, seconds into the window)
+    pub marks: Vec<(String, f64)>,
+}
+
+impl Comparison {
+    pub fn draw(&self, buf: &mut Buffer, area: Rect, braille: bool) {
+        let rect = centered(area, area.width.saturating_sub(8).min(100), 20);
+        let longest = self
+            .series
+            .iter()
+            .flatten()
+            .map(|p| p.0)
+            .fold(0.0, f64::max);
+        let values = self.series.iter().flatten().map(|p| p.1);
+        let (lo, hi) = values
+            .filter(|v| !v.is_nan())
+            .fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        // all gap
+        let (lo, hi) = if lo <= hi { (lo, hi) } else { (0.0, 1.0) };
+        let pad = ((hi - lo) * 0.1).max(f64::EPSILON);
+        let (lo, hi) = (lo - pad, hi + pad);
+
+        let marker = if braille {
+            Marker::Braille
+        } else {
+            Marker::Dot
+        };
+        let colors = [Color::Cyan, Color::LightMagenta];
+        // a line per stretch between gaps, so none is drawn across one;
+        // the first carries the window's name for the legend
+        let mut datasets: Vec<Dataset> = Vec::new();
+        for (i, series) in self.series.iter().enumerate() {
+            let stretches = series.split(|p| p.1.is_nan()).filter(|s| !s.is_empty());
+            for (n, stretch) in stretches.enumerate() {
+                let dataset = Dataset::default()
+                    .marker(marker)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(colors[i]))
+                    .data(stretch);
+                datasets.push(match n {
+                    0 => dataset.name(self.labels[i].clone()),
+                    _ => dataset,
+                });
+            }
+        }
+        // each mark as a vertical line across the plot
+        let rules: Vec<[(f64, f64); 2]> =
+            self.marks.iter().map(|m| [(m.1, lo), (m.1, hi)]).collect();
+        for (mark, rule) in self.marks.iter().zip(&rules) {
+            datasets.push(
+                Dataset::default()
+                    .name(mark.0.clone())
+                    .marker(marker)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(Color::LightYellow))
+                    .data(rule),
+            );
+        }
+        let dim = Style::default().fg(Color::DarkGray);
+        let chart = Chart::new(datasets)
+            // the legend names the windows and marks, so keep it unless it
+            // would cover most of the plot
+            .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan))
+                    .title(Span::styled(
+                        format!("compare • {} • Esc closes", self.metric),
+                        Style::default()
+                            .fg(Color::LightCyan)
+                            .add_modifier(Modifier::BOLD),
+                    )),
+            )
+            .x_axis(
+                Axis::default()
+                    .style(dim)
+                    .bounds([0.0, longest.max(1.0)])
+                    .labels(vec![
+                        Span::raw("0s"),
+                        Span::raw(format_duration(Duration::from_secs(longest as u64)).to_string()),
+                    ]),
+            )
+            .y_axis(
+                Axis::default()
+                    .style(dim)
+                    .bounds([lo, hi])
+                    .labels(vec![Span::raw(number(lo)), Span::raw(number(hi))]),
+            );
+        Clear.render(rect, buf);
+        chart.render(rect, buf);
+    }
+}
@@ -0,0 +1,523 @@
+use std::{
+    collections::BTreeMap,
+    env, fmt, fs, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+
+use clap::{parser::ValueSource, ArgMatches, ValueEnum};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    caps::ColorDepth,
+    chrome::PanelsSpec,
+    cli::{parse_bytes, Cli, ModeModifier},
+    control::ControlSpec,
+    derived::Formula,
+    dirs::AppDirs,
+    glyphs::GlyphSet,
+    health::HealthSpec,
+    hosts::HostSpec,
+    kafka::KafkaSpec,
+    kube::KubeSpec,
+    maintenance::Window,
+    mqtt::MqttSpec,
+    notify::{self, NotifierSpec},
+    profile::Profile,
+    prometheus::PrometheusSpec,
+    redact::RedactSpec,
+    ros2::Ros2Spec,
+    sampling::SamplingSpec,
+    tokens::ProxySpec,
+    websocket::WebSocketSpec,
+    Mode,
+};
+
+// Per-project settings, looked up from the working directory upwards
+const PROJECT_FILE: &str = ".ai-intui.toml";
+
+// Settings file (TOML). Keys mirror the long flags, and a flag given on the
+// command line always beats the file; `[health]`, `[prometheus]`,
+// `[control]`, `[websocket]`, `[mqtt]`, `[ros2]`, `[kafka]`, `[kube]`,
+// `[derived]`, `[programs]`, `[panels]`, `[llm-requests]`, `[redact]`,
+// `[[maintenance]]`, and `[[notify]]` are the tables without a flag.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    #[serde(deserialize_with = "value_enum", serialize_with = "value_name")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Profile>,
+    #[serde(deserialize_with = "value_enum", serialize_with = "value_name")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<Mode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_capacity: Option<usize>,
+    #[serde(deserialize_with = "byte_size")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_budget: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dedup_window: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dedup_similarity: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_budget: Option<u64>,
+    #[serde(deserialize_with = "value_enum", serialize_with = "value_name")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub glyphs: Option<GlyphSet>,
+    #[serde(deserialize_with = "value_enum", serialize_with = "value_name")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorDepth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reduced_motion: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub educator: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deltas: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attention: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulate: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_after: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mouse: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scenario: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<Vec<HostSpec>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follow: Option<Vec<PathBuf>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub journal: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<Vec<PathBuf>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capture: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gpu_processes: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docker: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kube_events: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub llm_proxy: Option<ProxySpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mark_listen: Option<SocketAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listen: Option<SocketAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub syslog: Option<SocketAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http: Option<SocketAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub socket: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub otlp: Option<SocketAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub statsd: Option<SocketAddr>,
+    #[serde(deserialize_with = "value_enum", serialize_with = "value_name")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode_modifier: Option<ModeModifier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<HealthSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prometheus: Option<PrometheusSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub control: Option<ControlSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub websocket: Option<WebSocketSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mqtt: Option<MqttSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ros2: Option<Ros2Spec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kafka: Option<KafkaSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kube: Option<KubeSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub derived: Option<BTreeMap<String, Formula>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub programs: Option<BTreeMap<String, PathBuf>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub panels: Option<PanelsSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub llm_requests: Option<SamplingSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redact: Option<RedactSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maintenance: Option<Vec<Window>>,
+    #[serde(deserialize_with = "notify::specs")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify: Option<Vec<NotifierSpec>>,
+}
+
+// One settings file in the lookup chain
+pub struct Layer {
+    pub name: &'static str,
+    pub path: PathBuf,
+}
+
+// Settings files from lowest to highest precedence: system-wide, the user's
+// (or --config), then the closest .ai-intui.toml from the working directory up
+pub fn layers(cli: &Cli) -> Vec<Layer> {
+    let mut layers = Vec::new();
+    if let Some(path) = system_file() {
+        layers.push(Layer {
+            name: "system",
+            path,
+        });
+    }
+    layers.push(Layer {
+        name: "user",
+        path: cli
+            .config
+            .clone()
+            .unwrap_or_else(|| AppDirs::resolve(cli).config_file()),
+    });
+    let project = env::current_dir().ok().and_then(|cwd| {
+        cwd.ancestors()
+            .map(|dir| dir.join(PROJECT_FILE))
+            .find(|path| path.is_file())
+    });
+    if let Some(path) = project {
+        layers.push(Layer {
+            name: "project",
+            path,
+        });
+    }
+    layers
+}
+
+#[cfg(unix)]
+fn system_file() -> Option<PathBuf> {
+    Some(PathBuf::from("/etc/ai-intui/config.toml"))
+}
+
+#[cfg(windows)]
+fn system_file() -> Option<PathBuf> {
+    env::var_os("ProgramData").map(|dir| PathBuf::from(dir).join("ai-intui").join("config.toml"))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn system_file() -> Option<PathBuf> {
+    None
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    // toml's message carries the line, column, and a snippet of the bad value
+    Parse(PathBuf, toml::de::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "{}: {e}", path.display()),
+            ConfigError::Parse(path, e) => write!(f, "{}: {e}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// Accepts the same names as the matching flag, e.g. `color = "256"`
+pub(crate) fn value_enum<'de, D, T>(d: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: ValueEnum,
+{
+    let raw = String::deserialize(d)?;
+    T::from_str(&raw, true).map(Some).map_err(|_| {
+        let names: Vec<String> = T::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect();
+        de::Error::custom(format!(
+            "unknown value `{raw}`, expected one of: {}",
+            names.join(", ")
+        ))
+    })
+}
+
+pub(crate) fn value_name<S: Serializer, T: ValueEnum>(
+    v: &Option<T>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    match v.as_ref().and_then(ValueEnum::to_possible_value) {
+        Some(v) => s.serialize_str(v.get_name()),
+        None => s.serialize_none(),
+    }
+}
+
+// A byte count, or a size string like "64M"
+fn byte_size<'de, D: Deserializer<'de>>(d: D) -> Result<Option<usize>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(usize),
+        Text(String),
+    }
+    match Size::deserialize(d)? {
+        Size::Bytes(n) => Ok(Some(n)),
+        Size::Text(s) => parse_bytes(&s).map(Some).map_err(de::Error::custom),
+    }
+}
+
+impl Config {
+    // None when the file doesn't exist
+    pub fn load(path: &Path) -> Result<Option<Self>, ConfigError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(ConfigError::Io(path.to_path_buf(), e)),
+        };
+        toml::from_str(&text)
+            .map(Some)
+            .map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    // Writes a fresh settings file, creating its directory
+    pub fn save(&self, path: &Path, header: &str) -> io::Result<()> {
+        let body = toml::to_string(self).map_err(io::Error::other)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, format!("{header}\n{body}"))
+    }
+
+    // Every layer merged, closest file winning key by key
+    pub fn load_layers(cli: &Cli) -> Result<Self, ConfigError> {
+        let mut merged = Config::default();
+        for layer in layers(cli) {
+            if let Some(config) = Config::load(&layer.path)? {
+                merged = merged.merge(config);
+            }
+        }
+        Ok(merged)
+    }
+
+    // Keys set in `over` replace ours
+    fn merge(self, over: Config) -> Config {
+        Config {
+            profile: over.profile.or(self.profile),
+            mode: over.mode.or(self.mode),
+            log_capacity: over.log_capacity.or(self.log_capacity),
+            log_budget: over.log_budget.or(self.log_budget),
+            dedup_window: over.dedup_window.or(self.dedup_window),
+            dedup_similarity: over.dedup_similarity.or(self.dedup_similarity),
+            frame_budget: over.frame_budget.or(self.frame_budget),
+            glyphs: over.glyphs.or(self.glyphs),
+            color: over.color.or(self.color),
+            reduced_motion: over.reduced_motion.or(self.reduced_motion),
+            educator: over.educator.or(self.educator),
+            deltas: over.deltas.or(self.deltas),
+            attention: over.attention.or(self.attention),
+            simulate: over.simulate.or(self.simulate),
+            idle_after: over.idle_after.or(self.idle_after),
+            mouse: over.mouse.or(self.mouse),
+            read_only: over.read_only.or(self.read_only),
+            log_file: over.log_file.or(self.log_file),
+            data_dir: over.data_dir.or(self.data_dir),
+            scenario: over.scenario.or(self.scenario),
+            host: over.host.or(self.host),
+            follow: over.follow.or(self.follow),
+            journal: over.journal.or(self.journal),
+            integrity: over.integrity.or(self.integrity),
+            capture: over.capture.or(self.capture),
+            gpu_processes: over.gpu_processes.or(self.gpu_processes),
+            docker: over.docker.or(self.docker),
+            kube_events: over.kube_events.or(self.kube_events),
+            llm_proxy: over.llm_proxy.or(self.llm_proxy),
+            mark_listen: over.mark_listen.or(self.mark_listen),
+            listen: over.listen.or(self.listen),
+            syslog: over.syslog.or(self.syslog),
+            http: over.http.or(self.http),
+            socket: over.socket.or(self.socket),
+            otlp: over.otlp.or(self.otlp),
+            statsd: over.statsd.or(self.statsd),
+            mode_modifier: over.mode_modifier.or(self.mode_modifier),
+            health: over.health.or(self.health),
+            prometheus: over.prometheus.or(self.prometheus),
+            control: over.control.or(self.control),
+            websocket: over.websocket.or(self.websocket),
+            mqtt: over.mqtt.or(self.mqtt),
+            ros2: over.ros2.or(self.ros2),
+            kafka: over.kafka.or(self.kafka),
+            kube: over.kube.or(self.kube),
+            derived: over.derived.or(self.derived),
+            programs: over.programs.or(self.programs),
+            panels: over.panels.or(self.panels),
+            llm_requests: over.llm_requests.or(self.llm_requests),
+            redact: over.redact.or(self.redact),
+            maintenance: over.maintenance.or(self.maintenance),
+            notify: over.notify.or(self.notify),
+        }
+    }
+
+    // The settings a run with `cli` ends up using; glyphs and color are left
+    // out while they're auto-detected
+    pub fn effective(cli: &Cli) -> Self {
+        Config {
+            profile: Some(cli.profile),
+            mode: Some(cli.mode.unwrap_or(cli.profile.mode())),
+            log_capacity: Some(cli.log_capacity),
+            log_budget: Some(cli.log_budget),
+            dedup_window: Some(cli.dedup_window),
+            dedup_similarity: Some(cli.dedup_similarity),
+            frame_budget: Some(cli.frame_budget),
+            glyphs: cli.glyphs,
+            color: cli.color,
+            reduced_motion: Some(cli.reduced_motion),
+            educator: Some(cli.educator),
+            deltas: Some(cli.deltas),
+            attention: Some(cli.attention),
+            simulate: Some(cli.simulate),
+            idle_after: Some(cli.idle_after),
+            mouse: Some(!cli.no_mouse),
+            read_only: Some(cli.read_only),
+            log_file: cli.log_file.clone(),
+            data_dir: cli.data_dir.clone(),
+            scenario: cli.scenario.clone(),
+            host: (!cli.host.is_empty()).then(|| cli.host.clone()),
+            follow: (!cli.follow.is_empty()).then(|| cli.follow.clone()),
+            journal: cli.journal.clone(),
+            integrity: (!cli.integrity.is_empty()).then(|| cli.integrity.clone()),
+            capture: cli.capture.clone(),
+            gpu_processes: Some(cli.gpu_processes),
+            docker: Some(cli.docker),
+            kube_events: cli.kube_events.clone(),
+            llm_proxy: cli.llm_proxy.clone(),
+            mark_listen: cli.mark_listen,
+            listen: cli.listen,
+            syslog: cli.syslog,
+            http: cli.http,
+            socket: cli.socket.clone(),
+            otlp: cli.otlp,
+            statsd: cli.statsd,
+            mode_modifier: Some(cli.mode_modifier),
+            health: cli.health.clone(),
+            prometheus: cli.prometheus.clone(),
+            control: cli.control.clone(),
+            websocket: cli.websocket.clone(),
+            mqtt: cli.mqtt.clone(),
+            ros2: cli.ros2.clone(),
+            kafka: cli.kafka.clone(),
+            kube: cli.kube.clone(),
+            derived: cli.derived.clone(),
+            programs: cli.programs.clone(),
+            panels: cli.panels.clone(),
+            llm_requests: cli.llm_requests.clone(),
+            redact: cli.redact.clone(),
+            maintenance: cli.maintenance.clone(),
+            notify: cli.notify.clone(),
+        }
+    }
+
+    // Fills in every setting the user didn't pass on the command line
+    pub fn apply(self, cli: &mut Cli, matches: &ArgMatches) {
+        let unset = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+        macro_rules! merge {
+            ($field:ident) => {
+                if let Some(v) = self.$field {
+                    if unset(stringify!($field)) {
+                        cli.$field = v.into();
+                    }
+                }
+            };
+        }
+        merge!(profile);
+        merge!(mode);
+        merge!(log_capacity);
+        merge!(log_budget);
+        merge!(dedup_window);
+        merge!(dedup_similarity);
+        merge!(frame_budget);
+        merge!(glyphs);
+        merge!(color);
+        merge!(reduced_motion);
+        merge!(educator);
+        merge!(deltas);
+        merge!(attention);
+        merge!(simulate);
+        merge!(idle_after);
+        merge!(mode_modifier);
+        merge!(read_only);
+        merge!(log_file);
+        merge!(data_dir);
+        merge!(scenario);
+        merge!(host);
+        merge!(follow);
+        merge!(journal);
+        merge!(integrity);
+        merge!(capture);
+        merge!(gpu_processes);
+        merge!(docker);
+        merge!(kube_events);
+        merge!(llm_proxy);
+        merge!(mark_listen);
+        merge!(listen);
+        merge!(syslog);
+        merge!(http);
+        merge!(socket);
+        merge!(otlp);
+        merge!(statsd);
+        if self.health.is_some() {
+            cli.health = self.health;
+        }
+        if self.prometheus.is_some() {
+            cli.prometheus = self.prometheus;
+        }
+        if self.control.is_some() {
+            cli.control = self.control;
+        }
+        if self.websocket.is_some() {
+            cli.websocket = self.websocket;
+        }
+        if self.mqtt.is_some() {
+            cli.mqtt = self.mqtt;
+        }
+        if self.ros2.is_some() {
+            cli.ros2 = self.ros2;
+        }
+        if self.kafka.is_some() {
+            cli.kafka = self.kafka;
+        }
+        if self.kube.is_some() {
+            cli.kube = self.kube;
+        }
+        if self.derived.is_some() {
+            cli.derived = self.derived;
+        }
+        if self.programs.is_some() {
+            cli.programs = self.programs;
+        }
+        if self.panels.is_some() {
+            cli.panels = self.panels;
+        }
+        if self.llm_requests.is_some() {
+            cli.llm_requests = self.llm_requests;
+        }
+        if self.redact.is_some() {
+            cli.redact = self.redact;
+        }
+        if self.maintenance.is_some() {
+            cli.maintenance = self.maintenance;
+        }
+        if self.notify.is_some() {
+            cli.notify = self.notify;
+        }
+        if let Some(mouse) = self.mouse {
+            if unset("no_mouse") {
+                cli.no_mouse = !mouse;
+            }
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Write},
+    net::TcpStream,
+    thread,
+    time::Duration,
+};
+
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    applog,
+    ingest::{IngestQueue, SourceSender},
+    logs::Level,
+    mqtt,
+    net::{authority, Authority},
+    ui::centered,
+};
+
+// What the audit trail is logged under
+pub const NAME: &str = "control";
+const QUEUE: usize = 64;
+// Connecting, and each read and write after that
+const TIMEOUT: Duration = Duration::from_secs(5);
+// Keys the dashboard already uses, which a binding can't take over; `y` and
+// `n` answer a setpoint's prompt
+const RESERVED: &str = "qcdehlmtzyn:0123456789";
+
+// `[control]` in config.toml: where robotics commands are sent, the
+// setpoints `set` may change and the range each accepts, and keys bound to
+// commands in robotics mode. Checked as it's read, so `ai-intui check`
+// points at a bad URL, range, or binding.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ControlSpec {
+    #[serde(deserialize_with = "url")]
+    pub url: String,
+    // setpoint → [min, max]
+    #[serde(default, deserialize_with = "setpoints")]
+    pub setpoints: BTreeMap<String, [f64; 2]>,
+    // key → command line
+    #[serde(default, deserialize_with = "keys")]
+    pub keys: BTreeMap<String, String>,
+}
+
+fn url<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    let url = String::deserialize(d)?;
+    Target::parse(&url).map_err(de::Error::custom)?;
+    Ok(url)
+}
+
+fn setpoints<'de, D: Deserializer<'de>>(d: D) -> Result<BTreeMap<String, [f64; 2]>, D::Error> {
+    let setpoints = BTreeMap::<String, [f64; 2]>::deserialize(d)?;
+    for (name, [min, max]) in &setpoints {
+        if name == "mode" || name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(de::Error::custom(format!(
+                "`{name}` can't be a setpoint: it needs to be one word, and not `mode`"
+            )));
+        }
+        if min.is_nan() || max.is_nan() || min > max {
+            return Err(de::Error::custom(format!(
+                "`{name}` = [{min}, {max}]: the minimum comes first"
+            )));
+        }
+    }
+    Ok(setpoints)
+}
+
+fn keys<'de, D: Deserializer<'de>>(d: D) -> Result<BTreeMap<String, String>, D::Error> {
+    let keys = BTreeMap::<String, String>::deserialize(d)?;
+    for (key, command) in &keys {
+        let mut chars = key.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if RESERVED.contains(c) => {
+                return Err(de::Error::custom(format!(
+                    "`{key}` is already taken by the dashboard"
+                )))
+            }
+            (Some(c), None) if !c.is_control() && c != ' ' => {}
+            _ => {
+                return Err(de::Error::custom(format!(
+                    "`{key}` isn't a key; bind a single character"
+                )))
+            }
+        }
+        Request::parse(command).map_err(|e| de::Error::custom(format!("`{key}`: {e}")))?;
+    }
+    Ok(keys)
+}
+
+// Something to send to the robot
+#[derive(Clone, Debug, PartialEq)]
+pub enum Request {
+    Estop,
+    Set { name: String, value: f64 },
+}
+
+impl Request {
+    // `estop` or `set NAME VALUE`
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words[..] {
+            ["estop"] => Ok(Request::Estop),
+            ["set", name, value] => {
+                let value = value
+                    .parse()
+                    .ok()
+                    .filter(|v: &f64| v.is_finite())
+                    .ok_or_else(|| format!("`{value}` isn't a number"))?;
+                Ok(Request::Set {
+                    name: name.to_string(),
+                    value,
+                })
+            }
+            _ => Err(format!(
+                "`{line}` isn't a command; use `estop` or `set NAME VALUE`"
+            )),
+        }
+    }
+
+    // The command line it came from, for the audit trail and the prompt
+    pub fn label(&self) -> String {
+        match self {
+            Request::Estop => "estop".to_string(),
+            Request::Set { name, value } => format!("set {name} {value}"),
+        }
+    }
+
+    fn payload(&self) -> String {
+        let body = match self {
+            Request::Estop => serde_json::json!({ "command": "estop" }),
+            Request::Set { name, value } => {
+                serde_json::json!({ "command": "set", "name": name, "value": value })
+            }
+        };
+        body.to_string()
+    }
+}
+
+// Where requests go: POSTed over HTTP, or published to an MQTT topic
+#[derive(Clone)]
+enum Target {
+    Http { authority: Authority, path: String },
+    Mqtt { broker: Authority, topic: String },
+}
+
+impl Target {
+    fn parse(url: &str) -> Result<Self, String> {
+        let scheme = url
+            .split_once("://")
+            .map(|(scheme, _)| scheme)
+            .ok_or_else(|| format!("expected an http:// or mqtt:// URL, got `{url}`"))?;
+        let default_port = match scheme {
+            "http" => 80,
+            "mqtt" => 1883,
+            "https" | "mqtts" => {
+                return Err(format!(
+                    "{scheme} isn't supported; send over http or mqtt, e.g. through a local proxy"
+                ))
+            }
+            _ => return Err(format!("expected an http:// or mqtt:// URL, got `{url}`")),
+        };
+        let (authority, path) = authority(url, default_port)?;
+        match scheme {
+            "http" => Ok(Target::Http {
+                authority,
+                path: match path {
+                    "" => "/".to_string(),
+                    path => path.to_string(),
+                },
+            }),
+            _ => {
+                let topic = path.trim_start_matches('/');
+                if topic.is_empty() || topic.contains(['+', '#']) {
+                    return Err(format!(
+                        "`{url}` needs a topic to publish to, without wildcards"
+                    ));
+                }
+                Ok(Target::Mqtt {
+                    broker: authority,
+                    topic: topic.to_string(),
+                })
+            }
+        }
+    }
+
+    fn connect(to: &Authority) -> io::Result<TcpStream> {
+        let stream = to.dial(TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        Ok(stream)
+    }
+
+    // What the other end said when it took the request
+    fn send(&self, payload: &str) -> io::Result<String> {
+        match self {
+            Target::Http { authority, path } => {
+                let mut stream = Self::connect(authority)?;
+                write!(
+                    stream,
+                    "POST {path} HTTP/1.0\r\nHost: {}\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nUser-Agent: ai-intui/{}\r\n\r\n{payload}",
+                    authority.written,
+                    payload.len(),
+                    env!("CARGO_PKG_VERSION")
+                )?;
+                let mut head = Vec::new();
+                stream.take(1024).read_to_end(&mut head)?;
+                let head = String::from_utf8_lossy(&head);
+                let status = head.lines().next().unwrap_or_default().trim();
+                match status.split_whitespace().nth(1) {
+                    Some(code) if code.starts_with('2') => Ok(status.to_string()),
+                    _ => Err(io::Error::other(format!("answered `{status}`"))),
+                }
+            }
+            Target::Mqtt { broker, topic } => {
+                let mut stream = Self::connect(broker)?;
+                mqtt::packet::publish(&mut stream, topic, payload.as_bytes())?;
+                Ok("acknowledged by the broker".to_string())
+            }
+        }
+    }
+}
+
+// `[control]`: sends robotics commands and keeps an audit trail of every
+// request, refusal, and reply in the logs and --log-file
+pub struct Control {
+    pub spec: ControlSpec,
+    target: Target,
+    log: SourceSender,
+}
+
+impl Control {
+    // Checks the key bindings against the setpoints too
+    pub fn new(spec: ControlSpec, ingest: &IngestQueue) -> Result<Self, String> {
+        let target = Target::parse(&spec.url)?;
+        let control = Self {
+            target,
+            log: ingest.sender(NAME, QUEUE),
+            spec,
+        };
+        for (key, command) in &control.spec.keys {
+            control
+                .check(command)
+                .map_err(|e| format!("key `{key}`: {e}"))?;
+        }
+        Ok(control)
+    }
+
+    // The command bound to a key, if any
+    pub fn key(&self, c: char) -> Option<&str> {
+        self.spec
+            .keys
+            .iter()
+            .find(|(key, _)| key.chars().eq([c]))
+            .map(|(_, command)| command.as_str())
+    }
+
+    // A command line as a request this target accepts
+    pub fn check(&self, line: &str) -> Result<Request, String> {
+        let request = Request::parse(line)?;
+        if let Request::Set { name, value } = &request {
+            let [min, max] = *self.spec.setpoints.get(name).ok_or_else(|| {
+                let known: Vec<&str> = self.spec.setpoints.keys().map(String::as_str).collect();
+                match known.is_empty() {
+                    true => format!("no setpoint `{name}`; [control.setpoints] is empty"),
+                    false => format!("no setpoint `{name}`; try {}", known.join(", ")),
+                }
+            })?;
+            if !(min..=max).contains(value) {
+                return Err(format!("{value} is outside {name}'s range {min}..{max}"));
+            }
+        }
+        Ok(request)
+    }
+
+    pub fn audit(&self, level: Level, message: String) {
+        applog::write(level, NAME, &message);
+        self.log.send(level, message);
+    }
+
+    // Sends on a thread of its own so a slow robot doesn't stall the
+    // dashboard; the outcome joins the audit trail
+    pub fn send(&self, request: &Request) {
+        let label = request.label();
+        let level = match request {
+            Request::Estop => Level::Warn,
+            Request::Set { .. } => Level::Info,
+        };
+        self.audit(level, format!("sending {label} to {}", self.spec.url));
+        let (target, log, payload) = (self.target.clone(), self.log.clone(), request.payload());
+        thread::spawn(move || {
+            let (level, message) = match target.send(&payload) {
+                Ok(reply) => (Level::Info, format!("{label} sent: {reply}")),
+                Err(e) => (Level::Error, format!("{label} failed: {e}")),
+            };
+            applog::write(level, NAME, &message);
+            log.send(level, message);
+        });
+    }
+}
+
+// The prompt shown before a setpoint is sent
+pub fn draw(buf: &mut Buffer, area: Rect, request: &Request, url: &str) {
+    let key = Style::default().add_modifier(Modifier::BOLD);
+    let lines = vec![
+        Line::from(vec![
+            Span::raw("send "),
+            Span::styled(request.label(), key.fg(Color::LightYellow)),
+        ]),
+        Line::styled(format!("to {url}"), Style::default().fg(Color::Gray)),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("y", key),
+            Span::raw(" / "),
+            Span::styled("Enter", key),
+            Span::raw(" sends • "),
+            Span::styled("n", key),
+            Span::raw(" / "),
+            Span::styled("Esc", key),
+            Span::raw(" cancels"),
+        ]),
+    ];
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) + 4;
+    let rect = centered(area, u16::try_from(width).unwrap_or(u16::MAX), 6);
+    Clear.render(rect, buf);
+    Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(Span::styled(
+                    "confirm • robotics",
+                    Style::default()
+                        .fg(Color::LightYellow)
+                        .add_modifier(Modifier::BOLD),
+                )),
+        )
+        .render(rect, buf);
+}
@@ -0,0 +1,277 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use crate::histogram::{self, Histogram, Quantile};
+
+// The AI panel's metrics, in row order; samples are matched to rows by these
+pub const METRICS: [&str; 7] = [
+    "latency p95",
+    "service load",
+    "tokens/min",
+    "errors/min",
+    "queue depth",
+    "sampler jitter",
+    "trust score",
+];
+
+// Samples kept per metric for sources that can't be read back in time
+const MAX_HISTORY: usize = 6 * 60 * 60;
+// Fastest a source can ask to be sampled: about a frame at 60Hz
+pub const MIN_INTERVAL: Duration = Duration::from_millis(16);
+// A row is stale once its source has been quiet this long, or three of its
+// own gaps between samples if that's longer, so a 15 s scrape isn't flagged
+// between scrapes
+pub const STALE_AFTER: Duration = Duration::from_secs(10);
+
+// One metric value from a producer
+pub struct Sample {
+    pub metric: String,
+    pub value: Value,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Gauge(f64),
+    // read at the panel's quantile; a histogram named after a row without
+    // its quantile (`latency`) fills that row (`latency p95`)
+    Histogram(Histogram),
+}
+
+impl Value {
+    pub fn at(&self, quantile: Quantile) -> f64 {
+        match self {
+            Value::Gauge(v) => *v,
+            Value::Histogram(h) => h.quantile(quantile.fraction()).unwrap_or(0.0),
+        }
+    }
+}
+
+// Whatever backs a mode's panel. `poll` hands over the samples that arrived
+// since the last call; the app stamps them with its clock and keeps them.
+pub trait DataSource {
+    fn poll(&mut self) -> Vec<Sample>;
+
+    // Where the numbers come from, for an expanded row's detail
+    fn describe(&self) -> String;
+
+    // A source that is a pure function of time can be read at any moment,
+    // which spares the history and lets time travel reach anywhere
+    fn at(&self, _t: f32) -> Option<Vec<Sample>> {
+        None
+    }
+
+    // How often to poll it between UI ticks, for signals faster than the
+    // screen (a control loop read every 20ms); None polls once per tick
+    fn interval(&self) -> Option<Duration> {
+        None
+    }
+}
+
+// The gauge readings a metric got during one tick
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spread {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub count: u32,
+}
+
+impl Spread {
+    fn new(v: f64) -> Self {
+        Self {
+            min: v,
+            max: v,
+            mean: v,
+            count: 1,
+        }
+    }
+
+    fn add(&mut self, v: f64) {
+        self.count += 1;
+        self.min = self.min.min(v);
+        self.max = self.max.max(v);
+        self.mean += (v - self.mean) / self.count as f64;
+    }
+}
+
+// What arrived for a metric since the last tick: gauges are aggregated, a
+// histogram is cumulative so the latest one says it all
+enum Pending {
+    Gauges(Spread),
+    Histogram(Histogram),
+}
+
+// A mode's source and, unless it can be read back in time, what it sent so far
+pub struct Feed {
+    source: Box<dyn DataSource>,
+    timeless: bool,
+    // one entry per tick: the tick's value (a gauge's mean) and, when it
+    // got several readings, their spread
+    history: HashMap<String, VecDeque<(Duration, Value, Option<Spread>)>>,
+    pending: HashMap<String, Pending>,
+    // what the panel reads out of histogram samples
+    pub quantile: Quantile,
+}
+
+impl Feed {
+    pub fn new(source: impl DataSource + 'static) -> Self {
+        Self {
+            timeless: source.at(0.0).is_some(),
+            source: Box::new(source),
+            history: HashMap::new(),
+            pending: HashMap::new(),
+            quantile: Quantile::default(),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        self.source.describe()
+    }
+
+    // The source's own sampling rate, if it wants one, no faster than MIN_INTERVAL
+    pub fn interval(&self) -> Option<Duration> {
+        match self.timeless {
+            true => None,
+            false => self.source.interval().map(|i| i.max(MIN_INTERVAL)),
+        }
+    }
+
+    // Takes what the source has now, to be folded into the next tick
+    pub fn sample(&mut self) {
+        if self.timeless {
+            return;
+        }
+        for sample in self.source.poll() {
+            let pending = self.pending.get_mut(&sample.metric);
+            match (sample.value, pending) {
+                (Value::Gauge(v), Some(Pending::Gauges(spread))) => spread.add(v),
+                (Value::Gauge(v), _) => {
+                    self.pending
+                        .insert(sample.metric, Pending::Gauges(Spread::new(v)));
+                }
+                (Value::Histogram(h), _) => {
+                    self.pending.insert(sample.metric, Pending::Histogram(h));
+                }
+            }
+        }
+    }
+
+    // Stamps whatever the source sent since the last tick with `now`: one
+    // value per metric, the mean of a gauge's readings
+    pub fn poll(&mut self, now: Duration) {
+        self.sample();
+        for (metric, pending) in self.pending.drain() {
+            let (value, spread) = match pending {
+                Pending::Gauges(s) => (Value::Gauge(s.mean), (s.count > 1).then_some(s)),
+                Pending::Histogram(h) => (Value::Histogram(h), None),
+            };
+            let samples = self.history.entry(metric).or_default();
+            if samples.len() == MAX_HISTORY {
+                samples.pop_front();
+            }
+            samples.push_back((now, value, spread));
+        }
+    }
+
+    // Each METRICS row's value at `t` seconds: the latest sample by then,
+    // or the first one before that
+    fn row_values(&self, t: f32) -> [Option<Value>; 7] {
+        if let Some(samples) = self.source.at(t) {
+            return METRICS.map(|label| {
+                let sample = samples.iter().find(|s| named(label, &s.metric));
+                sample.map(|s| s.value.clone())
+            });
+        }
+        let at = Duration::from_secs_f32(t.max(0.0));
+        METRICS.map(|label| {
+            let samples = match self.history.get(label) {
+                Some(samples) => samples,
+                None => self.history.get(histogram::base(label))?,
+            };
+            let i = samples.partition_point(|(stamp, ..)| *stamp <= at);
+            samples.get(i.saturating_sub(1)).map(|s| s.1.clone())
+        })
+    }
+
+    // How long before `t` seconds a row's latest sample came; None if none
+    // had by then. A timeless source's are never old.
+    pub fn age(&self, label: &str, t: f32) -> Option<Duration> {
+        if let Some(samples) = self.source.at(t) {
+            let sent = samples.iter().any(|s| named(label, &s.metric));
+            return sent.then_some(Duration::ZERO);
+        }
+        let samples = match self.history.get(label) {
+            Some(samples) => samples,
+            None => self.history.get(histogram::base(label))?,
+        };
+        let at = Duration::from_secs_f32(t.max(0.0));
+        let i = samples.partition_point(|(stamp, ..)| *stamp <= at);
+        let (stamp, ..) = samples.get(i.checked_sub(1)?)?;
+        Some(at - *stamp)
+    }
+
+    // How long the row had gone without a sample at `t` seconds, once that
+    // makes it stale; None while it's fresh, before its first sample, and
+    // for a timeless source
+    pub fn stale(&self, label: &str, t: f32) -> Option<Duration> {
+        let samples = match self.history.get(label) {
+            Some(samples) => samples,
+            None => self.history.get(histogram::base(label))?,
+        };
+        let at = Duration::from_secs_f32(t.max(0.0));
+        let i = samples.partition_point(|(stamp, ..)| *stamp <= at);
+        let last = samples.get(i.checked_sub(1)?)?.0;
+        let gap = i
+            .checked_sub(2)
+            .and_then(|before| samples.get(before))
+            .map_or(Duration::ZERO, |s| last - s.0);
+        let age = at - last;
+        (age > STALE_AFTER.max(gap * 3)).then_some(age)
+    }
+
+    // Every metric at `t` seconds, in METRICS order; zero for one never sent
+    pub fn values(&self, t: f32) -> [f32; 7] {
+        self.row_values(t)
+            .map(|v| v.map_or(0.0, |v| v.at(self.quantile) as f32))
+    }
+
+    // Whether the row is read out of a histogram at `t`, rather than a gauge
+    pub fn quantiled(&self, label: &str, t: f32) -> bool {
+        let i = METRICS.iter().position(|m| *m == label);
+        let values = self.row_values(t);
+        matches!(
+            i.and_then(|i| values[i].as_ref()),
+            Some(Value::Histogram(_))
+        )
+    }
+
+    // The lowest and highest reading of a row between two moments, counting
+    // the ones a tick's mean smoothed over; None when the history has none
+    pub fn extremes(&self, label: &str, from: f32, to: f32) -> Option<(f32, f32)> {
+        let samples = match self.history.get(label) {
+            Some(samples) => samples,
+            None => self.history.get(histogram::base(label))?,
+        };
+        let (from, to) = (
+            Duration::from_secs_f32(from.max(0.0)),
+            Duration::from_secs_f32(to.max(0.0)),
+        );
+        samples
+            .iter()
+            .filter(|(stamp, ..)| (from..=to).contains(stamp))
+            .map(|(_, value, spread)| match spread {
+                Some(s) => (s.min, s.max),
+                None => (value.at(self.quantile), value.at(self.quantile)),
+            })
+            .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+            .map(|(min, max)| (min as f32, max as f32))
+    }
+}
+
+// Whether a sample's metric feeds the row: by its label, or by the name a
+// histogram goes by (`latency` for `latency p95`)
+fn named(label: &str, metric: &str) -> bool {
+    metric == label || metric == histogram::base(label)
+}
@@ -0,0 +1,196 @@
+use std::{fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::pins::{Pin, Scope};
+
+// The scope `pin`, `watch`, and `silence` name derived metrics by
+pub const SCOPE: &str = "derived";
+
+// One `[derived]` entry: arithmetic over metrics from any source, written
+// with each metric in braces the way `pin` takes it:
+// `{cloud:tokens/min} / {web1:instances}`. Numbers, `+ - * /`, and
+// parentheses; no derived metric in another's formula.
+#[derive(Clone, Debug)]
+pub struct Formula {
+    text: String,
+    expr: Expr,
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Number(f64),
+    Metric(Pin),
+    Neg(Box<Expr>),
+    Op(Box<Expr>, char, Box<Expr>),
+}
+
+impl Formula {
+    // The metrics it reads, in the order written
+    pub fn operands(&self) -> Vec<&Pin> {
+        fn walk<'a>(expr: &'a Expr, out: &mut Vec<&'a Pin>) {
+            match expr {
+                Expr::Number(_) => {}
+                Expr::Metric(pin) => out.push(pin),
+                Expr::Neg(e) => walk(e, out),
+                Expr::Op(a, _, b) => {
+                    walk(a, out);
+                    walk(b, out);
+                }
+            }
+        }
+        let mut out = Vec::new();
+        walk(&self.expr, &mut out);
+        out
+    }
+
+    // The result with each operand as `read` has it; None when one is
+    // missing or it divides by zero, rather than a zero that reads as real
+    pub fn eval(&self, read: &dyn Fn(&Pin) -> Option<f64>) -> Option<f64> {
+        fn eval(expr: &Expr, read: &dyn Fn(&Pin) -> Option<f64>) -> Option<f64> {
+            Some(match expr {
+                Expr::Number(n) => *n,
+                Expr::Metric(pin) => read(pin)?,
+                Expr::Neg(e) => -eval(e, read)?,
+                Expr::Op(a, op, b) => {
+                    let (a, b) = (eval(a, read)?, eval(b, read)?);
+                    match op {
+                        '+' => a + b,
+                        '-' => a - b,
+                        '*' => a * b,
+                        _ if b == 0.0 => return None,
+                        _ => a / b,
+                    }
+                }
+            })
+        }
+        eval(&self.expr, read).filter(|v| v.is_finite())
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl FromStr for Formula {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let mut parser = Parser { text, at: 0 };
+        let expr = parser.sum()?;
+        parser.skip_spaces();
+        if parser.at < text.len() {
+            return Err(parser.error("an operator"));
+        }
+        Ok(Self {
+            text: text.trim().to_string(),
+            expr,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Formula {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        String::deserialize(d)?.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for Formula {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        self.text.serialize(s)
+    }
+}
+
+// Recursive descent, loosest binding first: sum, product, unary minus, atom
+struct Parser<'a> {
+    text: &'a str,
+    at: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.text[self.at..]
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.at += rest.len() - rest.trim_start().len();
+    }
+
+    // The next non-space character, taken if it's one of `ops`
+    fn op(&mut self, ops: &[char]) -> Option<char> {
+        self.skip_spaces();
+        let c = self.rest().chars().next().filter(|c| ops.contains(c))?;
+        self.at += 1;
+        Some(c)
+    }
+
+    fn error(&self, wanted: &str) -> String {
+        let column = self.text[..self.at].chars().count() + 1;
+        format!(
+            "expected {wanted} at column {column} of `{}`",
+            self.text.trim()
+        )
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+        while let Some(op) = self.op(&['+', '-']) {
+            expr = Expr::Op(Box::new(expr), op, Box::new(self.product()?));
+        }
+        Ok(expr)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while let Some(op) = self.op(&['*', '/']) {
+            expr = Expr::Op(Box::new(expr), op, Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.op(&['-']) {
+            Some(_) => Ok(Expr::Neg(Box::new(self.unary()?))),
+            None => self.atom(),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let wanted = "a number, `{SCOPE:METRIC}`, or `(`";
+        match self.op(&['(', '{']) {
+            Some('(') => {
+                let expr = self.sum()?;
+                self.op(&[')']).ok_or_else(|| self.error("`)`"))?;
+                Ok(expr)
+            }
+            Some(_) => {
+                let (spec, _) = self
+                    .rest()
+                    .split_once('}')
+                    .ok_or_else(|| self.error("`}`"))?;
+                let pin = Pin::parse(spec).ok_or_else(|| {
+                    format!("`{{{spec}}}` needs a scope, like `{{cloud:latency p95}}`")
+                })?;
+                if pin.scope == Scope::Derived {
+                    return Err(format!(
+                        "`{{{spec}}}`: a formula can't use another derived metric"
+                    ));
+                }
+                self.at += spec.len() + 1;
+                Ok(Expr::Metric(pin))
+            }
+            None => {
+                let len = self
+                    .rest()
+                    .find(|c: char| !c.is_ascii_digit() && c != '.')
+                    .unwrap_or(self.rest().len());
+                let n = self.rest()[..len].parse().map_err(|_| self.error(wanted))?;
+                self.at += len;
+                Ok(Expr::Number(n))
+            }
+        }
+    }
+}
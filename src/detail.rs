@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use ratatui::prelude::*;
+
+use crate::{focus::Focus, glyphs::GlyphSet};
+
+// How far back an expanded row's sparkline and min/max reach
+pub const WINDOW: Duration = Duration::from_secs(60);
+// Sparkline height in rows
+const HEIGHT: usize = 3;
+// Rows an expanded row adds under itself: the sparkline, min/max, and source
+pub const LINES: u16 = HEIGHT as u16 + 2;
+// Bottom-aligned eighth blocks, empty to full
+pub(crate) const LEVELS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Panel {
+    Ai,
+    System,
+}
+
+impl Panel {
+    pub fn focus(self) -> Focus {
+        match self {
+            Panel::Ai => Focus::Metrics,
+            Panel::System => Focus::System,
+        }
+    }
+}
+
+// The metric row picked with ↑/↓ in a focused metrics panel, and whether
+// Enter has expanded it
+#[derive(Clone, Copy, Debug)]
+pub struct Selection {
+    pub panel: Panel,
+    pub row: usize,
+    pub expanded: bool,
+}
+
+impl Selection {
+    // Down walks `panel`'s `rows` and wraps, Up goes back; coming from the
+    // other panel starts at its first (or last) row
+    pub fn step(current: Option<Self>, panel: Panel, rows: usize, back: bool) -> Option<Self> {
+        if rows == 0 {
+            return None;
+        }
+        let here = current.filter(|sel| sel.panel == panel);
+        let row = match here {
+            None if back => rows - 1,
+            None => 0,
+            Some(sel) if back => (sel.row + rows - 1) % rows,
+            Some(sel) => (sel.row + 1) % rows,
+        };
+        Some(Self {
+            panel,
+            row,
+            expanded: here.is_some_and(|sel| sel.expanded),
+        })
+    }
+}
+
+// What an expanded row shows under itself
+pub struct Detail {
+    // bar fills (0..1) across WINDOW, oldest first; None where the source
+    // had gone quiet
+    pub fills: Vec<Option<f32>>,
+    // sparkline columns where a `mark` falls
+    pub marks: Vec<usize>,
+    pub min: String,
+    pub max: String,
+    pub thresholds: String,
+    pub source: String,
+}
+
+impl Detail {
+    pub fn lines(&self, glyphs: GlyphSet, color: Color) -> Vec<Line<'static>> {
+        let dim = Style::default().fg(Color::DarkGray);
+        let mut lines: Vec<Line<'static>> = sparkline(&self.fills, &self.marks, glyphs, color)
+            .into_iter()
+            .map(|mut line| {
+                line.spans.insert(0, Span::raw("  "));
+                line
+            })
+            .collect();
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  last {}s: min {}, max {} · {}",
+                WINDOW.as_secs(),
+                self.min,
+                self.max,
+                self.thresholds
+            ),
+            dim,
+        )));
+        lines.push(Line::from(Span::styled(
+            format!("  source: {}", self.source),
+            dim,
+        )));
+        lines
+    }
+}
+
+// HEIGHT rows of bar `fills`, one column each, with `marks` cutting through
+// as vertical rules; a missing fill is a gap, dotted along the bottom so it
+// doesn't read as zero
+pub fn sparkline(
+    fills: &[Option<f32>],
+    marks: &[usize],
+    glyphs: GlyphSet,
+    color: Color,
+) -> Vec<Line<'static>> {
+    let rule = if glyphs == GlyphSet::Ascii {
+        '|'
+    } else {
+        '│'
+    };
+    (0..HEIGHT)
+        .map(|r| {
+            // eighths this row starts at, counted from the bottom
+            let floor = (HEIGHT - 1 - r) * 8;
+            let mut spans = Vec::new();
+            let mut run = String::new();
+            for (i, fill) in fills.iter().enumerate() {
+                // marks cut through the whole height as a vertical rule
+                if marks.contains(&i) {
+                    spans.push(Span::styled(
+                        std::mem::take(&mut run),
+                        Style::default().fg(color),
+                    ));
+                    spans.push(Span::styled(
+                        rule.to_string(),
+                        Style::default().fg(Color::LightYellow),
+                    ));
+                    continue;
+                }
+                let Some(fill) = fill else {
+                    spans.push(Span::styled(
+                        std::mem::take(&mut run),
+                        Style::default().fg(color),
+                    ));
+                    let gap = match (r == HEIGHT - 1, glyphs) {
+                        (false, _) => ' ',
+                        (true, GlyphSet::Ascii) => '.',
+                        (true, _) => '·',
+                    };
+                    spans.push(Span::styled(
+                        gap.to_string(),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                    continue;
+                };
+                let eighths = (fill.clamp(0.0, 1.0) * (HEIGHT * 8) as f32).round() as usize;
+                let level = eighths.saturating_sub(floor).min(8);
+                run.push(match glyphs {
+                    GlyphSet::Unicode => LEVELS[level],
+                    GlyphSet::Blocks if level >= 4 => '█',
+                    GlyphSet::Ascii if level >= 4 => '#',
+                    _ => ' ',
+                });
+            }
+            spans.push(Span::styled(run, Style::default().fg(color)));
+            Line::from(spans)
+        })
+        .collect()
+}
@@ -0,0 +1,120 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use directories::ProjectDirs;
+
+use crate::cli::Cli;
+
+// Where settings and session data live: XDG base directories on Linux/BSD,
+// ~/Library on macOS, %APPDATA% on Windows. `--config-dir` / `--data-dir`
+// override either; without a home directory everything falls back to
+// ./.ai-intui.
+pub struct AppDirs {
+    pub config: PathBuf,
+    pub data: PathBuf,
+    pub state: PathBuf,
+}
+
+impl AppDirs {
+    pub fn resolve(cli: &Cli) -> Self {
+        let project = ProjectDirs::from("", "IoTIVP", "ai-intui");
+        let fallback = || PathBuf::from(".ai-intui");
+        let data = cli.data_dir.clone().unwrap_or_else(|| {
+            project
+                .as_ref()
+                .map_or_else(fallback, |p| p.data_dir().to_path_buf())
+        });
+        Self {
+            config: cli.config_dir.clone().unwrap_or_else(|| {
+                project
+                    .as_ref()
+                    .map_or_else(fallback, |p| p.config_dir().to_path_buf())
+            }),
+            // history is state in XDG terms; other platforms keep it with the data
+            state: match (&cli.data_dir, &project) {
+                (None, Some(p)) => p.state_dir().unwrap_or(p.data_dir()).to_path_buf(),
+                _ => data.clone(),
+            },
+            data,
+        }
+    }
+
+    // Creates every directory that doesn't exist yet; true on first run
+    pub fn ensure(&self) -> io::Result<bool> {
+        let first_run = !self.config.exists();
+        for dir in [
+            self.config.clone(),
+            self.themes(),
+            self.layouts(),
+            self.data.clone(),
+            self.recordings(),
+            self.state.clone(),
+        ] {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(first_run)
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.config.join("config.toml")
+    }
+
+    // educator-mode overrides for the bundled metric explanations
+    pub fn explain_file(&self) -> PathBuf {
+        self.config.join("explain.toml")
+    }
+
+    pub fn themes(&self) -> PathBuf {
+        self.config.join("themes")
+    }
+
+    pub fn layouts(&self) -> PathBuf {
+        self.config.join("layouts")
+    }
+
+    pub fn recordings(&self) -> PathBuf {
+        self.data.join("recordings")
+    }
+
+    pub fn history_file(&self) -> PathBuf {
+        self.state.join("history")
+    }
+
+    pub fn pins_file(&self) -> PathBuf {
+        self.state.join("pins")
+    }
+
+    pub fn watchlist_file(&self) -> PathBuf {
+        self.state.join("watchlist.toml")
+    }
+
+    pub fn views_file(&self) -> PathBuf {
+        self.state.join("views.toml")
+    }
+
+    // (what, where) for the `paths` command
+    pub fn listing(&self) -> Vec<(&'static str, PathBuf)> {
+        vec![
+            ("config", self.config_file()),
+            ("explanations", self.explain_file()),
+            ("themes", self.themes()),
+            ("layouts", self.layouts()),
+            ("history", self.history_file()),
+            ("pins", self.pins_file()),
+            ("watchlist", self.watchlist_file()),
+            ("views", self.views_file()),
+            ("recordings", self.recordings()),
+        ]
+    }
+}
+
+// Shortens paths under the home directory to ~/… for display
+pub fn display(path: &Path) -> String {
+    let home = directories::BaseDirs::new().map(|b| b.home_dir().to_path_buf());
+    match home.as_deref().and_then(|h| path.strip_prefix(h).ok()) {
+        Some(rest) => format!("~/{}", rest.display()),
+        None => path.display().to_string(),
+    }
+}
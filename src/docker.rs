@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    io,
+    process::Stdio,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use ratatui::{prelude::*, widgets::Paragraph};
+use serde_json::Value;
+
+use crate::{
+    chrome::Chrome,
+    ingest::SourceSender,
+    layout::{fit, sanitize, table_rows},
+    logs::Level,
+    programs::Programs,
+    sysmetrics,
+};
+
+// What sampling problems are logged under
+pub const NAME: &str = "docker";
+pub const QUEUE: usize = 16;
+// Between samples; `docker stats --no-stream` itself takes about two
+// seconds, as it measures CPU over one
+const POLL: Duration = Duration::from_secs(1);
+
+// One running container
+#[derive(Clone, Debug, PartialEq)]
+pub struct Container {
+    pub name: String,
+    // of one core, so 2.0 is two cores' worth
+    pub cpu: f32,
+    // bytes in use
+    pub memory: f64,
+    // bytes a second received and sent, and read and written; None until
+    // the second sample
+    pub network: Option<f32>,
+    pub disk: Option<f32>,
+}
+
+// --docker: the running containers from `docker stats` every POLL, for cloud
+// mode, where they take the system panel's place. Busiest first.
+pub struct DockerStats {
+    pub containers: Vec<Container>,
+    // whether a sample came back yet
+    pub sampled: bool,
+    // why the last sample failed, until one succeeds
+    pub error: Option<String>,
+    rx: Receiver<io::Result<Vec<Container>>>,
+}
+
+impl DockerStats {
+    pub fn start(tx: SourceSender, programs: &Programs) -> Self {
+        let (samples, rx) = mpsc::channel();
+        let programs = programs.clone();
+        thread::spawn(move || sample(&tx, &samples, &programs));
+        Self {
+            containers: Vec::new(),
+            sampled: false,
+            error: None,
+            rx,
+        }
+    }
+
+    pub fn drain(&mut self) {
+        while let Ok(sample) = self.rx.try_recv() {
+            self.sampled = true;
+            match sample {
+                Ok(containers) => {
+                    self.containers = containers;
+                    self.error = None;
+                }
+                Err(e) => {
+                    self.containers.clear();
+                    self.error = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    // Whether Docker answered last time; until then, and while it doesn't,
+    // the system panel stays
+    pub fn available(&self) -> bool {
+        self.sampled && self.error.is_none()
+    }
+}
+
+fn sample(tx: &SourceSender, samples: &Sender<io::Result<Vec<Container>>>, programs: &Programs) {
+    // a failure is logged once, not every POLL, and so is the recovery
+    let mut failing = false;
+    // id → (when, bytes over the network, bytes to and from disk)
+    let mut last: HashMap<String, (Instant, f64, f64)> = HashMap::new();
+    loop {
+        let sample = read(&mut last, programs);
+        match &sample {
+            Err(e) if !failing => {
+                tx.send(Level::Warn, format!("can't read container stats: {e}"));
+                failing = true;
+            }
+            Ok(containers) if failing => {
+                tx.send(
+                    Level::Info,
+                    format!("reading stats of {} containers", containers.len()),
+                );
+                failing = false;
+            }
+            _ => {}
+        }
+        if samples.send(sample).is_err() {
+            // the app is gone
+            return;
+        }
+        thread::sleep(POLL);
+    }
+}
+
+// One JSON object per container, as `docker stats` formats it:
+//
+//   {"CPUPerc":"12.50%","ID":"3f2a…","MemUsage":"340MiB / 7.63GiB",
+//    "Name":"web","NetIO":"1.2MB / 648kB","BlockIO":"0B / 4.1MB",...}
+//
+// Network and disk come as totals since the container started, so their
+// rates are the change since the last sample.
+fn read(
+    last: &mut HashMap<String, (Instant, f64, f64)>,
+    programs: &Programs,
+) -> io::Result<Vec<Container>> {
+    let output = programs
+        .command("docker")
+        .args([
+            "stats",
+            "--no-stream",
+            "--no-trunc",
+            "--format",
+            "{{json .}}",
+        ])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::other("docker isn't on PATH"),
+            _ => e,
+        })?;
+    if !output.status.success() {
+        let text = String::from_utf8_lossy(&output.stderr);
+        let why = text.lines().map(str::trim).find(|l| !l.is_empty());
+        return Err(io::Error::other(match why {
+            Some(why) => format!("docker exited with {}: {why}", output.status),
+            None => format!("docker exited with {}", output.status),
+        }));
+    }
+    let now = Instant::now();
+    let mut seen = HashMap::new();
+    let mut containers: Vec<Container> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let stats: Value = serde_json::from_str(line).ok()?;
+            let field = |key: &str| stats.get(key).and_then(Value::as_str).unwrap_or("");
+            let id = field("ID").to_string();
+            let network = pair(field("NetIO")).map(|(rx, tx)| rx + tx);
+            let disk = pair(field("BlockIO")).map(|(r, w)| r + w);
+            let (network, disk) = (network.unwrap_or(0.0), disk.unwrap_or(0.0));
+            let rate = |now_bytes: f64, then_bytes: f64, then: Instant| {
+                let secs = now.duration_since(then).as_secs_f64();
+                // a restarted container starts its totals over
+                (secs > 0.0 && now_bytes >= then_bytes)
+                    .then(|| ((now_bytes - then_bytes) / secs) as f32)
+            };
+            let (network_rate, disk_rate) = match last.get(&id) {
+                Some(&(then, net, blk)) => (rate(network, net, then), rate(disk, blk, then)),
+                None => (None, None),
+            };
+            seen.insert(id, (now, network, disk));
+            Some(Container {
+                name: field("Name").trim_start_matches('/').to_string(),
+                cpu: percent(field("CPUPerc")).unwrap_or(0.0),
+                memory: field("MemUsage")
+                    .split('/')
+                    .next()
+                    .and_then(size)
+                    .unwrap_or(0.0),
+                network: network_rate,
+                disk: disk_rate,
+            })
+        })
+        .collect();
+    // containers that went away go from the totals too
+    *last = seen;
+    containers.sort_by(|a, b| {
+        b.cpu
+            .partial_cmp(&a.cpu)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(containers)
+}
+
+// `12.50%` as 0.125; None for the `--` of a container just starting
+fn percent(text: &str) -> Option<f32> {
+    text.trim()
+        .strip_suffix('%')?
+        .parse::<f32>()
+        .ok()
+        .map(|p| p / 100.0)
+}
+
+// `1.2MB / 648kB` as bytes
+fn pair(text: &str) -> Option<(f64, f64)> {
+    let (a, b) = text.split_once('/')?;
+    Some((size(a)?, size(b)?))
+}
+
+// `648kB`, `1.2MB`, or `340MiB` as bytes: Docker gives memory in binary
+// units and IO in decimal ones
+fn size(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let scale = match unit.trim() {
+        "" | "B" => 1.0,
+        "kB" | "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(number.parse::<f64>().ok()? * scale)
+}
+
+// `340M`, `1.2G`: five cells at most
+fn memory(bytes: f64) -> String {
+    match bytes {
+        b if b < 1e3 => format!("{b:.0}B"),
+        b if b < 1e6 => format!("{:.0}K", b / 1e3),
+        b if b < 1e9 => format!("{:.0}M", b / 1e6),
+        b => format!("{:.1}G", b / 1e9),
+    }
+}
+
+// Cloud mode's container table, in the system panel's place: one row per
+// container, busiest first
+pub fn draw(buf: &mut Buffer, area: Rect, docker: &DockerStats, chrome: Chrome) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let inner = area.width.saturating_sub(2) as usize;
+    let rows = table_rows(area, docker.containers.len());
+    // CPU, memory, network, and disk, each after a space
+    let fixed = 7 + 6 + 7 + 7;
+    let name_w = inner.saturating_sub(fixed).max(6);
+    let rate = |rate: Option<f32>| rate.map_or("-".to_string(), sysmetrics::rate);
+    let mut lines = vec![Line::styled(
+        format!(
+            "{:<name_w$} {:>6} {:>5} {:>6} {:>6}",
+            "container", "cpu", "mem", "net", "disk"
+        ),
+        dim,
+    )];
+    for container in docker.containers.iter().take(rows) {
+        let name = sanitize(&container.name);
+        let (name, pad) = fit(&name, name_w);
+        // over a core's worth reads hot
+        let cpu = match container.cpu {
+            c if c >= 1.0 => Color::Red,
+            c if c >= 0.5 => Color::Yellow,
+            _ => Color::LightGreen,
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{name}{pad} "), Style::default().fg(Color::White)),
+            Span::styled(
+                format!("{:>5.1}% ", container.cpu * 100.0),
+                Style::default().fg(cpu),
+            ),
+            Span::raw(format!(
+                "{:>5} {:>6} {:>6}",
+                memory(container.memory),
+                rate(container.network),
+                rate(container.disk)
+            )),
+        ]));
+    }
+    let hidden = docker.containers.len().saturating_sub(rows);
+    if docker.containers.is_empty() {
+        lines.push(Line::styled("no containers running", dim));
+    } else if hidden > 0 {
+        lines.push(Line::styled(format!("+{hidden} more"), dim));
+    }
+    let title = format!("containers • {} running", docker.containers.len());
+    Paragraph::new(lines)
+        .block(
+            chrome.block().border_style(dim).title(Span::styled(
+                title,
+                Style::default()
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+            )),
+        )
+        .render(area, buf);
+}
@@ -0,0 +1,270 @@
+use std::{fmt, fs, path::Path, time::Duration};
+
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use ratatui::{backend::TestBackend, Terminal};
+
+use crate::{
+    app::{handle_event, AppState, Control},
+    cli::Cli,
+    layout::PanelLayout,
+    logs::Level,
+    parse::level_from_name,
+    profiler::FrameProfiler,
+    timebase::Timebase,
+    ui::draw_frame,
+};
+
+// Terminal size a script starts with; `resize` changes it
+const START_SIZE: (u16, u16) = (100, 30);
+// Virtual time per `tick` unless the run passed --sim-step
+const TICK: Duration = Duration::from_millis(100);
+
+// One scripted input or check
+#[derive(Debug)]
+enum Step {
+    Event(Event),
+    Type(String),
+    Resize(u16, u16),
+    // a line as if a source named `source` had produced it
+    Source {
+        source: String,
+        level: Level,
+        message: String,
+    },
+    Tick(u32),
+    Expect(String),
+    Reject(String),
+    Dump,
+}
+
+#[derive(Debug)]
+pub struct DriveError {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for DriveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// End-to-end input script for the `drive` subcommand (built with the `e2e`
+// feature). Steps run in order through the real event handling, tick, and
+// drawing code, against an in-memory terminal on a virtual clock:
+//
+//   # comments and blank lines are ignored
+//   key 3                     one key: a character, or esc, enter, tab,
+//   key ctrl+c                backtab, backspace, del, up, down, left, right,
+//                             home, end, pgup, pgdn; with ctrl+/alt+/shift+
+//   type set mode cloud       one key press per character
+//   paste some text
+//   mouse down 10 5           down, up, drag, scroll-up, scroll-down at COL ROW
+//   resize 60 20
+//   source syslog warn disk almost full
+//   source api error a\nb      `\n` is a line break
+//   tick 10                   advance the clock and run the tick N times
+//   expect Cloud              the screen must contain this text...
+//   reject Robotics           ...or must not
+//   dump                      print the screen
+pub fn run(cli: &Cli, script: &Path) -> Result<(), String> {
+    let text = fs::read_to_string(script).map_err(|e| format!("{}: {e}", script.display()))?;
+    let steps = parse(&text).map_err(|e| format!("{}: {e}", script.display()))?;
+
+    // same run, same screens: virtual clock and a fixed seed
+    let mut app = AppState::new(cli);
+    app.timebase = Timebase::simulated();
+    app.sim_step = Some(cli.sim_step.map_or(TICK, Duration::from_millis));
+    app.rng = StdRng::seed_from_u64(cli.seed.unwrap_or_default());
+    let (w, h) = START_SIZE;
+    let mut terminal = Terminal::new(TestBackend::new(w, h)).map_err(|e| e.to_string())?;
+    let mut panels = PanelLayout::default();
+    let mut profiler = FrameProfiler::new(None);
+    let mut sources = Vec::new();
+    draw_frame(&mut terminal, &mut app, &mut panels, &mut profiler).map_err(|e| e.to_string())?;
+
+    for (line, step) in steps {
+        let fail =
+            |message: String| format!("{}: {}", script.display(), DriveError { line, message });
+        let mut events = Vec::new();
+        match step {
+            Step::Event(ev) => events.push(ev),
+            Step::Type(text) => events.extend(
+                text.chars()
+                    .map(|c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))),
+            ),
+            Step::Resize(w, h) => {
+                terminal.backend_mut().resize(w, h);
+                events.push(Event::Resize(w, h));
+            }
+            Step::Source {
+                source,
+                level,
+                message,
+            } => {
+                let known = sources.iter().position(|(name, _)| *name == source);
+                let i = known.unwrap_or_else(|| {
+                    sources.push((source.clone(), app.ingest.sender(&source, 1024)));
+                    sources.len() - 1
+                });
+                sources[i].1.send(level, message);
+                app.drain_ingest();
+            }
+            Step::Tick(n) => (0..n).for_each(|_| app.tick()),
+            // every step ends with a frame, so checks see what a user would
+            Step::Expect(text) => check(&terminal, &text, true).map_err(fail)?,
+            Step::Reject(text) => check(&terminal, &text, false).map_err(fail)?,
+            Step::Dump => println!("{}", screen(&terminal).join("\n")),
+        }
+        for ev in events {
+            if let Control::Quit = handle_event(&mut app, ev) {
+                return Ok(());
+            }
+        }
+        if app.dirty {
+            draw_frame(&mut terminal, &mut app, &mut panels, &mut profiler)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+fn screen(terminal: &Terminal<TestBackend>) -> Vec<String> {
+    let buf = terminal.backend().buffer();
+    (0..buf.area.height)
+        .map(|y| {
+            (0..buf.area.width)
+                .map(|x| buf.get(x, y).symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+fn check(terminal: &Terminal<TestBackend>, text: &str, present: bool) -> Result<(), String> {
+    let rows = screen(terminal);
+    if rows.iter().any(|row| row.contains(text)) == present {
+        return Ok(());
+    }
+    let what = if present { "missing" } else { "unexpected" };
+    Err(format!("{what} `{text}` on screen:\n{}", rows.join("\n")))
+}
+
+fn parse(text: &str) -> Result<Vec<(usize, Step)>, DriveError> {
+    let mut steps = Vec::new();
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let raw = raw.trim();
+        if raw.is_empty() || raw.starts_with('#') {
+            continue;
+        }
+        let (verb, rest) = raw.split_once(char::is_whitespace).unwrap_or((raw, ""));
+        let rest = rest.trim();
+        let err = |message: String| DriveError { line, message };
+        let step = match verb {
+            "key" => Step::Event(Event::Key(key(rest).map_err(err)?)),
+            "type" => Step::Type(rest.to_string()),
+            "paste" => Step::Event(Event::Paste(rest.to_string())),
+            "mouse" => Step::Event(Event::Mouse(mouse(rest).map_err(err)?)),
+            "resize" => {
+                let (w, h) = pair(rest).map_err(err)?;
+                Step::Resize(w, h)
+            }
+            "source" => {
+                let mut words = rest.splitn(3, ' ');
+                let (Some(source), Some(level), Some(message)) =
+                    (words.next(), words.next(), words.next())
+                else {
+                    return Err(err("expected `source NAME LEVEL MESSAGE`".to_string()));
+                };
+                Step::Source {
+                    source: source.to_string(),
+                    level: level_from_name(level),
+                    // `\n` stands for a line break, for multi-line payloads
+                    message: message.replace("\\n", "\n"),
+                }
+            }
+            "tick" => Step::Tick(match rest {
+                "" => 1,
+                n => n.parse().map_err(|_| err(format!("bad count `{n}`")))?,
+            }),
+            "expect" => Step::Expect(rest.to_string()),
+            "reject" => Step::Reject(rest.to_string()),
+            "dump" => Step::Dump,
+            _ => return Err(err(format!("unknown step `{verb}`"))),
+        };
+        steps.push((line, step));
+    }
+    Ok(steps)
+}
+
+fn key(spec: &str) -> Result<KeyEvent, String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut name = spec;
+    for (prefix, modifier) in [
+        ("ctrl+", KeyModifiers::CONTROL),
+        ("alt+", KeyModifiers::ALT),
+        ("shift+", KeyModifiers::SHIFT),
+    ] {
+        if let Some(rest) = name.strip_prefix(prefix) {
+            modifiers |= modifier;
+            name = rest;
+        }
+    }
+    let code = match name {
+        "esc" => KeyCode::Esc,
+        "enter" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "del" => KeyCode::Delete,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pgup" => KeyCode::PageUp,
+        "pgdn" => KeyCode::PageDown,
+        "space" => KeyCode::Char(' '),
+        _ => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return Err(format!("unknown key `{spec}`")),
+            }
+        }
+    };
+    Ok(KeyEvent::new(code, modifiers))
+}
+
+fn mouse(spec: &str) -> Result<MouseEvent, String> {
+    let (action, at) = spec.split_once(' ').unwrap_or((spec, ""));
+    let kind = match action {
+        "down" => MouseEventKind::Down(MouseButton::Left),
+        "up" => MouseEventKind::Up(MouseButton::Left),
+        "drag" => MouseEventKind::Drag(MouseButton::Left),
+        "scroll-up" => MouseEventKind::ScrollUp,
+        "scroll-down" => MouseEventKind::ScrollDown,
+        _ => return Err(format!("unknown mouse action `{action}`")),
+    };
+    let (column, row) = pair(at)?;
+    Ok(MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    })
+}
+
+fn pair(spec: &str) -> Result<(u16, u16), String> {
+    let mut words = spec.split_whitespace().map(str::parse::<u16>);
+    match (words.next(), words.next(), words.next()) {
+        (Some(Ok(a)), Some(Ok(b)), None) => Ok((a, b)),
+        _ => Err(format!("expected two numbers, got `{spec}`")),
+    }
+}
@@ -0,0 +1,37 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use crate::config::ConfigError;
+
+// Explanations shipped with the binary; see the file for the format
+const BUNDLED: &str = include_str!("../assets/explain.toml");
+
+// What each metric means, keyed by its dashboard label, for educator mode
+pub struct Catalog {
+    entries: BTreeMap<String, String>,
+}
+
+impl Catalog {
+    pub fn bundled() -> Self {
+        Self {
+            entries: toml::from_str(BUNDLED).expect("bundled explain.toml is valid"),
+        }
+    }
+
+    // The bundled catalog with the keys from `overrides` on top, if it exists
+    pub fn load(overrides: &Path) -> Result<Self, ConfigError> {
+        let mut catalog = Self::bundled();
+        let text = match fs::read_to_string(overrides) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(catalog),
+            Err(e) => return Err(ConfigError::Io(overrides.to_path_buf(), e)),
+        };
+        let custom: BTreeMap<String, String> =
+            toml::from_str(&text).map_err(|e| ConfigError::Parse(overrides.to_path_buf(), e))?;
+        catalog.entries.extend(custom);
+        Ok(catalog)
+    }
+
+    pub fn get(&self, label: &str) -> Option<&str> {
+        self.entries.get(label).map(String::as_str)
+    }
+}
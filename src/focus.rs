@@ -0,0 +1,59 @@
+use ratatui::style::{Color, Style};
+
+// The panel that gets the arrows, Enter, and Esc. Tab/Shift+Tab (or
+// Ctrl+arrows) walk them in this order; the command bar's focus is just
+// command mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Focus {
+    Banner,
+    Metrics,
+    System,
+    Logs,
+    Command,
+}
+
+const ORDER: [Focus; 5] = [
+    Focus::Banner,
+    Focus::Metrics,
+    Focus::System,
+    Focus::Logs,
+    Focus::Command,
+];
+
+impl Focus {
+    // From nothing focused, Tab starts at the banner and Shift+Tab at the command bar
+    pub fn step(current: Option<Self>, back: bool) -> Self {
+        let n = ORDER.len();
+        let at = current.and_then(|f| ORDER.iter().position(|&o| o == f));
+        let next = match (at, back) {
+            (None, false) => 0,
+            (None, true) => n - 1,
+            (Some(i), false) => (i + 1) % n,
+            (Some(i), true) => (i + n - 1) % n,
+        };
+        ORDER[next]
+    }
+
+    // What the idle command bar says while this panel has the keys
+    pub fn hint(self) -> &'static str {
+        match self {
+            Focus::Banner => "←/→ switch modes • Tab next panel • Esc unfocuses",
+            Focus::Metrics | Focus::System => {
+                "↑/↓ pick a row • Enter expands it • Tab next panel • Esc unfocuses"
+            }
+            Focus::Logs => {
+                "1–9 one source, 0 all • ↑/↓ pick a payload, Enter opens it • Tab next panel • Esc unfocuses"
+            }
+            Focus::Command => "",
+        }
+    }
+}
+
+// Border of a panel that may have the focus
+pub fn border(focused: bool) -> Style {
+    Style::default().fg(if focused {
+        Color::LightBlue
+    } else {
+        Color::DarkGray
+    })
+}
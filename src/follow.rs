@@ -0,0 +1,149 @@
+use std::{
+    fs::{self, File, Metadata},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    ingest::SourceSender,
+    logs::Level,
+    parse::{parse_entry, ParserPool},
+};
+
+// How often a followed file is checked for new lines
+const POLL: Duration = Duration::from_millis(250);
+// Lines buffered per file before the oldest are dropped
+pub const QUEUE: usize = 1024;
+// Read at most this much per poll, so a file that grew by gigabytes streams
+// in instead of landing at once
+const CHUNK: u64 = 1 << 20;
+// A line without a newline this long is sent as it is
+const MAX_LINE: usize = 64 << 10;
+
+// What a followed file's lines are shown under: its name, `app.log`
+pub fn source_name(path: &Path) -> String {
+    path.file_name().map_or_else(
+        || path.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+// Follows `path` from a background thread like `tail -F`: only lines added
+// after it starts are sent, a truncated file is read again from the top, and a
+// rotated one is read to its end before the new file under the name is opened.
+// Lines are parsed on a pool, so a busy JSON-lines file isn't held to one core.
+pub fn spawn(path: PathBuf, tx: SourceSender) {
+    let out = tx.clone();
+    let mut pool = ParserPool::new(parse_entry, move |lines| out.send_batch(lines));
+    thread::spawn(move || {
+        let mut tail = Tail {
+            path,
+            file: None,
+            read: 0,
+            partial: Vec::new(),
+            skip_existing: true,
+        };
+        // reported once until it changes, not every poll
+        let mut failing: Option<String> = None;
+        loop {
+            match tail.poll() {
+                Ok(lines) => {
+                    if failing.take().is_some() {
+                        tx.send(Level::Info, format!("reading {}", tail.path.display()));
+                    }
+                    pool.feed(lines);
+                }
+                Err(e) => {
+                    let error = format!("can't read {}: {e}; retrying", tail.path.display());
+                    if failing.as_ref() != Some(&error) {
+                        tx.send(Level::Warn, error.clone());
+                        failing = Some(error);
+                    }
+                }
+            }
+            thread::sleep(POLL);
+        }
+    });
+}
+
+struct Tail {
+    path: PathBuf,
+    file: Option<File>,
+    // bytes of the open file already read
+    read: u64,
+    // the unfinished last line
+    partial: Vec<u8>,
+    // true until the first poll: what the file held at startup is history,
+    // while one that only appears later is read whole
+    skip_existing: bool,
+}
+
+impl Tail {
+    // Every line appended since the last poll, as read
+    fn poll(&mut self) -> io::Result<Vec<String>> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => {
+                let skip = std::mem::replace(&mut self.skip_existing, false);
+                let file = File::open(&self.path)?;
+                self.read = match skip {
+                    true => file.metadata()?.len(),
+                    false => 0,
+                };
+                self.partial.clear();
+                self.file.insert(file)
+            }
+        };
+
+        let open = file.metadata()?;
+        if open.len() < self.read {
+            // truncated in place, e.g. `> app.log` or copytruncate
+            self.read = 0;
+            self.partial.clear();
+        }
+        file.seek(SeekFrom::Start(self.read))?;
+        let n = file.take(CHUNK).read_to_end(&mut self.partial)? as u64;
+        self.read += n;
+
+        // the old file is finished once it's read to the end and its name
+        // points somewhere else; a missing name keeps it open until a new
+        // file appears
+        if n < CHUNK {
+            if let Ok(named) = fs::metadata(&self.path) {
+                if replaced(&open, &named, self.read) {
+                    self.file = None;
+                }
+            }
+        }
+        Ok(self.lines())
+    }
+
+    fn lines(&mut self) -> Vec<String> {
+        let mut lines = Vec::new();
+        while let Some(end) = self.partial.iter().position(|&b| b == b'\n') {
+            let raw: Vec<u8> = self.partial.drain(..=end).collect();
+            lines.push(String::from_utf8_lossy(&raw).into_owned());
+        }
+        if self.partial.len() >= MAX_LINE || (self.file.is_none() && !self.partial.is_empty()) {
+            let raw = std::mem::take(&mut self.partial);
+            lines.push(String::from_utf8_lossy(&raw).into_owned());
+        }
+        lines
+    }
+}
+
+// Whether the name now belongs to a different file than the open one
+#[cfg(unix)]
+fn replaced(open: &Metadata, named: &Metadata, _read: u64) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    (open.dev(), open.ino()) != (named.dev(), named.ino())
+}
+
+// Without inode numbers a replacement only shows once it is shorter than
+// what was read of the old file
+#[cfg(not(unix))]
+fn replaced(_open: &Metadata, named: &Metadata, read: u64) -> bool {
+    named.len() < read
+}
@@ -0,0 +1,114 @@
+use clap::ValueEnum;
+
+use crate::layout::pad;
+
+// 64 cells of each fill glyph; bars are sliced out of these instead of allocating
+const FULL_BLOCKS: &str = "████████████████████████████████████████████████████████████████";
+const FULL_HASHES: &str = "################################################################";
+// left-aligned eighth blocks for the fractional cell
+const PARTIAL_BLOCKS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+const NO_PARTIALS: [&str; 8] = [""; 8];
+
+// Which bar glyphs the terminal can be trusted to draw
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum GlyphSet {
+    /// Full and eighth blocks (smooth bars)
+    Unicode,
+    /// Full blocks only, with ASCII borders and charts; for fonts without the eighth-block glyphs (legacy conhost)
+    Blocks,
+    /// Plain `#` and ASCII borders, for terminals without block glyphs at all
+    Ascii,
+}
+
+impl GlyphSet {
+    // Windows Terminal sets WT_SESSION; without it we're most likely on legacy
+    // conhost, whose default fonts lack the eighth blocks. Elsewhere a non-UTF-8
+    // locale means no block glyphs at all, and the Linux console font only has
+    // the full block.
+    pub fn detect() -> Self {
+        if cfg!(windows) {
+            return if std::env::var_os("WT_SESSION").is_none() {
+                GlyphSet::Blocks
+            } else {
+                GlyphSet::Unicode
+            };
+        }
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|k| std::env::var(k).ok().filter(|v| !v.is_empty()))
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if !locale.is_empty() && !locale.contains("utf-8") && !locale.contains("utf8") {
+            GlyphSet::Ascii
+        } else if std::env::var("TERM").is_ok_and(|t| t == "linux") {
+            GlyphSet::Blocks
+        } else {
+            GlyphSet::Unicode
+        }
+    }
+
+    fn fill(self) -> (&'static str, usize, &'static [&'static str; 8]) {
+        match self {
+            GlyphSet::Unicode => (FULL_BLOCKS, '█'.len_utf8(), &PARTIAL_BLOCKS),
+            GlyphSet::Blocks => (FULL_BLOCKS, '█'.len_utf8(), &NO_PARTIALS),
+            GlyphSet::Ascii => (FULL_HASHES, 1, &NO_PARTIALS),
+        }
+    }
+
+    // Bar in eighth-cell steps as (full cells, partial glyph, empty padding), all
+    // static. Sets without partial glyphs round to whole cells instead.
+    pub fn bar(self, norm: f32, len: usize) -> (&'static str, &'static str, &'static str) {
+        let (full_str, glyph_len, partials) = self.fill();
+        let len = len.min(full_str.len() / glyph_len);
+        let n = norm.clamp(0.0, 1.0);
+
+        let (full, partial) = if partials[1].is_empty() {
+            ((n * len as f32).round() as usize, "")
+        } else {
+            let eighths = (n * (len * 8) as f32).round() as usize;
+            let full = eighths / 8;
+            (
+                full,
+                if full < len {
+                    partials[eighths % 8]
+                } else {
+                    ""
+                },
+            )
+        };
+        let used = full + usize::from(!partial.is_empty());
+        (&full_str[..full * glyph_len], partial, pad(len - used))
+    }
+
+    // What a drawn cell becomes in this set: fonts without the eighth blocks
+    // can't be trusted with box drawing or Braille either, so those turn into
+    // ASCII lines and dots. None keeps the symbol.
+    pub fn plain(self, symbol: &str) -> Option<char> {
+        if self == GlyphSet::Unicode {
+            return None;
+        }
+        let mut chars = symbol.chars();
+        let c = chars.next()?;
+        if chars.next().is_some() {
+            return None;
+        }
+        match c {
+            '─' | '━' | '┄' | '┅' | '┈' | '┉' | '╌' | '╍' | '═' | '╴' | '╶' | '╸' | '╺' | '╼'
+            | '╾' => Some('-'),
+            '│' | '┃' | '┆' | '┇' | '┊' | '┋' | '╎' | '╏' | '║' | '╵' | '╷' | '╹' | '╻' | '╽'
+            | '╿' => Some('|'),
+            '\u{2500}'..='\u{257f}' => Some('+'),
+            '\u{2800}'..='\u{28ff}' => {
+                // dots in the top two rows, the bottom two, or both
+                let dots = c as u32 - 0x2800;
+                Some(match (dots & 0x1b != 0, dots & 0xe4 != 0) {
+                    (false, false) => ' ',
+                    (true, false) => '\'',
+                    (false, true) => '.',
+                    (true, true) => ':',
+                })
+            }
+            _ => None,
+        }
+    }
+}
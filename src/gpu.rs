@@ -0,0 +1,307 @@
+use std::{
+    collections::HashMap,
+    io,
+    process::Stdio,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+use ratatui::{prelude::*, widgets::Paragraph};
+
+use crate::{
+    chrome::Chrome,
+    glyphs::GlyphSet,
+    ingest::SourceSender,
+    layout::{fit, sanitize, table_rows},
+    logs::Level,
+    programs::Programs,
+};
+
+// What sampling problems are logged under
+pub const NAME: &str = "gpu";
+pub const QUEUE: usize = 16;
+// How often nvidia-smi is asked; `pmon` itself takes about a second
+const POLL: Duration = Duration::from_secs(2);
+
+// One process with a context on one GPU
+#[derive(Clone, Debug, PartialEq)]
+pub struct Process {
+    pub pid: u32,
+    pub name: String,
+    // the GPU's index, as nvidia-smi numbers them
+    pub gpu: u32,
+    // MiB, where the driver reports it (not under Windows WDDM)
+    pub memory: Option<f64>,
+    // of the card's total memory, 0..1
+    pub memory_share: Option<f32>,
+    // of the card's SM time over the last sample, 0..1
+    pub sm_share: Option<f32>,
+}
+
+// --gpu-processes: who's on the GPUs, read from NVML through nvidia-smi
+// every POLL, for the AI observability panel's strip. Busiest first.
+pub struct GpuProcesses {
+    pub processes: Vec<Process>,
+    // GPUs seen in the last sample; None until there's been one
+    pub gpus: Option<usize>,
+    // why the last sample failed, until one succeeds
+    pub error: Option<String>,
+    rx: Receiver<io::Result<(usize, Vec<Process>)>>,
+}
+
+impl GpuProcesses {
+    pub fn start(tx: SourceSender, programs: &Programs) -> Self {
+        let (samples, rx) = mpsc::channel();
+        let programs = programs.clone();
+        thread::spawn(move || sample(&tx, &samples, &programs));
+        Self {
+            processes: Vec::new(),
+            gpus: None,
+            error: None,
+            rx,
+        }
+    }
+
+    pub fn drain(&mut self) {
+        while let Ok(sample) = self.rx.try_recv() {
+            match sample {
+                Ok((gpus, processes)) => {
+                    self.gpus = Some(gpus);
+                    self.processes = processes;
+                    self.error = None;
+                }
+                Err(e) => {
+                    self.processes.clear();
+                    self.error = Some(e.to_string());
+                }
+            }
+        }
+    }
+}
+
+fn sample(
+    tx: &SourceSender,
+    samples: &Sender<io::Result<(usize, Vec<Process>)>>,
+    programs: &Programs,
+) {
+    // a failure is logged once, not every POLL, and so is the recovery
+    let mut failing = false;
+    loop {
+        let sample = read(programs);
+        match &sample {
+            Err(e) if !failing => {
+                tx.send(Level::Warn, format!("can't list GPU processes: {e}"));
+                failing = true;
+            }
+            Ok((gpus, _)) if failing => {
+                tx.send(Level::Info, format!("listing processes on {gpus} GPUs"));
+                failing = false;
+            }
+            _ => {}
+        }
+        if samples.send(sample).is_err() {
+            // the app is gone
+            return;
+        }
+        thread::sleep(POLL);
+    }
+}
+
+// nvidia-smi's stdout for `args`, or why there isn't any
+fn smi(programs: &Programs, args: &[&str]) -> io::Result<String> {
+    let output = programs
+        .command("nvidia-smi")
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::other("nvidia-smi isn't on PATH"),
+            _ => e,
+        })?;
+    if !output.status.success() {
+        // nvidia-smi reports driver trouble on stdout
+        let text = [output.stderr, output.stdout].concat();
+        let text = String::from_utf8_lossy(&text);
+        let why = text.lines().map(str::trim).find(|l| !l.is_empty());
+        return Err(io::Error::other(match why {
+            Some(why) => format!("nvidia-smi exited with {}: {why}", output.status),
+            None => format!("nvidia-smi exited with {}", output.status),
+        }));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// (number of GPUs, their compute processes): memory from the compute apps
+// query, SM share from `pmon`, which not every card supports
+fn read(programs: &Programs) -> io::Result<(usize, Vec<Process>)> {
+    let gpus = smi(
+        programs,
+        &[
+            "--query-gpu=uuid,index,memory.total",
+            "--format=csv,noheader,nounits",
+        ],
+    )?;
+    // uuid → (index, total MiB)
+    let gpus: HashMap<&str, (u32, Option<f64>)> = gpus
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [uuid, index, total] = fields[..] else {
+                return None;
+            };
+            Some((uuid, (index.parse().ok()?, total.parse().ok())))
+        })
+        .collect();
+    let apps = smi(
+        programs,
+        &[
+            "--query-compute-apps=gpu_uuid,pid,process_name,used_memory",
+            "--format=csv,noheader,nounits",
+        ],
+    )?;
+    let sm = smi(programs, &["pmon", "--count", "1", "--select", "u"])
+        .map(|table| sm_shares(&table))
+        .unwrap_or_default();
+
+    let mut processes: Vec<Process> = apps
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            // the name may have commas of its own
+            let [uuid, pid, .., memory] = fields[..] else {
+                return None;
+            };
+            let name = fields[2..fields.len() - 1].join(",");
+            let pid = pid.parse().ok()?;
+            let &(gpu, total) = gpus.get(uuid)?;
+            // `[N/A]` where the driver doesn't track it
+            let memory: Option<f64> = memory.parse().ok();
+            Some(Process {
+                pid,
+                // `/usr/bin/python3` reads as `python3`
+                name: name.rsplit(['/', '\\']).next().unwrap_or(&name).to_string(),
+                gpu,
+                memory,
+                memory_share: memory
+                    .zip(total.filter(|t| *t > 0.0))
+                    .map(|(m, t)| (m / t) as f32),
+                sm_share: sm.get(&(gpu, pid)).copied(),
+            })
+        })
+        .collect();
+    processes.sort_by(|a, b| {
+        let key = |p: &Process| (p.sm_share.unwrap_or(0.0), p.memory.unwrap_or(0.0) as f32);
+        key(b)
+            .partial_cmp(&key(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.pid.cmp(&b.pid))
+    });
+    Ok((gpus.len(), processes))
+}
+
+// (gpu, pid) → SM share from `nvidia-smi pmon --select u`, by the columns its
+// header names, since newer drivers add some:
+//
+//   # gpu         pid   type     sm    mem    enc    dec    command
+//   # Idx           #    C/G      %      %      %      %    name
+//       0       4242     C     63     20      -      -    python3
+fn sm_shares(table: &str) -> HashMap<(u32, u32), f32> {
+    let mut columns = None;
+    let mut shares = HashMap::new();
+    for line in table.lines() {
+        if let Some(header) = line.strip_prefix('#') {
+            let names: Vec<&str> = header.split_whitespace().collect();
+            let find = |name| names.iter().position(|n| *n == name);
+            if let (Some(gpu), Some(pid), Some(sm)) = (find("gpu"), find("pid"), find("sm")) {
+                columns = Some((gpu, pid, sm));
+            }
+            continue;
+        }
+        let Some((gpu, pid, sm)) = columns else {
+            continue;
+        };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let field = |i: usize| fields.get(i).copied().unwrap_or("");
+        // `-` for a process that had no SM time in the sample
+        if let (Ok(gpu), Ok(pid)) = (field(gpu).parse(), field(pid).parse()) {
+            let sm: f32 = field(sm).parse().unwrap_or(0.0);
+            shares.insert((gpu, pid), sm / 100.0);
+        }
+    }
+    shares
+}
+
+// The AI observability strip: one row per process, busiest first, with a bar
+// for its SM share
+pub fn draw(buf: &mut Buffer, area: Rect, gpu: &GpuProcesses, glyphs: GlyphSet, chrome: Chrome) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let inner = area.width.saturating_sub(2) as usize;
+    let rows = table_rows(area, gpu.processes.len());
+    // pid, gpu, memory, its share, and the SM share's number and bar
+    let fixed = 8 + 4 + 10 + 6 + 6;
+    let name_w = inner.saturating_sub(fixed + 12).clamp(8, 24);
+    let bar_len = inner.saturating_sub(fixed + name_w + 1);
+    let percent =
+        |share: Option<f32>| share.map_or("-".to_string(), |s| format!("{:.0}%", s * 100.0));
+    let mut lines = vec![Line::styled(
+        format!(
+            "{:>7} {:<name_w$} {:>3} {:>9} {:>5} {:>5}",
+            "pid", "process", "gpu", "memory", "mem", "sm"
+        ),
+        dim,
+    )];
+    for process in gpu.processes.iter().take(rows) {
+        let name = sanitize(&process.name);
+        let (name, pad) = fit(&name, name_w);
+        let memory = process
+            .memory
+            .map_or("-".to_string(), |m| format!("{m:.0} MiB"));
+        let (full, partial, _) = glyphs.bar(process.sm_share.unwrap_or(0.0), bar_len);
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:>7} ", process.pid), dim),
+            Span::styled(format!("{name}{pad} "), Style::default().fg(Color::White)),
+            Span::raw(format!(
+                "{:>3} {memory:>9} {:>5} ",
+                process.gpu,
+                percent(process.memory_share)
+            )),
+            Span::styled(
+                format!("{:>5} ", percent(process.sm_share)),
+                Style::default().fg(Color::LightCyan),
+            ),
+            Span::styled(format!("{full}{partial}"), Style::default().fg(Color::Cyan)),
+        ]));
+    }
+    let hidden = gpu.processes.len().saturating_sub(rows);
+    if let Some(error) = &gpu.error {
+        lines.push(Line::styled(
+            error.clone(),
+            Style::default().fg(Color::Yellow),
+        ));
+    } else if gpu.gpus.is_none() {
+        lines.push(Line::styled("asking nvidia-smi", dim));
+    } else if gpu.processes.is_empty() {
+        lines.push(Line::styled("no processes on the GPU", dim));
+    } else if hidden > 0 {
+        lines.push(Line::styled(format!("+{hidden} more"), dim));
+    }
+    let title = match gpu.gpus {
+        Some(gpus) => format!(
+            "gpu processes • {} on {gpus} GPU{}",
+            gpu.processes.len(),
+            if gpus == 1 { "" } else { "s" }
+        ),
+        None => "gpu processes".to_string(),
+    };
+    Paragraph::new(lines)
+        .block(
+            chrome.block().border_style(dim).title(Span::styled(
+                title,
+                Style::default()
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+            )),
+        )
+        .render(area, buf);
+}
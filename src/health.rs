@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::ui::centered;
+
+// Row label of the composite when `[health]` is configured; it takes the
+// place of the built-in trust score
+pub const LABEL: &str = "health index";
+
+// `[health]` in config.toml: the composite as a weighted mean over real
+// metrics. Each key is a metric as `pin` takes it (`errors/min`,
+// `system:cpu load`, `cloud:latency p95`); a positive weight means higher is
+// healthier, a negative one that higher is worse.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct HealthSpec {
+    // logs an error when the index drops below this (0..1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert_below: Option<f32>,
+    pub weights: BTreeMap<String, f32>,
+}
+
+// One metric's share of the index, for the breakdown
+pub struct Part {
+    pub metric: String,
+    pub value: String,
+    pub weight: f32,
+    // how healthy this metric alone reads, 0..1
+    pub score: f32,
+}
+
+impl Part {
+    // `fill` is the metric's bar fill, which already maps it onto 0..1
+    pub fn new(metric: String, value: String, weight: f32, fill: f32) -> Self {
+        let score = if weight >= 0.0 { fill } else { 1.0 - fill };
+        Self {
+            metric,
+            value,
+            weight,
+            score: score.clamp(0.0, 1.0),
+        }
+    }
+}
+
+// Weighted mean of the parts' scores
+pub fn combine(parts: &[Part]) -> f32 {
+    let total: f32 = parts.iter().map(|p| p.weight.abs()).sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+    parts.iter().map(|p| p.weight.abs() * p.score).sum::<f32>() / total
+}
+
+// The `health` popup: every part's value, weight, and points toward the index
+pub fn draw(buf: &mut Buffer, area: Rect, parts: &[Part], alert_below: Option<f32>) {
+    let total: f32 = parts
+        .iter()
+        .map(|p| p.weight.abs())
+        .sum::<f32>()
+        .max(f32::EPSILON);
+    let width = parts
+        .iter()
+        .map(|p| p.metric.len())
+        .max()
+        .unwrap_or(0)
+        .max(6);
+    let head = Style::default().fg(Color::DarkGray);
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            "{:<width$}  {:>9}  {:>6}  {:>6}  {:>6}",
+            "metric", "value", "weight", "health", "points"
+        ),
+        head,
+    ))];
+    for p in parts {
+        let color = match p.score {
+            s if s < 0.4 => Color::Red,
+            s if s < 0.7 => Color::Yellow,
+            _ => Color::Green,
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!(
+                "{:<width$}  {:>9}  {:>+6.1}  ",
+                p.metric, p.value, p.weight
+            )),
+            Span::styled(
+                format!("{:>5.0}%", p.score * 100.0),
+                Style::default().fg(color),
+            ),
+            Span::raw(format!(
+                "  {:>6.1}",
+                p.weight.abs() * p.score / total * 100.0
+            )),
+        ]));
+    }
+    let score = combine(parts);
+    let alert = alert_below
+        .map(|below| format!("   alerts below {:.0}%", below * 100.0))
+        .unwrap_or_default();
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(
+            format!("{LABEL} {:.0}%", score * 100.0),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(alert, head),
+    ]));
+
+    let rect = centered(area, width as u16 + 45, lines.len() as u16 + 2);
+    Clear.render(rect, buf);
+    Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(Span::styled(
+                    format!("{LABEL} • Esc closes"),
+                    Style::default()
+                        .fg(Color::LightCyan)
+                        .add_modifier(Modifier::BOLD),
+                )),
+        )
+        .render(rect, buf);
+}
@@ -0,0 +1,123 @@
+// Latency the way Prometheus and OTLP histograms report it: how many
+// observations fell at or below each bucket bound, cumulative, ascending. The
+// last bound may be +Inf.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    buckets: Vec<(f64, f64)>,
+}
+
+impl Histogram {
+    // None unless there is a bucket and the counts never go down
+    pub fn new(mut buckets: Vec<(f64, f64)>) -> Option<Self> {
+        buckets.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let ordered = buckets.windows(2).all(|w| w[0].1 <= w[1].1);
+        (!buckets.is_empty() && ordered).then_some(Self { buckets })
+    }
+
+    pub fn count(&self) -> f64 {
+        self.buckets.last().map_or(0.0, |b| b.1)
+    }
+
+    // The value below which `q` of the observations fall, interpolated
+    // linearly inside its bucket like Prometheus' histogram_quantile. One
+    // that lands in the +Inf bucket is reported as the highest finite bound.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let rank = q.clamp(0.0, 1.0) * self.count();
+        let i = self
+            .buckets
+            .iter()
+            .position(|&(_, n)| n >= rank && n > 0.0)?;
+        let (upper, count) = self.buckets[i];
+        let (lower, below) = match i {
+            0 => (0.0_f64.min(upper), 0.0),
+            _ => self.buckets[i - 1],
+        };
+        if upper.is_infinite() {
+            return (i > 0).then_some(lower);
+        }
+        Some(lower + (upper - lower) * (rank - below) / (count - below))
+    }
+}
+
+// Buckets, in ms, for latencies that arrive one at a time: OTLP spans and
+// StatsD timers
+pub const LATENCY_BOUNDS: [f64; 12] = [
+    5.0,
+    10.0,
+    25.0,
+    50.0,
+    100.0,
+    250.0,
+    500.0,
+    1000.0,
+    2500.0,
+    5000.0,
+    10000.0,
+    f64::INFINITY,
+];
+
+// Single observations bucketed at LATENCY_BOUNDS, each counted `weight` times
+pub fn observed(values: &[(f64, f64)]) -> Option<Histogram> {
+    let buckets = LATENCY_BOUNDS
+        .iter()
+        .map(|&bound| {
+            let n = values.iter().filter(|v| v.0 <= bound).map(|v| v.1).sum();
+            (bound, n)
+        })
+        .collect();
+    Histogram::new(buckets)
+}
+
+// Which quantile a panel reads out of its histograms
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Quantile {
+    P50,
+    #[default]
+    P95,
+    P99,
+}
+
+impl Quantile {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim_start_matches('p') {
+            "50" => Some(Quantile::P50),
+            "95" => Some(Quantile::P95),
+            "99" => Some(Quantile::P99),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Quantile::P50 => "p50",
+            Quantile::P95 => "p95",
+            Quantile::P99 => "p99",
+        }
+    }
+
+    pub fn fraction(self) -> f64 {
+        match self {
+            Quantile::P50 => 0.50,
+            Quantile::P95 => 0.95,
+            Quantile::P99 => 0.99,
+        }
+    }
+
+    // The AI panel's latency row when its histogram is read at this quantile
+    pub fn latency_label(self) -> &'static str {
+        match self {
+            Quantile::P50 => "latency p50",
+            Quantile::P95 => "latency p95",
+            Quantile::P99 => "latency p99",
+        }
+    }
+}
+
+// A row label without its quantile: a histogram called `latency` feeds the
+// `latency p95` row
+pub fn base(label: &str) -> &str {
+    ["p50", "p95", "p99"]
+        .iter()
+        .find_map(|q| label.strip_suffix(q)?.strip_suffix(' '))
+        .unwrap_or(label)
+}
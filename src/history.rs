@@ -0,0 +1,82 @@
+use std::{fs, io, path::Path};
+
+// Lines kept; the oldest go first
+const MAX_LINES: usize = 500;
+
+// Lines run from the command bar, oldest first, for Up/Down to bring back.
+// A `;` batch is one line. Kept for the next session, one per line.
+#[derive(Default)]
+pub struct History {
+    lines: Vec<String>,
+    // the line brought back, counting from the oldest; None while typing a
+    // new one
+    pick: Option<usize>,
+}
+
+impl History {
+    pub fn load(path: &Path) -> Self {
+        let lines: Vec<String> = fs::read_to_string(path)
+            .map(|text| {
+                text.lines()
+                    .filter(|l| !l.trim().is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let skip = lines.len().saturating_sub(MAX_LINES);
+        Self {
+            lines: lines.into_iter().skip(skip).collect(),
+            pick: None,
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text: String = self.lines.iter().map(|line| format!("{line}\n")).collect();
+        fs::write(path, text)
+    }
+
+    // A line just run; running the last one again doesn't keep it twice
+    pub fn push(&mut self, line: &str) {
+        self.pick = None;
+        let line = line.trim();
+        if line.is_empty() || self.lines.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.lines.push(line.to_string());
+        if self.lines.len() > MAX_LINES {
+            self.lines.remove(0);
+        }
+    }
+
+    // Up: the line before the one brought back, or the newest; None when
+    // there's nothing further back
+    pub fn older(&mut self) -> Option<&str> {
+        let pick = match self.pick {
+            Some(0) => return None,
+            Some(i) => i - 1,
+            None => self.lines.len().checked_sub(1)?,
+        };
+        self.pick = Some(pick);
+        Some(&self.lines[pick])
+    }
+
+    // Down: the line after the one brought back, or an empty one past the
+    // newest; None when nothing was brought back
+    pub fn newer(&mut self) -> Option<&str> {
+        let pick = self.pick? + 1;
+        if pick == self.lines.len() {
+            self.pick = None;
+            return Some("");
+        }
+        self.pick = Some(pick);
+        Some(&self.lines[pick])
+    }
+
+    // Back to typing a new line
+    pub fn reset(&mut self) {
+        self.pick = None;
+    }
+}
@@ -0,0 +1,427 @@
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader},
+    net::TcpStream,
+    process::{Child, Stdio},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph},
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    datasource::Value,
+    glyphs::GlyphSet,
+    histogram::{Histogram, Quantile},
+    ingest::{IngestQueue, SourceSender},
+    layout::fit,
+    net::split_port,
+    parse::parse_line,
+    programs::Programs,
+    reconnect::{Connector, LinkState, Links},
+};
+
+// What `ssh://` hosts run on the far side; it streams JSON lines to stdout
+const AGENT_COMMAND: &str = "ai-intui agent";
+// Lines buffered per host before the oldest are dropped
+const HOST_QUEUE: usize = 1024;
+// Narrowest tile before the grid drops a column
+const TILE_W: u16 = 34;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    // user@host, optionally with a port
+    Ssh { dest: String, port: Option<u16> },
+    // host:port of an agent whose output is served over TCP
+    Tcp(String),
+}
+
+// One `--host NAME=URL` entry
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HostSpec {
+    pub name: String,
+    pub target: Target,
+}
+
+impl FromStr for HostSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, url) = s.split_once('=').ok_or_else(|| {
+            format!("expected NAME=ssh://HOST or NAME=tcp://HOST:PORT, got `{s}`")
+        })?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("host name is empty".to_string());
+        }
+        let target = if let Some(rest) = url.strip_prefix("ssh://") {
+            let (dest, port) = split_port(rest).map_err(|_| format!("bad port in `{url}`"))?;
+            Target::Ssh {
+                dest: dest.to_string(),
+                port,
+            }
+        } else if let Some(addr) = url.strip_prefix("tcp://") {
+            if !addr.contains(':') {
+                return Err(format!("`{url}` needs a port"));
+            }
+            Target::Tcp(addr.to_string())
+        } else {
+            return Err(format!("`{url}` must start with ssh:// or tcp://"));
+        };
+        if matches!(&target, Target::Ssh { dest, .. } if dest.is_empty()) {
+            return Err(format!("`{url}` has no host"));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            target,
+        })
+    }
+}
+
+impl fmt::Display for HostSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.target {
+            Target::Ssh { dest, port: None } => write!(f, "{}=ssh://{dest}", self.name),
+            Target::Ssh {
+                dest,
+                port: Some(port),
+            } => write!(f, "{}=ssh://{dest}:{port}", self.name),
+            Target::Tcp(addr) => write!(f, "{}=tcp://{addr}", self.name),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HostSpec {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        String::deserialize(d)?.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for HostSpec {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+// Agent protocol: one JSON object per line, either a metric sample or a log
+// line in any format `parse_line` understands
+pub fn metric_line(name: &str, value: f64) -> String {
+    // a dashboard never needs more than three decimals, and f32 samples widened
+    // to f64 would otherwise carry a tail of noise digits
+    let value = (value * 1000.0).round() / 1000.0;
+    serde_json::json!({ "metric": name, "value": value }).to_string()
+}
+
+// A sample is `{"metric": NAME, "value": N}`, or for a histogram
+// `{"metric": NAME, "buckets": [[LE, COUNT], ..., ["+Inf", COUNT]]}` with
+// cumulative counts, as Prometheus exposes them
+pub(crate) fn parse_metric(line: &str) -> Option<(String, Value)> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    let sample: serde_json::Value = serde_json::from_str(line).ok()?;
+    let name = sample.get("metric")?.as_str()?.to_string();
+    if let Some(value) = sample.get("value") {
+        return Some((name, Value::Gauge(value.as_f64()?)));
+    }
+    let buckets = sample.get("buckets")?.as_array()?.iter().map(|bucket| {
+        let le = match bucket.get(0)? {
+            serde_json::Value::String(s) => s.parse().ok()?,
+            le => le.as_f64()?,
+        };
+        Some((le, bucket.get(1)?.as_f64()?))
+    });
+    let histogram = Histogram::new(buckets.collect::<Option<_>>()?)?;
+    Some((name, Value::Histogram(histogram)))
+}
+
+// Latest sample of every metric a host has reported, in first-seen order
+type Metrics = Arc<Mutex<Vec<(String, Value)>>>;
+
+struct AgentConnector {
+    target: Target,
+    metrics: Metrics,
+    programs: Programs,
+}
+
+impl Connector for AgentConnector {
+    // ssh keeps its child so `run` can report how it exited
+    type Conn = (Box<dyn BufRead + Send>, Option<Child>);
+
+    fn connect(&mut self) -> io::Result<Self::Conn> {
+        match &self.target {
+            Target::Tcp(addr) => {
+                let stream = TcpStream::connect(addr)?;
+                Ok((Box::new(BufReader::new(stream)), None))
+            }
+            Target::Ssh { dest, port } => {
+                let mut ssh = self.programs.command("ssh");
+                ssh.args(["-T", "-o", "BatchMode=yes"]);
+                if let Some(port) = port {
+                    ssh.arg("-p").arg(port.to_string());
+                }
+                // stderr would scribble over the dashboard
+                let mut child = ssh
+                    .arg(dest)
+                    .arg(AGENT_COMMAND)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::null())
+                    .spawn()?;
+                let stdout = child.stdout.take().expect("stdout is piped");
+                Ok((Box::new(BufReader::new(stdout)), Some(child)))
+            }
+        }
+    }
+
+    fn run(&mut self, (reader, child): Self::Conn, tx: &SourceSender) -> io::Result<()> {
+        for line in reader.lines() {
+            let line = line?;
+            if let Some((name, value)) = parse_metric(&line) {
+                let mut metrics = self.metrics.lock().unwrap();
+                match metrics.iter_mut().find(|(n, _)| *n == name) {
+                    Some(slot) => slot.1 = value,
+                    None => metrics.push((name, value)),
+                }
+            } else if let Some(parsed) = parse_line(&line) {
+                tx.send(parsed.level, parsed.message);
+            }
+        }
+        match child.map(|mut c| c.wait()).transpose()? {
+            Some(status) if !status.success() => {
+                Err(io::Error::other(format!("ssh exited with {status}")))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct Host {
+    pub name: Arc<str>,
+    metrics: Metrics,
+    // what the host's tile reads out of histogram samples
+    pub quantile: Quantile,
+}
+
+impl Host {
+    // Histograms come out at the tile's quantile, named for it (`request ms p95`)
+    pub fn metrics(&self) -> Vec<(String, f64)> {
+        let metrics = self.metrics.lock().unwrap();
+        metrics
+            .iter()
+            .map(|(name, value)| match value {
+                Value::Gauge(v) => (name.clone(), *v),
+                Value::Histogram(_) => (
+                    format!("{name} {}", self.quantile.name()),
+                    value.at(self.quantile),
+                ),
+            })
+            .collect()
+    }
+
+    pub fn has_histograms(&self) -> bool {
+        let metrics = self.metrics.lock().unwrap();
+        metrics
+            .iter()
+            .any(|(_, v)| matches!(v, Value::Histogram(_)))
+    }
+}
+
+// Every `--host`, each on its own reconnecting link. Log lines go through the
+// ingest queue under the host's name; metrics land here.
+#[derive(Default)]
+pub struct Fleet {
+    pub hosts: Vec<Host>,
+}
+
+impl Fleet {
+    pub fn start(
+        specs: &[HostSpec],
+        programs: &Programs,
+        links: &Links,
+        ingest: &IngestQueue,
+    ) -> Self {
+        let hosts = specs
+            .iter()
+            .map(|spec| {
+                let metrics = Metrics::default();
+                let connector = AgentConnector {
+                    target: spec.target.clone(),
+                    programs: programs.clone(),
+                    metrics: metrics.clone(),
+                };
+                links.spawn(&spec.name, connector, ingest.sender(&spec.name, HOST_QUEUE));
+                Host {
+                    name: Arc::from(spec.name.as_str()),
+                    metrics,
+                    quantile: Quantile::default(),
+                }
+            })
+            .collect();
+        Self { hosts }
+    }
+}
+
+// Grid of hosts, or one of them up close
+#[derive(Clone, Copy, Default)]
+pub struct HostView {
+    pub focus: usize,
+    pub detail: bool,
+}
+
+fn state_style(state: Option<LinkState>) -> Style {
+    match state {
+        Some(LinkState::Connected) => Style::default().fg(Color::Green),
+        Some(LinkState::Connecting) => Style::default().fg(Color::Yellow),
+        Some(LinkState::Retrying { .. }) | None => Style::default().fg(Color::Red),
+    }
+}
+
+fn metric_lines(
+    metrics: &[(String, f64)],
+    width: usize,
+    glyphs: GlyphSet,
+    max: usize,
+) -> Vec<Line<'static>> {
+    let label_width = (width / 2).clamp(8, 18);
+    let value_width = 9;
+    let bar_len = width.saturating_sub(label_width + 1 + value_width + 1);
+    metrics
+        .iter()
+        .take(max)
+        .map(|(name, value)| {
+            let (label, label_pad) = fit(name, label_width);
+            let text = if value.abs() < 10.0 {
+                format!("{value:.2}")
+            } else {
+                format!("{value:.0}")
+            };
+            let (text, value_pad) = fit(&text, value_width);
+            let mut spans = vec![
+                Span::styled(label.to_string(), Style::default().fg(Color::Gray)),
+                Span::raw(label_pad),
+                Span::raw(" "),
+                Span::styled(
+                    format!("{value_pad}{text}"),
+                    Style::default().fg(Color::White),
+                ),
+            ];
+            // fractions get a bar; counts and durations have no natural scale
+            if (0.0..=1.0).contains(value) && bar_len > 0 {
+                let (full, partial, _) = glyphs.bar(*value as f32, bar_len);
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(
+                    format!("{full}{partial}"),
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn host_block(host: &Host, state: Option<LinkState>, focused: bool) -> Block<'static> {
+    let border = if focused {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default().fg(Color::DarkGray)
+    };
+    let label = state.map_or_else(|| "unknown".to_string(), LinkState::label);
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(border)
+        .title(Line::from(vec![
+            Span::styled(
+                host.name.to_string(),
+                Style::default()
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" • "),
+            Span::styled(label, state_style(state)),
+        ]))
+}
+
+// Mini-dashboards, as many columns as fit at TILE_W
+pub fn draw_grid(
+    buf: &mut Buffer,
+    area: Rect,
+    fleet: &Fleet,
+    states: &[(Arc<str>, LinkState)],
+    // a host's uptime line at a given width, once it has a history
+    uptime: &dyn Fn(&str, usize) -> Option<Line<'static>>,
+    view: HostView,
+    glyphs: GlyphSet,
+) {
+    let n = fleet.hosts.len().max(1) as u16;
+    let cols = (area.width / TILE_W).clamp(1, n);
+    let rows = n.div_ceil(cols);
+    let tile_h = (area.height / rows).max(3);
+    for (i, host) in fleet.hosts.iter().enumerate() {
+        let (col, row) = (i as u16 % cols, i as u16 / cols);
+        let y = area.y + row * tile_h;
+        if y + tile_h > area.bottom() {
+            break;
+        }
+        let x = area.x + col * (area.width / cols);
+        // the last column takes whatever width is left over
+        let w = if col + 1 == cols {
+            area.right() - x
+        } else {
+            area.width / cols
+        };
+        let tile = Rect::new(x, y, w, tile_h);
+        let state = states.iter().find(|(n, _)| *n == host.name).map(|s| s.1);
+        let block = host_block(host, state, i == view.focus);
+        let inner = block.inner(tile);
+        let width = inner.width as usize;
+        let mut lines: Vec<Line> = uptime(&host.name, width).into_iter().collect();
+        let room = (inner.height as usize).saturating_sub(lines.len());
+        lines.extend(metric_lines(&host.metrics(), width, glyphs, room));
+        Paragraph::new(lines).block(block).render(tile, buf);
+    }
+}
+
+// One host: every metric, then its recent log lines
+pub fn draw_detail(
+    buf: &mut Buffer,
+    area: Rect,
+    host: &Host,
+    state: Option<LinkState>,
+    uptime: Option<Line>,
+    glyphs: GlyphSet,
+    logs: Vec<Line>,
+) {
+    let block = host_block(host, state, true);
+    let inner = block.inner(area);
+    block.render(area, buf);
+
+    let metrics = host.metrics();
+    let width = inner.width as usize;
+    let mut lines: Vec<Line> = uptime.into_iter().collect();
+    let metrics_h = (metrics.len() as u16 + lines.len() as u16).min(inner.height / 2);
+    let top = Rect::new(inner.x, inner.y, inner.width, metrics_h);
+    let room = (metrics_h as usize).saturating_sub(lines.len());
+    lines.extend(metric_lines(&metrics, width, glyphs, room));
+    Paragraph::new(lines).render(top, buf);
+
+    let bottom = Rect::new(
+        inner.x,
+        inner.y + metrics_h,
+        inner.width,
+        inner.height - metrics_h,
+    );
+    let log_block = Block::default()
+        .borders(Borders::TOP)
+        .border_style(Style::default().fg(Color::DarkGray))
+        .title(Span::styled("logs", Style::default().fg(Color::LightBlue)));
+    let shown = log_block.inner(bottom).height as usize;
+    let skip = logs.len().saturating_sub(shown);
+    Paragraph::new(logs.into_iter().skip(skip).collect::<Vec<_>>())
+        .block(log_block)
+        .render(bottom, buf);
+}
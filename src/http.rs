@@ -0,0 +1,276 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+// Just enough HTTP/1.1 for the endpoints the dashboard serves (--http,
+// --mark-listen, --otlp, --llm-proxy) and the pages it reads: one request a
+// connection, closed after the reply.
+
+// A status line's code and reason, and the text that goes with it
+pub type Reply = (&'static str, String);
+
+// The most a request line or header may take, and how many headers there may
+// be; a client sending more is told so instead of being read into memory
+const MAX_LINE: u64 = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+
+// Every connection to `listener` on a thread of its own, so a slow client
+// doesn't hold up the others; its reads and writes time out after `timeout`
+pub fn serve<F>(listener: TcpListener, timeout: Duration, handle: F)
+where
+    F: Fn(TcpStream, BufReader<TcpStream>) -> io::Result<()> + Send + Sync + 'static,
+{
+    let handle = Arc::new(handle);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let handle = handle.clone();
+            thread::spawn(move || {
+                let _ = stream.set_read_timeout(Some(timeout));
+                let _ = stream.set_write_timeout(Some(timeout));
+                // one bad client shouldn't take the endpoint down
+                if let Ok(reader) = stream.try_clone().map(BufReader::new) {
+                    let _ = handle(stream, reader);
+                }
+            });
+        }
+    });
+}
+
+// A request's line and headers; its body is left on the reader
+pub struct Request {
+    pub method: String,
+    // the path and query, as sent
+    pub target: String,
+    // (name, value) as sent, in order
+    pub headers: Vec<(String, String)>,
+}
+
+// A line of the request's head, or None when it runs past MAX_LINE
+fn head_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    reader.by_ref().take(MAX_LINE + 1).read_line(&mut line)?;
+    Ok((line.len() as u64 <= MAX_LINE).then_some(line))
+}
+
+impl Request {
+    // Err is the reply for a head over the caps
+    pub fn read(reader: &mut impl BufRead) -> io::Result<Result<Self, Reply>> {
+        let Some(line) = head_line(reader)? else {
+            return Ok(Err((
+                "400 Bad Request",
+                format!("request line over {MAX_LINE} bytes"),
+            )));
+        };
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap_or("").to_string();
+        let target = parts.next().unwrap_or("").to_string();
+        let too_large = "431 Request Header Fields Too Large";
+        let mut headers = Vec::new();
+        loop {
+            let Some(header) = head_line(reader)? else {
+                return Ok(Err((too_large, format!("header over {MAX_LINE} bytes"))));
+            };
+            if header.trim().is_empty() {
+                break;
+            }
+            if headers.len() == MAX_HEADERS {
+                return Ok(Err((too_large, format!("over {MAX_HEADERS} headers"))));
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        Ok(Ok(Self {
+            method,
+            target,
+            headers,
+        }))
+    }
+
+    pub fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or("")
+    }
+
+    // `?name=`'s value, decoded
+    pub fn param(&self, name: &str) -> Option<String> {
+        let (_, query) = self.target.split_once('?')?;
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+            .map(decode)
+    }
+
+    // A header's value by name, in any case
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    // The body, chunked or as long as Content-Length says; None when it's
+    // over `max` bytes
+    pub fn body(&self, reader: &mut impl BufRead, max: usize) -> io::Result<Option<Vec<u8>>> {
+        let chunked = self
+            .header("transfer-encoding")
+            .is_some_and(|te| te.to_ascii_lowercase().contains("chunked"));
+        if chunked {
+            return read_chunked(reader, max);
+        }
+        let length = self
+            .header("content-length")
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(0);
+        if length > max {
+            return Ok(None);
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        Ok(Some(body))
+    }
+}
+
+// A whole reply; the connection closes after it
+pub fn reply(stream: &mut impl Write, status: &str, kind: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {kind}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+// A reply of one line of plain text, for curl to print
+pub fn reply_text(stream: &mut impl Write, (status, text): Reply) -> io::Result<()> {
+    reply(stream, status, "text/plain", &format!("{text}\n"))
+}
+
+// Query-string decoding: `+` is a space, `%XX` a byte
+pub fn decode(raw: &str) -> String {
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut rest = raw.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        rest = tail;
+        match b {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = rest
+                    .get(..2)
+                    .and_then(|h| std::str::from_utf8(h).ok())
+                    .and_then(|h| u8::from_str_radix(h, 16).ok());
+                match hex {
+                    Some(byte) => {
+                        bytes.push(byte);
+                        rest = &rest[2..];
+                    }
+                    None => bytes.push(b'%'),
+                }
+            }
+            _ => bytes.push(b),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+// A chunked body read off `reader` up to the empty chunk; None when it's
+// over `max` bytes
+fn read_chunked(reader: &mut impl BufRead, max: usize) -> io::Result<Option<Vec<u8>>> {
+    let mut chunks = Chunks::default();
+    let mut body = Vec::new();
+    while !chunks.done() {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let n = buf.len();
+        body.extend(chunks.feed(buf));
+        reader.consume(n);
+        if body.len() > max {
+            return Ok(None);
+        }
+    }
+    match chunks {
+        Chunks::Bad => Err(io::Error::new(io::ErrorKind::InvalidData, "bad chunk size")),
+        _ => Ok(Some(body)),
+    }
+}
+
+// A chunked body that's all there, decoded; None when it's malformed or cut
+// short
+pub fn dechunk(body: &[u8]) -> Option<Vec<u8>> {
+    let mut chunks = Chunks::default();
+    let data = chunks.feed(body);
+    matches!(chunks, Chunks::Done).then_some(data)
+}
+
+// Where a chunked body is up to, for decoding it as it arrives
+pub enum Chunks {
+    // reading a size line
+    Size(Vec<u8>),
+    // this much chunk data left
+    Data(usize),
+    // the CRLF after a chunk's data, this many bytes of it left
+    End(usize),
+    // the empty chunk has been seen; trailers are ignored
+    Done,
+    // a size line that isn't one; nothing after it is read
+    Bad,
+}
+
+impl Default for Chunks {
+    fn default() -> Self {
+        Chunks::Size(Vec::new())
+    }
+}
+
+impl Chunks {
+    pub fn done(&self) -> bool {
+        matches!(self, Chunks::Done | Chunks::Bad)
+    }
+
+    // The chunk data in `bytes`
+    pub fn feed(&mut self, mut bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        while let Some((&byte, rest)) = bytes.split_first() {
+            match self {
+                Chunks::Size(line) if byte == b'\n' => {
+                    let size = String::from_utf8_lossy(line);
+                    let size = size.split(';').next().unwrap_or("").trim();
+                    *self = match usize::from_str_radix(size, 16) {
+                        Ok(0) => Chunks::Done,
+                        Ok(size) => Chunks::Data(size),
+                        Err(_) => Chunks::Bad,
+                    };
+                    bytes = rest;
+                }
+                // a size, and perhaps extensions, doesn't run this long
+                Chunks::Size(line) if line.len() >= 1024 => *self = Chunks::Bad,
+                Chunks::Size(line) => {
+                    line.push(byte);
+                    bytes = rest;
+                }
+                Chunks::Data(left) => {
+                    let n = (*left).min(bytes.len());
+                    out.extend_from_slice(&bytes[..n]);
+                    *left -= n;
+                    if *left == 0 {
+                        *self = Chunks::End(2);
+                    }
+                    bytes = &bytes[n..];
+                }
+                Chunks::End(left) => {
+                    *left -= 1;
+                    if *left == 0 {
+                        *self = Chunks::Size(Vec::new());
+                    }
+                    bytes = rest;
+                }
+                Chunks::Done | Chunks::Bad => break,
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,105 @@
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    prelude::*,
+    widgets::{Clear, Paragraph},
+};
+
+// Tick rate while the ambient view is up; the clock only shows minutes
+pub const IDLE_TICK: Duration = Duration::from_secs(2);
+
+// 3x5 digits for the ambient clock, '#' marking filled cells
+const DIGITS: [[&str; 5]; 10] = [
+    ["###", "# #", "# #", "# #", "###"],
+    ["  #", "  #", "  #", "  #", "  #"],
+    ["###", "  #", "###", "#  ", "###"],
+    ["###", "  #", "###", "  #", "###"],
+    ["# #", "# #", "###", "  #", "  #"],
+    ["###", "#  ", "###", "  #", "###"],
+    ["###", "#  ", "###", "# #", "###"],
+    ["###", "  #", "  #", "  #", "  #"],
+    ["###", "# #", "###", "# #", "###"],
+    ["###", "# #", "###", "  #", "###"],
+];
+const COLON: [&str; 5] = [" ", "#", " ", "#", " "];
+
+// Screensaver for wall displays: after a stretch without input the dashboard
+// gives way to a dim ambient view until the next key
+pub struct Idle {
+    after: Option<Duration>,
+    last_input: Instant,
+    active: bool,
+}
+
+impl Idle {
+    // A zero period disables it
+    pub fn new(after: Duration) -> Self {
+        Self {
+            after: (!after.is_zero()).then_some(after),
+            last_input: Instant::now(),
+            active: false,
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    // Records input; true if it woke the ambient view
+    pub fn touch(&mut self) -> bool {
+        self.last_input = Instant::now();
+        std::mem::take(&mut self.active)
+    }
+
+    // True when the idle period has just run out
+    pub fn check(&mut self) -> bool {
+        let due = self
+            .after
+            .is_some_and(|after| self.last_input.elapsed() >= after);
+        let entered = due && !self.active;
+        self.active |= due;
+        entered
+    }
+}
+
+// Big HH:MM clock with a few headline metrics underneath, all dimmed.
+// `fill` draws the clock's cells, so ASCII terminals can pass '#'.
+pub fn draw(buf: &mut Buffer, area: Rect, time: &str, fill: char, headline: &[Line]) {
+    let mut rows = vec![String::new(); 5];
+    for c in time.chars() {
+        let glyph = match c.to_digit(10) {
+            Some(d) => DIGITS[d as usize],
+            None => COLON,
+        };
+        for (row, part) in rows.iter_mut().zip(glyph) {
+            if !row.is_empty() {
+                row.push(' ');
+            }
+            row.push_str(part);
+        }
+    }
+    let clock = rows.into_iter().map(|row| {
+        let row: String = row
+            .chars()
+            .map(|c| if c == '#' { fill } else { c })
+            .collect();
+        Line::from(Span::styled(row, Style::default().fg(Color::DarkGray)))
+    });
+
+    let mut lines: Vec<Line> = clock.collect();
+    lines.push(Line::from(""));
+    lines.extend(headline.iter().cloned());
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "any key to return",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let height = u16::try_from(lines.len()).unwrap_or(u16::MAX);
+    let top = area.y + area.height.saturating_sub(height) / 2;
+    let rect = Rect::new(area.x, top, area.width, height.min(area.height));
+    Clear.render(area, buf);
+    Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .render(rect, buf);
+}
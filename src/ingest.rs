@@ -0,0 +1,127 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::Notify;
+
+use crate::logs::Level;
+
+pub struct Ingested {
+    pub source: Arc<str>,
+    pub level: Level,
+    pub message: String,
+}
+
+struct SourceQueue {
+    name: Arc<str>,
+    lines: VecDeque<(Level, String)>,
+    capacity: usize,
+    dropped: u64,
+}
+
+#[derive(Default)]
+struct Shared {
+    queues: Vec<SourceQueue>,
+}
+
+// Bounded per-source buffers between producer threads and the UI. A source that
+// outpaces the UI loses its oldest lines (counted in `dropped`) instead of growing
+// memory or blocking the producer.
+#[derive(Clone, Default)]
+pub struct IngestQueue {
+    shared: Arc<Mutex<Shared>>,
+    // raised by every send, so the UI can pick lines up before its next tick
+    ready: Arc<Notify>,
+}
+
+#[derive(Clone)]
+pub struct SourceSender {
+    index: usize,
+    shared: Arc<Mutex<Shared>>,
+    ready: Arc<Notify>,
+}
+
+impl IngestQueue {
+    // Registers a source with its own buffer; producers keep the returned sender
+    pub fn sender(&self, name: &str, capacity: usize) -> SourceSender {
+        let mut shared = self.shared.lock().unwrap();
+        shared.queues.push(SourceQueue {
+            name: Arc::from(name),
+            lines: VecDeque::new(),
+            capacity: capacity.max(1),
+            dropped: 0,
+        });
+        SourceSender {
+            index: shared.queues.len() - 1,
+            shared: self.shared.clone(),
+            ready: self.ready.clone(),
+        }
+    }
+
+    // Resolves once something was sent since the last time it did
+    pub async fn ready(&self) {
+        self.ready.notified().await;
+    }
+
+    // Takes up to `max` lines, shared fairly between sources so one chatty
+    // source cannot starve the others
+    pub fn drain(&self, max: usize) -> Vec<Ingested> {
+        let mut shared = self.shared.lock().unwrap();
+        let mut out = Vec::new();
+        let busy = shared.queues.iter().filter(|q| !q.lines.is_empty()).count();
+        if busy == 0 {
+            return out;
+        }
+        let per_source = (max / busy).max(1);
+        for q in shared.queues.iter_mut() {
+            let n = q.lines.len().min(per_source);
+            for (level, message) in q.lines.drain(..n) {
+                out.push(Ingested {
+                    source: q.name.clone(),
+                    level,
+                    message,
+                });
+            }
+        }
+        out
+    }
+
+    // (source, dropped lines) for every source that has lost data
+    pub fn dropped(&self) -> Vec<(Arc<str>, u64)> {
+        let shared = self.shared.lock().unwrap();
+        shared
+            .queues
+            .iter()
+            .filter(|q| q.dropped > 0)
+            .map(|q| (q.name.clone(), q.dropped))
+            .collect()
+    }
+}
+
+impl SourceSender {
+    pub fn send(&self, level: Level, message: String) {
+        let mut shared = self.shared.lock().unwrap();
+        let q = &mut shared.queues[self.index];
+        if q.lines.len() >= q.capacity {
+            q.lines.pop_front();
+            q.dropped += 1;
+        }
+        q.lines.push_back((level, message));
+        self.ready.notify_one();
+    }
+
+    // Same as `send` for a whole batch, taking the lock once
+    pub fn send_batch(&self, lines: Vec<(Level, String)>) {
+        let mut shared = self.shared.lock().unwrap();
+        let q = &mut shared.queues[self.index];
+        for line in lines {
+            if q.lines.len() >= q.capacity {
+                q.lines.pop_front();
+                q.dropped += 1;
+            }
+            q.lines.push_back(line);
+        }
+        self.ready.notify_one();
+    }
+}
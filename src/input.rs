@@ -0,0 +1,148 @@
+use std::cell::Cell;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// Single-line editor for the command bar. The cursor is a byte offset that
+// always sits on a grapheme boundary, so CJK, emoji, and combining sequences
+// are edited as one unit, and widths are measured in terminal cells.
+#[derive(Default)]
+pub struct LineInput {
+    text: String,
+    cursor: usize,
+    // first cell shown when the text is wider than its box; moved by `view`
+    // only as far as it takes to keep the cursor in sight
+    scroll: Cell<usize>,
+}
+
+// What fits of the text in a box, and where the cursor lands in it
+pub struct View {
+    pub text: String,
+    // cells from the left edge of the box
+    pub cursor: usize,
+}
+
+impl LineInput {
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    // Pasted or IME-composed text; line breaks become spaces
+    pub fn insert_str(&mut self, s: &str) {
+        let clean: String = s
+            .chars()
+            .map(|c| if c == '\n' || c == '\r' { ' ' } else { c })
+            .filter(|c| !c.is_control())
+            .collect();
+        self.text.insert_str(self.cursor, &clean);
+        self.cursor += clean.len();
+    }
+
+    fn prev_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor, |g| self.cursor + g.len())
+    }
+
+    pub fn backspace(&mut self) {
+        let start = self.prev_boundary();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn delete(&mut self) {
+        let end = self.next_boundary();
+        self.text.replace_range(self.cursor..end, "");
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.prev_boundary();
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = self.next_boundary();
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    // Display width in cells of the text before the cursor
+    pub fn cursor_width(&self) -> usize {
+        self.text[..self.cursor].width()
+    }
+
+    // The text cut to `width` cells around the cursor, with `…` at either
+    // edge where more is hidden
+    pub fn view(&self, width: usize) -> View {
+        let total = self.text.width();
+        let at = self.cursor_width();
+        // the cursor needs a cell of its own past the last character
+        if total < width || width < 3 {
+            self.scroll.set(0);
+            return View {
+                text: self.text.clone(),
+                cursor: at,
+            };
+        }
+        // keep a cell between the cursor and each ellipsis
+        let mut scroll = self.scroll.get().min(total + 1 - width);
+        if at < scroll + 1 {
+            scroll = at.saturating_sub(1);
+        } else if at + 2 > scroll + width {
+            scroll = at + 2 - width;
+        }
+        let scroll = scroll.min(total + 1 - width);
+        self.scroll.set(scroll);
+
+        let (more_left, more_right) = (scroll > 0, total > scroll + width);
+        let lo = scroll + usize::from(more_left);
+        let hi = scroll + width - usize::from(more_right);
+        let mut text = String::new();
+        if more_left {
+            text.push('…');
+        }
+        let mut cell = 0;
+        let mut filled = lo;
+        for g in self.text.graphemes(true) {
+            let end = cell + g.width();
+            // a wide glyph cut by an edge is left out, its cells blank
+            if cell >= lo && end <= hi {
+                text.push_str(&" ".repeat(cell - filled));
+                text.push_str(g);
+                filled = end;
+            }
+            cell = end;
+        }
+        if more_right {
+            text.push_str(&" ".repeat(hi.saturating_sub(filled)));
+            text.push('…');
+        }
+        View {
+            text,
+            cursor: at - scroll,
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs::{self, File, Metadata},
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use ratatui::{prelude::*, widgets::Paragraph};
+use sha2::{Digest as _, Sha256};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    chrome::Chrome,
+    ingest::SourceSender,
+    layout::{fit, sanitize},
+    logs::Level,
+    timebase::Timebase,
+};
+
+// What alerts are logged under
+pub const NAME: &str = "integrity";
+pub const QUEUE: usize = 256;
+// How often watched paths are walked; only files whose size or mtime moved
+// are hashed again
+const POLL: Duration = Duration::from_secs(2);
+// Changes kept for the table
+const RECENT: usize = 64;
+// Walks stop here, so a mistyped `/` doesn't hash the whole disk
+const MAX_FILES: usize = 100_000;
+
+pub type Digest = [u8; 32];
+
+// One file that changed since the last walk: added (no `before`), removed
+// (no `after`), or modified
+pub struct Change {
+    pub at: Duration,
+    pub path: PathBuf,
+    pub before: Option<Digest>,
+    pub after: Option<Digest>,
+}
+
+impl Change {
+    fn kind(&self) -> &'static str {
+        match (self.before, self.after) {
+            (None, _) => "added",
+            (_, None) => "removed",
+            _ => "modified",
+        }
+    }
+}
+
+enum Event {
+    // files under watch after a walk
+    Watching(usize),
+    Changed(PathBuf, Option<Digest>, Option<Digest>),
+}
+
+// --integrity: SHA-256 of every file under the watched paths, taken at
+// startup and compared on each walk; changes are logged as alerts and kept
+// for the data forensics table
+pub struct Integrity {
+    pub paths: Vec<PathBuf>,
+    // None until the baseline is hashed
+    pub files: Option<usize>,
+    pub recent: VecDeque<Change>,
+    // all of them, including those that fell out of `recent`
+    pub changes: usize,
+    rx: Receiver<Event>,
+}
+
+impl Integrity {
+    pub fn start(paths: Vec<PathBuf>, tx: SourceSender) -> Self {
+        let (events, rx) = mpsc::channel();
+        let walked = paths.clone();
+        thread::spawn(move || watch(&walked, &tx, &events));
+        Self {
+            paths,
+            files: None,
+            recent: VecDeque::new(),
+            changes: 0,
+            rx,
+        }
+    }
+
+    pub fn drain(&mut self, now: Duration) {
+        while let Ok(event) = self.rx.try_recv() {
+            match event {
+                Event::Watching(files) => self.files = Some(files),
+                Event::Changed(path, before, after) => {
+                    self.changes += 1;
+                    if self.recent.len() == RECENT {
+                        self.recent.pop_back();
+                    }
+                    self.recent.push_front(Change {
+                        at: now,
+                        path,
+                        before,
+                        after,
+                    });
+                }
+            }
+        }
+    }
+}
+
+// What a file looked like when it was last hashed
+struct Hashed {
+    len: u64,
+    modified: Option<SystemTime>,
+    digest: Digest,
+}
+
+fn watch(paths: &[PathBuf], tx: &SourceSender, events: &Sender<Event>) {
+    let mut known: HashMap<PathBuf, Hashed> = HashMap::new();
+    let mut baseline = true;
+    // reported once until they recover, not every walk
+    let mut failing: HashSet<PathBuf> = HashSet::new();
+    loop {
+        let mut found = Vec::new();
+        for path in paths {
+            match walk(path, &mut found) {
+                Ok(()) => {
+                    if failing.remove(path) {
+                        tx.send(Level::Info, format!("reading {}", path.display()));
+                    }
+                }
+                Err(e) => {
+                    if failing.insert(path.clone()) {
+                        tx.send(
+                            Level::Warn,
+                            format!("can't read {}: {e}; retrying", path.display()),
+                        );
+                    }
+                }
+            }
+        }
+        if found.len() >= MAX_FILES && baseline {
+            tx.send(
+                Level::Warn,
+                format!("watching the first {MAX_FILES} files only"),
+            );
+        }
+
+        let mut next = HashMap::with_capacity(found.len());
+        let mut changes = Vec::new();
+        for (path, meta) in found {
+            let (len, modified) = (meta.len(), meta.modified().ok());
+            let old = known.remove(&path);
+            if let Some(old) = old
+                .as_ref()
+                .filter(|o| (o.len, o.modified) == (len, modified))
+            {
+                let digest = old.digest;
+                next.insert(
+                    path,
+                    Hashed {
+                        len,
+                        modified,
+                        digest,
+                    },
+                );
+                continue;
+            }
+            let digest = match hash(&path, &meta) {
+                Ok(digest) => {
+                    failing.remove(&path);
+                    digest
+                }
+                Err(e) => {
+                    if failing.insert(path.clone()) {
+                        tx.send(Level::Warn, format!("can't hash {}: {e}", path.display()));
+                    }
+                    // keep the last good hash, so a file that's briefly
+                    // unreadable doesn't show as removed and added
+                    if let Some(old) = old {
+                        next.insert(path, old);
+                    }
+                    continue;
+                }
+            };
+            let before = old.map(|o| o.digest);
+            if !baseline && before != Some(digest) {
+                changes.push((path.clone(), before, Some(digest)));
+            }
+            next.insert(
+                path,
+                Hashed {
+                    len,
+                    modified,
+                    digest,
+                },
+            );
+        }
+        // whatever wasn't found again is gone
+        for (path, old) in known.drain() {
+            changes.push((path, Some(old.digest), None));
+        }
+        known = next;
+
+        if baseline {
+            let roots: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+            tx.send(
+                Level::Info,
+                format!(
+                    "baseline: {} files hashed under {}",
+                    known.len(),
+                    roots.join(", ")
+                ),
+            );
+        }
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+        let alerts: Vec<_> = changes
+            .iter()
+            .map(|(path, before, after)| alert(path, *before, *after))
+            .collect();
+        if !alerts.is_empty() {
+            tx.send_batch(alerts);
+        }
+        let watching = Event::Watching(known.len());
+        let sent = std::iter::once(watching)
+            .chain(
+                changes
+                    .into_iter()
+                    .map(|(path, before, after)| Event::Changed(path, before, after)),
+            )
+            .try_for_each(|event| events.send(event));
+        if sent.is_err() {
+            // the app is gone
+            return;
+        }
+        baseline = false;
+        thread::sleep(POLL);
+    }
+}
+
+fn alert(path: &Path, before: Option<Digest>, after: Option<Digest>) -> (Level, String) {
+    let path = path.display();
+    match (before, after) {
+        (None, Some(after)) => (
+            Level::Warn,
+            format!("added {path}: sha256 {}", short(&after)),
+        ),
+        (Some(before), None) => (
+            Level::Error,
+            format!("removed {path}: was sha256 {}", short(&before)),
+        ),
+        (Some(before), Some(after)) => (
+            Level::Error,
+            format!(
+                "modified {path}: sha256 {} → {}",
+                short(&before),
+                short(&after)
+            ),
+        ),
+        (None, None) => unreachable!("a change has a hash on at least one side"),
+    }
+}
+
+// Regular files and symlinks under `path`; other kinds (pipes, devices) are
+// skipped since reading them can block. Unreadable subdirectories are
+// skipped too; only a root that can't be read is an error.
+fn walk(path: &Path, found: &mut Vec<(PathBuf, Metadata)>) -> io::Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    if meta.is_dir() {
+        for entry in fs::read_dir(path)? {
+            if found.len() >= MAX_FILES {
+                break;
+            }
+            if let Ok(entry) = entry {
+                let _ = walk(&entry.path(), found);
+            }
+        }
+    } else if (meta.is_file() || meta.file_type().is_symlink()) && found.len() < MAX_FILES {
+        found.push((path.to_path_buf(), meta));
+    }
+    Ok(())
+}
+
+// A symlink hashes as where it points, so retargeting one shows up
+fn hash(path: &Path, meta: &Metadata) -> io::Result<Digest> {
+    let mut hasher = Sha256::new();
+    if meta.file_type().is_symlink() {
+        hasher.update(fs::read_link(path)?.to_string_lossy().as_bytes());
+    } else {
+        io::copy(&mut File::open(path)?, &mut hasher)?;
+    }
+    Ok(hasher.finalize().into())
+}
+
+// The first 8 hex digits, enough to tell two versions apart at a glance
+fn short(digest: &Digest) -> String {
+    digest[..4].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+// The data forensics table: newest change first
+pub fn draw(
+    buf: &mut Buffer,
+    area: Rect,
+    integrity: &Integrity,
+    timebase: &Timebase,
+    ascii: bool,
+    chrome: Chrome,
+) {
+    let inner = area.width.saturating_sub(2) as usize;
+    let (arrow, none) = match ascii {
+        true => ("->", "--------"),
+        false => ("→", "————————"),
+    };
+    let mut lines: Vec<Line> = integrity
+        .recent
+        .iter()
+        .take(area.height.saturating_sub(2) as usize)
+        .map(|change| {
+            let color = match change.before {
+                None => Color::Yellow,
+                Some(_) => Color::Red,
+            };
+            let side = |d: Option<Digest>| d.map_or_else(|| none.to_string(), |d| short(&d));
+            let delta = format!("{} {arrow} {}", side(change.before), side(change.after));
+            let path = change.path.display().to_string();
+            let path = sanitize(&path);
+            let path_w = inner.saturating_sub(9 + 9 + delta.width() + 1);
+            let (path, _) = fit(&path, path_w);
+            Line::from(vec![
+                Span::styled(
+                    timebase.local_at(change.at).format("%H:%M:%S ").to_string(),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!("{:<9}", change.kind()),
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(delta, Style::default().fg(Color::Gray)),
+                Span::raw(" "),
+                Span::raw(path.to_string()),
+            ])
+        })
+        .collect();
+    if lines.is_empty() {
+        let note = match integrity.files {
+            Some(_) => "no changes since the baseline",
+            None => "hashing the baseline",
+        };
+        lines.push(Line::styled(note, Style::default().fg(Color::DarkGray)));
+    }
+    let title = match integrity.files {
+        Some(files) => format!(
+            "integrity • {files} file{}",
+            if files == 1 { "" } else { "s" }
+        ),
+        None => "integrity".to_string(),
+    };
+    Paragraph::new(lines)
+        .block(
+            chrome
+                .block()
+                .border_style(Style::default().fg(Color::DarkGray))
+                .title(Span::styled(
+                    title,
+                    Style::default()
+                        .fg(Color::LightGreen)
+                        .add_modifier(Modifier::BOLD),
+                )),
+        )
+        .render(area, buf);
+}
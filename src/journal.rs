@@ -0,0 +1,154 @@
+use std::{
+    io::{self, BufRead, BufReader, Read},
+    process::{Child, ChildStdout, Stdio},
+};
+
+use serde_json::Value;
+
+use crate::{
+    ingest::{IngestQueue, SourceSender},
+    logs::Level,
+    programs::Programs,
+    reconnect::{Connector, Links},
+};
+
+// What entries are logged under, and the link's name
+pub const NAME: &str = "journald";
+pub const QUEUE: usize = 1024;
+// `--journal` with no unit reads every one
+pub const ALL: &str = "all";
+// Entries from before startup shown for context
+const BACKLOG: &str = "20";
+
+// --journal: the systemd journal, as `journalctl --follow` prints it, on its
+// own link so journalctl is started again if it exits. A restart picks up
+// after the last entry read.
+pub fn start(
+    units: &str,
+    programs: &Programs,
+    links: &Links,
+    ingest: &IngestQueue,
+) -> Result<(), String> {
+    if !cfg!(target_os = "linux") {
+        return Err("the systemd journal is only on Linux".to_string());
+    }
+    let units = match units {
+        ALL => Vec::new(),
+        units => units
+            .split(',')
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .map(str::to_string)
+            .collect(),
+    };
+    let reader = Journalctl {
+        units,
+        cursor: None,
+        programs: programs.clone(),
+    };
+    links.spawn(NAME, reader, ingest.sender(NAME, QUEUE));
+    Ok(())
+}
+
+struct Journalctl {
+    // none for every unit
+    units: Vec<String>,
+    // the last entry read, so a restart doesn't repeat or skip any
+    cursor: Option<String>,
+    programs: Programs,
+}
+
+impl Connector for Journalctl {
+    type Conn = (Child, ChildStdout);
+
+    fn connect(&mut self) -> io::Result<Self::Conn> {
+        let mut journalctl = self.programs.command("journalctl");
+        journalctl.args(["--follow", "--output", "json", "--no-pager"]);
+        match &self.cursor {
+            Some(cursor) => journalctl.arg("--after-cursor").arg(cursor),
+            None => journalctl.args(["--lines", BACKLOG]),
+        };
+        for unit in &self.units {
+            journalctl.arg("--unit").arg(unit);
+        }
+        // stderr is read once journalctl exits, for why it did
+        let mut child = journalctl
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::NotFound => io::Error::other("journalctl isn't on PATH"),
+                _ => e,
+            })?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+        Ok((child, stdout))
+    }
+
+    fn run(&mut self, (mut child, stdout): Self::Conn, tx: &SourceSender) -> io::Result<()> {
+        // one object per line
+        for line in BufReader::new(stdout).lines() {
+            let Ok(entry) = serde_json::from_str::<Value>(&line?) else {
+                continue;
+            };
+            if let Some(cursor) = entry.get("__CURSOR").and_then(Value::as_str) {
+                self.cursor = Some(cursor.to_string());
+            }
+            if let Some((level, message)) = entry_line(&entry) {
+                tx.send(level, message);
+            }
+        }
+        let status = child.wait()?;
+        if status.success() {
+            return Ok(());
+        }
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            let _ = pipe.read_to_string(&mut stderr);
+        }
+        Err(io::Error::other(match stderr.lines().last() {
+            Some(why) => format!("journalctl exited with {status}: {}", why.trim()),
+            None => format!("journalctl exited with {status}"),
+        }))
+    }
+}
+
+// An entry as `sshd[812]: Accepted publickey for ops`, at the level its
+// priority maps to: emerg through err are errors, warning a warning, notice
+// and info info, debug debug
+fn entry_line(entry: &Value) -> Option<(Level, String)> {
+    let message = text(entry.get("MESSAGE")?)?;
+    let level = match entry.get("PRIORITY").and_then(text).as_deref() {
+        Some("0" | "1" | "2" | "3") => Level::Error,
+        Some("4") => Level::Warn,
+        Some("7") => Level::Debug,
+        _ => Level::Info,
+    };
+    let who = ["SYSLOG_IDENTIFIER", "_SYSTEMD_UNIT", "_COMM"]
+        .iter()
+        .find_map(|field| entry.get(*field).and_then(text));
+    let pid = ["SYSLOG_PID", "_PID"]
+        .iter()
+        .find_map(|field| entry.get(*field).and_then(text));
+    let line = match (who, pid) {
+        (Some(who), Some(pid)) => format!("{who}[{pid}]: {message}"),
+        (Some(who), None) => format!("{who}: {message}"),
+        _ => message,
+    };
+    Some((level, line))
+}
+
+// A field's value: a string, or an array of bytes where it isn't valid UTF-8
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(bytes) => {
+            let bytes: Option<Vec<u8>> = bytes
+                .iter()
+                .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+                .collect();
+            Some(String::from_utf8_lossy(&bytes?).into_owned())
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,989 @@
+use std::sync::mpsc::Receiver;
+
+use ratatui::{prelude::*, widgets::Paragraph};
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    chrome::Chrome,
+    glyphs::GlyphSet,
+    ingest::IngestQueue,
+    layout::{fit, sanitize, table_rows},
+    net::authority,
+    reconnect::Links,
+};
+
+// `[kafka]` in config.toml: brokers to start from, the topic to watch, and
+// the consumer group whose lag is shown. Checked as it's read, like `[mqtt]`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct KafkaSpec {
+    #[serde(default = "local", deserialize_with = "brokers")]
+    pub brokers: Vec<String>,
+    #[serde(deserialize_with = "topic")]
+    pub topic: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+fn local() -> Vec<String> {
+    vec!["127.0.0.1:9092".to_string()]
+}
+
+fn brokers<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<String>, D::Error> {
+    let brokers = Vec::<String>::deserialize(d)?;
+    if brokers.is_empty() {
+        return Err(de::Error::custom("list at least one broker"));
+    }
+    for addr in &brokers {
+        broker(addr).map_err(de::Error::custom)?;
+    }
+    Ok(brokers)
+}
+
+// Kafka's own rule: letters, digits, `.`, `_`, and `-`, up to 249 of them
+fn topic<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    let topic = String::deserialize(d)?;
+    let legal = |c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-');
+    match !topic.is_empty() && topic.len() <= 249 && topic.chars().all(legal) {
+        true => Ok(topic),
+        false => Err(de::Error::custom(format!(
+            "`{topic}` isn't a topic name; they're letters, digits, `.`, `_`, and `-`"
+        ))),
+    }
+}
+
+// (host, port) out of `HOST[:PORT]`, port 9092 by default
+fn broker(addr: &str) -> Result<(String, u16), String> {
+    if addr.contains("://") {
+        return Err(format!(
+            "`{addr}` should be HOST:PORT; only plaintext listeners are supported"
+        ));
+    }
+    let (broker, _) = authority(addr, 9092)?;
+    Ok((broker.host, broker.port))
+}
+
+// Where one partition stands
+#[derive(Clone, Debug, PartialEq)]
+pub struct Partition {
+    pub index: i32,
+    // the high watermark: the offset the next record gets
+    pub end: i64,
+    // the next offset the dashboard reads
+    pub read: i64,
+    // the group's, once asked; None if it never committed one
+    pub committed: Option<i64>,
+}
+
+// [kafka]: where each partition of the topic stands, for Cloud mode's strip
+pub struct Kafka {
+    pub topic: String,
+    pub group: Option<String>,
+    // by partition
+    pub partitions: Vec<Partition>,
+    rx: Receiver<Vec<Partition>>,
+}
+
+impl Kafka {
+    // Checks the spec, then consumes on its own reconnecting link
+    pub fn start(spec: &KafkaSpec, links: &Links, ingest: &IngestQueue) -> Result<Self, String> {
+        let rx = sys::consume(spec, links, ingest)?;
+        Ok(Self {
+            topic: spec.topic.clone(),
+            group: spec.group.clone(),
+            partitions: Vec::new(),
+            rx,
+        })
+    }
+
+    pub fn drain(&mut self) {
+        if let Some(partitions) = self.rx.try_iter().last() {
+            self.partitions = partitions;
+        }
+    }
+
+    // How far behind the group is, or the dashboard without one
+    pub fn lag(&self, partition: &Partition) -> Option<i64> {
+        let from = match self.group {
+            Some(_) => partition.committed?,
+            None => partition.read,
+        };
+        Some((partition.end - from).max(0))
+    }
+}
+
+// Each partition's end, where the dashboard or the group has read to, and
+// the lag as a bar against the largest
+pub fn draw(buf: &mut Buffer, area: Rect, kafka: &Kafka, glyphs: GlyphSet, chrome: Chrome) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let inner = area.width.saturating_sub(2) as usize;
+    let rows = table_rows(area, kafka.partitions.len());
+    let fixed = 5 + 13 + 13 + 11;
+    let bar_len = inner.saturating_sub(fixed);
+    let lags: Vec<Option<i64>> = kafka.partitions.iter().map(|p| kafka.lag(p)).collect();
+    let largest = lags.iter().flatten().copied().max().unwrap_or(0);
+    let total: i64 = lags.iter().flatten().sum();
+    let from = match kafka.group {
+        Some(_) => "committed",
+        None => "read",
+    };
+    let mut lines = vec![Line::styled(
+        format!("{:>4} {:>12} {:>12} {:>10}", "part", "end", from, "lag"),
+        dim,
+    )];
+    for (partition, lag) in kafka.partitions.iter().zip(&lags).take(rows) {
+        let at = match kafka.group {
+            Some(_) => partition.committed,
+            None => Some(partition.read),
+        };
+        let share = match (lag, largest) {
+            (Some(lag), largest) if largest > 0 => *lag as f32 / largest as f32,
+            _ => 0.0,
+        };
+        let (full, part, _) = glyphs.bar(share, bar_len);
+        let color = match share {
+            s if s >= 0.8 => Color::Red,
+            s if s >= 0.5 => Color::Yellow,
+            _ => Color::Green,
+        };
+        let dash = || "-".to_string();
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:>4} ", partition.index), dim),
+            Span::raw(format!(
+                "{:>12} {:>12} ",
+                partition.end,
+                at.map_or_else(dash, |at| at.to_string())
+            )),
+            Span::styled(
+                format!("{:>10} ", lag.map_or_else(dash, |lag| lag.to_string())),
+                Style::default().fg(Color::White),
+            ),
+            Span::styled(format!("{full}{part}"), Style::default().fg(color)),
+        ]));
+    }
+    let hidden = kafka.partitions.len().saturating_sub(rows);
+    if kafka.partitions.is_empty() {
+        lines.push(Line::styled("asking the brokers", dim));
+    } else if hidden > 0 {
+        lines.push(Line::styled(format!("+{hidden} more"), dim));
+    }
+    let topic = sanitize(&kafka.topic);
+    let title = match &kafka.group {
+        Some(group) => format!("kafka lag • {topic} for {} • {total}", sanitize(group)),
+        None => format!("kafka lag • {topic} • {total}"),
+    };
+    let (title, _) = fit(&title, inner);
+    Paragraph::new(lines)
+        .block(
+            chrome.block().border_style(dim).title(Span::styled(
+                title.to_string(),
+                Style::default()
+                    .fg(Color::LightMagenta)
+                    .add_modifier(Modifier::BOLD),
+            )),
+        )
+        .render(area, buf);
+}
+
+// The consumer and its wire protocol, only in builds with the feature
+#[cfg(feature = "kafka")]
+mod sys {
+    use std::{
+        collections::{BTreeMap, HashMap},
+        io,
+        net::TcpStream,
+        sync::mpsc::{self, Receiver, Sender},
+        time::{Duration, Instant},
+    };
+
+    use super::{broker, KafkaSpec, Partition};
+    use crate::{
+        ingest::{IngestQueue, SourceSender},
+        logs::Level,
+        net,
+        parse::parse_line,
+        reconnect::{Connector, Links},
+    };
+
+    // What the consumer's link and the logged records are shown under
+    const NAME: &str = "kafka";
+    const QUEUE: usize = 1024;
+    // Connecting, and any answer but a fetch's
+    const TIMEOUT: Duration = Duration::from_secs(10);
+    // How long a fetch waits on the broker for new records
+    const WAIT: Duration = Duration::from_millis(500);
+    // How often the group's committed offsets are asked for
+    const COMMITTED_EVERY: Duration = Duration::from_secs(5);
+    // Read at most this much per fetch, and per partition in it
+    const MAX_BYTES: i32 = 1 << 20;
+    const PARTITION_BYTES: i32 = 256 << 10;
+
+    // The spec's brokers checked, then the topic consumed on its own
+    // reconnecting link; where each partition stands comes back on the
+    // receiver
+    pub fn consume(
+        spec: &KafkaSpec,
+        links: &Links,
+        ingest: &IngestQueue,
+    ) -> Result<Receiver<Vec<Partition>>, String> {
+        let brokers = spec
+            .brokers
+            .iter()
+            .map(|addr| broker(addr))
+            .collect::<Result<_, _>>()?;
+        let (updates, rx) = mpsc::channel();
+        let consumer = Consumer {
+            brokers,
+            topic: spec.topic.clone(),
+            group: spec.group.clone(),
+            read: BTreeMap::new(),
+            ends: BTreeMap::new(),
+            committed: BTreeMap::new(),
+            updates,
+            correlation: 0,
+            skipped: Vec::new(),
+        };
+        links.spawn(NAME, consumer, ingest.sender(NAME, QUEUE));
+        Ok(rx)
+    }
+
+    // A broker's answer about a partition that wasn't asked about
+    fn unasked(partition: i32) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("the broker answered for partition {partition}, which wasn't asked about"),
+        )
+    }
+
+    fn connect(host: &str, port: u16) -> io::Result<TcpStream> {
+        let stream = net::dial(host, port, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT + WAIT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        stream.set_nodelay(true)?;
+        Ok(stream)
+    }
+
+    // The cluster as the last metadata had it, and connections to the brokers
+    // asked so far
+    struct Cluster {
+        nodes: HashMap<i32, (String, u16)>,
+        // partition → the broker leading it
+        leaders: BTreeMap<i32, i32>,
+        streams: HashMap<i32, TcpStream>,
+        // the group's coordinator, once found
+        coordinator: Option<TcpStream>,
+        asked: Option<Instant>,
+    }
+
+    impl Cluster {
+        fn stream(&mut self, node: i32) -> io::Result<&mut TcpStream> {
+            if !self.streams.contains_key(&node) {
+                let (host, port) = self.nodes.get(&node).ok_or_else(|| {
+                    io::Error::other(format!("broker {node} isn't in the metadata"))
+                })?;
+                let stream = connect(host, *port)?;
+                self.streams.insert(node, stream);
+            }
+            Ok(self.streams.get_mut(&node).expect("just connected"))
+        }
+    }
+
+    struct Consumer {
+        brokers: Vec<(String, u16)>,
+        topic: String,
+        group: Option<String>,
+        // kept across reconnects, so none are read twice
+        read: BTreeMap<i32, i64>,
+        ends: BTreeMap<i32, i64>,
+        committed: BTreeMap<i32, i64>,
+        updates: Sender<Vec<Partition>>,
+        correlation: i32,
+        // why batches were skipped, each mentioned once
+        skipped: Vec<&'static str>,
+    }
+
+    impl Connector for Consumer {
+        type Conn = Cluster;
+
+        // The first broker that answers says who leads each partition; a new
+        // partition starts at its end, like --follow
+        fn connect(&mut self) -> io::Result<Cluster> {
+            let mut last = io::Error::other("no brokers");
+            for (host, port) in self.brokers.clone() {
+                let metadata = connect(&host, port).and_then(|mut stream| {
+                    let request = wire::metadata(&self.topic);
+                    let body = self.call(&mut stream, wire::METADATA, 1, &request)?;
+                    wire::parse_metadata(&body, &self.topic)
+                });
+                let (nodes, leaders) = match metadata {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        last = e;
+                        continue;
+                    }
+                };
+                let mut cluster = Cluster {
+                    nodes,
+                    leaders,
+                    streams: HashMap::new(),
+                    coordinator: None,
+                    asked: None,
+                };
+                let new: Vec<i32> = cluster
+                    .leaders
+                    .keys()
+                    .filter(|p| !self.read.contains_key(p))
+                    .copied()
+                    .collect();
+                for (partition, end) in self.latest(&mut cluster, &new)? {
+                    self.read.insert(partition, end);
+                }
+                return Ok(cluster);
+            }
+            Err(last)
+        }
+
+        fn run(&mut self, mut cluster: Cluster, tx: &SourceSender) -> io::Result<()> {
+            loop {
+                let mut by_leader: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+                for (partition, leader) in &cluster.leaders {
+                    by_leader.entry(*leader).or_default().push(*partition);
+                }
+                for (leader, partitions) in by_leader {
+                    self.fetch(&mut cluster, leader, &partitions, tx)?;
+                }
+                if cluster
+                    .asked
+                    .filter(|at| at.elapsed() < COMMITTED_EVERY)
+                    .is_none()
+                {
+                    cluster.asked = Some(Instant::now());
+                    self.ask_committed(&mut cluster, tx);
+                }
+                let partitions = cluster
+                    .leaders
+                    .keys()
+                    .map(|p| Partition {
+                        index: *p,
+                        end: self.ends.get(p).copied().unwrap_or(0),
+                        read: self.read.get(p).copied().unwrap_or(0),
+                        committed: self.committed.get(p).copied(),
+                    })
+                    .collect();
+                if self.updates.send(partitions).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    impl Consumer {
+        fn call(
+            &mut self,
+            stream: &mut TcpStream,
+            api: i16,
+            version: i16,
+            body: &[u8],
+        ) -> io::Result<Vec<u8>> {
+            self.correlation = self.correlation.wrapping_add(1);
+            wire::call(stream, api, version, self.correlation, body)
+        }
+
+        // Each partition's end, from its leader
+        fn latest(
+            &mut self,
+            cluster: &mut Cluster,
+            partitions: &[i32],
+        ) -> io::Result<Vec<(i32, i64)>> {
+            let mut by_leader: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+            for p in partitions {
+                let leader = cluster.leaders.get(p).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("no leader known for partition {p}"),
+                    )
+                })?;
+                by_leader.entry(*leader).or_default().push(*p);
+            }
+            let mut ends = Vec::new();
+            for (leader, partitions) in by_leader {
+                let request = wire::list_offsets(&self.topic, &partitions);
+                let body = self.call(cluster.stream(leader)?, wire::LIST_OFFSETS, 1, &request)?;
+                for (partition, error, offset) in wire::parse_list_offsets(&body)? {
+                    if error != 0 {
+                        return Err(wire::error(error, &format!("partition {partition}")));
+                    }
+                    ends.push((partition, offset));
+                }
+            }
+            Ok(ends)
+        }
+
+        // Records past where the dashboard read, logged; a leader that moved
+        // means new metadata, so the link reconnects
+        fn fetch(
+            &mut self,
+            cluster: &mut Cluster,
+            leader: i32,
+            partitions: &[i32],
+            tx: &SourceSender,
+        ) -> io::Result<()> {
+            let offsets = partitions
+                .iter()
+                .map(|p| Ok((*p, self.from(*p)?)))
+                .collect::<io::Result<Vec<_>>>()?;
+            let request = wire::fetch(&self.topic, &offsets);
+            let body = self.call(cluster.stream(leader)?, wire::FETCH, 4, &request)?;
+            let mut lines = Vec::new();
+            let mut gone = Vec::new();
+            for fetched in wire::parse_fetch(&body)? {
+                let partition = fetched.partition;
+                match fetched.error {
+                    0 => {}
+                    // retention got there first
+                    1 => {
+                        gone.push(partition);
+                        continue;
+                    }
+                    error => return Err(wire::error(error, &format!("partition {partition}"))),
+                }
+                let from = self.from(partition)?;
+                self.ends.insert(partition, fetched.end);
+                let set = wire::records(&fetched.records, from);
+                for reason in set.skipped {
+                    if !self.skipped.contains(&reason) {
+                        self.skipped.push(reason);
+                        lines.push((Level::Warn, format!("skipping {reason}")));
+                    }
+                }
+                for record in set.records {
+                    lines.push(self.line(partition, &record));
+                }
+                if let Some(next) = set.next {
+                    self.read.insert(partition, next.max(from));
+                }
+            }
+            if !gone.is_empty() {
+                for (partition, end) in self.latest(cluster, &gone)? {
+                    let from = self.from(partition)?;
+                    lines.push((
+                        Level::Warn,
+                        format!(
+                            "{}/{partition}: offset {from} was deleted; skipping to {end}",
+                            self.topic
+                        ),
+                    ));
+                    self.read.insert(partition, end);
+                }
+            }
+            if !lines.is_empty() {
+                tx.send_batch(lines);
+            }
+            Ok(())
+        }
+
+        // Where the dashboard read `partition` to
+        fn from(&self, partition: i32) -> io::Result<i64> {
+            self.read
+                .get(&partition)
+                .copied()
+                .ok_or_else(|| unasked(partition))
+        }
+
+        // `orders/3 42: payment declined`, at the level the value says, as
+        // --follow reads it
+        fn line(&self, partition: i32, record: &wire::Record) -> (Level, String) {
+            let at = match &record.key {
+                Some(key) => format!(
+                    "{}/{partition} {}",
+                    self.topic,
+                    String::from_utf8_lossy(key)
+                ),
+                None => format!("{}/{partition}", self.topic),
+            };
+            let value = record.value.as_deref().map(String::from_utf8_lossy);
+            match value.as_deref().and_then(parse_line) {
+                Some(parsed) => (parsed.level, format!("{at}: {}", parsed.message)),
+                None if value.is_none() => (Level::Info, format!("{at}: deleted")),
+                None => (Level::Info, format!("{at}:")),
+            }
+        }
+
+        // The group's committed offsets from its coordinator. Lag is shown as
+        // unknown until they come, so a failure here is logged, not fatal.
+        fn ask_committed(&mut self, cluster: &mut Cluster, tx: &SourceSender) {
+            let Some(group) = self.group.clone() else {
+                return;
+            };
+            match self.committed(cluster, &group) {
+                Ok(offsets) => {
+                    for (partition, offset) in offsets {
+                        match offset {
+                            // never committed
+                            -1 => self.committed.remove(&partition),
+                            offset => self.committed.insert(partition, offset),
+                        };
+                    }
+                }
+                Err(e) => {
+                    cluster.coordinator = None;
+                    tx.send(Level::Warn, format!("group `{group}`'s offsets: {e}"));
+                }
+            }
+        }
+
+        fn committed(&mut self, cluster: &mut Cluster, group: &str) -> io::Result<Vec<(i32, i64)>> {
+            if cluster.coordinator.is_none() {
+                // any broker knows which one coordinates the group
+                let any = cluster.leaders.values().next().copied().unwrap_or_default();
+                let request = wire::find_coordinator(group);
+                let body = self.call(cluster.stream(any)?, wire::FIND_COORDINATOR, 0, &request)?;
+                let (host, port) = wire::parse_find_coordinator(&body)?;
+                cluster.coordinator = Some(connect(&host, port)?);
+            }
+            let partitions: Vec<i32> = cluster.leaders.keys().copied().collect();
+            let request = wire::offset_fetch(group, &self.topic, &partitions);
+            let coordinator = cluster.coordinator.as_mut().expect("found above");
+            let body = self.call(coordinator, wire::OFFSET_FETCH, 1, &request)?;
+            wire::parse_offset_fetch(&body)
+        }
+    }
+
+    // Just enough of the Kafka protocol to read a topic and a group's offsets,
+    // at versions every broker since 0.11 speaks: Metadata v1, ListOffsets v1,
+    // Fetch v4, FindCoordinator v0, and OffsetFetch v1
+    mod wire {
+        use std::{
+            collections::{BTreeMap, HashMap},
+            io::{self, Read, Write},
+        };
+
+        pub const FETCH: i16 = 1;
+        pub const LIST_OFFSETS: i16 = 2;
+        pub const METADATA: i16 = 3;
+        pub const OFFSET_FETCH: i16 = 9;
+        pub const FIND_COORDINATOR: i16 = 10;
+        const CLIENT: &str = "ai-intui";
+        // Largest response taken; a fetch asks for far less
+        const MAX_RESPONSE: usize = 16 << 20;
+
+        fn string(s: &str, out: &mut Vec<u8>) {
+            out.extend_from_slice(&(s.len() as i16).to_be_bytes());
+            out.extend_from_slice(s.as_bytes());
+        }
+
+        fn i32s(n: usize, out: &mut Vec<u8>) {
+            out.extend_from_slice(&(n as i32).to_be_bytes());
+        }
+
+        // Sends a request and reads its response's body
+        pub fn call(
+            stream: &mut (impl Read + Write),
+            api: i16,
+            version: i16,
+            correlation: i32,
+            body: &[u8],
+        ) -> io::Result<Vec<u8>> {
+            let mut request = Vec::new();
+            request.extend_from_slice(&api.to_be_bytes());
+            request.extend_from_slice(&version.to_be_bytes());
+            request.extend_from_slice(&correlation.to_be_bytes());
+            string(CLIENT, &mut request);
+            request.extend_from_slice(body);
+            let mut framed = Vec::with_capacity(request.len() + 4);
+            i32s(request.len(), &mut framed);
+            framed.extend_from_slice(&request);
+            stream.write_all(&framed)?;
+            let mut size = [0; 4];
+            stream.read_exact(&mut size)?;
+            let size = usize::try_from(i32::from_be_bytes(size))
+                .ok()
+                .filter(|size| (4..=MAX_RESPONSE).contains(size))
+                .ok_or_else(|| malformed("a response size"))?;
+            let mut response = vec![0; size];
+            stream.read_exact(&mut response)?;
+            if response[..4] != correlation.to_be_bytes() {
+                return Err(malformed("a correlation id"));
+            }
+            response.drain(..4);
+            Ok(response)
+        }
+
+        fn malformed(what: &str) -> io::Error {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("malformed {what} from the broker"),
+            )
+        }
+
+        // A protocol error code as an io::Error about `what`
+        pub fn error(code: i16, what: &str) -> io::Error {
+            let why = match code {
+                1 => "offset out of range".to_string(),
+                3 => "no such topic or partition".to_string(),
+                5 => "leader not available".to_string(),
+                6 => "not the leader".to_string(),
+                7 => "request timed out".to_string(),
+                14 => "coordinator is loading".to_string(),
+                15 => "coordinator not available".to_string(),
+                16 => "not the coordinator".to_string(),
+                29 => "not authorized for the topic".to_string(),
+                30 => "not authorized for the group".to_string(),
+                code => format!("error {code}"),
+            };
+            io::Error::other(format!("{what}: {why}"))
+        }
+
+        // A cursor over a response
+        struct In<'a>(&'a [u8]);
+
+        impl<'a> In<'a> {
+            fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+                if self.0.len() < n {
+                    return Err(malformed("a response"));
+                }
+                let (taken, rest) = self.0.split_at(n);
+                self.0 = rest;
+                Ok(taken)
+            }
+
+            fn i8(&mut self) -> io::Result<i8> {
+                Ok(self.take(1)?[0] as i8)
+            }
+
+            fn i16(&mut self) -> io::Result<i16> {
+                Ok(i16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+            }
+
+            fn i32(&mut self) -> io::Result<i32> {
+                Ok(i32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+            }
+
+            fn i64(&mut self) -> io::Result<i64> {
+                Ok(i64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+            }
+
+            // -1 is null, which reads as empty
+            fn string(&mut self) -> io::Result<String> {
+                let len = self.i16()?;
+                let bytes = self.take(usize::try_from(len).unwrap_or(0))?;
+                Ok(String::from_utf8_lossy(bytes).into_owned())
+            }
+
+            fn bytes(&mut self) -> io::Result<&'a [u8]> {
+                let len = self.i32()?;
+                self.take(usize::try_from(len).unwrap_or(0))
+            }
+
+            // An array's length, null as none
+            fn count(&mut self) -> io::Result<usize> {
+                Ok(usize::try_from(self.i32()?).unwrap_or(0))
+            }
+
+            // Zigzag varints, as records are written
+            fn varint(&mut self) -> io::Result<i64> {
+                let mut n: u64 = 0;
+                for shift in (0..64).step_by(7) {
+                    let byte = self.take(1)?[0];
+                    n |= u64::from(byte & 0x7f) << shift;
+                    if byte & 0x80 == 0 {
+                        return Ok((n >> 1) as i64 ^ -((n & 1) as i64));
+                    }
+                }
+                Err(malformed("a varint"))
+            }
+
+            // -1 is null
+            fn varbytes(&mut self) -> io::Result<Option<&'a [u8]>> {
+                match self.varint()? {
+                    -1 => Ok(None),
+                    len => Ok(Some(
+                        self.take(usize::try_from(len).map_err(|_| malformed("a length"))?)?,
+                    )),
+                }
+            }
+        }
+
+        pub fn metadata(topic: &str) -> Vec<u8> {
+            let mut out = Vec::new();
+            i32s(1, &mut out);
+            string(topic, &mut out);
+            out
+        }
+
+        // The brokers by node id, and the leader of each of `topic`'s partitions
+        pub type Metadata = (HashMap<i32, (String, u16)>, BTreeMap<i32, i32>);
+
+        pub fn parse_metadata(body: &[u8], topic: &str) -> io::Result<Metadata> {
+            let mut r = In(body);
+            let mut nodes = HashMap::new();
+            for _ in 0..r.count()? {
+                let id = r.i32()?;
+                let host = r.string()?;
+                let port = u16::try_from(r.i32()?).map_err(|_| malformed("a port"))?;
+                let _rack = r.string()?;
+                nodes.insert(id, (host, port));
+            }
+            let _controller = r.i32()?;
+            for _ in 0..r.count()? {
+                let error = r.i16()?;
+                let name = r.string()?;
+                let _internal = r.i8()?;
+                let mut leaders = BTreeMap::new();
+                for _ in 0..r.count()? {
+                    let _error = r.i16()?;
+                    let partition = r.i32()?;
+                    let leader = r.i32()?;
+                    for _ in 0..2 {
+                        // replicas, then in-sync replicas
+                        let n = r.count()?;
+                        r.take(n * 4)?;
+                    }
+                    leaders.insert(partition, leader);
+                }
+                if name != topic {
+                    continue;
+                }
+                if error != 0 {
+                    return Err(self::error(error, &format!("topic `{topic}`")));
+                }
+                if let Some((partition, _)) = leaders.iter().find(|(_, leader)| **leader < 0) {
+                    return Err(io::Error::other(format!(
+                        "partition {partition} has no leader"
+                    )));
+                }
+                return Ok((nodes, leaders));
+            }
+            Err(self::error(3, &format!("topic `{topic}`")))
+        }
+
+        // The latest offset of each partition
+        pub fn list_offsets(topic: &str, partitions: &[i32]) -> Vec<u8> {
+            let mut out = (-1i32).to_be_bytes().to_vec();
+            i32s(1, &mut out);
+            string(topic, &mut out);
+            i32s(partitions.len(), &mut out);
+            for p in partitions {
+                out.extend_from_slice(&p.to_be_bytes());
+                out.extend_from_slice(&(-1i64).to_be_bytes());
+            }
+            out
+        }
+
+        // (partition, error, offset)
+        pub fn parse_list_offsets(body: &[u8]) -> io::Result<Vec<(i32, i16, i64)>> {
+            let mut r = In(body);
+            let mut offsets = Vec::new();
+            for _ in 0..r.count()? {
+                let _topic = r.string()?;
+                for _ in 0..r.count()? {
+                    let partition = r.i32()?;
+                    let error = r.i16()?;
+                    let _timestamp = r.i64()?;
+                    offsets.push((partition, error, r.i64()?));
+                }
+            }
+            Ok(offsets)
+        }
+
+        pub fn fetch(topic: &str, offsets: &[(i32, i64)]) -> Vec<u8> {
+            let mut out = (-1i32).to_be_bytes().to_vec();
+            out.extend_from_slice(&(super::WAIT.as_millis() as i32).to_be_bytes());
+            out.extend_from_slice(&1i32.to_be_bytes());
+            out.extend_from_slice(&super::MAX_BYTES.to_be_bytes());
+            // read uncommitted: what's written, as it's written
+            out.push(0);
+            i32s(1, &mut out);
+            string(topic, &mut out);
+            i32s(offsets.len(), &mut out);
+            for (partition, offset) in offsets {
+                out.extend_from_slice(&partition.to_be_bytes());
+                out.extend_from_slice(&offset.to_be_bytes());
+                out.extend_from_slice(&super::PARTITION_BYTES.to_be_bytes());
+            }
+            out
+        }
+
+        pub struct Fetched {
+            pub partition: i32,
+            pub error: i16,
+            pub end: i64,
+            pub records: Vec<u8>,
+        }
+
+        pub fn parse_fetch(body: &[u8]) -> io::Result<Vec<Fetched>> {
+            let mut r = In(body);
+            let _throttle = r.i32()?;
+            let mut fetched = Vec::new();
+            for _ in 0..r.count()? {
+                let _topic = r.string()?;
+                for _ in 0..r.count()? {
+                    let partition = r.i32()?;
+                    let error = r.i16()?;
+                    let end = r.i64()?;
+                    let _last_stable = r.i64()?;
+                    // aborted transactions: producer id and first offset
+                    let aborted = r.count()?;
+                    r.take(aborted * 16)?;
+                    let records = r.bytes()?.to_vec();
+                    fetched.push(Fetched {
+                        partition,
+                        error,
+                        end,
+                        records,
+                    });
+                }
+            }
+            Ok(fetched)
+        }
+
+        pub struct Record {
+            pub key: Option<Vec<u8>>,
+            pub value: Option<Vec<u8>>,
+        }
+
+        pub struct RecordSet {
+            pub records: Vec<Record>,
+            // the offset after the last whole batch
+            pub next: Option<i64>,
+            pub skipped: Vec<&'static str>,
+        }
+
+        // The records at `from` and after, out of record batches (format v2).
+        // A fetch may end partway through a batch; that one comes whole next
+        // time. Compressed batches and older formats are skipped, since reading
+        // them needs codecs this doesn't carry.
+        pub fn records(set: &[u8], from: i64) -> RecordSet {
+            let mut out = RecordSet {
+                records: Vec::new(),
+                next: None,
+                skipped: Vec::new(),
+            };
+            let mut r = In(set);
+            while r.0.len() >= 12 {
+                let base = r.i64().unwrap_or_default();
+                let len = r.i32().unwrap_or_default();
+                let Ok(batch) = r.take(usize::try_from(len).unwrap_or(usize::MAX)) else {
+                    break;
+                };
+                let mut b = In(batch);
+                let Ok(next) = batch_records(&mut b, base, from, &mut out) else {
+                    break;
+                };
+                out.next = Some(next);
+            }
+            out
+        }
+
+        fn batch_records(b: &mut In, base: i64, from: i64, out: &mut RecordSet) -> io::Result<i64> {
+            let _leader_epoch = b.i32()?;
+            if b.i8()? != 2 {
+                out.skipped.push("messages in the format before Kafka 0.11");
+                return offset(base, 1);
+            }
+            let _crc = b.i32()?;
+            let attributes = b.i16()?;
+            let next = offset(base, i64::from(b.i32()?) + 1)?;
+            // timestamps, producer id, epoch, and base sequence
+            b.take(8 + 8 + 8 + 2 + 4)?;
+            let count = b.count()?;
+            // transaction markers
+            if attributes & 0x20 != 0 {
+                return Ok(next);
+            }
+            if attributes & 0x07 != 0 {
+                out.skipped
+                    .push("compressed batches; produce this topic uncompressed to see them");
+                return Ok(next);
+            }
+            for _ in 0..count {
+                let _len = b.varint()?;
+                let _attributes = b.i8()?;
+                let _timestamp = b.varint()?;
+                let offset = offset(base, b.varint()?)?;
+                let key = b.varbytes()?.map(<[u8]>::to_vec);
+                let value = b.varbytes()?.map(<[u8]>::to_vec);
+                for _ in 0..b.varint()? {
+                    b.varbytes()?;
+                    b.varbytes()?;
+                }
+                if offset >= from {
+                    out.records.push(Record { key, value });
+                }
+            }
+            Ok(next)
+        }
+
+        // `base` plus a delta from the broker, which could be anything
+        fn offset(base: i64, delta: i64) -> io::Result<i64> {
+            base.checked_add(delta)
+                .ok_or_else(|| malformed("a record offset"))
+        }
+
+        pub fn find_coordinator(group: &str) -> Vec<u8> {
+            let mut out = Vec::new();
+            string(group, &mut out);
+            out
+        }
+
+        pub fn parse_find_coordinator(body: &[u8]) -> io::Result<(String, u16)> {
+            let mut r = In(body);
+            let error = r.i16()?;
+            if error != 0 {
+                return Err(self::error(error, "finding the group's coordinator"));
+            }
+            let _node = r.i32()?;
+            let host = r.string()?;
+            let port = u16::try_from(r.i32()?).map_err(|_| malformed("a port"))?;
+            Ok((host, port))
+        }
+
+        pub fn offset_fetch(group: &str, topic: &str, partitions: &[i32]) -> Vec<u8> {
+            let mut out = Vec::new();
+            string(group, &mut out);
+            i32s(1, &mut out);
+            string(topic, &mut out);
+            i32s(partitions.len(), &mut out);
+            for p in partitions {
+                out.extend_from_slice(&p.to_be_bytes());
+            }
+            out
+        }
+
+        // (partition, committed offset), -1 for none
+        pub fn parse_offset_fetch(body: &[u8]) -> io::Result<Vec<(i32, i64)>> {
+            let mut r = In(body);
+            let mut offsets = Vec::new();
+            for _ in 0..r.count()? {
+                let _topic = r.string()?;
+                for _ in 0..r.count()? {
+                    let partition = r.i32()?;
+                    let offset = r.i64()?;
+                    let _metadata = r.string()?;
+                    let error = r.i16()?;
+                    if error != 0 {
+                        return Err(self::error(error, &format!("partition {partition}")));
+                    }
+                    offsets.push((partition, offset));
+                }
+            }
+            Ok(offsets)
+        }
+    }
+}
+
+#[cfg(not(feature = "kafka"))]
+mod sys {
+    use std::sync::mpsc::Receiver;
+
+    use super::{KafkaSpec, Partition};
+    use crate::{ingest::IngestQueue, reconnect::Links};
+
+    pub fn consume(
+        _spec: &KafkaSpec,
+        _links: &Links,
+        _ingest: &IngestQueue,
+    ) -> Result<Receiver<Vec<Partition>>, String> {
+        Err("this build doesn't include it; rebuild with `--features kafka`".to_string())
+    }
+}
@@ -0,0 +1,543 @@
+use std::sync::mpsc::Receiver;
+
+use ratatui::{prelude::*, widgets::Paragraph};
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    chrome::Chrome,
+    ingest::IngestQueue,
+    layout::{fit, sanitize, table_rows},
+    programs::Programs,
+    reconnect::Links,
+    scaling::ALL,
+};
+
+// `[kube]` in config.toml: the namespace whose pods are listed, `all` for
+// every one, and a label selector to narrow them
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct KubeSpec {
+    #[serde(default = "default_namespace", deserialize_with = "namespace")]
+    pub namespace: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+}
+
+fn default_namespace() -> String {
+    "default".to_string()
+}
+
+// Kubernetes' rule for namespaces: lowercase letters, digits, and `-`, up to
+// 63 of them
+fn namespace<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    let namespace = String::deserialize(d)?;
+    let legal = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-';
+    match !namespace.is_empty() && namespace.len() <= 63 && namespace.chars().all(legal) {
+        true => Ok(namespace),
+        false => Err(de::Error::custom(format!(
+            "`{namespace}` isn't a namespace; they're lowercase letters, digits, and `-`"
+        ))),
+    }
+}
+
+// One pod as `kubectl get pods` would list it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pod {
+    pub namespace: String,
+    pub name: String,
+    // the phase, or what holds a container back (`CrashLoopBackOff`)
+    pub status: String,
+    // containers ready, of how many
+    pub ready: (usize, usize),
+    // of all its containers
+    pub restarts: u64,
+    // why a container last exited (`OOMKilled`), for the restart's log line
+    pub exited: Option<String>,
+}
+
+impl Pod {
+    // Running with every container ready, or done
+    pub fn healthy(&self) -> bool {
+        match self.status.as_str() {
+            "Running" => self.ready.0 == self.ready.1,
+            "Succeeded" | "Completed" => true,
+            _ => false,
+        }
+    }
+}
+
+// [kube]: the pods of a namespace, from a list and then a watch that streams
+// every change after it, through `kubectl get --raw`. Phase changes,
+// restarts, and pods coming and going are logged; cloud mode lists the pods
+// in the system panel's place, troubled ones first.
+pub struct Pods {
+    // None for every namespace
+    pub namespace: Option<String>,
+    pub pods: Vec<Pod>,
+    // whether the first list came back yet
+    pub listed: bool,
+    rx: Receiver<Vec<Pod>>,
+}
+
+impl Pods {
+    pub fn start(
+        spec: &KubeSpec,
+        programs: &Programs,
+        links: &Links,
+        ingest: &IngestQueue,
+    ) -> Result<Self, String> {
+        let namespace = Some(spec.namespace.clone()).filter(|ns| ns != ALL);
+        let rx = sys::watch(spec, namespace.clone(), programs, links, ingest)?;
+        Ok(Self {
+            namespace,
+            pods: Vec::new(),
+            listed: false,
+            rx,
+        })
+    }
+
+    pub fn drain(&mut self) {
+        if let Some(pods) = self.rx.try_iter().last() {
+            self.pods = pods;
+            self.listed = true;
+        }
+    }
+}
+
+// Statuses worth a warning when a pod turns to them
+fn troubled(status: &str) -> bool {
+    !matches!(
+        status,
+        "Running" | "Succeeded" | "Completed" | "Pending" | "ContainerCreating" | "Terminating"
+    )
+}
+
+// Cloud mode's pod table, in the system panel's place: one row per pod,
+// troubled ones first
+pub fn draw(buf: &mut Buffer, area: Rect, pods: &Pods, chrome: Chrome) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let inner = area.width.saturating_sub(2) as usize;
+    let rows = table_rows(area, pods.pods.len());
+    // ready and restarts, each after a space; the status gives way to the
+    // name down to a few cells of each
+    let fixed = 6 + 9;
+    let status_w = inner.saturating_sub(fixed + 1 + 8).clamp(7, 17);
+    let name_w = inner.saturating_sub(fixed + 1 + status_w).max(6);
+    let mut lines = vec![Line::styled(
+        format!(
+            "{:<name_w$} {:<status_w$} {:>5} {:>8}",
+            "pod", "status", "ready", "restarts"
+        ),
+        dim,
+    )];
+    for pod in pods.pods.iter().take(rows) {
+        let name = match &pods.namespace {
+            Some(_) => sanitize(&pod.name).into_owned(),
+            None => sanitize(&format!("{}/{}", pod.namespace, pod.name)).into_owned(),
+        };
+        let (name, name_pad) = fit(&name, name_w);
+        let (status, pad) = fit(&pod.status, status_w);
+        let color = match (pod.healthy(), troubled(&pod.status)) {
+            (true, _) => Color::LightGreen,
+            (false, true) => Color::Red,
+            (false, false) => Color::Yellow,
+        };
+        let restarts = match pod.restarts {
+            0 => Style::default(),
+            _ => Style::default().fg(Color::Yellow),
+        };
+        let ready = format!("{}/{}", pod.ready.0, pod.ready.1);
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{name}{name_pad} "),
+                Style::default().fg(Color::White),
+            ),
+            Span::styled(format!("{status}{pad} "), Style::default().fg(color)),
+            Span::raw(format!("{ready:>5} ")),
+            Span::styled(format!("{:>8}", pod.restarts), restarts),
+        ]));
+    }
+    let hidden = pods.pods.len().saturating_sub(rows);
+    if !pods.listed {
+        lines.push(Line::styled("listing pods…", dim));
+    } else if pods.pods.is_empty() {
+        lines.push(Line::styled("no pods", dim));
+    } else if hidden > 0 {
+        lines.push(Line::styled(format!("+{hidden} more"), dim));
+    }
+    let running = pods.pods.iter().filter(|p| p.status == "Running").count();
+    let title = match &pods.namespace {
+        Some(ns) => format!("pods • {ns} • {running}/{} running", pods.pods.len()),
+        None => format!(
+            "pods • all namespaces • {running}/{} running",
+            pods.pods.len()
+        ),
+    };
+    Paragraph::new(lines)
+        .block(
+            chrome.block().border_style(dim).title(Span::styled(
+                title,
+                Style::default()
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+            )),
+        )
+        .render(area, buf);
+}
+
+// The list and watch through kubectl, only in builds with the feature
+#[cfg(feature = "kube")]
+mod sys {
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        io::{self, BufReader, Read},
+        process::{Child, ChildStdout, Command, Stdio},
+        sync::mpsc::{self, Receiver, Sender},
+    };
+
+    use serde_json::Value;
+
+    use super::{troubled, KubeSpec, Pod};
+    use crate::{
+        ingest::{IngestQueue, SourceSender},
+        logs::Level,
+        programs::Programs,
+        reconnect::{Connector, Links},
+    };
+
+    // What the watch's link and the logged changes are shown under
+    const NAME: &str = "pods";
+    const QUEUE: usize = 256;
+
+    // The pods of `namespace`, every one's for None, listed and then watched
+    // on their own reconnecting link
+    pub fn watch(
+        spec: &KubeSpec,
+        namespace: Option<String>,
+        programs: &Programs,
+        links: &Links,
+        ingest: &IngestQueue,
+    ) -> Result<Receiver<Vec<Pod>>, String> {
+        let (updates, rx) = mpsc::channel();
+        let watch = Watch {
+            namespace,
+            programs: programs.clone(),
+            selector: spec.selector.clone(),
+            pods: BTreeMap::new(),
+            listed: false,
+            before: None,
+            updates,
+        };
+        links.spawn(NAME, watch, ingest.sender(NAME, QUEUE));
+        Ok(rx)
+    }
+
+    // (namespace, name) → pod
+    type Known = BTreeMap<(String, String), Pod>;
+
+    struct Watch {
+        namespace: Option<String>,
+        programs: Programs,
+        selector: Option<String>,
+        pods: Known,
+        // false until the first list, whose pods aren't news
+        listed: bool,
+        // the pods before a reconnect's list, to log what changed meanwhile
+        before: Option<Known>,
+        updates: Sender<Vec<Pod>>,
+    }
+
+    impl Watch {
+        // `/api/v1/namespaces/shop/pods?labelSelector=app%3Dweb`, with `query`
+        // after the selector
+        fn path(&self, query: &str) -> String {
+            let mut path = match &self.namespace {
+                Some(ns) => format!("/api/v1/namespaces/{ns}/pods"),
+                None => "/api/v1/pods".to_string(),
+            };
+            let mut params = Vec::new();
+            if let Some(selector) = &self.selector {
+                params.push(format!("labelSelector={}", encode(selector)));
+            }
+            if !query.is_empty() {
+                params.push(query.to_string());
+            }
+            if !params.is_empty() {
+                path = format!("{path}?{}", params.join("&"));
+            }
+            path
+        }
+
+        // The pods as the app should show them, troubled first, then by the
+        // most restarts
+        fn update(&self) -> bool {
+            let mut pods: Vec<Pod> = self.pods.values().cloned().collect();
+            pods.sort_by(|a, b| {
+                a.healthy()
+                    .cmp(&b.healthy())
+                    .then(b.restarts.cmp(&a.restarts))
+                    .then_with(|| (&a.namespace, &a.name).cmp(&(&b.namespace, &b.name)))
+            });
+            self.updates.send(pods).is_ok()
+        }
+
+        // How `pod` is named in the logs: with its namespace when watching all
+        fn label(&self, pod: &Pod) -> String {
+            match self.namespace {
+                Some(_) => pod.name.clone(),
+                None => format!("{}/{}", pod.namespace, pod.name),
+            }
+        }
+
+        // Logs what changed between `was` and `now`
+        fn changed(&self, was: Option<&Pod>, now: Option<&Pod>, tx: &SourceSender) {
+            match (was, now) {
+                (None, Some(pod)) => tx.send(
+                    Level::Info,
+                    format!("{}: added ({})", self.label(pod), pod.status),
+                ),
+                (Some(pod), None) => tx.send(Level::Info, format!("{}: deleted", self.label(pod))),
+                (Some(was), Some(pod)) => {
+                    if pod.restarts > was.restarts {
+                        let exited = match &pod.exited {
+                            Some(why) => format!("; last exit {why}"),
+                            None => String::new(),
+                        };
+                        tx.send(
+                            Level::Warn,
+                            format!(
+                                "{}: restarted ({} restarts{exited})",
+                                self.label(pod),
+                                pod.restarts
+                            ),
+                        );
+                    }
+                    if pod.status != was.status {
+                        let level = match troubled(&pod.status) {
+                            true => Level::Warn,
+                            false => Level::Info,
+                        };
+                        tx.send(
+                            level,
+                            format!("{}: {} → {}", self.label(pod), was.status, pod.status),
+                        );
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+    }
+
+    impl Connector for Watch {
+        type Conn = (Child, ChildStdout);
+
+        // Lists the pods, then watches from the list's version on, so nothing
+        // between the two is missed. Changes while the watch was down show up
+        // against the list.
+        fn connect(&mut self) -> io::Result<Self::Conn> {
+            let output = kubectl(&self.programs, &self.path(""))
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .output()
+                .map_err(not_found)?;
+            if !output.status.success() {
+                return Err(exited(
+                    output.status,
+                    &String::from_utf8_lossy(&output.stderr),
+                ));
+            }
+            let list: Value = serde_json::from_slice(&output.stdout).map_err(io::Error::other)?;
+            let version = list
+                .pointer("/metadata/resourceVersion")
+                .and_then(Value::as_str)
+                .ok_or_else(|| io::Error::other("kubectl's pod list has no resourceVersion"))?
+                .to_string();
+            let listed: Known = list
+                .get("items")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(pod)
+                .map(|pod| ((pod.namespace.clone(), pod.name.clone()), pod))
+                .collect();
+            let before = std::mem::replace(&mut self.pods, listed);
+            if self.listed {
+                self.before = Some(before);
+            }
+            self.listed = true;
+            self.update();
+
+            let watch = self.path(&format!("watch=1&resourceVersion={version}"));
+            // stderr is read once kubectl exits, for why it did
+            let mut child = kubectl(&self.programs, &watch)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(not_found)?;
+            let stdout = child.stdout.take().expect("stdout is piped");
+            Ok((child, stdout))
+        }
+
+        fn run(&mut self, (mut child, stdout): Self::Conn, tx: &SourceSender) -> io::Result<()> {
+            match self.before.take() {
+                None => tx.send(Level::Info, format!("watching {} pods", self.pods.len())),
+                Some(before) => {
+                    let keys: BTreeSet<_> = before.keys().chain(self.pods.keys()).collect();
+                    for key in keys {
+                        self.changed(before.get(key), self.pods.get(key), tx);
+                    }
+                }
+            }
+            // one `{"type": ..., "object": ...}` after another
+            let events = serde_json::Deserializer::from_reader(BufReader::new(stdout));
+            for event in events.into_iter::<Value>() {
+                let event = event.map_err(io::Error::other)?;
+                let kind = event.get("type").and_then(Value::as_str).unwrap_or("");
+                let object = event.get("object").unwrap_or(&Value::Null);
+                if kind == "ERROR" {
+                    // a version too old to watch from (410) among them; the list
+                    // that comes with reconnecting starts over
+                    let _ = child.kill();
+                    let why = object.get("message").and_then(Value::as_str);
+                    return Err(io::Error::other(format!(
+                        "the watch ended: {}",
+                        why.unwrap_or("no reason given")
+                    )));
+                }
+                let Some(pod) = pod(object) else {
+                    // BOOKMARK, and anything newer
+                    continue;
+                };
+                let key = (pod.namespace.clone(), pod.name.clone());
+                let was = match kind {
+                    "DELETED" => self.pods.remove(&key),
+                    "ADDED" | "MODIFIED" => self.pods.insert(key.clone(), pod),
+                    _ => continue,
+                };
+                self.changed(was.as_ref(), self.pods.get(&key), tx);
+                if !self.update() {
+                    // the app is gone
+                    let _ = child.kill();
+                    return Ok(());
+                }
+            }
+            let status = child.wait()?;
+            if status.success() {
+                return Ok(());
+            }
+            let mut stderr = String::new();
+            if let Some(mut pipe) = child.stderr.take() {
+                let _ = pipe.read_to_string(&mut stderr);
+            }
+            Err(exited(status, &stderr))
+        }
+    }
+
+    fn kubectl(programs: &Programs, path: &str) -> Command {
+        let mut kubectl = programs.command("kubectl");
+        kubectl.args(["get", "--raw", path]).stdin(Stdio::null());
+        kubectl
+    }
+
+    fn not_found(e: io::Error) -> io::Error {
+        match e.kind() {
+            io::ErrorKind::NotFound => io::Error::other("kubectl isn't on PATH"),
+            _ => e,
+        }
+    }
+
+    fn exited(status: std::process::ExitStatus, stderr: &str) -> io::Error {
+        io::Error::other(
+            match stderr.lines().map(str::trim).rfind(|l| !l.is_empty()) {
+                Some(why) => format!("kubectl exited with {status}: {why}"),
+                None => format!("kubectl exited with {status}"),
+            },
+        )
+    }
+
+    // A label selector as a query parameter: `app=web,tier!=db` percent-encoded
+    fn encode(text: &str) -> String {
+        text.bytes()
+            .map(|b| match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    (b as char).to_string()
+                }
+                _ => format!("%{b:02X}"),
+            })
+            .collect()
+    }
+
+    // A pod object, with its status worked out the way kubectl's STATUS column
+    // does: being deleted, evicted, a container waiting or exited with a reason,
+    // else the phase
+    fn pod(object: &Value) -> Option<Pod> {
+        let text = |pointer: &str| object.pointer(pointer).and_then(Value::as_str);
+        let name = text("/metadata/name")?.to_string();
+        let namespace = text("/metadata/namespace").unwrap_or_default().to_string();
+        let containers: &[Value] = object
+            .pointer("/status/containerStatuses")
+            .and_then(Value::as_array)
+            .map_or(&[], Vec::as_slice);
+        let declared = object
+            .pointer("/spec/containers")
+            .and_then(Value::as_array)
+            .map_or(containers.len(), Vec::len);
+        let reason = |state: &str| {
+            containers.iter().find_map(|c| {
+                c.pointer(&format!("/state/{state}/reason"))
+                    .and_then(Value::as_str)
+            })
+        };
+        let phase = text("/status/phase").unwrap_or("Unknown");
+        let status = if text("/metadata/deletionTimestamp").is_some() {
+            "Terminating"
+        } else if let Some(reason) = text("/status/reason") {
+            reason
+        } else if let Some(reason) = reason("waiting") {
+            reason
+        } else if let Some(reason) = reason("terminated").filter(|_| phase != "Running") {
+            reason
+        } else {
+            phase
+        };
+        Some(Pod {
+            namespace,
+            name,
+            status: status.to_string(),
+            ready: (
+                containers
+                    .iter()
+                    .filter(|c| c.get("ready").and_then(Value::as_bool) == Some(true))
+                    .count(),
+                declared,
+            ),
+            restarts: containers
+                .iter()
+                .filter_map(|c| c.get("restartCount").and_then(Value::as_u64))
+                .sum(),
+            exited: containers.iter().find_map(|c| {
+                c.pointer("/lastState/terminated/reason")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            }),
+        })
+    }
+}
+
+#[cfg(not(feature = "kube"))]
+mod sys {
+    use std::sync::mpsc::Receiver;
+
+    use super::{KubeSpec, Pod};
+    use crate::{ingest::IngestQueue, programs::Programs, reconnect::Links};
+
+    pub fn watch(
+        _spec: &KubeSpec,
+        _namespace: Option<String>,
+        _programs: &Programs,
+        _links: &Links,
+        _ingest: &IngestQueue,
+    ) -> Result<Receiver<Vec<Pod>>, String> {
+        Err("this build doesn't include it; rebuild with `--features kube`".to_string())
+    }
+}
@@ -0,0 +1,215 @@
+use std::borrow::Cow;
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::detail::{self, Panel};
+
+// Fixed heights and minimum sizes. When space runs out panels shrink in
+// priority order (logs first, then metrics) and collapse to an empty rect
+// once below their minimum, rather than squashing bars and borders.
+const BANNER_H: u16 = 3;
+const METRICS_H: u16 = 9;
+const COMMAND_H: u16 = 3;
+const LOGS_MIN_H: u16 = 3;
+const METRICS_MIN_H: u16 = 3;
+// Below this the dashboard is drawn offscreen at this size and panned
+pub const MIN_W: u16 = 80;
+pub const MIN_H: u16 = 24;
+// borders + label + value + gaps + a 4-cell bar
+pub const AI_MIN_W: u16 = 2 + 15 + 2 + 8 + 2 + 4;
+pub const SYS_MIN_W: u16 = 2 + 12 + 1 + 6 + 1 + 4;
+// the watchlist takes this much off the right of the logs row, when the logs
+// keep at least LOGS_MIN_W
+const WATCH_W: u16 = 34;
+const LOGS_MIN_W: u16 = 46;
+// a mode's strip (data forensics tables, cloud's scaling timeline) takes this
+// much off the top of the logs row, when the logs keep at least LOGS_MIN_H
+const STRIP_H: u16 = 7;
+
+// Panel rects for the current terminal size; only recomputed when the size changes
+#[derive(Default)]
+pub struct PanelLayout {
+    area: Rect,
+    watching: bool,
+    emphasis: Option<Panel>,
+    strip_shown: bool,
+    pub banner: [Rect; 3],
+    pub ai_metrics: Rect,
+    pub system: Rect,
+    pub logs: Rect,
+    pub watchlist: Rect,
+    pub strip: Rect,
+    pub command: Rect,
+}
+
+impl PanelLayout {
+    // `watching`: whether there's a watchlist to make room for; `emphasis`:
+    // a metrics panel in critical state, which goes first and gets the rows
+    // for its expanded metric, taken from the logs; `strip`: whether
+    // the mode has a strip to show
+    pub fn update(&mut self, area: Rect, watching: bool, emphasis: Option<Panel>, strip: bool) {
+        if area == self.area
+            && watching == self.watching
+            && emphasis == self.emphasis
+            && strip == self.strip_shown
+            && area != Rect::default()
+        {
+            return;
+        }
+        self.area = area;
+        self.watching = watching;
+        self.emphasis = emphasis;
+        self.strip_shown = strip;
+
+        // the command bar always wins, then the banner
+        let mut left = area.height;
+        let command_h = COMMAND_H.min(left);
+        left -= command_h;
+        let banner_h = BANNER_H.min(left);
+        left -= banner_h;
+        let mut metrics_h = METRICS_H.min(left);
+        if emphasis.is_some() {
+            let room = left.saturating_sub(LOGS_MIN_H);
+            metrics_h = metrics_h.max((METRICS_H + detail::LINES).min(room));
+        }
+        if metrics_h < METRICS_MIN_H {
+            metrics_h = 0;
+        }
+        left -= metrics_h;
+        let logs_h = if left < LOGS_MIN_H { 0 } else { left };
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(banner_h),
+                Constraint::Length(metrics_h),
+                Constraint::Length(logs_h),
+                Constraint::Min(0), // rows given up by collapsed panels
+                Constraint::Length(command_h),
+            ])
+            .split(area);
+
+        // 25 / 50 / 25 so the center stays centered and uptime never pushes hints around
+        let banner = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(25),
+                Constraint::Percentage(50),
+                Constraint::Percentage(25),
+            ])
+            .split(rows[0]);
+
+        // 60 / 40, nudged so both panels keep their minimum width; the
+        // second panel collapses when there isn't room for both. An
+        // emphasized system panel swaps places with the AI one.
+        let width = area.width;
+        let ((first_min, second_min), swapped) = match emphasis {
+            Some(Panel::System) => ((SYS_MIN_W, AI_MIN_W), true),
+            _ => ((AI_MIN_W, SYS_MIN_W), false),
+        };
+        let (first_w, second_w) = if width >= first_min + second_min {
+            let first = ((width as u32 * 60 / 100) as u16)
+                .max(first_min)
+                .min(width - second_min);
+            (first, width - first)
+        } else {
+            (width, 0)
+        };
+        let metrics = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(first_w), Constraint::Length(second_w)])
+            .split(rows[1]);
+
+        self.banner = [banner[0], banner[1], banner[2]];
+        (self.ai_metrics, self.system) = match swapped {
+            true => (metrics[1], metrics[0]),
+            false => (metrics[0], metrics[1]),
+        };
+        let (table, logs_row) = if strip && rows[2].height >= STRIP_H + LOGS_MIN_H {
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(STRIP_H), Constraint::Min(0)])
+                .split(rows[2]);
+            (split[0], split[1])
+        } else {
+            (Rect::default(), rows[2])
+        };
+        self.strip = table;
+        (self.logs, self.watchlist) = if watching && logs_row.width >= LOGS_MIN_W + WATCH_W {
+            let split = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(0), Constraint::Length(WATCH_W)])
+                .split(logs_row);
+            (split[0], split[1])
+        } else {
+            (logs_row, Rect::default())
+        };
+        self.command = rows[4];
+    }
+}
+
+// Static run of spaces so padding never allocates
+const SPACES: &str = "                                                                ";
+
+pub fn pad(width: usize) -> &'static str {
+    &SPACES[..width.min(SPACES.len())]
+}
+
+// Truncates to at most `width` cells on a grapheme boundary and returns the
+// padding that fills the rest; wide glyphs that would straddle the edge are dropped
+pub fn fit(label: &str, width: usize) -> (&str, &'static str) {
+    let mut used = 0;
+    let mut end = 0;
+    for (i, g) in label.grapheme_indices(true) {
+        let w = g.width();
+        if used + w > width {
+            break;
+        }
+        used += w;
+        end = i + g.len();
+    }
+    (&label[..end], pad(width - used))
+}
+
+// How many of `len` rows a bordered table fits in `area` under its header
+// line, keeping a line for `+N more` when they don't all fit
+pub fn table_rows(area: Rect, len: usize) -> usize {
+    let rows = area.height.saturating_sub(3) as usize;
+    match len > rows {
+        true => rows.saturating_sub(1),
+        false => rows,
+    }
+}
+
+// Text from outside (sources, files, peers) with control characters replaced,
+// so nothing it contains can move the cursor or restyle the terminal
+pub fn sanitize(text: &str) -> Cow<'_, str> {
+    if !text.contains(|c: char| c.is_control()) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.chars()
+            .map(|c| match c {
+                '\t' => ' ',
+                c if c.is_control() => '\u{fffd}',
+                c => c,
+            })
+            .collect(),
+    )
+}
+
+// Same as `sanitize`, but line breaks survive (CRLF becomes LF) so stack
+// traces and other multi-line payloads keep their shape
+pub fn sanitize_lines(text: &str) -> Cow<'_, str> {
+    if !text.contains(|c: char| c.is_control() && c != '\n') {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.split('\n')
+            .map(|line| sanitize(line.strip_suffix('\r').unwrap_or(line)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
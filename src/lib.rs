@@ -0,0 +1,91 @@
+// The dashboard as a library: `app` holds the state and applies events,
+// `command` runs command-bar lines, `ui` draws a frame into any ratatui
+// backend, `sim` generates the demo data, and `term` drives a real terminal.
+// The binary only parses arguments and picks one of those to run.
+
+mod alerts;
+pub mod app;
+pub mod applog;
+mod auth;
+mod availability;
+mod bench;
+mod caps;
+mod capture;
+mod chrome;
+pub mod cli;
+mod command;
+mod compare;
+pub mod config;
+mod control;
+pub mod datasource;
+mod derived;
+mod detail;
+mod dirs;
+mod docker;
+#[cfg(feature = "e2e")]
+pub mod driver;
+mod explain;
+mod focus;
+mod follow;
+mod glyphs;
+mod gpu;
+mod health;
+pub mod histogram;
+mod history;
+pub mod hosts;
+mod http;
+mod idle;
+mod ingest;
+mod input;
+mod integrity;
+mod journal;
+mod kafka;
+mod kube;
+pub mod layout;
+mod listen;
+mod logs;
+mod maintenance;
+pub mod manual;
+mod marks;
+mod motion;
+mod mqtt;
+mod net;
+mod notify;
+mod otlp;
+mod parse;
+mod payload;
+mod pins;
+mod profile;
+pub mod profiler;
+mod programs;
+mod prometheus;
+mod push;
+mod reconnect;
+mod redact;
+mod report;
+mod ros2;
+mod sampling;
+mod scaling;
+mod scenario;
+mod search;
+mod serial;
+mod signals;
+pub mod sim;
+mod socket;
+mod statsd;
+mod stdin;
+mod syslog;
+pub mod sysmetrics;
+pub mod term;
+mod timebase;
+mod timeline;
+mod tls;
+mod tokens;
+pub mod ui;
+mod views;
+mod watchdog;
+mod watchlist;
+mod websocket;
+mod wizard;
+
+pub use app::{AppState, Mode};
@@ -0,0 +1,80 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Read},
+    net::{SocketAddr, TcpListener, TcpStream},
+    thread,
+};
+
+use crate::{
+    ingest::{IngestQueue, SourceSender},
+    logs::Level,
+    parse::{parse_line, Parsed},
+};
+
+// Lines buffered per sender before the oldest are dropped
+pub const QUEUE: usize = 1024;
+// Lines handed over at once while more are already waiting on the socket
+const BATCH: usize = 256;
+// A line without a newline this long is sent as it is
+const MAX_LINE: u64 = 64 << 10;
+// Connections shown under their own address; after this many, new ones share
+// one per IP, so a client that reconnects for every line doesn't grow the
+// list of sources without end
+const MAX_TAGS: usize = 256;
+
+// `--listen`: newline-delimited text or JSON lines over plain TCP, as with
+// `nc 127.0.0.1 7777`, each connection's lines under the sender's address.
+// Returns the address bound, which differs from `addr` for port 0.
+pub fn spawn(addr: SocketAddr, ingest: IngestQueue) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr)?;
+    let addr = listener.local_addr()?;
+    thread::spawn(move || {
+        let mut tags: HashMap<String, SourceSender> = HashMap::new();
+        for stream in listener.incoming().flatten() {
+            let Ok(peer) = stream.peer_addr() else {
+                continue;
+            };
+            let tag = match tags.len() < MAX_TAGS {
+                true => peer.to_string(),
+                false => peer.ip().to_string(),
+            };
+            let tx = tags
+                .entry(tag)
+                .or_insert_with_key(|tag| ingest.sender(tag, QUEUE))
+                .clone();
+            // one slow sender shouldn't hold up the others
+            thread::spawn(move || read(stream, &tx));
+        }
+    });
+    Ok(addr)
+}
+
+// Parses lines like any other source's until the sender hangs up
+fn read(stream: TcpStream, tx: &SourceSender) {
+    let mut reader = BufReader::new(stream);
+    let mut raw = Vec::new();
+    let mut batch = Vec::new();
+    let flush = |batch: &mut Vec<Parsed>| {
+        if !batch.is_empty() {
+            tx.send_batch(batch.drain(..).map(|p| (p.level, p.message)).collect());
+        }
+    };
+    loop {
+        raw.clear();
+        match (&mut reader).take(MAX_LINE).read_until(b'\n', &mut raw) {
+            Ok(0) => break,
+            Ok(_) => batch.extend(parse_line(&String::from_utf8_lossy(&raw))),
+            Err(e) => {
+                flush(&mut batch);
+                tx.send(Level::Warn, format!("connection lost: {e}"));
+                return;
+            }
+        }
+        // send once the socket is drained, so a trickle shows up line by
+        // line and a flood in batches
+        if batch.len() >= BATCH || reader.buffer().is_empty() {
+            flush(&mut batch);
+        }
+    }
+    flush(&mut batch);
+}
@@ -0,0 +1,266 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::search::SearchIndex;
+
+// Full severity scale; not every level has a producer yet
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+// Index into the interned source table; cheap to copy and compare when filtering
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SourceId(u16);
+
+impl SourceId {
+    // ai-intui's own messages (command echo, mode changes, errors)
+    pub const APP: SourceId = SourceId(0);
+    // `mark` annotations, drawn highlighted
+    pub const MARK: SourceId = SourceId(1);
+    // every source past the 65,535th, once the ids have run out
+    pub const OTHER: SourceId = SourceId(u16::MAX);
+}
+
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    // monotonic offset from app start; see Timebase for wall time
+    pub at: Duration,
+    pub level: Level,
+    pub source: SourceId,
+    pub message: Arc<str>,
+    // how many lines this one stands for once repeats were collapsed into
+    // it, and when the latest of them arrived
+    pub repeats: u32,
+    pub last: Duration,
+}
+
+// When a line is a repeat of the one before it: same source and level,
+// within `window` of that line's latest repeat, and at least `similarity` of
+// its words the same. A zero window keeps every line.
+#[derive(Clone, Copy, Debug)]
+pub struct Dedup {
+    pub window: Duration,
+    pub similarity: f32,
+}
+
+impl Dedup {
+    fn collapses(&self, prev: &LogEntry, next: &LogEntry) -> bool {
+        !self.window.is_zero()
+            && prev.source == next.source
+            && prev.level == next.level
+            // two marks are two moments, however alike their names
+            && prev.source != SourceId::MARK
+            && next.at.saturating_sub(prev.last) <= self.window
+            && similarity(&prev.message, &next.message) >= self.similarity
+    }
+}
+
+// Share of word positions two messages agree on: 1 for identical text, 0.75
+// for `upstream timeout after 312ms` against `upstream timeout after 298ms`
+fn similarity(a: &str, b: &str) -> f32 {
+    if a == b {
+        return 1.0;
+    }
+    let a: Vec<&str> = a.split_whitespace().collect();
+    let b: Vec<&str> = b.split_whitespace().collect();
+    let same = a.iter().zip(&b).filter(|(x, y)| x == y).count();
+    same as f32 / a.len().max(b.len()).max(1) as f32
+}
+
+// Interns source prefixes like "AI[core]" so each entry only carries a SourceId
+struct SourceTable {
+    names: Vec<Arc<str>>,
+    index: HashMap<Arc<str>, SourceId>,
+}
+
+impl SourceTable {
+    fn new() -> Self {
+        let mut table = Self {
+            names: Vec::new(),
+            index: HashMap::new(),
+        };
+        table.intern("app");
+        table.intern("mark");
+        table
+    }
+
+    fn intern(&mut self, name: &str) -> SourceId {
+        if let Some(id) = self.index.get(name) {
+            return *id;
+        }
+        // a listener taking one source per sender address could otherwise
+        // wrap around and file new lines under `app`
+        if self.names.len() >= usize::from(SourceId::OTHER.0) {
+            return SourceId::OTHER;
+        }
+        let id = SourceId(self.names.len() as u16);
+        let name: Arc<str> = Arc::from(name);
+        self.names.push(name.clone());
+        self.index.insert(name, id);
+        id
+    }
+
+    fn name(&self, id: SourceId) -> &str {
+        match id {
+            SourceId::OTHER => "other",
+            id => self.names.get(id.0 as usize).map_or("?", |n| n),
+        }
+    }
+}
+
+pub struct LogBuffer {
+    entries: VecDeque<LogEntry>,
+    sources: SourceTable,
+    capacity: usize,
+    // approximate bytes held by retained entries and their share of the
+    // search index; 0 budget means unbounded
+    bytes: usize,
+    budget: usize,
+    // sequence number the next pushed entry gets; the oldest retained is next_seq - len
+    next_seq: u64,
+    index: SearchIndex,
+    dedup: Dedup,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize, budget: usize, dedup: Dedup) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            entries: VecDeque::with_capacity(capacity.min(4096)),
+            sources: SourceTable::new(),
+            capacity,
+            bytes: 0,
+            budget,
+            next_seq: 0,
+            index: SearchIndex::default(),
+            dedup,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    pub fn index_bytes(&self) -> usize {
+        self.index.bytes()
+    }
+
+    // A line's trigrams are charged at most one index entry each
+    fn entry_bytes(&self, entry: &LogEntry) -> usize {
+        let rendered = match entry.source {
+            SourceId::APP => entry.message.len(),
+            id => self.source_name(id).len() + 1 + entry.message.len(),
+        };
+        std::mem::size_of::<LogEntry>()
+            + entry.message.len()
+            + rendered.saturating_sub(2) * std::mem::size_of::<u64>()
+    }
+
+    pub fn source(&mut self, name: &str) -> SourceId {
+        self.sources.intern(name)
+    }
+
+    pub fn source_name(&self, id: SourceId) -> &str {
+        self.sources.name(id)
+    }
+
+    fn first_seq(&self) -> u64 {
+        self.next_seq - self.entries.len() as u64
+    }
+
+    pub fn push(&mut self, entry: LogEntry) {
+        // a storm of one error stays one line with a count, rather than
+        // pushing everything before it out of the buffer
+        let dedup = self.dedup;
+        if let Some(prev) = self.entries.back_mut() {
+            if dedup.collapses(prev, &entry) {
+                prev.repeats += 1;
+                prev.last = entry.at;
+                return;
+            }
+        }
+        self.index.insert(self.next_seq, &self.render(&entry));
+        self.next_seq += 1;
+        self.bytes += self.entry_bytes(&entry);
+        self.entries.push_back(entry);
+        // always keep the newest line, even if it alone exceeds the budget
+        while self.entries.len() > 1
+            && (self.entries.len() > self.capacity || (self.budget > 0 && self.bytes > self.budget))
+        {
+            if let Some(old) = self.entries.pop_front() {
+                self.bytes -= self.entry_bytes(&old);
+            }
+        }
+        self.index.evict_below(self.first_seq(), self.entries.len());
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes = 0;
+        self.index.clear(self.next_seq);
+    }
+
+    // Case-insensitive substring search over the rendered lines, oldest first.
+    // Queries of 3+ bytes go through the trigram index; shorter ones scan.
+    pub fn search(&self, query: &str) -> Vec<&LogEntry> {
+        let needle = query.to_ascii_lowercase();
+        let matches = |e: &&LogEntry| self.render(e).to_ascii_lowercase().contains(&needle);
+        match self.index.candidates(query) {
+            Some(seqs) => {
+                let first = self.first_seq();
+                seqs.into_iter()
+                    .filter_map(|seq| self.entries.get((seq - first) as usize))
+                    .filter(matches)
+                    .collect()
+            }
+            None => self.entries.iter().filter(matches).collect(),
+        }
+    }
+
+    // Sources with lines still retained, app excluded, in the order they first
+    // appeared; the log pane's number keys pick from this list
+    pub fn active_sources(&self) -> Vec<SourceId> {
+        let mut seen: Vec<SourceId> = self
+            .entries
+            .iter()
+            .map(|e| e.source)
+            .filter(|&id| id != SourceId::APP)
+            .collect();
+        seen.sort_unstable_by_key(|id| id.0);
+        seen.dedup();
+        seen
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &LogEntry> + ExactSizeIterator {
+        self.entries.iter()
+    }
+
+    // Display form: app messages are shown bare, everything else gets its source prefix
+    pub fn render(&self, entry: &LogEntry) -> String {
+        if entry.source == SourceId::APP {
+            entry.message.to_string()
+        } else {
+            format!("{} {}", self.source_name(entry.source), entry.message)
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use humantime::{format_duration, parse_duration};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+// How a one-shot window's `at` may be written, local time
+const AT_FORMATS: [&str; 4] = [
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+];
+
+// One `[[maintenance]]` table: planned work during which alerts are held.
+// It starts on a `cron` schedule or once, `at` a local time, and lasts
+// `duration`.
+#[derive(Clone, Debug)]
+pub struct Window {
+    pub name: String,
+    pub when: When,
+    pub duration: Duration,
+}
+
+#[derive(Clone, Debug)]
+pub enum When {
+    Cron(Cron),
+    Once(NaiveDateTime),
+}
+
+// The window as written in config.toml
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct Written {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cron: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    at: Option<String>,
+    duration: String,
+}
+
+impl<'de> Deserialize<'de> for Window {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let Written {
+            name,
+            cron,
+            at,
+            duration,
+        } = Written::deserialize(d)?;
+        let when = match (cron, at) {
+            (Some(cron), None) => When::Cron(cron.parse().map_err(de::Error::custom)?),
+            (None, Some(at)) => When::Once(
+                AT_FORMATS
+                    .iter()
+                    .find_map(|f| NaiveDateTime::parse_from_str(at.trim(), f).ok())
+                    .ok_or_else(|| {
+                        de::Error::custom(format!("bad time `{at}`; e.g. 2026-10-20 22:00"))
+                    })?,
+            ),
+            _ => {
+                return Err(de::Error::custom(format!(
+                    "maintenance `{name}` needs one of `cron` or `at`"
+                )))
+            }
+        };
+        let duration = parse_duration(&duration)
+            .ok()
+            .filter(|d| *d >= Duration::from_secs(60))
+            .ok_or_else(|| {
+                de::Error::custom(format!(
+                    "bad duration `{duration}`; a minute or more, e.g. 30m"
+                ))
+            })?;
+        Ok(Self {
+            name,
+            when,
+            duration,
+        })
+    }
+}
+
+impl Serialize for Window {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let (cron, at) = match &self.when {
+            When::Cron(cron) => (Some(cron.spec.clone()), None),
+            When::Once(at) => (None, Some(at.format(AT_FORMATS[1]).to_string())),
+        };
+        Written {
+            name: self.name.clone(),
+            cron,
+            at,
+            duration: format_duration(self.duration).to_string(),
+        }
+        .serialize(s)
+    }
+}
+
+impl Window {
+    // When the occurrence covering `now` ends, if one does
+    fn end_after(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let length = chrono::Duration::from_std(self.duration).ok()?;
+        match &self.when {
+            When::Once(at) => (*at <= now && now < *at + length).then(|| *at + length),
+            // the latest start first, so the first hit ends last
+            When::Cron(cron) => {
+                let minute = now.with_second(0)?.with_nanosecond(0)?;
+                (0..=length.num_minutes())
+                    .map(|back| minute - chrono::Duration::minutes(back))
+                    .filter(|start| now < *start + length)
+                    .find(|start| cron.matches(*start))
+                    .map(|start| start + length)
+            }
+        }
+    }
+}
+
+// Five fields, as in crontab: minute, hour, day of month, month, day of week
+// (0 or 7 for Sunday). Each takes `*`, a number, a range `1-5`, a step
+// `*/15` or `0-30/10`, or a list of those. When both days are restricted,
+// either one matching will do.
+#[derive(Clone, Debug)]
+pub struct Cron {
+    spec: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl std::str::FromStr for Cron {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = spec.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "cron `{spec}` needs five fields: minute hour day month weekday"
+            ));
+        };
+        let field = |text: &str, min: u32, max: u32| {
+            bits(text, min, max).ok_or_else(|| format!("bad cron field `{text}` in `{spec}`"))
+        };
+        let mut weekdays = field(weekday, 0, 7)?;
+        // 7 is Sunday too
+        if weekdays & 1 << 7 != 0 {
+            weekdays |= 1;
+        }
+        Ok(Self {
+            spec: spec.trim().to_string(),
+            minutes: field(minute, 0, 59)?,
+            hours: field(hour, 0, 23)?,
+            days: field(day, 1, 31)?,
+            months: field(month, 1, 12)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+}
+
+impl Cron {
+    fn matches(&self, t: NaiveDateTime) -> bool {
+        let has = |set: u64, n: u32| set & 1 << n != 0;
+        let day = has(self.days, t.day());
+        let weekday = has(self.weekdays, t.weekday().num_days_from_sunday());
+        let date = match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        };
+        has(self.minutes, t.minute())
+            && has(self.hours, t.hour())
+            && has(self.months, t.month())
+            && date
+    }
+}
+
+// A field's values as a bit set, or None if it doesn't parse or runs
+// outside `min..=max`
+fn bits(field: &str, min: u32, max: u32) -> Option<u64> {
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0)?),
+            None => (part, 1),
+        };
+        let (from, to) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((a, b)) => (a.parse().ok()?, b.parse().ok()?),
+                None => {
+                    let n = range.parse().ok()?;
+                    // `5/15` runs from 5 to the end
+                    (n, if part.contains('/') { max } else { n })
+                }
+            },
+        };
+        if from < min || to > max || from > to {
+            return None;
+        }
+        for n in (from..=to).step_by(step as usize) {
+            set |= 1 << n;
+        }
+    }
+    Some(set)
+}
+
+// The windows, and the one in force
+#[derive(Default)]
+pub struct Maintenance {
+    windows: Vec<Window>,
+    // name, and when it ends
+    pub current: Option<(String, NaiveDateTime)>,
+    // the minute last looked at, so schedules are searched once a minute
+    checked: Option<NaiveDateTime>,
+}
+
+pub enum Change {
+    Began(String, NaiveDateTime),
+    Ended(String),
+}
+
+impl Maintenance {
+    pub fn new(windows: Vec<Window>) -> Self {
+        Self {
+            windows,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    // For the startup line, e.g. `backup (0 2 * * * for 30m)`
+    pub fn describe(&self) -> String {
+        self.windows
+            .iter()
+            .map(|w| {
+                let when = match &w.when {
+                    When::Cron(cron) => cron.spec.clone(),
+                    When::Once(at) => at.format(AT_FORMATS[0]).to_string(),
+                };
+                format!("{} ({when} for {})", w.name, format_duration(w.duration))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    // Whether a window began or ended by `now`, local time. Of overlapping
+    // windows, the one ending last is in force.
+    pub fn check(&mut self, now: NaiveDateTime) -> Option<Change> {
+        if let Some((name, end)) = &self.current {
+            if now < *end {
+                return None;
+            }
+            let name = name.clone();
+            self.current = None;
+            self.checked = None;
+            return Some(Change::Ended(name));
+        }
+        let minute = now.with_second(0)?.with_nanosecond(0)?;
+        if self.checked == Some(minute) {
+            return None;
+        }
+        self.checked = Some(minute);
+        let (name, end) = self
+            .windows
+            .iter()
+            .filter_map(|w| w.end_after(now).map(|end| (w.name.clone(), end)))
+            .max_by_key(|(_, end)| *end)?;
+        self.current = Some((name.clone(), end));
+        Some(Change::Began(name, end))
+    }
+}
@@ -0,0 +1,198 @@
+use std::io::{self, Write};
+
+use clap_mangen::{
+    roff::{bold, roman, Roff},
+    Man,
+};
+
+// Keep these in step with the Controls and Commands sections of the README
+const KEYS: &[(&str, &str)] = &[
+    ("1 .. 5", "Switch mode: AI observability, robotics, cloud, data forensics, sandbox."),
+    (
+        "Alt+1 .. Alt+5",
+        "Switch mode from anywhere, including while typing a command (Ctrl with --mode-modifier ctrl).",
+    ),
+    (":", "Enter command mode."),
+    ("Esc", "Leave command mode without running anything."),
+    ("Enter", "Run the command."),
+    ("Left, Right, Home, End, Backspace, Del", "Edit the command line."),
+    (
+        "Up, Down",
+        "In command mode, bring back earlier command lines; the last 500 are kept across sessions.",
+    ),
+    (
+        "Arrow keys",
+        "Pan around the dashboard when the terminal is smaller than 80x24 (outside command mode).",
+    ),
+    (
+        "Tab, Shift+Tab",
+        "Move the focus between banner, metrics, system, logs, and command bar (also Ctrl+arrows); Esc unfocuses. A focused banner switches modes with Left/Right; in a focused metrics panel Up/Down pick a row and Enter expands it with a sparkline, recent min/max, thresholds, and its source.",
+    ),
+    ("e", "Show or hide metric explanations (--educator)."),
+    ("d", "Show or hide each metric's change over the last minute (--deltas)."),
+    (
+        "t",
+        "Time travel: scrub back through retained history with Left/Right (1s), PgUp/PgDn (1m), Home, or by dragging the timeline; End or Esc returns to live.",
+    ),
+    (
+        "l",
+        "Focus the log pane; 1 .. 9 then show only one source, 0 all of them, Up/Down pick a JSON or multi-line entry, Enter opens it pretty-printed, and Esc unfocuses.",
+    ),
+    (
+        "h",
+        "Toggle the multi-host view (--host) with each host's uptime and outage strip; arrows pick a host, Enter opens it, Esc goes back.",
+    ),
+    ("m", "Release or restore mouse capture, for the terminal's own text selection."),
+    (
+        "[control.keys]",
+        "In robotics mode, run the command bound to the key; a setpoint asks y/n (Enter/Esc) first.",
+    ),
+    ("q, Ctrl+C", "Quit (outside command mode)."),
+    ("Ctrl+Z", "Suspend to the shell; fg resumes with a full redraw (Unix)."),
+];
+
+const COMMANDS: &[(&str, &str)] = &[
+    ("help, ?", "Show help in the log panel."),
+    (
+        "COMMAND; COMMAND ...",
+        "Run several commands in order from one line, which Up brings back whole; \\; is a literal semicolon.",
+    ),
+    ("mode", "Show the current mode."),
+    (
+        "set mode ai|robotics|cloud|forensics|sandbox",
+        "Switch mode.",
+    ),
+    ("clear", "Clear the logs."),
+    (
+        "grep TEXT, search TEXT",
+        "Show only log lines containing TEXT (case-insensitive); grep alone clears the filter.",
+    ),
+    ("diag", "Toggle the diagnostics overlay."),
+    ("mouse [on|off]", "Toggle or set mouse capture."),
+    (
+        "source",
+        "List network-backed sources, their connection state, and their uptime this session.",
+    ),
+    (
+        "source retry NAME",
+        "Reconnect a source now instead of waiting out its backoff.",
+    ),
+    (
+        "tail [PATH]",
+        "Stream lines appended to the file at PATH into the logs, under the file's name, in place of the synthetic ones; tail alone lists followed files.",
+    ),
+    (
+        "serial open PATH [BAUD]",
+        "Stream a serial port's lines (115200 baud unless given) into the logs, under the device's name, in place of the synthetic ones; key=value numbers fill the robotics rows they're named after. serial alone shows the port, serial close closes it.",
+    ),
+    (
+        "integrity",
+        "Show the paths --integrity hashes, how many files that is, and how many changed.",
+    ),
+    (
+        "profile [demo|ops|dev]",
+        "Show or switch the active profile: start mode, tick rate, and whether synthetic logs run.",
+    ),
+    (
+        "pin [SCOPE:]METRIC",
+        "Pin a metric to the favorites strip, shown in every mode; SCOPE is a mode, system, derived, or a --host name. pin alone lists pins.",
+    ),
+    ("unpin METRIC, unpin all", "Remove pins."),
+    (
+        "mark NAME",
+        "Mark this moment: highlighted in the logs, drawn on the charts, and usable by compare.",
+    ),
+    (
+        "push-log [LEVEL] TEXT",
+        "Add TEXT to the logs under `user`; LEVEL is debug, info, warn, or error (default info).",
+    ),
+    (
+        "compare METRIC WINDOW WINDOW",
+        "Overlay two windows of a metric and log the delta statistics. A window is LEN, LEN@-AGO, or LEN@MARK.",
+    ),
+    (
+        "report PATH",
+        "Write a Markdown session summary: duration, metric min/avg/max, source availability, alerts, anomalies, and recent log lines.",
+    ),
+    (
+        "health",
+        "Show or hide the breakdown of the health index defined by [health] in config.toml.",
+    ),
+    (
+        "derived",
+        "List the [derived] metrics from config.toml: each value, its formula, and any metric it has no recent sample of.",
+    ),
+    (
+        "requests",
+        "Show or hide the requests seen by --llm-proxy: latency, status, and tokens, with the sampled prompt and response behind Enter.",
+    ),
+    (
+        "estop",
+        "Send an emergency stop to the [control] target at once.",
+    ),
+    (
+        "set SETPOINT VALUE",
+        "Send a setpoint to the [control] target after a y/n prompt; values outside its range are refused.",
+    ),
+    (
+        "control",
+        "Show the [control] target, its setpoints and ranges, and the key bindings.",
+    ),
+    (
+        "watch METRIC [as NAME] [warn N] [crit N]",
+        "Add or update a watchlist item; crit below warn means lower is worse. watch alone lists them.",
+    ),
+    ("watch sort worst|added", "Order the watchlist worst first or as added."),
+    ("unwatch METRIC|NAME, unwatch all", "Remove watchlist items."),
+    (
+        "alerts",
+        "List firing alerts: watched metrics past a threshold and the health index under alert-below.",
+    ),
+    (
+        "ack RULE, ack all",
+        "Acknowledge a firing alert so it stops repeating until it clears.",
+    ),
+    (
+        "silence RULE|LABEL=VALUE DURATION",
+        "Keep one rule, or every alert with scope= or severity=, quiet for DURATION. silence alone lists them.",
+    ),
+    ("unsilence N, unsilence all", "Cancel silences."),
+    (
+        "quantile [p50|p95|p99]",
+        "Read histogram metrics at this quantile: the focused host in the host view, else the mode's panel.",
+    ),
+    (
+        "view save|load|delete NAME",
+        "Keep the mode, search, log source filter, picked metric row, compare chart, and time-travel moment under NAME, or bring them back; view alone lists them.",
+    ),
+    (
+        "paths",
+        "Show where config, themes, layouts, history, saved views, and recordings live.",
+    ),
+];
+
+fn list_section(w: &mut dyn Write, title: &str, items: &[(&str, &str)]) -> io::Result<()> {
+    let mut roff = Roff::new();
+    roff.control("SH", [title]);
+    for (term, desc) in items {
+        roff.control("TP", []);
+        roff.text([bold(*term)]);
+        roff.text([roman(*desc)]);
+    }
+    roff.to_writer(w)
+}
+
+// clap's generated sections plus the interactive keys and commands, which
+// live outside the argument parser
+pub fn render(cmd: clap::Command, w: &mut dyn Write) -> io::Result<()> {
+    let man = Man::new(cmd);
+    man.render_title(w)?;
+    man.render_name_section(w)?;
+    man.render_synopsis_section(w)?;
+    man.render_description_section(w)?;
+    man.render_options_section(w)?;
+    man.render_subcommands_section(w)?;
+    list_section(w, "KEYBINDINGS", KEYS)?;
+    list_section(w, "COMMANDS", COMMANDS)?;
+    man.render_version_section(w)
+}
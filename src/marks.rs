@@ -0,0 +1,60 @@
+use std::{
+    io::{self, BufRead},
+    net::{SocketAddr, TcpListener},
+    sync::mpsc::Sender,
+    time::Duration,
+};
+
+use crate::http::{self, Reply, Request};
+
+// Longest label body read from a request
+const MAX_BODY: usize = 4096;
+
+// `--mark-listen`: a tiny HTTP endpoint so deploy scripts can drop markers,
+// e.g. `curl -d 'deploy v1.4' http://127.0.0.1:7171/mark`. Labels go to `tx`
+// and the UI picks them up on its next tick. --http serves /mark as well.
+pub fn listen(addr: SocketAddr, tx: Sender<String>) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    http::serve(
+        listener,
+        Duration::from_secs(2),
+        move |mut stream, mut reader| {
+            let request = match Request::read(&mut reader)? {
+                Ok(request) => request,
+                Err(reply) => return http::reply_text(&mut stream, reply),
+            };
+            let reply = match request.path() {
+                "/mark" => mark(&request, &mut reader, &tx)?,
+                _ => ("404 Not Found", "only /mark is served".to_string()),
+            };
+            http::reply_text(&mut stream, reply)
+        },
+    );
+    Ok(())
+}
+
+// `POST /mark` with the label as the body or as `?label=...`
+pub fn mark(
+    request: &Request,
+    reader: &mut impl BufRead,
+    tx: &Sender<String>,
+) -> io::Result<Reply> {
+    if request.method != "POST" {
+        return Ok(("405 Method Not Allowed", "use POST".to_string()));
+    }
+    let Some(body) = request.body(reader, MAX_BODY)? else {
+        return Ok(("413 Payload Too Large", format!("over {MAX_BODY} bytes")));
+    };
+    let label = request
+        .param("label")
+        .unwrap_or_else(|| String::from_utf8_lossy(&body).into_owned());
+    // first line only, so a stray payload can't spread across the log
+    let label = label.lines().next().unwrap_or("").trim().to_string();
+    if label.is_empty() {
+        return Ok(("400 Bad Request", "empty label".to_string()));
+    }
+    let reply = format!("marked {label}");
+    // the app is gone if this fails; nobody is left to tell
+    let _ = tx.send(label);
+    Ok(("200 OK", reply))
+}
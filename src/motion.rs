@@ -0,0 +1,29 @@
+use std::{cell::RefCell, collections::HashMap};
+
+// Smallest move of a bar (as a share of its full length) worth redrawing
+const MATERIAL_CHANGE: f32 = 0.05;
+
+// --reduced-motion: metrics hold their last shown value until the underlying
+// one moves materially, so the dashboard doesn't breathe constantly. Draws
+// only get `&AppState`, hence the RefCell.
+#[derive(Default)]
+pub struct Steady {
+    shown: RefCell<HashMap<&'static str, (f32, f32)>>,
+}
+
+impl Steady {
+    // (value, bar fill) to display for `label`
+    pub fn hold(&self, label: &'static str, value: f32, norm: f32) -> (f32, f32) {
+        let mut shown = self.shown.borrow_mut();
+        let held = shown.entry(label).or_insert((value, norm));
+        if (norm - held.1).abs() >= MATERIAL_CHANGE {
+            *held = (value, norm);
+        }
+        *held
+    }
+
+    // Everything shows its live value on the next draw (e.g. after a mode switch)
+    pub fn reset(&self) {
+        self.shown.borrow_mut().clear();
+    }
+}
@@ -0,0 +1,439 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    io,
+    sync::mpsc::{self, Receiver, Sender},
+    time::{Duration, Instant},
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    auth::{self, AuthSpec},
+    datasource::{DataSource, Sample, Value, METRICS},
+    ingest::{IngestQueue, SourceSender},
+    logs::Level,
+    net::{authority, Authority, Remote, Stream},
+    parse::parse_line,
+    push::row,
+    reconnect::{Connector, Links},
+};
+
+// What the subscriber's link and the logged payloads are shown under
+pub const NAME: &str = "mqtt";
+const QUEUE: usize = 1024;
+// Connecting, and the broker's answers to CONNECT and SUBSCRIBE
+const TIMEOUT: Duration = Duration::from_secs(10);
+// The keep-alive asked of the broker; quiet for half of it and it's pinged
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+
+// `[mqtt]` in config.toml: a broker, the topics whose payloads are logged,
+// and which topics fill which row of robotics mode's panel. Checked as it's
+// read, like `[prometheus]`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct MqttSpec {
+    #[serde(deserialize_with = "url")]
+    pub url: String,
+    // topic filters, wildcards and all
+    #[serde(default, deserialize_with = "filters")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<String>,
+    // row label → topic
+    #[serde(default, deserialize_with = "metrics")]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, String>,
+    // `[mqtt.auth]`: a user name and password for CONNECT, and certificates
+    // for mqtts
+    #[serde(default, deserialize_with = "auth::mqtt")]
+    #[serde(skip_serializing_if = "AuthSpec::is_empty")]
+    pub auth: AuthSpec,
+}
+
+fn url<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    let url = String::deserialize(d)?;
+    let (broker, secure) = broker(&url).map_err(de::Error::custom)?;
+    Remote::new(broker, secure, &AuthSpec::default()).map_err(de::Error::custom)?;
+    Ok(url)
+}
+
+fn filters<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<String>, D::Error> {
+    let topics = Vec::<String>::deserialize(d)?;
+    for topic in &topics {
+        filter(topic).map_err(de::Error::custom)?;
+    }
+    Ok(topics)
+}
+
+fn metrics<'de, D: Deserializer<'de>>(d: D) -> Result<BTreeMap<String, String>, D::Error> {
+    let metrics = BTreeMap::<String, String>::deserialize(d)?;
+    for (label, topic) in &metrics {
+        if !METRICS.contains(&label.as_str()) {
+            return Err(de::Error::custom(format!(
+                "no row `{label}` to map; rows are {}",
+                METRICS.join(", ")
+            )));
+        }
+        if topic.is_empty() || topic.contains(['+', '#']) {
+            return Err(de::Error::custom(format!(
+                "`{label}` needs one topic, without wildcards"
+            )));
+        }
+    }
+    Ok(metrics)
+}
+
+// `#` may only be a whole last level, and `+` a whole level
+fn filter(topic: &str) -> Result<(), String> {
+    let levels: Vec<&str> = topic.split('/').collect();
+    let bad = topic.is_empty()
+        || levels.iter().enumerate().any(|(i, level)| {
+            (level.contains('#') && (*level != "#" || i + 1 < levels.len()))
+                || (level.contains('+') && *level != "+")
+        });
+    match bad {
+        true => Err(format!("`{topic}` isn't a valid topic filter")),
+        false => Ok(()),
+    }
+}
+
+// The broker out of `mqtt://HOST[:PORT]`, or `mqtts://` for TLS
+fn broker(url: &str) -> Result<(Authority, bool), String> {
+    let (secure, port) = match url {
+        u if u.starts_with("mqtt://") => (false, 1883),
+        u if u.starts_with("mqtts://") => (true, 8883),
+        u => return Err(format!("expected an mqtt:// or mqtts:// URL, got `{u}`")),
+    };
+    let (broker, path) = authority(url, port)?;
+    if !path.trim_start_matches('/').is_empty() {
+        return Err(format!(
+            "`{url}` names a broker only; topics go in `topics` and `metrics`"
+        ));
+    }
+    Ok((broker, secure))
+}
+
+// A broker connection with reads and writes that time out after `timeout`
+fn connect(broker: &Remote, timeout: Duration) -> io::Result<Stream> {
+    let stream = broker.dial(timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    Ok(stream)
+}
+
+// The robotics panel feed: numbers published on mapped topics since the
+// last tick. Text payloads go straight to the logs.
+pub struct Telemetry {
+    url: String,
+    rx: Receiver<Vec<Sample>>,
+}
+
+impl Telemetry {
+    // Checks the spec, then subscribes on its own reconnecting link
+    pub fn start(spec: &MqttSpec, links: &Links, ingest: &IngestQueue) -> Result<Self, String> {
+        let (broker, secure) = broker(&spec.url)?;
+        let broker = Remote::new(broker, secure, &spec.auth)?;
+        let mut filters = spec.topics.clone();
+        for topic in spec.metrics.values() {
+            if !filters.contains(topic) {
+                filters.push(topic.clone());
+            }
+        }
+        if filters.is_empty() {
+            return Err("nothing to subscribe to; list `topics` or `metrics`".to_string());
+        }
+        let (samples, rx) = mpsc::channel();
+        let subscriber = Subscriber {
+            broker,
+            filters,
+            rows: spec
+                .metrics
+                .iter()
+                .map(|(label, topic)| (topic.clone(), label.clone()))
+                .collect(),
+            auth: spec.auth.clone(),
+            samples,
+            mentioned: HashSet::new(),
+        };
+        links.spawn(NAME, subscriber, ingest.sender(NAME, QUEUE));
+        Ok(Self {
+            url: spec.url.clone(),
+            rx,
+        })
+    }
+}
+
+impl DataSource for Telemetry {
+    fn poll(&mut self) -> Vec<Sample> {
+        self.rx.try_iter().flatten().collect()
+    }
+
+    fn describe(&self) -> String {
+        format!("MQTT {}", self.url)
+    }
+}
+
+struct Subscriber {
+    broker: Remote,
+    filters: Vec<String>,
+    // topic → row label
+    rows: BTreeMap<String, String>,
+    // its user name and password, if any
+    auth: AuthSpec,
+    samples: Sender<Vec<Sample>>,
+    // topics with numbers that fill no row, mentioned once
+    mentioned: HashSet<String>,
+}
+
+impl Connector for Subscriber {
+    type Conn = Stream;
+
+    fn connect(&mut self) -> io::Result<Stream> {
+        let mut stream = connect(&self.broker, TIMEOUT)?;
+        // an id of its own, so `[control]` connecting doesn't take this
+        // session over
+        let id = format!("ai-intui-{}-telemetry", std::process::id());
+        packet::connect(&mut stream, &id, KEEP_ALIVE, &self.auth)?;
+        packet::subscribe(&mut stream, &self.filters)?;
+        Ok(stream)
+    }
+
+    fn run(&mut self, mut stream: Stream, tx: &SourceSender) -> io::Result<()> {
+        stream.set_read_timeout(Some(KEEP_ALIVE / 4))?;
+        let (mut sent, mut pinged) = (Instant::now(), None::<Instant>);
+        loop {
+            // a broker drops clients that say nothing for the keep-alive,
+            // however much it's sending them
+            if sent.elapsed() >= KEEP_ALIVE / 2 {
+                packet::ping(&mut stream)?;
+                sent = Instant::now();
+                pinged.get_or_insert(sent);
+            }
+            if pinged.is_some_and(|at| at.elapsed() >= KEEP_ALIVE / 2) {
+                return Err(io::Error::other("the broker stopped answering pings"));
+            }
+            let (kind, flags, body) = match packet::read(&mut stream) {
+                Ok(packet) => packet,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    continue
+                }
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            match kind {
+                packet::PINGRESP => pinged = None,
+                packet::PUBLISH => {
+                    let Some((topic, payload)) = packet::message(flags, &body) else {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "malformed PUBLISH from the broker",
+                        ));
+                    };
+                    self.publish(&topic, payload, tx);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Subscriber {
+    // A number fills its topic's row; anything else is logged
+    fn publish(&mut self, topic: &str, payload: &[u8], tx: &SourceSender) {
+        let text = String::from_utf8_lossy(payload);
+        let text = text.trim();
+        let Some(value) = text.parse::<f64>().ok().filter(|v| v.is_finite()) else {
+            if let Some(line) = parse_line(text) {
+                tx.send(line.level, format!("{topic}: {}", line.message));
+            }
+            return;
+        };
+        let value = Value::Gauge(value);
+        // mapped, or named after a row by its last level: `robot/queue_depth`
+        let last = topic.rsplit('/').next().unwrap_or(topic);
+        let metric = self.rows.get(topic).cloned().or_else(|| row(last, &value));
+        match metric {
+            Some(metric) => {
+                // the app is gone if this fails; the link goes with it
+                let _ = self.samples.send(vec![Sample { metric, value }]);
+            }
+            None if self.mentioned.insert(topic.to_string()) => tx.send(
+                Level::Warn,
+                format!("{topic}: numbers here fill no row; map it under [mqtt.metrics]"),
+            ),
+            None => {}
+        }
+    }
+}
+
+// Just enough MQTT 3.1.1 to publish a message at QoS 1 and hear the broker
+// take it, and to subscribe at QoS 0 and read what's published
+pub mod packet {
+    use std::{
+        io::{self, Read, Write},
+        time::Duration,
+    };
+
+    use crate::auth::AuthSpec;
+
+    pub const PUBLISH: u8 = 3;
+    pub const PINGRESP: u8 = 13;
+
+    // Remaining length, 7 bits a byte, low first
+    fn length(mut n: usize, out: &mut Vec<u8>) {
+        loop {
+            let byte = (n % 128) as u8;
+            n /= 128;
+            out.push(if n > 0 { byte | 0x80 } else { byte });
+            if n == 0 {
+                break;
+            }
+        }
+    }
+
+    fn string(s: &[u8], out: &mut Vec<u8>) {
+        out.extend_from_slice(&(s.len() as u16).to_be_bytes());
+        out.extend_from_slice(s);
+    }
+
+    fn packet(kind: u8, body: &[u8]) -> Vec<u8> {
+        let mut out = vec![kind];
+        length(body.len(), &mut out);
+        out.extend_from_slice(body);
+        out
+    }
+
+    // (packet type, flags, body) of the next packet
+    pub fn read(stream: &mut impl Read) -> io::Result<(u8, u8, Vec<u8>)> {
+        let mut head = [0; 1];
+        stream.read_exact(&mut head)?;
+        let mut len = 0usize;
+        for shift in (0..4).map(|i| i * 7) {
+            let mut byte = [0; 1];
+            stream.read_exact(&mut byte)?;
+            len |= usize::from(byte[0] & 0x7f) << shift;
+            if byte[0] & 0x80 == 0 {
+                let mut body = vec![0; len];
+                stream.read_exact(&mut body)?;
+                return Ok((head[0] >> 4, head[0] & 0x0f, body));
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "malformed packet length from the broker",
+        ))
+    }
+
+    fn unexpected(what: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected {what} from the broker"),
+        )
+    }
+
+    // CONNECT with a clean session, and `auth`'s user name and password if
+    // it has them; then the broker's CONNACK
+    pub fn connect(
+        stream: &mut (impl Read + Write),
+        id: &str,
+        keep_alive: Duration,
+        auth: &AuthSpec,
+    ) -> io::Result<()> {
+        let mut flags = 0x02;
+        if auth.username.is_some() {
+            flags |= 0x80;
+        }
+        if auth.password.is_some() {
+            flags |= 0x40;
+        }
+        let mut connect = Vec::new();
+        string(b"MQTT", &mut connect);
+        connect.extend_from_slice(&[4, flags]);
+        connect.extend_from_slice(&(keep_alive.as_secs() as u16).to_be_bytes());
+        string(id.as_bytes(), &mut connect);
+        for field in [&auth.username, &auth.password].into_iter().flatten() {
+            string(field.as_bytes(), &mut connect);
+        }
+        stream.write_all(&packet(0x10, &connect))?;
+        match read(stream)? {
+            (2, _, body) if body.get(1) == Some(&0) => Ok(()),
+            (2, _, body) => {
+                let why = match body.get(1) {
+                    Some(1) => "unsupported protocol version",
+                    Some(2) => "client id rejected",
+                    Some(3) => "server unavailable",
+                    Some(4) => "bad user name or password",
+                    Some(5) => "not authorized",
+                    _ => "refused",
+                };
+                Err(io::Error::other(format!("broker refused: {why}")))
+            }
+            _ => Err(unexpected("CONNACK")),
+        }
+    }
+
+    pub fn publish(
+        stream: &mut (impl Read + Write),
+        topic: &str,
+        payload: &[u8],
+    ) -> io::Result<()> {
+        // a client id of our own, 60s keep-alive
+        let id = format!("ai-intui-{}", std::process::id());
+        connect(stream, &id, Duration::from_secs(60), &AuthSpec::default())?;
+
+        // PUBLISH at QoS 1, packet id 1
+        let mut publish = Vec::new();
+        string(topic.as_bytes(), &mut publish);
+        publish.extend_from_slice(&[0, 1]);
+        publish.extend_from_slice(payload);
+        stream.write_all(&packet(0x32, &publish))?;
+        match read(stream)? {
+            (4, _, id) if id == [0, 1] => {}
+            _ => return Err(unexpected("PUBACK")),
+        }
+        stream.write_all(&packet(0xe0, &[]))
+    }
+
+    // SUBSCRIBE to every filter at QoS 0, packet id 1; a filter the broker
+    // refuses fails the lot
+    pub fn subscribe(stream: &mut (impl Read + Write), filters: &[String]) -> io::Result<()> {
+        let mut subscribe = vec![0, 1];
+        for filter in filters {
+            string(filter.as_bytes(), &mut subscribe);
+            subscribe.push(0);
+        }
+        stream.write_all(&packet(0x82, &subscribe))?;
+        match read(stream)? {
+            (9, _, body) if body.starts_with(&[0, 1]) => {
+                match body[2..].iter().position(|code| *code == 0x80) {
+                    Some(i) => Err(io::Error::other(format!(
+                        "broker refused the subscription to `{}`",
+                        filters.get(i).map_or("", String::as_str)
+                    ))),
+                    None => Ok(()),
+                }
+            }
+            _ => Err(unexpected("SUBACK")),
+        }
+    }
+
+    pub fn ping(stream: &mut impl Write) -> io::Result<()> {
+        stream.write_all(&packet(0xc0, &[]))
+    }
+
+    // (topic, payload) of a PUBLISH body; at QoS 1 or 2 a packet id comes
+    // between them
+    pub fn message(flags: u8, body: &[u8]) -> Option<(String, &[u8])> {
+        let len = usize::from(u16::from_be_bytes([*body.first()?, *body.get(1)?]));
+        let topic = std::str::from_utf8(body.get(2..2 + len)?).ok()?;
+        let skip = match (flags >> 1) & 0x03 {
+            0 => 0,
+            _ => 2,
+        };
+        Some((topic.to_string(), body.get(2 + len + skip..)?))
+    }
+}
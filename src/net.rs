@@ -0,0 +1,158 @@
+use std::{
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use crate::{auth::AuthSpec, tls::Tls};
+
+// Where a URL points: `host[:port]` out of `scheme://host[:port]/path`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Authority {
+    // host[:port] as written, for the Host header
+    pub written: String,
+    // without an IPv6 address's brackets
+    pub host: String,
+    pub port: u16,
+}
+
+impl Authority {
+    // A connection to it, given up on after `timeout`; reads and writes are
+    // left to the caller to time out
+    pub fn dial(&self, timeout: Duration) -> io::Result<TcpStream> {
+        dial(&self.host, self.port, timeout)
+    }
+}
+
+// A connection to `host` on `port`, given up on after `timeout`
+pub fn dial(host: &str, port: u16, timeout: Duration) -> io::Result<TcpStream> {
+    let addr = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::other(format!("{host} doesn't resolve")))?;
+    TcpStream::connect_timeout(&addr, timeout)
+}
+
+// The authority of `url`, with `default_port` when it names none, and the
+// path after it (empty when there isn't one). A `scheme://` is skipped, so a
+// bare `HOST:PORT` works too.
+pub fn authority(url: &str, default_port: u16) -> Result<(Authority, &str), String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let (written, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, ""),
+    };
+    let (host, port) =
+        split_port(written).map_err(|port| format!("bad port `{port}` in `{url}`"))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return Err(format!("no host in `{url}`"));
+    }
+    Ok((
+        Authority {
+            written: written.to_string(),
+            host: host.to_string(),
+            port: port.unwrap_or(default_port),
+        },
+        path,
+    ))
+}
+
+// `host[:port]` split at the last colon, unless it's inside [ipv6]; the host
+// keeps its brackets. Err is the port that isn't one.
+pub fn split_port(authority: &str) -> Result<(&str, Option<u16>), &str> {
+    match authority.rsplit_once(':') {
+        Some((host, port)) if !port.ends_with(']') => match port.parse() {
+            Ok(port) => Ok((host, Some(port))),
+            Err(_) => Err(port),
+        },
+        _ => Ok((authority, None)),
+    }
+}
+
+// A source's server: where it is, and TLS when its URL's scheme asks for it
+pub struct Remote {
+    pub authority: Authority,
+    tls: Option<Tls>,
+}
+
+impl Remote {
+    // `secure` for https://, wss://, and mqtts://; the files `auth` names are
+    // for those alone
+    pub fn new(authority: Authority, secure: bool, auth: &AuthSpec) -> Result<Self, String> {
+        if !secure && auth.wants_tls() {
+            return Err(
+                "`cert`, `key`, and `ca` are for TLS; use an https://, wss://, or mqtts:// URL"
+                    .to_string(),
+            );
+        }
+        let tls = match secure {
+            true => Some(Tls::new(auth)?),
+            false => None,
+        };
+        Ok(Self { authority, tls })
+    }
+
+    // A connection, its TLS handshake done, given up on after `timeout`;
+    // reads and writes are left to the caller to time out
+    pub fn dial(&self, timeout: Duration) -> io::Result<Stream> {
+        let tcp = self.authority.dial(timeout)?;
+        match &self.tls {
+            Some(tls) => tls.wrap(&self.authority.host, tcp, timeout),
+            None => Ok(Stream::Plain(tcp)),
+        }
+    }
+}
+
+// A connection in the clear, or over TLS
+pub enum Stream {
+    Plain(TcpStream),
+    #[cfg(feature = "tls")]
+    Tls(Box<rustls::StreamOwned<rustls::ClientConnection, TcpStream>>),
+}
+
+impl Stream {
+    fn tcp(&self) -> &TcpStream {
+        match self {
+            Stream::Plain(tcp) => tcp,
+            #[cfg(feature = "tls")]
+            Stream::Tls(tls) => &tls.sock,
+        }
+    }
+
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.tcp().set_read_timeout(timeout)
+    }
+
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.tcp().set_write_timeout(timeout)
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Plain(tcp) => tcp.read(buf),
+            #[cfg(feature = "tls")]
+            Stream::Tls(tls) => tls.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Plain(tcp) => tcp.write(buf),
+            #[cfg(feature = "tls")]
+            Stream::Tls(tls) => tls.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Plain(tcp) => tcp.flush(),
+            #[cfg(feature = "tls")]
+            Stream::Tls(tls) => tls.flush(),
+        }
+    }
+}
@@ -0,0 +1,526 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    sync::mpsc::{self, SyncSender, TrySendError},
+    thread,
+    time::Duration,
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::json;
+
+use crate::{
+    alerts::{self, Notice},
+    applog,
+    auth::{self, base64, AuthSpec},
+    ingest::{IngestQueue, SourceSender},
+    logs::Level,
+    net::{authority, Remote, Stream},
+    pins::Pin,
+    watchlist::Status,
+};
+
+// What deliveries that fail are logged under
+pub const NAME: &str = "notify";
+const QUEUE: usize = 64;
+// Connecting, and each read and write after that
+const TIMEOUT: Duration = Duration::from_secs(10);
+// Alerts held for a notifier that's still sending; past this, new ones are
+// dropped with a note
+const BACKLOG: usize = 32;
+// What a template can name, besides a metric
+const FIELDS: [&str; 9] = [
+    "rule", "name", "event", "status", "value", "limit", "for", "mode", "message",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Kind {
+    // the alert as JSON, its text under `text`
+    Webhook,
+    // an incoming webhook's `{"text": ...}`
+    Slack,
+    // a mail through an SMTP relay
+    Email,
+}
+
+// Which changes a notifier hears about
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Event {
+    Fired,
+    // still firing, every few minutes until acknowledged or silenced
+    Repeat,
+    Resolved,
+}
+
+impl Event {
+    // What a notifier hears of a notice; nothing of an alert a maintenance
+    // window holds
+    pub fn of(notice: Notice) -> Option<Self> {
+        match notice {
+            Notice::Fired => Some(Event::Fired),
+            Notice::Repeat => Some(Event::Repeat),
+            Notice::Resolved => Some(Event::Resolved),
+            Notice::Suppressed => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Event::Fired => "fired",
+            Event::Repeat => "repeat",
+            Event::Resolved => "resolved",
+        }
+    }
+}
+
+// One `[[notify]]` table in config.toml: where alerts go besides the logs,
+// and what they say there. Checked as it's read, templates and all, so
+// `ai-intui check` points at a bad URL or field.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct NotifierSpec {
+    pub kind: Kind,
+    // http(s):// for a webhook or Slack, smtp(s)://HOST[:PORT] for email
+    pub url: String,
+    // the message; the line the dashboard logs unless set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    // email's Subject
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    // email's sender and recipients
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub to: Vec<String>,
+    #[serde(default = "fired_and_resolved")]
+    pub on: Vec<Event>,
+    // headers for a webhook or Slack, a login for the relay, and TLS files
+    #[serde(default, deserialize_with = "auth::http")]
+    #[serde(skip_serializing_if = "AuthSpec::is_empty")]
+    pub auth: AuthSpec,
+}
+
+fn fired_and_resolved() -> Vec<Event> {
+    vec![Event::Fired, Event::Resolved]
+}
+
+// Every `[[notify]]`, each checked whole
+pub fn specs<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<NotifierSpec>>, D::Error> {
+    let specs = Vec::<NotifierSpec>::deserialize(d)?;
+    for spec in &specs {
+        Notifier::parts(spec, &AuthSpec::default())
+            .map_err(|e| de::Error::custom(format!("notify {}: {e}", site(&spec.url))))?;
+    }
+    Ok(Some(specs))
+}
+
+// A notifier's URL as the logs show it: scheme and host. The path stays out,
+// since a Slack webhook's path is its secret, and so does any login.
+pub fn site(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    format!("{scheme}://{host}")
+}
+
+// An alert as a notifier's templates see it
+pub struct Alert {
+    pub rule: String,
+    pub name: String,
+    pub event: Event,
+    pub status: Status,
+    pub value: String,
+    // the threshold it crossed, if it has one
+    pub limit: Option<String>,
+    // how long it had been firing
+    pub since: Duration,
+    pub mode: &'static str,
+    // the line the dashboard logged
+    pub message: String,
+}
+
+impl Alert {
+    fn field(&self, name: &str) -> String {
+        match name {
+            "rule" => self.rule.clone(),
+            "name" => self.name.clone(),
+            "event" => self.event.name().to_string(),
+            "status" => status(self.status).to_string(),
+            "value" => self.value.clone(),
+            "limit" => self.limit.clone().unwrap_or_default(),
+            "for" => alerts::span(self.since),
+            "mode" => self.mode.to_string(),
+            _ => self.message.clone(),
+        }
+    }
+}
+
+fn status(status: Status) -> &'static str {
+    match status {
+        Status::Crit => "crit",
+        Status::Warn => "warn",
+        Status::Ok | Status::Unknown => "ok",
+    }
+}
+
+// A message with `{field}`s filled in from the alert, and `{SCOPE:METRIC}`
+// from that metric's value when it's sent; `{{` and `}}` are braces
+pub struct Template(Vec<Piece>);
+
+enum Piece {
+    Text(String),
+    Field(&'static str),
+    Metric(Pin),
+}
+
+impl Template {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut pieces = Vec::new();
+        let mut plain = String::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' | '}' if chars.peek() == Some(&c) => {
+                    chars.next();
+                    plain.push(c);
+                }
+                '}' => return Err("a lone `}`; write `}}` for a brace".to_string()),
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(format!("`{{{name}` isn't closed")),
+                        }
+                    }
+                    if !plain.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut plain)));
+                    }
+                    pieces.push(Self::piece(name.trim())?);
+                }
+                c => plain.push(c),
+            }
+        }
+        if !plain.is_empty() {
+            pieces.push(Piece::Text(plain));
+        }
+        Ok(Self(pieces))
+    }
+
+    fn piece(name: &str) -> Result<Piece, String> {
+        if let Some(field) = FIELDS.iter().find(|f| **f == name) {
+            return Ok(Piece::Field(field));
+        }
+        match name.contains(':').then(|| Pin::parse(name)).flatten() {
+            Some(pin) => Ok(Piece::Metric(pin)),
+            None => Err(format!(
+                "no field `{name}`; fields are {}, or a metric like `{{cloud:queue depth}}`",
+                FIELDS.join(", ")
+            )),
+        }
+    }
+
+    // `metric` gives a metric's value as its panel shows it; one without a
+    // reading is `—`
+    pub fn render(&self, alert: &Alert, metric: &dyn Fn(&Pin) -> Option<String>) -> String {
+        self.0
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => text.clone(),
+                Piece::Field(name) => alert.field(name),
+                Piece::Metric(pin) => metric(pin).unwrap_or_else(|| "—".to_string()),
+            })
+            .collect()
+    }
+}
+
+// Where a notifier delivers
+enum Target {
+    Http {
+        remote: Remote,
+        path: String,
+        headers: String,
+    },
+    Smtp {
+        remote: Remote,
+        from: String,
+        to: Vec<String>,
+        auth: AuthSpec,
+    },
+}
+
+// One filled-in alert on its way out
+struct Outgoing {
+    subject: String,
+    text: String,
+    fields: serde_json::Value,
+}
+
+// A `[[notify]]` ready to send: its templates, and a thread of its own that
+// delivers in order, so a slow endpoint doesn't stall the dashboard and a
+// resolved alert doesn't overtake its firing
+pub struct Notifier {
+    kind: Kind,
+    site: String,
+    on: Vec<Event>,
+    template: Option<Template>,
+    subject: Template,
+    tx: SyncSender<Outgoing>,
+    log: SourceSender,
+}
+
+impl Notifier {
+    pub fn start(spec: &NotifierSpec, ingest: &IngestQueue) -> Result<Self, String> {
+        let (target, template, subject) = Self::parts(spec, &spec.auth)?;
+        let log = ingest.sender(NAME, QUEUE);
+        let (tx, rx) = mpsc::sync_channel::<Outgoing>(BACKLOG);
+        let (kind, shown, errors) = (spec.kind, site(&spec.url), log.clone());
+        thread::spawn(move || {
+            for outgoing in rx {
+                if let Err(e) = deliver(kind, &target, &outgoing) {
+                    let message = format!("{shown}: {e}; `{}` wasn't sent", outgoing.subject);
+                    applog::write(Level::Error, NAME, &message);
+                    errors.send(Level::Error, message);
+                }
+            }
+        });
+        Ok(Self {
+            kind: spec.kind,
+            site: site(&spec.url),
+            on: spec.on.clone(),
+            template,
+            subject,
+            tx,
+            log,
+        })
+    }
+
+    // The spec checked: where it sends, and its templates. `auth` is the
+    // spec's once it starts; its files aren't read before then.
+    fn parts(
+        spec: &NotifierSpec,
+        auth: &AuthSpec,
+    ) -> Result<(Target, Option<Template>, Template), String> {
+        let template = spec.template.as_deref().map(Template::parse).transpose()?;
+        let subject = Template::parse(
+            spec.subject
+                .as_deref()
+                .unwrap_or("[ai-intui] {name} {event}: {value}"),
+        )
+        .map_err(|e| format!("subject: {e}"))?;
+        let (secure, port) = match (spec.kind, spec.url.split_once("://")) {
+            (Kind::Webhook | Kind::Slack, Some(("http", _))) => (false, 80),
+            (Kind::Webhook | Kind::Slack, Some(("https", _))) => (true, 443),
+            (Kind::Email, Some(("smtp", _))) => (false, 25),
+            (Kind::Email, Some(("smtps", _))) => (true, 465),
+            (Kind::Email, _) => return Err("email goes to an smtp:// or smtps:// relay".into()),
+            _ => return Err("expected an http:// or https:// URL".into()),
+        };
+        let (host, path) = authority(&spec.url, port)
+            .map_err(|_| format!("no host, or a bad port, in `{}`", site(&spec.url)))?;
+        let remote = Remote::new(host, secure, auth)?;
+        if spec.kind != Kind::Email {
+            if spec.subject.is_some() || spec.from.is_some() || !spec.to.is_empty() {
+                return Err("`subject`, `from`, and `to` are for email".into());
+            }
+            return Ok((
+                Target::Http {
+                    remote,
+                    path: match path {
+                        "" => "/".to_string(),
+                        path => path.to_string(),
+                    },
+                    headers: auth.header_lines(),
+                },
+                template,
+                subject,
+            ));
+        }
+        if spec.auth.bearer.is_some() || !spec.auth.headers.is_empty() {
+            return Err("a relay takes `username` and `password`, not headers".into());
+        }
+        // there's no STARTTLS, so a login would cross the network in the clear
+        if spec.auth.username.is_some() && !secure {
+            return Err("a login needs an smtps:// relay".into());
+        }
+        let from = spec.from.clone().ok_or("email needs `from`")?;
+        if spec.to.is_empty() {
+            return Err("email needs `to`".into());
+        }
+        for address in spec.to.iter().chain([&from]) {
+            let bad = |c: char| c.is_whitespace() || c.is_control() || "<>".contains(c);
+            if !address.contains('@') || address.contains(bad) {
+                return Err(format!("`{address}` isn't an email address"));
+            }
+        }
+        let target = Target::Smtp {
+            remote,
+            from,
+            to: spec.to.clone(),
+            auth: auth.clone(),
+        };
+        Ok((target, template, subject))
+    }
+
+    pub fn describe(&self) -> String {
+        let kind = match self.kind {
+            Kind::Webhook => "webhook",
+            Kind::Slack => "Slack",
+            Kind::Email => "email",
+        };
+        let on: Vec<&str> = self.on.iter().map(|e| e.name()).collect();
+        format!("{kind} {} ({})", self.site, on.join(", "))
+    }
+
+    // Fills the templates now, from the alert and the metrics as they
+    // stand, and leaves the sending to the notifier's thread
+    pub fn send(&self, alert: &Alert, metric: &dyn Fn(&Pin) -> Option<String>) {
+        if !self.on.contains(&alert.event) {
+            return;
+        }
+        let text = match &self.template {
+            Some(template) => template.render(alert, metric),
+            None => alert.message.clone(),
+        };
+        // one line, whatever the metrics held
+        let subject = self
+            .subject
+            .render(alert, metric)
+            .replace(|c: char| c.is_control(), " ");
+        let mut fields = json!({ "text": text });
+        for name in FIELDS.iter().filter(|name| **name != "message") {
+            fields[*name] = json!(alert.field(name));
+        }
+        let outgoing = Outgoing {
+            subject,
+            text,
+            fields,
+        };
+        if let Err(TrySendError::Full(outgoing)) = self.tx.try_send(outgoing) {
+            self.log.send(
+                Level::Warn,
+                format!(
+                    "{} to {} dropped; {BACKLOG} are already waiting",
+                    outgoing.subject, self.site
+                ),
+            );
+        }
+    }
+}
+
+fn deliver(kind: Kind, target: &Target, outgoing: &Outgoing) -> io::Result<()> {
+    match target {
+        Target::Http {
+            remote,
+            path,
+            headers,
+        } => {
+            let body = match kind {
+                Kind::Slack => json!({ "text": outgoing.text }).to_string(),
+                _ => outgoing.fields.to_string(),
+            };
+            let mut stream = connect(remote)?;
+            write!(
+                stream,
+                "POST {path} HTTP/1.0\r\nHost: {}\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nUser-Agent: ai-intui/{}\r\n{headers}\r\n{body}",
+                remote.authority.written,
+                body.len(),
+                env!("CARGO_PKG_VERSION")
+            )?;
+            let mut status = String::new();
+            BufReader::new(stream).read_line(&mut status)?;
+            match status.split_whitespace().nth(1) {
+                Some(code) if code.starts_with('2') => Ok(()),
+                _ => Err(io::Error::other(format!("answered `{}`", status.trim()))),
+            }
+        }
+        Target::Smtp {
+            remote,
+            from,
+            to,
+            auth,
+        } => mail(connect(remote)?, from, to, auth, outgoing),
+    }
+}
+
+fn connect(remote: &Remote) -> io::Result<Stream> {
+    let stream = remote.dial(TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    Ok(stream)
+}
+
+// Just enough SMTP to hand a relay one plain-text mail
+fn mail(
+    stream: Stream,
+    from: &str,
+    to: &[String],
+    auth: &AuthSpec,
+    outgoing: &Outgoing,
+) -> io::Result<()> {
+    let mut conn = BufReader::new(stream);
+    reply(&mut conn, '2')?;
+    command(&mut conn, "EHLO ai-intui", '2')?;
+    if let Some(username) = &auth.username {
+        let password = auth.password.as_deref().unwrap_or("");
+        let login = base64(format!("\0{username}\0{password}").as_bytes());
+        command(&mut conn, &format!("AUTH PLAIN {login}"), '2')?;
+    }
+    command(&mut conn, &format!("MAIL FROM:<{from}>"), '2')?;
+    for to in to {
+        command(&mut conn, &format!("RCPT TO:<{to}>"), '2')?;
+    }
+    command(&mut conn, "DATA", '3')?;
+    let subject = match outgoing.subject.is_ascii() {
+        true => outgoing.subject.clone(),
+        false => format!("=?utf-8?B?{}?=", base64(outgoing.subject.as_bytes())),
+    };
+    // a line that starts with a dot gets another, so it can't end the mail
+    let body: String = outgoing
+        .text
+        .lines()
+        .map(|line| match line.starts_with('.') {
+            true => format!(".{line}\r\n"),
+            false => format!("{line}\r\n"),
+        })
+        .collect();
+    let data = format!(
+        "From: {from}\r\nTo: {}\r\nSubject: {subject}\r\nDate: {}\r\n\
+         Content-Type: text/plain; charset=utf-8\r\n\r\n{body}.",
+        to.join(", "),
+        chrono::Local::now().to_rfc2822()
+    );
+    command(&mut conn, &data, '2')?;
+    let _ = command(&mut conn, "QUIT", '2');
+    Ok(())
+}
+
+// One line to the relay, and its reply, whose code must start with `class`
+fn command(conn: &mut BufReader<Stream>, line: &str, class: char) -> io::Result<()> {
+    write!(conn.get_mut(), "{line}\r\n")?;
+    reply(conn, class)
+}
+
+// A reply read to its last line (`250 ...` after any `250-...`)
+fn reply(conn: &mut BufReader<Stream>, class: char) -> io::Result<()> {
+    loop {
+        let mut line = String::new();
+        if conn.read_line(&mut line)? == 0 {
+            return Err(io::Error::other("the relay hung up"));
+        }
+        if line.as_bytes().get(3) == Some(&b'-') {
+            continue;
+        }
+        return match line.starts_with(class) {
+            true => Ok(()),
+            false => Err(io::Error::other(format!(
+                "relay answered `{}`",
+                line.trim()
+            ))),
+        };
+    }
+}
@@ -0,0 +1,813 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io::{self, BufReader},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serde_json::Value as Json;
+
+use crate::{
+    datasource::{DataSource, Sample, Value, METRICS},
+    histogram::{self, Histogram},
+    http::{self, Request},
+    ingest::SourceSender,
+    logs::Level,
+};
+
+// What span events and receiver notes are logged under
+pub const NAME: &str = "otlp";
+pub const QUEUE: usize = 1024;
+// Largest export request read
+const MAX_BODY: usize = 4 << 20;
+// Reading a request, and writing the reply
+const TIMEOUT: Duration = Duration::from_secs(10);
+// Spans that ended longer ago than this no longer count toward latency and
+// errors/min
+const WINDOW: Duration = Duration::from_secs(60);
+
+// What one export request carried, decoded from either encoding
+#[derive(Default)]
+struct Export {
+    spans: Vec<Span>,
+    points: Vec<Point>,
+}
+
+struct Span {
+    service: String,
+    name: String,
+    start: u64,
+    end: u64,
+    // the status message of a span that failed
+    error: Option<String>,
+    events: Vec<Event>,
+}
+
+impl Span {
+    fn millis(&self) -> f64 {
+        self.end.saturating_sub(self.start) as f64 / 1e6
+    }
+}
+
+struct Event {
+    name: String,
+    attributes: Vec<(String, String)>,
+}
+
+// A metric's data points, summed across their attributes
+struct Point {
+    name: String,
+    unit: String,
+    data: Data,
+}
+
+#[derive(Clone)]
+enum Data {
+    Number {
+        value: f64,
+        monotonic: bool,
+        delta: bool,
+    },
+    // per-bucket counts, one more than there are bounds
+    Histogram {
+        bounds: Vec<f64>,
+        counts: Vec<u64>,
+    },
+}
+
+// `--otlp`: an OTLP/HTTP receiver in place of the AI observability panel's
+// synthetic numbers. Spans fill latency and errors/min, metrics fill the rows
+// they're named after, and span events and failures go to the logs.
+pub struct Otlp {
+    addr: SocketAddr,
+    rx: Receiver<(Instant, Export)>,
+    // (ended, duration in ms, failed) for spans inside WINDOW
+    spans: VecDeque<(Instant, f64, bool)>,
+    traced: bool,
+    // a counter's last reading and when it came, for its rate
+    counters: HashMap<String, (Instant, f64)>,
+}
+
+// Log lines from the connection threads, some of them only once
+struct Notes {
+    log: SourceSender,
+    said: Mutex<HashSet<String>>,
+}
+
+impl Notes {
+    fn once(&self, level: Level, key: &str, message: String) {
+        if self.said.lock().unwrap().insert(key.to_string()) {
+            self.log.send(level, message);
+        }
+    }
+}
+
+impl Otlp {
+    pub fn listen(addr: SocketAddr, log: SourceSender) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let (tx, rx) = mpsc::channel();
+        let notes = Notes {
+            log,
+            said: Mutex::new(HashSet::new()),
+        };
+        http::serve(listener, TIMEOUT, move |stream, reader| {
+            serve(stream, reader, &tx, &notes)
+        });
+        Ok(Self {
+            addr,
+            rx,
+            spans: VecDeque::new(),
+            traced: false,
+            counters: HashMap::new(),
+        })
+    }
+
+    fn reading(&mut self, at: Instant, point: Point) -> Option<Sample> {
+        let (metric, rate) = row(&point.name, &point.data)?;
+        let value = match point.data {
+            Data::Histogram { bounds, counts } => {
+                let scale = match point.unit.as_str() {
+                    "s" => 1000.0,
+                    "us" => 0.001,
+                    "ns" => 1e-6,
+                    _ => 1.0,
+                };
+                // counts from any client, so they can't be trusted to fit
+                let mut total: u64 = 0;
+                let buckets = counts
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &n)| {
+                        total = total.saturating_add(n);
+                        let bound = bounds.get(i).map_or(f64::INFINITY, |b| b * scale);
+                        (bound, total as f64)
+                    })
+                    .collect();
+                Value::Histogram(Histogram::new(buckets)?)
+            }
+            Data::Number {
+                value,
+                delta,
+                monotonic: _,
+            } if rate => {
+                let last = self.counters.insert(point.name, (at, value));
+                let (then, before) = last?;
+                let increase = match delta {
+                    true => value,
+                    // a counter that went down was restarted
+                    false if value < before => return None,
+                    false => value - before,
+                };
+                let secs = at.duration_since(then).as_secs_f64();
+                if secs <= 0.0 {
+                    return None;
+                }
+                Value::Gauge(increase / secs * 60.0)
+            }
+            Data::Number { value, .. } => Value::Gauge(value),
+        };
+        Some(Sample { metric, value })
+    }
+}
+
+impl DataSource for Otlp {
+    fn poll(&mut self) -> Vec<Sample> {
+        let mut samples = Vec::new();
+        let exports: Vec<_> = self.rx.try_iter().collect();
+        for (at, export) in exports {
+            for span in &export.spans {
+                self.spans
+                    .push_back((at, span.millis(), span.error.is_some()));
+                self.traced = true;
+            }
+            for point in export.points {
+                samples.extend(self.reading(at, point));
+            }
+        }
+        let now = Instant::now();
+        while let Some(&(at, ..)) = self.spans.front() {
+            if now.duration_since(at) <= WINDOW {
+                break;
+            }
+            self.spans.pop_front();
+        }
+        if self.traced {
+            let errors = self.spans.iter().filter(|s| s.2).count();
+            samples.push(Sample {
+                metric: METRICS[3].to_string(),
+                value: Value::Gauge(errors as f64 * 60.0 / WINDOW.as_secs_f64()),
+            });
+        }
+        if !self.spans.is_empty() {
+            let durations: Vec<_> = self.spans.iter().map(|s| (s.1, 1.0)).collect();
+            samples.extend(histogram::observed(&durations).map(|h| Sample {
+                metric: histogram::base(METRICS[0]).to_string(),
+                value: Value::Histogram(h),
+            }));
+        }
+        samples
+    }
+
+    fn describe(&self) -> String {
+        format!("OTLP/HTTP receiver on {}", self.addr)
+    }
+}
+
+// The row a metric fills, by name: `queue.depth` or `queue_depth` fills queue
+// depth, a counter `tokens` fills tokens/min with its per-minute rate, and a
+// histogram `latency` fills latency p95. True when it's read as a rate.
+fn row(name: &str, data: &Data) -> Option<(String, bool)> {
+    let spelled = name.to_lowercase().replace(['.', '_'], " ");
+    match data {
+        Data::Histogram { .. } => METRICS
+            .iter()
+            .map(|m| histogram::base(m))
+            .find(|base| *base == spelled && !METRICS.contains(base))
+            .map(|base| (base.to_string(), false)),
+        Data::Number { monotonic, .. } => {
+            if METRICS.contains(&spelled.as_str()) {
+                return Some((spelled, false));
+            }
+            let rate = format!("{spelled}/min");
+            (*monotonic && METRICS.contains(&rate.as_str())).then_some((rate, true))
+        }
+    }
+}
+
+fn serve(
+    mut stream: TcpStream,
+    mut reader: BufReader<TcpStream>,
+    tx: &Sender<(Instant, Export)>,
+    notes: &Notes,
+) -> io::Result<()> {
+    let request = match Request::read(&mut reader)? {
+        Ok(request) => request,
+        Err(reply) => return http::reply_text(&mut stream, reply),
+    };
+    if request.method == "PRI" {
+        notes.once(
+            Level::Warn,
+            "grpc",
+            "an exporter sent OTLP/gRPC, which isn't supported; \
+             set it to http/protobuf or http/json"
+                .to_string(),
+        );
+        return Ok(());
+    }
+    let json = request
+        .header("content-type")
+        .is_some_and(|kind| kind.to_ascii_lowercase().starts_with("application/json"));
+    let encoding = request
+        .header("content-encoding")
+        .unwrap_or("")
+        .to_ascii_lowercase();
+
+    let path = request.path();
+    let reply = if path != "/v1/traces" && path != "/v1/metrics" {
+        Err((
+            "404 Not Found",
+            "only /v1/traces and /v1/metrics are served".to_string(),
+        ))
+    } else if request.method != "POST" {
+        Err(("405 Method Not Allowed", "use POST".to_string()))
+    } else if !encoding.is_empty() && encoding != "identity" {
+        notes.once(
+            Level::Warn,
+            "compression",
+            format!("an exporter sent {encoding}-compressed data; set its compression to none"),
+        );
+        Err((
+            "415 Unsupported Media Type",
+            "compressed requests aren't supported".to_string(),
+        ))
+    } else {
+        match request.body(&mut reader, MAX_BODY)? {
+            None => Err(("413 Payload Too Large", format!("over {MAX_BODY} bytes"))),
+            Some(body) => {
+                let decoded = match (path, json) {
+                    ("/v1/traces", true) => json_traces(&body),
+                    ("/v1/traces", false) => pb_traces(&body),
+                    (_, true) => json_metrics(&body),
+                    (_, false) => pb_metrics(&body),
+                };
+                decoded.map_err(|e| ("400 Bad Request", e))
+            }
+        }
+    };
+
+    let (status, text) = match reply {
+        Ok(export) => {
+            report(&export, notes);
+            let _ = tx.send((Instant::now(), export));
+            ("200 OK", if json { "{}" } else { "" }.to_string())
+        }
+        Err((status, error)) => (status, error),
+    };
+    let kind = match json || !status.starts_with("200") {
+        true => "application/json",
+        false => "application/x-protobuf",
+    };
+    let text = match status.starts_with("200") {
+        true => text,
+        false => serde_json::json!({ "message": text }).to_string(),
+    };
+    http::reply(&mut stream, status, kind, &text)
+}
+
+// Span events and failed spans into the logs, and a note for each metric
+// that fills no row
+fn report(export: &Export, notes: &Notes) {
+    let mut lines = Vec::new();
+    for span in &export.spans {
+        let who = match span.service.is_empty() {
+            true => span.name.clone(),
+            false => format!("{} {}", span.service, span.name),
+        };
+        if let Some(message) = &span.error {
+            let reason = match message.is_empty() {
+                true => String::new(),
+                false => format!(": {message}"),
+            };
+            lines.push((
+                Level::Warn,
+                format!("{who} failed after {:.0} ms{reason}", span.millis()),
+            ));
+        }
+        for event in &span.events {
+            let level = match event.name.as_str() {
+                "exception" => Level::Error,
+                _ => Level::Info,
+            };
+            let attributes: String = event
+                .attributes
+                .iter()
+                .map(|(k, v)| format!(" {k}={v}"))
+                .collect();
+            lines.push((level, format!("{who}: {}{attributes}", event.name)));
+        }
+    }
+    if !lines.is_empty() {
+        notes.log.send_batch(lines);
+    }
+    for point in &export.points {
+        if row(&point.name, &point.data).is_none() {
+            notes.once(
+                Level::Info,
+                &point.name,
+                format!(
+                    "metric `{}` fills no AI observability row; name it after one \
+                     (queue.depth, a tokens counter for tokens/min)",
+                    point.name
+                ),
+            );
+        }
+    }
+}
+
+// Adds up a metric's data points: numbers by value, histograms bucket by
+// bucket as long as their bounds agree
+fn combine(points: Vec<Data>) -> Option<Data> {
+    points.into_iter().reduce(|sum, point| match (sum, point) {
+        (
+            Data::Number {
+                value: a,
+                monotonic,
+                delta,
+            },
+            Data::Number { value: b, .. },
+        ) => Data::Number {
+            value: a + b,
+            monotonic,
+            delta,
+        },
+        (
+            Data::Histogram { bounds, counts: a },
+            Data::Histogram {
+                bounds: other,
+                counts: b,
+            },
+        ) if bounds == other && a.len() == b.len() => Data::Histogram {
+            bounds,
+            counts: a
+                .iter()
+                .zip(&b)
+                .map(|(a, b)| a.saturating_add(*b))
+                .collect(),
+        },
+        (sum, _) => sum,
+    })
+}
+
+// Protobuf wire format, just enough to walk OTLP messages: a field number
+// and one of these
+enum Wire<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Fixed32,
+    Bytes(&'a [u8]),
+}
+
+fn varint(buf: &mut &[u8]) -> Result<u64, String> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = buf.split_first().ok_or("truncated varint")?;
+        *buf = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("varint too long".to_string())
+}
+
+fn take<'a>(buf: &mut &'a [u8], n: usize) -> Result<&'a [u8], String> {
+    if buf.len() < n {
+        return Err("truncated field".to_string());
+    }
+    let (head, rest) = buf.split_at(n);
+    *buf = rest;
+    Ok(head)
+}
+
+fn fields(mut buf: &[u8]) -> Result<Vec<(u64, Wire<'_>)>, String> {
+    let mut out = Vec::new();
+    while !buf.is_empty() {
+        let key = varint(&mut buf)?;
+        let wire = match key & 7 {
+            0 => Wire::Varint(varint(&mut buf)?),
+            1 => Wire::Fixed64(u64::from_le_bytes(take(&mut buf, 8)?.try_into().unwrap())),
+            2 => {
+                let n = varint(&mut buf)? as usize;
+                Wire::Bytes(take(&mut buf, n)?)
+            }
+            5 => {
+                take(&mut buf, 4)?;
+                Wire::Fixed32
+            }
+            other => return Err(format!("unsupported wire type {other}")),
+        };
+        out.push((key >> 3, wire));
+    }
+    Ok(out)
+}
+
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+// Repeated fixed64 fields, packed or not
+fn fixed64s(wire: Wire, out: &mut Vec<u64>) {
+    match wire {
+        Wire::Fixed64(v) => out.push(v),
+        Wire::Bytes(packed) => out.extend(
+            packed
+                .chunks_exact(8)
+                .map(|c| u64::from_le_bytes(c.try_into().unwrap())),
+        ),
+        _ => {}
+    }
+}
+
+// ExportTraceServiceRequest
+fn pb_traces(buf: &[u8]) -> Result<Export, String> {
+    let mut spans = Vec::new();
+    for (n, wire) in fields(buf)? {
+        let (1, Wire::Bytes(resource_spans)) = (n, wire) else {
+            continue;
+        };
+        let mut service = String::new();
+        let mut scopes = Vec::new();
+        for (n, wire) in fields(resource_spans)? {
+            match (n, wire) {
+                (1, Wire::Bytes(resource)) => service = pb_service(resource)?,
+                (2, Wire::Bytes(scope)) => scopes.push(scope),
+                _ => {}
+            }
+        }
+        for scope in scopes {
+            for (n, wire) in fields(scope)? {
+                if let (2, Wire::Bytes(span)) = (n, wire) {
+                    spans.push(pb_span(span, &service)?);
+                }
+            }
+        }
+    }
+    Ok(Export {
+        spans,
+        points: Vec::new(),
+    })
+}
+
+// A Resource's `service.name`
+fn pb_service(resource: &[u8]) -> Result<String, String> {
+    for (n, wire) in fields(resource)? {
+        if let (1, Wire::Bytes(kv)) = (n, wire) {
+            let (key, value) = pb_attribute(kv)?;
+            if key == "service.name" {
+                return Ok(value);
+            }
+        }
+    }
+    Ok(String::new())
+}
+
+// A KeyValue, its value written out
+fn pb_attribute(kv: &[u8]) -> Result<(String, String), String> {
+    let (mut key, mut value) = (String::new(), String::new());
+    for (n, wire) in fields(kv)? {
+        match (n, wire) {
+            (1, Wire::Bytes(k)) => key = text(k),
+            (2, Wire::Bytes(any)) => {
+                for (n, wire) in fields(any)? {
+                    value = match (n, wire) {
+                        (1, Wire::Bytes(s)) => text(s),
+                        (2, Wire::Varint(b)) => (b != 0).to_string(),
+                        (3, Wire::Varint(i)) => (i as i64).to_string(),
+                        (4, Wire::Fixed64(d)) => f64::from_bits(d).to_string(),
+                        _ => "…".to_string(),
+                    };
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((key, value))
+}
+
+fn pb_span(buf: &[u8], service: &str) -> Result<Span, String> {
+    let mut span = Span {
+        service: service.to_string(),
+        name: String::new(),
+        start: 0,
+        end: 0,
+        error: None,
+        events: Vec::new(),
+    };
+    for (n, wire) in fields(buf)? {
+        match (n, wire) {
+            (5, Wire::Bytes(name)) => span.name = text(name),
+            (7, Wire::Fixed64(t)) => span.start = t,
+            (8, Wire::Fixed64(t)) => span.end = t,
+            (11, Wire::Bytes(event)) => {
+                let mut e = Event {
+                    name: String::new(),
+                    attributes: Vec::new(),
+                };
+                for (n, wire) in fields(event)? {
+                    match (n, wire) {
+                        (2, Wire::Bytes(name)) => e.name = text(name),
+                        (3, Wire::Bytes(kv)) => e.attributes.push(pb_attribute(kv)?),
+                        _ => {}
+                    }
+                }
+                span.events.push(e);
+            }
+            (15, Wire::Bytes(status)) => {
+                let (mut message, mut code) = (String::new(), 0);
+                for (n, wire) in fields(status)? {
+                    match (n, wire) {
+                        (2, Wire::Bytes(m)) => message = text(m),
+                        (3, Wire::Varint(c)) => code = c,
+                        _ => {}
+                    }
+                }
+                // STATUS_CODE_ERROR
+                span.error = (code == 2).then_some(message);
+            }
+            _ => {}
+        }
+    }
+    Ok(span)
+}
+
+// ExportMetricsServiceRequest
+fn pb_metrics(buf: &[u8]) -> Result<Export, String> {
+    let mut points = Vec::new();
+    for (n, wire) in fields(buf)? {
+        let (1, Wire::Bytes(resource_metrics)) = (n, wire) else {
+            continue;
+        };
+        for (n, wire) in fields(resource_metrics)? {
+            let (2, Wire::Bytes(scope)) = (n, wire) else {
+                continue;
+            };
+            for (n, wire) in fields(scope)? {
+                if let (2, Wire::Bytes(metric)) = (n, wire) {
+                    points.extend(pb_metric(metric)?);
+                }
+            }
+        }
+    }
+    Ok(Export {
+        spans: Vec::new(),
+        points,
+    })
+}
+
+fn pb_metric(buf: &[u8]) -> Result<Option<Point>, String> {
+    let (mut name, mut unit, mut data) = (String::new(), String::new(), Vec::new());
+    for (n, wire) in fields(buf)? {
+        match (n, wire) {
+            (1, Wire::Bytes(s)) => name = text(s),
+            (3, Wire::Bytes(s)) => unit = text(s),
+            // gauge, sum, histogram
+            (5 | 7 | 9, Wire::Bytes(body)) => {
+                let (mut monotonic, mut delta, mut points) = (false, false, Vec::new());
+                for (m, wire) in fields(body)? {
+                    match (m, wire) {
+                        (1, Wire::Bytes(point)) => points.push(point),
+                        // AGGREGATION_TEMPORALITY_DELTA
+                        (2, Wire::Varint(t)) => delta = t == 1,
+                        (3, Wire::Varint(b)) => monotonic = n == 7 && b != 0,
+                        _ => {}
+                    }
+                }
+                for point in points {
+                    data.extend(match n {
+                        9 => pb_histogram_point(point)?,
+                        _ => pb_number_point(point, monotonic, delta)?,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(combine(data).map(|data| Point { name, unit, data }))
+}
+
+fn pb_number_point(buf: &[u8], monotonic: bool, delta: bool) -> Result<Option<Data>, String> {
+    let mut value = None;
+    for (n, wire) in fields(buf)? {
+        match (n, wire) {
+            (4, Wire::Fixed64(d)) => value = Some(f64::from_bits(d)),
+            (6, Wire::Fixed64(i)) => value = Some(i as i64 as f64),
+            _ => {}
+        }
+    }
+    Ok(value.map(|value| Data::Number {
+        value,
+        monotonic,
+        delta,
+    }))
+}
+
+fn pb_histogram_point(buf: &[u8]) -> Result<Option<Data>, String> {
+    let (mut counts, mut bounds) = (Vec::new(), Vec::new());
+    for (n, wire) in fields(buf)? {
+        match n {
+            6 => fixed64s(wire, &mut counts),
+            7 => fixed64s(wire, &mut bounds),
+            _ => {}
+        }
+    }
+    let bounds: Vec<f64> = bounds.into_iter().map(f64::from_bits).collect();
+    Ok((counts.len() == bounds.len() + 1).then_some(Data::Histogram { bounds, counts }))
+}
+
+// The JSON encoding: the same messages with camelCase names, and 64-bit
+// integers written as strings
+
+fn json_body(body: &[u8]) -> Result<Json, String> {
+    serde_json::from_slice(body).map_err(|e| format!("bad JSON: {e}"))
+}
+
+fn list<'a>(v: &'a Json, key: &str) -> &'a [Json] {
+    v.get(key)
+        .and_then(Json::as_array)
+        .map_or(&[], Vec::as_slice)
+}
+
+fn number(v: &Json) -> Option<f64> {
+    v.as_f64()
+        .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+}
+
+fn json_value(any: &Json) -> String {
+    match any.as_object().and_then(|o| o.iter().next()) {
+        Some((_, Json::String(s))) => s.clone(),
+        Some((_, v)) if !v.is_object() && !v.is_array() => v.to_string(),
+        _ => "…".to_string(),
+    }
+}
+
+fn json_attributes(v: &Json) -> Vec<(String, String)> {
+    list(v, "attributes")
+        .iter()
+        .map(|kv| {
+            let key = kv.get("key").and_then(Json::as_str).unwrap_or("");
+            let value = kv.get("value").map_or_else(String::new, json_value);
+            (key.to_string(), value)
+        })
+        .collect()
+}
+
+fn json_traces(body: &[u8]) -> Result<Export, String> {
+    let root = json_body(body)?;
+    let mut spans = Vec::new();
+    for resource_spans in list(&root, "resourceSpans") {
+        let service = resource_spans
+            .get("resource")
+            .map(json_attributes)
+            .unwrap_or_default()
+            .into_iter()
+            .find_map(|(k, v)| (k == "service.name").then_some(v))
+            .unwrap_or_default();
+        for scope in list(resource_spans, "scopeSpans") {
+            for span in list(scope, "spans") {
+                let nanos = |key| span.get(key).and_then(number).unwrap_or(0.0) as u64;
+                let status = span.get("status");
+                let code = status.and_then(|s| s.get("code"));
+                let failed = code.and_then(Json::as_u64) == Some(2)
+                    || code.and_then(Json::as_str) == Some("STATUS_CODE_ERROR");
+                let message = status
+                    .and_then(|s| s.get("message"))
+                    .and_then(Json::as_str)
+                    .unwrap_or("");
+                spans.push(Span {
+                    service: service.clone(),
+                    name: span
+                        .get("name")
+                        .and_then(Json::as_str)
+                        .unwrap_or("")
+                        .to_string(),
+                    start: nanos("startTimeUnixNano"),
+                    end: nanos("endTimeUnixNano"),
+                    error: failed.then(|| message.to_string()),
+                    events: list(span, "events")
+                        .iter()
+                        .map(|e| Event {
+                            name: e
+                                .get("name")
+                                .and_then(Json::as_str)
+                                .unwrap_or("")
+                                .to_string(),
+                            attributes: json_attributes(e),
+                        })
+                        .collect(),
+                });
+            }
+        }
+    }
+    Ok(Export {
+        spans,
+        points: Vec::new(),
+    })
+}
+
+fn json_metrics(body: &[u8]) -> Result<Export, String> {
+    let root = json_body(body)?;
+    let mut points = Vec::new();
+    for resource_metrics in list(&root, "resourceMetrics") {
+        for scope in list(resource_metrics, "scopeMetrics") {
+            for metric in list(scope, "metrics") {
+                let name = metric.get("name").and_then(Json::as_str).unwrap_or("");
+                let unit = metric.get("unit").and_then(Json::as_str).unwrap_or("");
+                let mut data = Vec::new();
+                if let Some(body) = metric.get("gauge").or_else(|| metric.get("sum")) {
+                    let monotonic = metric.get("sum").is_some()
+                        && body.get("isMonotonic").and_then(Json::as_bool) == Some(true);
+                    let temporality = body.get("aggregationTemporality");
+                    let delta = temporality.and_then(Json::as_u64) == Some(1)
+                        || temporality.and_then(Json::as_str)
+                            == Some("AGGREGATION_TEMPORALITY_DELTA");
+                    data.extend(list(body, "dataPoints").iter().filter_map(|p| {
+                        let value = p.get("asDouble").or_else(|| p.get("asInt"));
+                        Some(Data::Number {
+                            value: value.and_then(number)?,
+                            monotonic,
+                            delta,
+                        })
+                    }));
+                }
+                if let Some(body) = metric.get("histogram") {
+                    data.extend(list(body, "dataPoints").iter().filter_map(|p| {
+                        let counts: Vec<u64> = list(p, "bucketCounts")
+                            .iter()
+                            .filter_map(|n| number(n).map(|n| n as u64))
+                            .collect();
+                        let bounds: Vec<f64> = list(p, "explicitBounds")
+                            .iter()
+                            .filter_map(number)
+                            .collect();
+                        (counts.len() == bounds.len() + 1)
+                            .then_some(Data::Histogram { bounds, counts })
+                    }));
+                }
+                points.extend(combine(data).map(|data| Point {
+                    name: name.to_string(),
+                    unit: unit.to_string(),
+                    data,
+                }));
+            }
+        }
+    }
+    Ok(Export {
+        spans: Vec::new(),
+        points,
+    })
+}
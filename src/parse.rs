@@ -0,0 +1,154 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use crate::logs::Level;
+
+pub struct Parsed {
+    pub level: Level,
+    pub message: String,
+}
+
+pub fn level_from_name(name: &str) -> Level {
+    match name.to_ascii_lowercase().as_str() {
+        "error" | "err" | "fatal" | "critical" | "crit" | "panic" | "alert" | "emerg" => {
+            Level::Error
+        }
+        "warn" | "warning" => Level::Warn,
+        "debug" | "trace" => Level::Debug,
+        _ => Level::Info,
+    }
+}
+
+// Syslog severity (PRI % 8) onto our scale
+pub fn level_from_syslog(severity: u8) -> Level {
+    match severity {
+        0..=3 => Level::Error,
+        4 => Level::Warn,
+        7 => Level::Debug,
+        _ => Level::Info,
+    }
+}
+
+// Understands JSON objects with a level/msg field and `<PRI>` syslog prefixes;
+// anything else is passed through as an info line. Blank lines are dropped.
+pub fn parse_line(raw: &str) -> Option<Parsed> {
+    let line = raw.trim_end_matches(['\r', '\n']);
+    if line.trim().is_empty() {
+        return None;
+    }
+
+    if line.starts_with('{') {
+        if let Ok(serde_json::Value::Object(obj)) = serde_json::from_str(line) {
+            let level = ["level", "severity", "lvl"]
+                .iter()
+                .find_map(|k| obj.get(*k).and_then(|v| v.as_str()))
+                .map_or(Level::Info, level_from_name);
+            let message = ["msg", "message", "event"]
+                .iter()
+                .find_map(|k| obj.get(*k).and_then(|v| v.as_str()))
+                .map_or_else(|| line.to_string(), str::to_string);
+            return Some(Parsed { level, message });
+        }
+    }
+
+    if let Some(rest) = line.strip_prefix('<') {
+        if let Some((pri, msg)) = rest.split_once('>') {
+            if let Ok(pri) = pri.parse::<u8>() {
+                return Some(Parsed {
+                    level: level_from_syslog(pri % 8),
+                    message: msg.to_string(),
+                });
+            }
+        }
+    }
+
+    Some(Parsed {
+        level: Level::Info,
+        message: line.to_string(),
+    })
+}
+
+// Small worker pool that parses raw line batches on threads of their own,
+// for sources that can send faster than one thread parses. Batches are
+// numbered on the way in and re-sequenced before delivery, so lines reach
+// `deliver` in their original order; what `parse` drops never does.
+type Job<I> = (u64, Vec<I>);
+
+// Most lines a worker takes at once
+const SPLIT: usize = 256;
+
+pub struct ParserPool<I> {
+    tx: Sender<Job<I>>,
+    next_seq: u64,
+}
+
+impl<I: Send + 'static> ParserPool<I> {
+    pub fn new<O, D>(parse: fn(I) -> Option<O>, mut deliver: D) -> Self
+    where
+        O: Send + 'static,
+        D: FnMut(Vec<O>) + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel::<Job<I>>();
+        let (done_tx, done_rx) = mpsc::channel::<Job<O>>();
+        let rx = Arc::new(Mutex::new(rx));
+
+        for _ in 0..workers() {
+            let rx: Arc<Mutex<Receiver<Job<I>>>> = rx.clone();
+            let done_tx = done_tx.clone();
+            thread::spawn(move || loop {
+                let (seq, batch) = match rx.lock().unwrap().recv() {
+                    Ok(job) => job,
+                    Err(_) => break,
+                };
+                let parsed = batch.into_iter().filter_map(parse).collect();
+                if done_tx.send((seq, parsed)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(done_tx);
+
+        thread::spawn(move || {
+            let mut pending = BTreeMap::new();
+            let mut next = 0u64;
+            for (seq, parsed) in done_rx {
+                pending.insert(seq, parsed);
+                while let Some(batch) = pending.remove(&next) {
+                    if !batch.is_empty() {
+                        deliver(batch);
+                    }
+                    next += 1;
+                }
+            }
+        });
+
+        Self { tx, next_seq: 0 }
+    }
+
+    // A big batch is split, so the workers share it
+    pub fn feed(&mut self, mut batch: Vec<I>) {
+        while !batch.is_empty() {
+            let rest = batch.split_off(batch.len().min(SPLIT));
+            let _ = self.tx.send((self.next_seq, batch));
+            self.next_seq += 1;
+            batch = rest;
+        }
+    }
+}
+
+// A pool's threads: one per core, up to four, since parsing a line is cheap
+// next to handing it on
+fn workers() -> usize {
+    thread::available_parallelism().map_or(2, |n| n.get().min(4))
+}
+
+// A raw line as the logs take it
+pub fn parse_entry(raw: String) -> Option<(Level, String)> {
+    parse_line(&raw).map(|p| (p.level, p.message))
+}
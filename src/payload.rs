@@ -0,0 +1,103 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::{layout::fit, ui::centered};
+
+// A log message too structured to read wrapped: shown as one collapsed line
+// in the pane, and in full in the popup
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Payload {
+    // a JSON object or array with this many fields or items
+    Json(usize),
+    // a stack trace or similar, with this many lines after the first
+    Lines(usize),
+}
+
+impl Payload {
+    pub fn detect(message: &str) -> Option<Self> {
+        let text = message.trim();
+        if text.starts_with(['{', '[']) {
+            match serde_json::from_str(text) {
+                Ok(serde_json::Value::Object(obj)) => return Some(Payload::Json(obj.len())),
+                Ok(serde_json::Value::Array(items)) => return Some(Payload::Json(items.len())),
+                _ => {}
+            }
+        }
+        let more = message.trim_end().lines().count().saturating_sub(1);
+        (more > 0).then_some(Payload::Lines(more))
+    }
+
+    // What follows the collapsed line, e.g. `▸ json, 4 fields`
+    pub fn hint(self, ascii: bool) -> String {
+        let arrow = if ascii { '>' } else { '▸' };
+        match self {
+            Payload::Json(n) => format!(" {arrow} json, {n} fields"),
+            Payload::Lines(n) => format!(" {arrow} +{n} lines"),
+        }
+    }
+}
+
+// The first line, cut to `width` cells so it never wraps
+pub fn collapsed(message: &str, width: usize) -> &str {
+    fit(message.lines().next().unwrap_or(""), width).0
+}
+
+// The popup body: JSON re-indented, anything else line by line
+pub fn expanded(message: &str) -> Vec<String> {
+    serde_json::from_str::<serde_json::Value>(message.trim())
+        .ok()
+        .filter(|v| v.is_object() || v.is_array())
+        .and_then(|v| serde_json::to_string_pretty(&v).ok())
+        .unwrap_or_else(|| message.trim_end().to_string())
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+// An open log line, scrolled with ↑/↓
+pub struct Popup {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub scroll: usize,
+}
+
+impl Popup {
+    pub fn draw(&self, buf: &mut Buffer, area: Rect) {
+        let width = self
+            .lines
+            .iter()
+            .map(|l| l.chars().count())
+            .max()
+            .unwrap_or(0);
+        let width = width.max(self.title.chars().count()) as u16 + 4;
+        let rect = centered(area, width, self.lines.len() as u16 + 2);
+        let shown = rect.height.saturating_sub(2) as usize;
+        let scroll = self.scroll.min(self.lines.len().saturating_sub(shown));
+        let lines: Vec<Line> = self.lines[scroll..]
+            .iter()
+            .map(|l| Line::from(l.as_str()))
+            .collect();
+        let more = self.lines.len().saturating_sub(scroll + shown);
+        let title = if more > 0 {
+            format!("{} • {more} more ↓", self.title)
+        } else {
+            self.title.clone()
+        };
+        Clear.render(rect, buf);
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan))
+                    .title(Span::styled(
+                        title,
+                        Style::default()
+                            .fg(Color::LightCyan)
+                            .add_modifier(Modifier::BOLD),
+                    )),
+            )
+            .render(rect, buf);
+    }
+}
@@ -0,0 +1,84 @@
+use std::{fmt, fs, io, path::Path};
+
+use clap::ValueEnum;
+
+use crate::{app::Mode, derived};
+
+// Where a pinned metric comes from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Scope {
+    // an AI panel row, as that mode computes it
+    Mode(Mode),
+    System,
+    // a metric reported by a --host agent
+    Host(String),
+    // a `[derived]` formula over the others, by name
+    Derived,
+}
+
+// A metric kept on the favorites strip whatever the active mode
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pin {
+    pub scope: Scope,
+    pub label: String,
+}
+
+impl Pin {
+    // `SCOPE:LABEL`, where SCOPE is a mode name, `system`, `derived`, or a
+    // host name
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (scope, label) = spec.split_once(':')?;
+        let (scope, label) = (scope.trim(), label.trim());
+        if scope.is_empty() || label.is_empty() {
+            return None;
+        }
+        let scope = match Mode::from_str(scope, true) {
+            Ok(mode) => Scope::Mode(mode),
+            Err(_) if scope == "system" => Scope::System,
+            Err(_) if scope == derived::SCOPE => Scope::Derived,
+            Err(_) => Scope::Host(scope.to_string()),
+        };
+        Some(Self {
+            scope,
+            label: label.to_string(),
+        })
+    }
+
+    pub fn scope_name(&self) -> &str {
+        match &self.scope {
+            Scope::Mode(mode) => mode.short(),
+            Scope::System => "sys",
+            Scope::Host(host) => host,
+            Scope::Derived => "drv",
+        }
+    }
+}
+
+impl fmt::Display for Pin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.scope {
+            Scope::Mode(mode) => {
+                let name = mode.to_possible_value().expect("no skipped modes");
+                write!(f, "{}:{}", name.get_name(), self.label)
+            }
+            Scope::System => write!(f, "system:{}", self.label),
+            Scope::Host(host) => write!(f, "{host}:{}", self.label),
+            Scope::Derived => write!(f, "{}:{}", derived::SCOPE, self.label),
+        }
+    }
+}
+
+// One pin per line; lines that no longer parse are dropped
+pub fn load(path: &Path) -> Vec<Pin> {
+    fs::read_to_string(path)
+        .map(|text| text.lines().filter_map(Pin::parse).collect())
+        .unwrap_or_default()
+}
+
+pub fn save(path: &Path, pins: &[Pin]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text: String = pins.iter().map(|pin| format!("{pin}\n")).collect();
+    fs::write(path, text)
+}
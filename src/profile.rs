@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+use crate::app::Mode;
+
+// Start mode, tick rate, and synthetic logs for common setups, also switchable
+// at runtime; data sources and scenarios keep their own flags
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Profile {
+    /// Synthetic data for showing the dashboard off
+    Demo,
+    /// Calmer cadence for watching real systems, no synthetic logs
+    Ops,
+    /// Fast cadence for developing against piped-in data, no synthetic logs
+    Dev,
+}
+
+impl Profile {
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::Demo => "demo",
+            Profile::Ops => "ops",
+            Profile::Dev => "dev",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Profile::from_str(name, true).ok()
+    }
+
+    pub fn mode(self) -> Mode {
+        match self {
+            Profile::Demo => Mode::AiObservability,
+            Profile::Ops => Mode::Cloud,
+            Profile::Dev => Mode::Sandbox,
+        }
+    }
+
+    pub fn tick_rate(self) -> Duration {
+        match self {
+            Profile::Demo => Duration::from_millis(200),
+            Profile::Ops => Duration::from_millis(1000),
+            Profile::Dev => Duration::from_millis(100),
+        }
+    }
+
+    // whether the synthetic generator writes log lines
+    pub fn synthetic(self) -> bool {
+        self == Profile::Demo
+    }
+}
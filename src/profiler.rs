@@ -0,0 +1,134 @@
+use std::{
+    fmt::Write as _,
+    fs, io,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+// Keeps enough samples for stable percentiles without growing forever
+const MAX_SAMPLES: usize = 100_000;
+
+#[derive(Default)]
+struct Stats {
+    count: u64,
+    total: Duration,
+    max: Duration,
+    samples_us: Vec<u32>,
+}
+
+impl Stats {
+    fn record(&mut self, d: Duration) {
+        self.count += 1;
+        self.total += d;
+        self.max = self.max.max(d);
+        if self.samples_us.len() < MAX_SAMPLES {
+            self.samples_us
+                .push(d.as_micros().min(u32::MAX as u128) as u32);
+        }
+    }
+
+    fn avg_us(&self) -> u128 {
+        if self.count == 0 {
+            0
+        } else {
+            self.total.as_micros() / self.count as u128
+        }
+    }
+
+    fn p95_us(&self) -> u32 {
+        let mut sorted = self.samples_us.clone();
+        sorted.sort_unstable();
+        sorted
+            .get(sorted.len() * 95 / 100)
+            .or(sorted.last())
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+// Per-section timings for `--profile-frames`; a disabled profiler just runs the closures
+pub struct FrameProfiler {
+    output: Option<PathBuf>,
+    started: Instant,
+    sections: Vec<(&'static str, Stats)>,
+}
+
+impl FrameProfiler {
+    pub fn new(output: Option<PathBuf>) -> Self {
+        Self {
+            output,
+            started: Instant::now(),
+            sections: Vec::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.output.is_some()
+    }
+
+    pub fn time<T>(&mut self, section: &'static str, f: impl FnOnce() -> T) -> T {
+        if !self.enabled() {
+            return f();
+        }
+        let start = Instant::now();
+        let out = f();
+        self.record(section, start.elapsed());
+        out
+    }
+
+    pub fn record(&mut self, section: &'static str, d: Duration) {
+        if !self.enabled() {
+            return;
+        }
+        match self.sections.iter_mut().find(|(name, _)| *name == section) {
+            Some((_, stats)) => stats.record(d),
+            None => {
+                let mut stats = Stats::default();
+                stats.record(d);
+                self.sections.push((section, stats));
+            }
+        }
+    }
+
+    // (section, avg µs, max µs) for the live breakdown
+    pub fn summary(&self) -> Vec<(&'static str, u128, u128)> {
+        self.sections
+            .iter()
+            .map(|(name, s)| (*name, s.avg_us(), s.max.as_micros()))
+            .collect()
+    }
+
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "ai-intui frame profile");
+        let _ = writeln!(
+            out,
+            "session {:.1}s\n",
+            self.started.elapsed().as_secs_f64()
+        );
+        let _ = writeln!(
+            out,
+            "{:<14} {:>8} {:>10} {:>10} {:>10}",
+            "section", "count", "avg µs", "p95 µs", "max µs"
+        );
+        for (name, s) in &self.sections {
+            let _ = writeln!(
+                out,
+                "{:<14} {:>8} {:>10} {:>10} {:>10}",
+                name,
+                s.count,
+                s.avg_us(),
+                s.p95_us(),
+                s.max.as_micros()
+            );
+        }
+        out
+    }
+
+    pub fn write_report(&self) -> io::Result<()> {
+        match &self.output {
+            Some(path) => fs::write(path, self.report()),
+            None => Ok(()),
+        }
+    }
+}
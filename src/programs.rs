@@ -0,0 +1,20 @@
+use std::{collections::BTreeMap, path::PathBuf, process::Command};
+
+// Where the external programs sources run are: kubectl, docker, nvidia-smi,
+// journalctl, and ssh. `[programs]` names a path for any of them; the rest
+// are looked up on PATH.
+#[derive(Clone, Debug, Default)]
+pub struct Programs(BTreeMap<String, PathBuf>);
+
+impl Programs {
+    pub fn new(paths: BTreeMap<String, PathBuf>) -> Self {
+        Self(paths)
+    }
+
+    pub fn command(&self, name: &str) -> Command {
+        match self.0.get(name) {
+            Some(path) => Command::new(path),
+            None => Command::new(name),
+        }
+    }
+}
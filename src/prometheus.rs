@@ -0,0 +1,447 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Write},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    auth::{self, AuthSpec},
+    config::{value_enum, value_name},
+    datasource::{DataSource, Sample, Value, METRICS},
+    histogram::{self, Histogram},
+    http,
+    ingest::{IngestQueue, SourceSender},
+    logs::Level,
+    net::{authority, Remote},
+    reconnect::{Connector, Links},
+    Mode,
+};
+
+// What the scraper's link state and log lines are shown under
+pub const NAME: &str = "prometheus";
+const QUEUE: usize = 64;
+// Connecting, and each read of the response
+const TIMEOUT: Duration = Duration::from_secs(10);
+// Largest page read; a /metrics page is rarely more than a few megabytes
+const MAX_PAGE: u64 = 32 << 20;
+
+// `[prometheus]` in config.toml: a /metrics endpoint scraped on an interval,
+// and which of its metrics fill which row of a mode's panel. Checked as it's
+// read, so `ai-intui check` points at a bad URL or row.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct PrometheusSpec {
+    #[serde(deserialize_with = "url")]
+    pub url: String,
+    // seconds between scrapes
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+    // the panel it feeds; AI observability's unless set
+    #[serde(
+        default,
+        deserialize_with = "value_enum",
+        serialize_with = "value_name"
+    )]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<Mode>,
+    // row label → metric
+    #[serde(deserialize_with = "metrics")]
+    pub metrics: BTreeMap<String, Mapping>,
+    // `[prometheus.auth]`: for an endpoint that wants credentials
+    #[serde(default, deserialize_with = "auth::http")]
+    #[serde(skip_serializing_if = "AuthSpec::is_empty")]
+    pub auth: AuthSpec,
+}
+
+fn default_interval() -> u64 {
+    15
+}
+
+fn url<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    let url = String::deserialize(d)?;
+    Endpoint::parse(&url, &AuthSpec::default()).map_err(de::Error::custom)?;
+    Ok(url)
+}
+
+fn metrics<'de, D: Deserializer<'de>>(d: D) -> Result<BTreeMap<String, Mapping>, D::Error> {
+    let metrics = BTreeMap::<String, Mapping>::deserialize(d)?;
+    for (label, mapping) in &metrics {
+        Row::new(label, mapping).map_err(de::Error::custom)?;
+    }
+    Ok(metrics)
+}
+
+// A row's metric: a selector such as `job_queue_depth` or
+// `http_requests_total{code="500"}`, or a table that also scales it or reads
+// a counter as a per-minute rate
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Mapping {
+    Selector(String),
+    Table {
+        metric: String,
+        #[serde(default = "unscaled")]
+        scale: f64,
+        #[serde(default)]
+        rate: bool,
+    },
+}
+
+fn unscaled() -> f64 {
+    1.0
+}
+
+// The panel feed: whatever the scraper thread read since the last tick
+pub struct Prometheus {
+    url: String,
+    every: Duration,
+    rx: Receiver<Vec<Sample>>,
+}
+
+impl Prometheus {
+    // Checks the spec, then scrapes on its own reconnecting link
+    pub fn start(
+        spec: &PrometheusSpec,
+        links: &Links,
+        ingest: &IngestQueue,
+    ) -> Result<Self, String> {
+        let endpoint = Endpoint::parse(&spec.url, &spec.auth)?;
+        let rows = spec
+            .metrics
+            .iter()
+            .map(|(label, mapping)| Row::new(label, mapping))
+            .collect::<Result<Vec<_>, _>>()?;
+        let every = Duration::from_secs(spec.interval.max(1));
+        let (tx, rx) = mpsc::channel();
+        let scraper = Scraper {
+            endpoint,
+            every,
+            rows,
+            tx,
+        };
+        links.spawn(NAME, scraper, ingest.sender(NAME, QUEUE));
+        Ok(Self {
+            url: spec.url.clone(),
+            every,
+            rx,
+        })
+    }
+}
+
+impl DataSource for Prometheus {
+    fn poll(&mut self) -> Vec<Sample> {
+        self.rx.try_iter().flatten().collect()
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "Prometheus {}, scraped every {}s",
+            self.url,
+            self.every.as_secs()
+        )
+    }
+}
+
+struct Scraper {
+    endpoint: Endpoint,
+    every: Duration,
+    rows: Vec<Row>,
+    tx: Sender<Vec<Sample>>,
+}
+
+impl Connector for Scraper {
+    // the first page, fetched to tell a reachable endpoint from one that isn't
+    type Conn = String;
+
+    fn connect(&mut self) -> io::Result<String> {
+        self.endpoint.get()
+    }
+
+    // Scrapes until one fails, which hands the link back to the backoff
+    fn run(&mut self, mut page: String, tx: &SourceSender) -> io::Result<()> {
+        let mut first = true;
+        loop {
+            let now = Instant::now();
+            let series: Vec<Series> = page.lines().filter_map(parse_series).collect();
+            if first {
+                for row in self.rows.iter().filter(|row| !row.present(&series)) {
+                    tx.send(
+                        Level::Warn,
+                        format!("no `{}` on the page for {}", row.selector.name, row.label),
+                    );
+                }
+                first = false;
+            }
+            let samples = self
+                .rows
+                .iter_mut()
+                .filter_map(|row| {
+                    Some(Sample {
+                        metric: row.label.clone(),
+                        value: row.read(&series, now)?,
+                    })
+                })
+                .collect();
+            if self.tx.send(samples).is_err() {
+                return Ok(());
+            }
+            thread::sleep(self.every);
+            page = self.endpoint.get()?;
+        }
+    }
+}
+
+// One mapped row and, for a rate, the counter's last total
+struct Row {
+    label: String,
+    selector: Selector,
+    scale: f64,
+    rate: bool,
+    last: Option<(Instant, f64)>,
+}
+
+impl Row {
+    fn new(label: &str, mapping: &Mapping) -> Result<Self, String> {
+        if !METRICS
+            .iter()
+            .any(|m| *m == label || histogram::base(m) == label)
+        {
+            return Err(format!(
+                "no row `{label}` to map; rows are {}",
+                METRICS.join(", ")
+            ));
+        }
+        let (metric, scale, rate) = match mapping {
+            Mapping::Selector(metric) => (metric, 1.0, false),
+            Mapping::Table {
+                metric,
+                scale,
+                rate,
+            } => (metric, *scale, *rate),
+        };
+        Ok(Self {
+            label: label.to_string(),
+            selector: Selector::parse(metric)?,
+            scale,
+            rate,
+            last: None,
+        })
+    }
+
+    fn present(&self, series: &[Series]) -> bool {
+        let bucket = format!("{}_bucket", self.selector.name);
+        series.iter().any(|s| {
+            self.selector.matches(s, &self.selector.name) || self.selector.matches(s, &bucket)
+        })
+    }
+
+    // The sum of the matching series, or their buckets added up into one
+    // histogram; None when the page has neither, and for a rate's first scrape
+    fn read(&mut self, series: &[Series], now: Instant) -> Option<Value> {
+        let name = &self.selector.name;
+        let mut matching = series
+            .iter()
+            .filter(|s| self.selector.matches(s, name))
+            .peekable();
+        if matching.peek().is_some() {
+            let total: f64 = matching.map(|s| s.value).sum();
+            if !self.rate {
+                return Some(Value::Gauge(total * self.scale));
+            }
+            let (then, before) = self.last.replace((now, total))?;
+            let secs = now.duration_since(then).as_secs_f64();
+            // a counter that went down was reset; the next scrape has a rate again
+            if total < before || secs <= 0.0 {
+                return None;
+            }
+            return Some(Value::Gauge((total - before) / secs * 60.0 * self.scale));
+        }
+
+        let bucket = format!("{name}_bucket");
+        let mut buckets: Vec<(f64, f64)> = Vec::new();
+        for s in series.iter().filter(|s| self.selector.matches(s, &bucket)) {
+            let Some(le) = s.label("le").and_then(|le| le.parse::<f64>().ok()) else {
+                continue;
+            };
+            let le = le * self.scale;
+            match buckets.iter_mut().find(|b| b.0 == le) {
+                Some(b) => b.1 += s.value,
+                None => buckets.push((le, s.value)),
+            }
+        }
+        Histogram::new(buckets).map(Value::Histogram)
+    }
+}
+
+// `name{label="value",...}` as written in the config
+struct Selector {
+    name: String,
+    labels: Vec<(String, String)>,
+}
+
+impl Selector {
+    fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let bad = || format!("can't read metric selector `{s}`");
+        let (name, labels) = match s.split_once('{') {
+            Some((name, rest)) => match parse_labels(rest) {
+                Some((labels, "")) => (name, labels),
+                _ => return Err(bad()),
+            },
+            None => (s, Vec::new()),
+        };
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(bad());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            labels,
+        })
+    }
+
+    // Named `name` and carrying every label the selector asks for
+    fn matches(&self, series: &Series, name: &str) -> bool {
+        series.name == name && self.labels.iter().all(|l| series.labels.contains(l))
+    }
+}
+
+// One sample line of the text exposition format
+struct Series {
+    name: String,
+    labels: Vec<(String, String)>,
+    value: f64,
+}
+
+impl Series {
+    fn label(&self, key: &str) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+// `name{labels} value [timestamp]`; comments, blank and malformed lines are
+// None
+fn parse_series(line: &str) -> Option<Series> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let end = line.find(|c: char| c == '{' || c.is_whitespace())?;
+    let (labels, rest) = match line[end..].strip_prefix('{') {
+        Some(rest) => parse_labels(rest)?,
+        None => (Vec::new(), &line[end..]),
+    };
+    Some(Series {
+        name: line[..end].to_string(),
+        labels,
+        value: rest.split_whitespace().next()?.parse().ok()?,
+    })
+}
+
+// `a="x",b="y\"z"}` after the opening brace: the labels, and whatever
+// follows the closing one
+fn parse_labels(s: &str) -> Option<(Vec<(String, String)>, &str)> {
+    let mut labels = Vec::new();
+    let mut rest = s.trim_start();
+    loop {
+        if let Some(after) = rest.strip_prefix('}') {
+            return Some((labels, after));
+        }
+        let (key, value) = rest.split_once('=')?;
+        let value = value.trim_start().strip_prefix('"')?;
+        let mut text = String::new();
+        let mut chars = value.char_indices();
+        let close = loop {
+            match chars.next()? {
+                (_, '\\') => match chars.next()?.1 {
+                    'n' => text.push('\n'),
+                    c => text.push(c),
+                },
+                (i, '"') => break i,
+                (_, c) => text.push(c),
+            }
+        };
+        labels.push((key.trim().to_string(), text));
+        rest = value[close + 1..].trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+}
+
+// Where to scrape, and the headers that go with each request
+struct Endpoint {
+    remote: Remote,
+    path: String,
+    headers: String,
+}
+
+impl Endpoint {
+    // https only in builds with TLS
+    fn parse(url: &str, auth: &AuthSpec) -> Result<Self, String> {
+        let (secure, port) = match url {
+            u if u.starts_with("http://") => (false, 80),
+            u if u.starts_with("https://") => (true, 443),
+            u => return Err(format!("expected an http:// or https:// URL, got `{u}`")),
+        };
+        let (authority, path) = authority(url, port)?;
+        Ok(Self {
+            remote: Remote::new(authority, secure, auth)?,
+            path: match path {
+                "" => "/metrics".to_string(),
+                path => path.to_string(),
+            },
+            headers: auth.header_lines(),
+        })
+    }
+
+    // GET over HTTP/1.0, so the server closes the connection after a page
+    // it doesn't need to chunk
+    fn get(&self) -> io::Result<String> {
+        let mut stream = self.remote.dial(TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        write!(
+            stream,
+            "GET {} HTTP/1.0\r\nHost: {}\r\nAccept: text/plain\r\nUser-Agent: ai-intui/{}\r\n{}\r\n",
+            self.path,
+            self.remote.authority.written,
+            env!("CARGO_PKG_VERSION"),
+            self.headers
+        )?;
+        let mut response = Vec::new();
+        match stream.take(MAX_PAGE).read_to_end(&mut response) {
+            // a TLS server may hang up without a close_notify once it's
+            // sent the page
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof && !response.is_empty() => {}
+            result => {
+                result?;
+            }
+        }
+
+        let split = response
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed HTTP response"))?;
+        let head = String::from_utf8_lossy(&response[..split]).to_ascii_lowercase();
+        let body = &response[split + 4..];
+        let status = head.lines().next().unwrap_or_default();
+        if status.split_whitespace().nth(1) != Some("200") {
+            return Err(io::Error::other(format!("scrape answered `{status}`")));
+        }
+        let chunked = head
+            .lines()
+            .any(|h| h.starts_with("transfer-encoding:") && h.contains("chunked"));
+        let body = match chunked {
+            // some servers chunk even for HTTP/1.0
+            true => http::dechunk(body).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "malformed chunked body")
+            })?,
+            false => body.to_vec(),
+        };
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::{SocketAddr, TcpListener},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use crate::{
+    datasource::{DataSource, Sample, Value, METRICS},
+    histogram,
+    hosts::parse_metric,
+    http::{self, Reply, Request},
+    ingest::{IngestQueue, SourceSender},
+    layout::sanitize,
+    marks,
+    parse::parse_line,
+};
+
+// What `POST /log` lines show under without `?source=`
+pub const NAME: &str = "http";
+// Lines buffered per source before the oldest are dropped
+const QUEUE: usize = 1024;
+// Largest request body read
+const MAX_BODY: usize = 1 << 20;
+// Reading a request, and writing the reply
+const TIMEOUT: Duration = Duration::from_secs(10);
+// `?source=` names shown as given; past this many, new ones go under NAME so
+// a client naming every request differently can't grow the list without end
+const MAX_TAGS: usize = 64;
+
+// `--http`: `POST /log` takes log lines, plain or JSON like `--follow`, and
+// `POST /metric` takes samples in the `--host` agent's format, one JSON
+// object per line, for the AI observability panel's rows:
+// `curl -d '{"metric": "queue depth", "value": 12}' http://127.0.0.1:8080/metric`
+// `POST /mark` drops a marker, as it does on --mark-listen.
+pub struct Push {
+    addr: SocketAddr,
+    rx: Receiver<Vec<Sample>>,
+}
+
+// Senders for every `?source=` seen, shared by the connection threads
+struct Tags {
+    ingest: IngestQueue,
+    senders: Mutex<HashMap<String, SourceSender>>,
+}
+
+impl Tags {
+    fn sender(&self, name: &str) -> SourceSender {
+        let mut senders = self.senders.lock().unwrap();
+        let name = match senders.contains_key(name) || senders.len() < MAX_TAGS {
+            true => name,
+            false => NAME,
+        };
+        senders
+            .entry(name.to_string())
+            .or_insert_with_key(|name| self.ingest.sender(name, QUEUE))
+            .clone()
+    }
+}
+
+impl Push {
+    pub fn listen(
+        addr: SocketAddr,
+        ingest: IngestQueue,
+        marks: Sender<String>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let (tx, rx) = mpsc::channel();
+        let tags = Tags {
+            ingest,
+            senders: Mutex::new(HashMap::new()),
+        };
+        http::serve(listener, TIMEOUT, move |mut stream, mut reader| {
+            let request = match Request::read(&mut reader)? {
+                Ok(request) => request,
+                Err(reply) => return http::reply_text(&mut stream, reply),
+            };
+            let reply = match request.path() {
+                "/mark" => marks::mark(&request, &mut reader, &marks)?,
+                "/log" | "/metric" if request.method != "POST" => {
+                    ("405 Method Not Allowed", "use POST".to_string())
+                }
+                "/log" | "/metric" => match request.body(&mut reader, MAX_BODY)? {
+                    None => ("413 Payload Too Large", format!("over {MAX_BODY} bytes")),
+                    Some(body) => {
+                        let body = String::from_utf8_lossy(&body);
+                        match request.path() {
+                            "/log" => ("200 OK", log(&body, &request, &tags)),
+                            _ => metric(&body, &tx),
+                        }
+                    }
+                },
+                _ => (
+                    "404 Not Found",
+                    "only /log, /metric, and /mark are served".to_string(),
+                ),
+            };
+            http::reply_text(&mut stream, reply)
+        });
+        Ok(Self { addr, rx })
+    }
+}
+
+impl DataSource for Push {
+    fn poll(&mut self) -> Vec<Sample> {
+        self.rx.try_iter().flatten().collect()
+    }
+
+    fn describe(&self) -> String {
+        format!("HTTP push API on http://{}", self.addr)
+    }
+}
+
+// Every line of the body under `?source=`, or NAME
+fn log(body: &str, request: &Request, tags: &Tags) -> String {
+    let source = request
+        .param("source")
+        .map(|s| sanitize(s.trim()).into_owned())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| NAME.to_string());
+    let lines: Vec<_> = body
+        .lines()
+        .filter_map(parse_line)
+        .map(|p| (p.level, p.message))
+        .collect();
+    let n = lines.len();
+    if n > 0 {
+        tags.sender(&source).send_batch(lines);
+    }
+    format!("logged {n} line{}", if n == 1 { "" } else { "s" })
+}
+
+// Every line is checked before any is shown, so a bad batch changes nothing
+fn metric(body: &str, tx: &Sender<Vec<Sample>>) -> Reply {
+    let mut samples = Vec::new();
+    for (i, line) in body.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some((name, value)) = parse_metric(line) else {
+            return (
+                "400 Bad Request",
+                format!(
+                    "line {}: expected {{\"metric\": NAME, \"value\": N}} or \
+                     {{\"metric\": NAME, \"buckets\": [[LE, COUNT], ...]}}",
+                    i + 1
+                ),
+            );
+        };
+        let Some(metric) = row(&name, &value) else {
+            return (
+                "422 Unprocessable Entity",
+                format!("`{name}` fills no row; rows are {}", METRICS.join(", ")),
+            );
+        };
+        samples.push(Sample { metric, value });
+    }
+    let reply = match &samples[..] {
+        [] => "nothing to set".to_string(),
+        [one] => format!("set {}", one.metric),
+        many => format!("set {} rows", many.len()),
+    };
+    if !samples.is_empty() {
+        // the app is gone if this fails; nobody is left to tell
+        let _ = tx.send(samples);
+    }
+    ("200 OK", reply)
+}
+
+// The row a sample fills, by name the way `--otlp` matches them: a value
+// `queue.depth` fills queue depth and a histogram `latency` fills latency p95
+pub(crate) fn row(name: &str, value: &Value) -> Option<String> {
+    let spelled = name.to_lowercase().replace(['.', '_'], " ");
+    match value {
+        Value::Histogram(_) => METRICS
+            .iter()
+            .map(|m| histogram::base(m))
+            .find(|base| *base == spelled && !METRICS.contains(base))
+            .map(str::to_string),
+        Value::Gauge(_) => METRICS.contains(&spelled.as_str()).then_some(spelled),
+    }
+}
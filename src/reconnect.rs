@@ -0,0 +1,192 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use rand::Rng;
+
+use crate::{applog, ingest::SourceSender, logs::Level};
+
+const BACKOFF_BASE: Duration = Duration::from_millis(500);
+const BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+// Exponential backoff with jitter: each delay is drawn from the upper half of
+// base * 2^attempt (capped), so sources that dropped together don't all
+// reconnect in the same instant
+pub struct Backoff {
+    attempt: u32,
+}
+
+impl Backoff {
+    pub fn new() -> Self {
+        Self { attempt: 0 }
+    }
+
+    pub fn next_delay(&mut self) -> Duration {
+        let ceiling = BACKOFF_BASE
+            .saturating_mul(1 << self.attempt.min(16))
+            .min(BACKOFF_MAX);
+        self.attempt = self.attempt.saturating_add(1);
+        ceiling.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+// A network-backed source: connect, then pump lines until the connection drops
+pub trait Connector: Send + 'static {
+    type Conn;
+
+    fn connect(&mut self) -> io::Result<Self::Conn>;
+
+    // Returns when the peer goes away; Ok for a clean close, Err otherwise
+    fn run(&mut self, conn: Self::Conn, tx: &SourceSender) -> io::Result<()>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkState {
+    Connecting,
+    Connected,
+    // waiting out a backoff delay before the next attempt
+    Retrying { attempt: u32 },
+}
+
+impl LinkState {
+    pub fn label(self) -> String {
+        match self {
+            LinkState::Connecting => "connecting".to_string(),
+            LinkState::Connected => "connected".to_string(),
+            LinkState::Retrying { attempt } => format!("retrying (attempt {attempt})"),
+        }
+    }
+}
+
+struct Link {
+    state: LinkState,
+    // set by `source retry` to cut the current backoff short
+    retry_now: bool,
+    // which thread it belongs to, so one closed and opened again under the
+    // same name isn't taken over by the old thread
+    id: u64,
+}
+
+#[derive(Default)]
+struct Shared {
+    links: Mutex<BTreeMap<Arc<str>, Link>>,
+    wake: Condvar,
+    spawned: AtomicU64,
+}
+
+// Connection state of every network-backed source, shared between the
+// supervisor threads and the UI
+#[derive(Clone, Default)]
+pub struct Links {
+    shared: Arc<Shared>,
+}
+
+impl Links {
+    // Runs `connector` on its own thread, reconnecting with backoff until the
+    // process exits or the link is closed. State changes are logged through
+    // `tx` under the source's own name.
+    pub fn spawn<C: Connector>(&self, name: &str, mut connector: C, tx: SourceSender) {
+        let name: Arc<str> = Arc::from(name);
+        let id = self.shared.spawned.fetch_add(1, Ordering::Relaxed);
+        self.shared.links.lock().unwrap().insert(
+            name.clone(),
+            Link {
+                state: LinkState::Connecting,
+                retry_now: false,
+                id,
+            },
+        );
+        // a closed link stays gone
+        let shared = self.shared.clone();
+        let key = name.clone();
+        let set = move |state| {
+            let mut links = shared.links.lock().unwrap();
+            if let Some(link) = links.get_mut(&key).filter(|l| l.id == id) {
+                link.state = state;
+            }
+        };
+
+        let shared = self.shared.clone();
+        thread::spawn(move || {
+            let mut backoff = Backoff::new();
+            loop {
+                let result = connector.connect().and_then(|conn| {
+                    set(LinkState::Connected);
+                    applog::write(Level::Info, &name, "connected");
+                    tx.send(Level::Info, "connected".to_string());
+                    backoff.reset();
+                    connector.run(conn, &tx)
+                });
+                // closed while it ran: the thread goes with it
+                let links = shared.links.lock().unwrap();
+                if links.get(&name).filter(|l| l.id == id).is_none() {
+                    return;
+                }
+                drop(links);
+                let attempt = backoff.attempt + 1;
+                let delay = backoff.next_delay();
+                set(LinkState::Retrying { attempt });
+                let why = match result {
+                    Ok(()) => "connection closed".to_string(),
+                    Err(e) => e.to_string(),
+                };
+                let msg = format!("{why}; retrying in {:.1}s", delay.as_secs_f32());
+                applog::write(Level::Warn, &name, &msg);
+                tx.send(Level::Warn, msg);
+
+                // sleep out the delay unless `source retry` wakes us first
+                let links = shared.links.lock().unwrap();
+                let (mut links, _) = shared
+                    .wake
+                    .wait_timeout_while(links, delay, |links| {
+                        links.get(&name).is_some_and(|l| l.id == id && !l.retry_now)
+                    })
+                    .unwrap();
+                match links.get_mut(&name).filter(|l| l.id == id) {
+                    Some(link) => {
+                        link.retry_now = false;
+                        link.state = LinkState::Connecting;
+                    }
+                    None => return,
+                }
+            }
+        });
+    }
+
+    // Skips the remaining backoff for `name` if it's waiting to reconnect;
+    // returns the state it was in, or None if there's no such source
+    pub fn retry(&self, name: &str) -> Option<LinkState> {
+        let mut links = self.shared.links.lock().unwrap();
+        let link = links.get_mut(name)?;
+        if let LinkState::Retrying { .. } = link.state {
+            link.retry_now = true;
+            self.shared.wake.notify_all();
+        }
+        Some(link.state)
+    }
+
+    // Forgets `name`: its thread stops once its connector's `run` returns, or
+    // at once if it's waiting to reconnect. The connector has to be told to
+    // return on its own.
+    pub fn close(&self, name: &str) {
+        self.shared.links.lock().unwrap().remove(name);
+        self.shared.wake.notify_all();
+    }
+
+    // (name, state) for every source, sorted by name
+    pub fn states(&self) -> Vec<(Arc<str>, LinkState)> {
+        let links = self.shared.links.lock().unwrap();
+        links.iter().map(|(n, l)| (n.clone(), l.state)).collect()
+    }
+}
@@ -0,0 +1,181 @@
+use std::borrow::Cow;
+
+use regex::Regex;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+// What a match is shown as when a rule doesn't say
+const MASK: &str = "[redacted]";
+
+// `[redact]` in config.toml: what's masked in every log line before it's
+// shown, searched, reported, or written to --log-file, so the dashboard can
+// be shared. Built-in maskers go first, then `keys`, then `rules` in order.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RedactSpec {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mask: Vec<Builtin>,
+    // JSON fields and `key=value` pairs whose values are masked
+    #[serde(
+        default,
+        deserialize_with = "keys",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub keys: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Builtin {
+    Emails,
+    // bearer tokens, JWTs, and API keys in the shapes the big providers use
+    Tokens,
+    // IPv4 and IPv6 addresses
+    Ips,
+}
+
+impl Builtin {
+    fn rules(self) -> Vec<Rule> {
+        let patterns: &[(&str, &str)] = match self {
+            Builtin::Emails => &[(
+                r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}",
+                "[email]",
+            )],
+            Builtin::Tokens => &[
+                (r"(?i)\b(bearer|basic)\s+[A-Za-z0-9._~+/-]+=*", "$1 [token]"),
+                (
+                    r"\beyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+",
+                    "[token]",
+                ),
+                (
+                    r"\b(?:sk|pk|rk)-[A-Za-z0-9_-]{16,}|\bgh[pousr]_[A-Za-z0-9]{20,}|\bxox[abprs]-[A-Za-z0-9-]{10,}|\bAKIA[0-9A-Z]{16}\b",
+                    "[token]",
+                ),
+            ],
+            Builtin::Ips => &[
+                (r"\b(?:\d{1,3}\.){3}\d{1,3}\b", "[ip]"),
+                // full, or with a `::`, so clock times like 12:40:05 are left alone
+                (
+                    r"\b(?:[0-9A-Fa-f]{1,4}:){7}[0-9A-Fa-f]{1,4}\b|(?:\b[0-9A-Fa-f]{1,4})?(?::[0-9A-Fa-f]{1,4})*::(?:[0-9A-Fa-f]{1,4}:)*[0-9A-Fa-f]{1,4}\b",
+                    "[ip]",
+                ),
+            ],
+        };
+        patterns
+            .iter()
+            .map(|(pattern, with)| Rule {
+                pattern: Regex::new(pattern).expect("built-in pattern"),
+                with: with.to_string(),
+            })
+            .collect()
+    }
+}
+
+fn keys<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<String>, D::Error> {
+    let keys = Vec::<String>::deserialize(d)?;
+    if keys.iter().any(|k| k.trim().is_empty()) {
+        return Err(de::Error::custom("keys can't be empty"));
+    }
+    Ok(keys)
+}
+
+impl RedactSpec {
+    // Every rule, in the order they're applied
+    pub fn rules(&self) -> Vec<Rule> {
+        let mut rules: Vec<Rule> = self.mask.iter().flat_map(|b| b.rules()).collect();
+        if !self.keys.is_empty() {
+            let names = self
+                .keys
+                .iter()
+                .map(|k| regex::escape(k.trim()))
+                .collect::<Vec<_>>()
+                .join("|");
+            // `"password": "..."` or a number in JSON, then `password=...`
+            // or `password: ...` anywhere else
+            let json = format!(r#"(?i)("(?:{names})"\s*:\s*)(?:"(?:[^"\\]|\\.)*"|[-\d.eE+]+)"#);
+            let plain = format!(
+                r#"(?i)\b((?:{names})\s*[=:]\s*)(?:(?:bearer|basic)\s+)?(?:"[^"]*"|'[^']*'|[^\s,;&"']+)"#
+            );
+            for (pattern, with) in [
+                (json, format!(r#"${{1}}"{MASK}""#)),
+                (plain, format!("${{1}}{MASK}")),
+            ] {
+                rules.push(Rule {
+                    pattern: Regex::new(&pattern).expect("escaped keys"),
+                    with,
+                });
+            }
+        }
+        rules.extend(self.rules.iter().cloned());
+        rules
+    }
+
+    // For the startup line, e.g. `emails, ips, 2 keys, 1 rule`
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<String> = self
+            .mask
+            .iter()
+            .map(|b| format!("{b:?}").to_lowercase())
+            .collect();
+        let plural = |n: usize, what: &str| format!("{n} {what}{}", if n == 1 { "" } else { "s" });
+        if !self.keys.is_empty() {
+            parts.push(plural(self.keys.len(), "key"));
+        }
+        if !self.rules.is_empty() {
+            parts.push(plural(self.rules.len(), "rule"));
+        }
+        parts.join(", ")
+    }
+}
+
+// One `redact` rule: text matching `pattern` is shown as `with`, which may
+// refer to the pattern's groups as `$1` or `$name`
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pattern: Regex,
+    with: String,
+}
+
+// The rule as written in config.toml
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct Written {
+    pattern: String,
+    #[serde(default = "mask")]
+    with: String,
+}
+
+fn mask() -> String {
+    MASK.to_string()
+}
+
+impl<'de> Deserialize<'de> for Rule {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let Written { pattern, with } = Written::deserialize(d)?;
+        let pattern = Regex::new(&pattern)
+            .map_err(|e| de::Error::custom(format!("bad pattern `{pattern}`: {e}")))?;
+        Ok(Self { pattern, with })
+    }
+}
+
+impl Serialize for Rule {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        Written {
+            pattern: self.pattern.as_str().to_string(),
+            with: self.with.clone(),
+        }
+        .serialize(s)
+    }
+}
+
+// `text` with every rule applied in turn; borrowed when nothing matched
+pub fn apply<'a>(rules: &[Rule], text: &'a str) -> Cow<'a, str> {
+    let mut text = Cow::Borrowed(text);
+    for rule in rules {
+        if let Cow::Owned(masked) = rule.pattern.replace_all(&text, rule.with.as_str()) {
+            text = Cow::Owned(masked);
+        }
+    }
+    text
+}
@@ -0,0 +1,177 @@
+use std::{fmt::Write, fs, io, path::Path, time::Duration};
+
+use chrono::{DateTime, FixedOffset};
+use humantime::format_duration;
+
+// Caps so a long or noisy session still gives a pasteable document
+const MAX_SAMPLES: u32 = 10_000;
+const MAX_EVENTS: usize = 50;
+
+// One metric's spread over the time it was on screen
+pub struct MetricSummary {
+    pub label: &'static str,
+    pub min: String,
+    pub avg: String,
+    pub max: String,
+}
+
+// An alert or a log line worth calling out, already rendered for display
+pub struct Incident {
+    pub at: String,
+    pub source: String,
+    pub message: String,
+}
+
+// A network source's availability over the session
+pub struct Uptime {
+    pub name: String,
+    pub uptime: String,
+    // (started at, how long); the last one is still going if `still_down`
+    pub outages: Vec<(String, String)>,
+    pub still_down: bool,
+}
+
+// Everything `report PATH` writes; main gathers it, this renders it as Markdown
+pub struct Report {
+    pub started: DateTime<FixedOffset>,
+    pub ended: DateTime<FixedOffset>,
+    pub duration: Duration,
+    // (panel heading, its metrics)
+    pub panels: Vec<(String, Vec<MetricSummary>)>,
+    pub markers: Vec<(String, String)>,
+    pub availability: Vec<Uptime>,
+    // [[maintenance]] windows that began: (name, start, end if it ended)
+    pub maintenance: Vec<(String, String, Option<String>)>,
+    // every alert that fired, those a window held marked as such
+    pub alerts: Vec<Incident>,
+    // error and warning lines from sources
+    pub anomalies: Vec<Incident>,
+    pub excerpt: Vec<String>,
+}
+
+// (seconds, value) pairs about once a second across `spans`, thinned out for
+// long sessions
+pub fn sample(spans: &[(Duration, Duration)], value: impl Fn(Duration) -> f32) -> Vec<(f64, f64)> {
+    let total: Duration = spans.iter().map(|(start, end)| *end - *start).sum();
+    let step = Duration::from_secs(1).max(total / MAX_SAMPLES);
+    let mut points = Vec::new();
+    for &(start, end) in spans {
+        let mut at = start;
+        while at <= end {
+            points.push((at.as_secs_f64(), f64::from(value(at))));
+            at += step;
+        }
+    }
+    points
+}
+
+// `|` would end a table cell early
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+impl Report {
+    pub fn markdown(&self) -> String {
+        let stamp = "%Y-%m-%d %H:%M:%S %:z";
+        let mut out = String::new();
+        let _ = writeln!(out, "# ai-intui session report\n");
+        let _ = writeln!(out, "- Started: {}", self.started.format(stamp));
+        let _ = writeln!(out, "- Ended: {}", self.ended.format(stamp));
+        let duration = Duration::from_secs(self.duration.as_secs());
+        let _ = writeln!(out, "- Duration: {}", format_duration(duration));
+
+        for (heading, metrics) in &self.panels {
+            let _ = writeln!(out, "\n## {heading}\n");
+            let _ = writeln!(out, "| Metric | Min | Avg | Max |");
+            let _ = writeln!(out, "|---|---:|---:|---:|");
+            for m in metrics {
+                let _ = writeln!(out, "| {} | {} | {} | {} |", m.label, m.min, m.avg, m.max);
+            }
+        }
+
+        if !self.markers.is_empty() {
+            let _ = writeln!(out, "\n## Markers\n");
+            for (name, at) in &self.markers {
+                let _ = writeln!(out, "- {at} `{name}`");
+            }
+        }
+
+        if !self.availability.is_empty() {
+            let _ = writeln!(out, "\n## Availability\n");
+            let _ = writeln!(out, "| Source | Uptime | Outages |");
+            let _ = writeln!(out, "|---|---:|---:|");
+            for u in &self.availability {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} |",
+                    cell(&u.name),
+                    u.uptime,
+                    u.outages.len()
+                );
+            }
+            for u in self.availability.iter().filter(|u| !u.outages.is_empty()) {
+                let _ = writeln!(out, "\n`{}` was down:\n", u.name);
+                let skipped = u.outages.len().saturating_sub(MAX_EVENTS);
+                for (i, (at, length)) in u.outages.iter().enumerate().skip(skipped) {
+                    let ongoing = if u.still_down && i + 1 == u.outages.len() {
+                        ", still down at the end"
+                    } else {
+                        ""
+                    };
+                    let _ = writeln!(out, "- {at} for {length}{ongoing}");
+                }
+                if skipped > 0 {
+                    let _ = writeln!(out, "\n{skipped} earlier outages left out.");
+                }
+            }
+        }
+
+        if !self.maintenance.is_empty() {
+            let _ = writeln!(out, "\n## Maintenance\n");
+            for (name, start, end) in &self.maintenance {
+                let end = end.as_deref().unwrap_or("still on at the end");
+                let _ = writeln!(out, "- {start} to {end} `{name}`");
+            }
+        }
+
+        for (heading, events) in [("Alerts", &self.alerts), ("Anomalies", &self.anomalies)] {
+            let _ = writeln!(out, "\n## {heading}\n");
+            if events.is_empty() {
+                let _ = writeln!(out, "None.");
+                continue;
+            }
+            let _ = writeln!(out, "| Time | Source | Message |");
+            let _ = writeln!(out, "|---|---|---|");
+            let skipped = events.len().saturating_sub(MAX_EVENTS);
+            for e in &events[skipped..] {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} |",
+                    e.at,
+                    cell(&e.source),
+                    cell(&e.message)
+                );
+            }
+            if skipped > 0 {
+                let _ = writeln!(out, "\n{skipped} earlier lines left out.");
+            }
+        }
+
+        if !self.excerpt.is_empty() {
+            let _ = writeln!(out, "\n## Recent log lines\n");
+            let _ = writeln!(out, "```");
+            for line in &self.excerpt {
+                let _ = writeln!(out, "{line}");
+            }
+            let _ = writeln!(out, "```");
+        }
+        out
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.markdown())
+    }
+}
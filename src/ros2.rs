@@ -0,0 +1,646 @@
+use std::{collections::BTreeMap, sync::mpsc::Receiver};
+
+use ratatui::{prelude::*, widgets::Paragraph};
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    auth::{self, AuthSpec},
+    chrome::Chrome,
+    datasource::{DataSource, Sample, METRICS},
+    glyphs::GlyphSet,
+    ingest::IngestQueue,
+    layout::{fit, sanitize},
+    reconnect::Links,
+    websocket::Endpoint,
+};
+
+// `[ros2]` in config.toml: a rosbridge server, the topics it relays, and
+// which topics fill which row of robotics mode's panel. Checked as it's
+// read, like `[mqtt]`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Ros2Spec {
+    #[serde(default = "rosbridge", deserialize_with = "url")]
+    pub url: String,
+    #[serde(default = "standard", deserialize_with = "names")]
+    pub topics: Vec<String>,
+    // row label → topic with a numeric `data`, like std_msgs/Float64
+    #[serde(default, deserialize_with = "metrics")]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, String>,
+    // `[ros2.auth]`: for a bridge behind a proxy that wants credentials
+    #[serde(default, deserialize_with = "auth::http")]
+    #[serde(skip_serializing_if = "AuthSpec::is_empty")]
+    pub auth: AuthSpec,
+}
+
+fn rosbridge() -> String {
+    "ws://127.0.0.1:9090".to_string()
+}
+
+fn standard() -> Vec<String> {
+    vec!["/joint_states".to_string(), "/diagnostics".to_string()]
+}
+
+fn url<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    let url = String::deserialize(d)?;
+    Endpoint::parse(&url, &AuthSpec::default()).map_err(de::Error::custom)?;
+    Ok(url)
+}
+
+// Absolute names only: rosbridge doesn't know our namespace
+fn topic(name: &str) -> Result<(), String> {
+    match name.starts_with('/') && name.len() > 1 && !name.contains(char::is_whitespace) {
+        true => Ok(()),
+        false => Err(format!(
+            "`{name}` isn't a topic name; write it like `/joint_states`"
+        )),
+    }
+}
+
+fn names<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<String>, D::Error> {
+    let topics = Vec::<String>::deserialize(d)?;
+    for name in &topics {
+        topic(name).map_err(de::Error::custom)?;
+    }
+    Ok(topics)
+}
+
+fn metrics<'de, D: Deserializer<'de>>(d: D) -> Result<BTreeMap<String, String>, D::Error> {
+    let metrics = BTreeMap::<String, String>::deserialize(d)?;
+    for (label, name) in &metrics {
+        if !METRICS.contains(&label.as_str()) {
+            return Err(de::Error::custom(format!(
+                "no row `{label}` to map; rows are {}",
+                METRICS.join(", ")
+            )));
+        }
+        topic(name).map_err(de::Error::custom)?;
+    }
+    Ok(metrics)
+}
+
+// One joint out of sensor_msgs/JointState; a publisher may leave any of
+// the three out
+#[derive(Clone, Debug, PartialEq)]
+pub struct Joint {
+    pub name: String,
+    pub position: Option<f64>,
+    pub velocity: Option<f64>,
+    pub effort: Option<f64>,
+}
+
+// One diagnostic_msgs/DiagnosticStatus
+#[derive(Clone, Debug, PartialEq)]
+pub struct Status {
+    pub name: String,
+    pub level: u8,
+    pub message: String,
+}
+
+// DiagnosticStatus levels, as the log and the strip show them
+fn level_name(level: u8) -> &'static str {
+    match level {
+        0 => "OK",
+        1 => "WARN",
+        2 => "ERROR",
+        _ => "STALE",
+    }
+}
+
+// For ordering: ERROR, then STALE, then WARN, then OK
+fn severity(level: u8) -> u8 {
+    match level {
+        2 => 3,
+        3 => 2,
+        level => level,
+    }
+}
+
+fn level_style(level: u8) -> Style {
+    Style::default().fg(match level {
+        0 => Color::Green,
+        1 => Color::Yellow,
+        2 => Color::Red,
+        _ => Color::DarkGray,
+    })
+}
+
+// What the client sends the strip; without the feature there's no client
+#[cfg_attr(not(feature = "ros2"), allow(dead_code))]
+enum Update {
+    Joints(Vec<Joint>),
+    Diagnostics(Vec<Status>),
+}
+
+// [ros2]: the latest joint states and diagnostics, for robotics mode's strip
+pub struct Ros2 {
+    pub url: String,
+    // in the order they were first published
+    pub joints: Vec<Joint>,
+    pub diagnostics: Vec<Status>,
+    rx: Receiver<Update>,
+}
+
+// The robotics panel feed: numbers published on mapped topics since the
+// last tick
+pub struct Bridge {
+    url: String,
+    rx: Receiver<Vec<Sample>>,
+}
+
+impl Ros2 {
+    // Checks the spec, then subscribes on its own reconnecting link
+    pub fn start(
+        spec: &Ros2Spec,
+        links: &Links,
+        ingest: &IngestQueue,
+    ) -> Result<(Self, Bridge), String> {
+        let (rx, samples_rx) = sys::subscribe(spec, links, ingest)?;
+        let ros2 = Self {
+            url: spec.url.clone(),
+            joints: Vec::new(),
+            diagnostics: Vec::new(),
+            rx,
+        };
+        let bridge = Bridge {
+            url: spec.url.clone(),
+            rx: samples_rx,
+        };
+        Ok((ros2, bridge))
+    }
+
+    // A JointState may name only some joints, and a DiagnosticArray only
+    // some statuses; the rest keep their last values
+    pub fn drain(&mut self) {
+        while let Ok(update) = self.rx.try_recv() {
+            match update {
+                Update::Joints(joints) => merge(&mut self.joints, joints, |j| &j.name),
+                Update::Diagnostics(statuses) => {
+                    merge(&mut self.diagnostics, statuses, |s| &s.name)
+                }
+            }
+        }
+    }
+}
+
+fn merge<T>(kept: &mut Vec<T>, new: Vec<T>, name: impl Fn(&T) -> &String) {
+    for item in new {
+        match kept.iter_mut().find(|k| name(k) == name(&item)) {
+            Some(k) => *k = item,
+            None => kept.push(item),
+        }
+    }
+}
+
+impl DataSource for Bridge {
+    fn poll(&mut self) -> Vec<Sample> {
+        self.rx.try_iter().flatten().collect()
+    }
+
+    fn describe(&self) -> String {
+        format!("ROS 2 via rosbridge {}", self.url)
+    }
+}
+
+// Robotics mode's strip: joints with their effort as a bar, next to the
+// diagnostics, worst first
+pub fn draw(buf: &mut Buffer, area: Rect, ros2: &Ros2, glyphs: GlyphSet, chrome: Chrome) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let split = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(area);
+    let block = |title: String| {
+        chrome.block().border_style(dim).title(Span::styled(
+            title,
+            Style::default()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+        ))
+    };
+
+    // name, position, velocity, effort, and the effort against the largest
+    let joints = block(format!("joints • {}", ros2.joints.len()));
+    let inner = joints.inner(split[0]);
+    joints.render(split[0], buf);
+    let width = inner.width as usize;
+    let name_w = width.saturating_sub(28 + 12).clamp(6, 16);
+    let bar_len = width.saturating_sub(name_w + 28);
+    let number = |n: Option<f64>| n.map_or("-".to_string(), |n| format!("{n:+.2}"));
+    let strongest = ros2
+        .joints
+        .iter()
+        .filter_map(|j| j.effort)
+        .fold(0.0, |max: f64, e| max.max(e.abs()));
+    let mut lines = vec![Line::styled(
+        format!(
+            "{:<name_w$} {:>8} {:>8} {:>8}",
+            "joint", "pos", "vel", "effort"
+        ),
+        dim,
+    )];
+    let rows = (inner.height as usize).saturating_sub(1);
+    for joint in ros2.joints.iter().take(rows) {
+        let name = sanitize(&joint.name);
+        let (name, pad) = fit(&name, name_w);
+        let share = match (joint.effort, strongest > 0.0) {
+            (Some(effort), true) => (effort.abs() / strongest) as f32,
+            _ => 0.0,
+        };
+        let (full, partial, _) = glyphs.bar(share, bar_len);
+        lines.push(Line::from(vec![
+            Span::styled(format!("{name}{pad} "), Style::default().fg(Color::White)),
+            Span::raw(format!(
+                "{:>8} {:>8} {:>8} ",
+                number(joint.position),
+                number(joint.velocity),
+                number(joint.effort)
+            )),
+            Span::styled(
+                format!("{full}{partial}"),
+                Style::default().fg(Color::Yellow),
+            ),
+        ]));
+    }
+    if ros2.joints.is_empty() {
+        lines.push(Line::styled("waiting for joint states", dim));
+    }
+    Paragraph::new(lines).render(inner, buf);
+
+    let worst = ros2
+        .diagnostics
+        .iter()
+        .map(|s| s.level)
+        .max_by_key(|l| severity(*l));
+    let title = match worst {
+        Some(level) => format!("diagnostics • {}", level_name(level)),
+        None => "diagnostics".to_string(),
+    };
+    let diagnostics = block(title);
+    let inner = diagnostics.inner(split[1]);
+    diagnostics.render(split[1], buf);
+    let mut statuses: Vec<&Status> = ros2.diagnostics.iter().collect();
+    statuses.sort_by_key(|s| std::cmp::Reverse(severity(s.level)));
+    let width = inner.width as usize;
+    let mut lines = Vec::new();
+    for status in statuses.iter().take(inner.height as usize) {
+        let name = sanitize(&status.name);
+        let (name, _) = fit(&name, width.saturating_sub(7).min(24));
+        let message = sanitize(&status.message);
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{:<5} ", level_name(status.level)),
+                level_style(status.level),
+            ),
+            Span::styled(format!("{name} "), Style::default().fg(Color::White)),
+            Span::styled(message.into_owned(), dim),
+        ]));
+    }
+    if statuses.is_empty() {
+        lines.push(Line::styled("waiting for diagnostics", dim));
+    }
+    Paragraph::new(lines).render(inner, buf);
+}
+
+// The rosbridge client, only in builds with the feature
+#[cfg(feature = "ros2")]
+mod sys {
+    use std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        io::{self, BufReader},
+        sync::mpsc::{self, Receiver, Sender},
+        time::Duration,
+    };
+
+    use serde_json::{json, Value as Json};
+
+    use super::{level_name, Joint, Ros2Spec, Status, Update};
+    use crate::{
+        datasource::{Sample, Value},
+        ingest::{IngestQueue, SourceSender},
+        logs::Level,
+        net::Stream,
+        parse::parse_line,
+        push::row,
+        reconnect::{Connector, Links},
+        websocket::{frame, messages, Endpoint},
+    };
+
+    // What the bridge's link and the logged messages are shown under
+    const NAME: &str = "ros2";
+    const QUEUE: usize = 1024;
+    // Fastest rosbridge sends any one topic; the strip only redraws so often
+    const THROTTLE: Duration = Duration::from_millis(200);
+    // Characters of a logged message, past which it's cut
+    const MAX_LOGGED: usize = 240;
+
+    // The spec's topics subscribed to on their own reconnecting link: joint
+    // states and diagnostics come back on the first receiver, mapped numbers
+    // on the second
+    pub fn subscribe(
+        spec: &Ros2Spec,
+        links: &Links,
+        ingest: &IngestQueue,
+    ) -> Result<(Receiver<Update>, Receiver<Vec<Sample>>), String> {
+        let endpoint = Endpoint::parse(&spec.url, &spec.auth)?;
+        let mut topics = spec.topics.clone();
+        for name in spec.metrics.values() {
+            if !topics.contains(name) {
+                topics.push(name.clone());
+            }
+        }
+        if topics.is_empty() {
+            return Err("nothing to subscribe to; list `topics` or `metrics`".to_string());
+        }
+        let (updates, rx) = mpsc::channel();
+        let (samples, samples_rx) = mpsc::channel();
+        let client = Client {
+            endpoint,
+            topics,
+            rows: spec
+                .metrics
+                .iter()
+                .map(|(label, name)| (name.clone(), label.clone()))
+                .collect(),
+            updates,
+            samples,
+            advertised: Vec::new(),
+            levels: HashMap::new(),
+            mentioned: HashSet::new(),
+        };
+        links.spawn(NAME, client, ingest.sender(NAME, QUEUE));
+        Ok((rx, samples_rx))
+    }
+
+    struct Client {
+        endpoint: Endpoint,
+        topics: Vec<String>,
+        // topic → row label
+        rows: BTreeMap<String, String>,
+        updates: Sender<Update>,
+        samples: Sender<Vec<Sample>>,
+        // (topic, type) from the last discovery, so a reconnect only logs changes
+        advertised: Vec<(String, String)>,
+        // each diagnostic's last level, so only changes are logged
+        levels: HashMap<String, u8>,
+        // topics with numbers that fill no row, mentioned once
+        mentioned: HashSet<String>,
+    }
+
+    impl Connector for Client {
+        type Conn = BufReader<Stream>;
+
+        fn connect(&mut self) -> io::Result<Self::Conn> {
+            self.endpoint.handshake()
+        }
+
+        fn run(&mut self, mut conn: Self::Conn, tx: &SourceSender) -> io::Result<()> {
+            // rosapi lists what's advertised; subscribing doesn't wait on it
+            let discover =
+                json!({"op": "call_service", "service": "/rosapi/topics", "id": "discover"});
+            frame::write(conn.get_mut(), frame::TEXT, discover.to_string().as_bytes())?;
+            for name in &self.topics {
+                let subscribe = json!({
+                    "op": "subscribe",
+                    "id": format!("ai-intui:{name}"),
+                    "topic": name,
+                    "throttle_rate": THROTTLE.as_millis() as u64,
+                    "queue_length": 1,
+                });
+                frame::write(
+                    conn.get_mut(),
+                    frame::TEXT,
+                    subscribe.to_string().as_bytes(),
+                )?;
+            }
+            messages(&mut conn, |text| self.message(text, tx))
+        }
+    }
+
+    impl Client {
+        fn message(&mut self, text: &str, tx: &SourceSender) {
+            let Ok(message) = serde_json::from_str::<Json>(text) else {
+                return;
+            };
+            match message.get("op").and_then(Json::as_str) {
+                Some("publish") => {
+                    let name = message.get("topic").and_then(Json::as_str).unwrap_or("?");
+                    if let Some(msg) = message.get("msg") {
+                        self.publish(name, msg, tx);
+                    }
+                }
+                Some("service_response") if message.get("id") == Some(&json!("discover")) => {
+                    self.discovered(&message, tx)
+                }
+                Some("status") => {
+                    let level = match message.get("level").and_then(Json::as_str) {
+                        Some("error") => Level::Error,
+                        Some("warning") => Level::Warn,
+                        _ => return,
+                    };
+                    let msg = message.get("msg").and_then(Json::as_str).unwrap_or("");
+                    tx.send(level, format!("rosbridge: {msg}"));
+                }
+                _ => {}
+            }
+        }
+
+        // Every advertised topic and its type, and the subscribed ones that
+        // aren't among them
+        fn discovered(&mut self, response: &Json, tx: &SourceSender) {
+            let values = response.get("values");
+            if response.get("result") == Some(&json!(false)) {
+                tx.send(
+                    Level::Warn,
+                    format!(
+                        "topic discovery failed ({}); is rosapi running? subscribed anyway",
+                        values.map_or(String::new(), Json::to_string)
+                    ),
+                );
+                return;
+            }
+            let list = |key: &str| -> Vec<String> {
+                values
+                    .and_then(|v| v.get(key))
+                    .and_then(Json::as_array)
+                    .into_iter()
+                    .flatten()
+                    .map(|t| t.as_str().unwrap_or("").to_string())
+                    .collect()
+            };
+            let (names, types) = (list("topics"), list("types"));
+            let mut advertised: Vec<(String, String)> = names
+                .into_iter()
+                .enumerate()
+                .map(|(i, name)| (name, types.get(i).cloned().unwrap_or_default()))
+                .collect();
+            advertised.sort();
+            if advertised == self.advertised {
+                return;
+            }
+            let mut lines = vec![(
+                Level::Info,
+                format!("{} topics advertised", advertised.len()),
+            )];
+            for (name, kind) in &advertised {
+                lines.push((Level::Info, format!("  {name} {kind}")));
+            }
+            for name in &self.topics {
+                if !advertised.iter().any(|(n, _)| n == name) {
+                    lines.push((
+                        Level::Warn,
+                        format!(
+                            "{name} isn't advertised yet; it shows up once something publishes it"
+                        ),
+                    ));
+                }
+            }
+            tx.send_batch(lines);
+            self.advertised = advertised;
+        }
+
+        // Joint states and diagnostics by their shape, mapped numbers into
+        // their rows, and anything else logged
+        fn publish(&mut self, name: &str, msg: &Json, tx: &SourceSender) {
+            let data = msg.get("data");
+            if let Some(number) = data.and_then(Json::as_f64).filter(|v| v.is_finite()) {
+                let value = Value::Gauge(number);
+                // mapped, or named after a row by its last part: `/queue_depth`
+                let last = name.rsplit('/').next().unwrap_or(name);
+                let metric = self.rows.get(name).cloned().or_else(|| row(last, &value));
+                match metric {
+                    Some(metric) => {
+                        // the app is gone if this fails; the link goes with it
+                        let _ = self.samples.send(vec![Sample { metric, value }]);
+                    }
+                    None if self.mentioned.insert(name.to_string()) => tx.send(
+                        Level::Warn,
+                        format!("{name}: numbers here fill no row; map it under [ros2.metrics]"),
+                    ),
+                    None => {}
+                }
+                return;
+            }
+            if let Some(joints) = joints(msg) {
+                let _ = self.updates.send(Update::Joints(joints));
+                return;
+            }
+            if let Some(statuses) = statuses(msg) {
+                let mut lines = Vec::new();
+                for status in &statuses {
+                    if self.levels.insert(status.name.clone(), status.level) == Some(status.level) {
+                        continue;
+                    }
+                    let level = match status.level {
+                        0 => Level::Info,
+                        2 => Level::Error,
+                        _ => Level::Warn,
+                    };
+                    lines.push((
+                        level,
+                        format!(
+                            "{} {}: {}",
+                            level_name(status.level),
+                            status.name,
+                            status.message
+                        ),
+                    ));
+                }
+                if !lines.is_empty() {
+                    tx.send_batch(lines);
+                }
+                let _ = self.updates.send(Update::Diagnostics(statuses));
+                return;
+            }
+            let line = match data.and_then(Json::as_str) {
+                // std_msgs/String, read like any other log line
+                Some(text) => match parse_line(text) {
+                    Some(line) => line,
+                    None => return,
+                },
+                None => match parse_line(&compact(msg)) {
+                    Some(line) => line,
+                    None => return,
+                },
+            };
+            tx.send(line.level, format!("{name}: {}", line.message));
+        }
+    }
+
+    // A JointState: `name`, with `position`, `velocity`, and `effort` as long
+    // or empty
+    fn joints(msg: &Json) -> Option<Vec<Joint>> {
+        let names = msg.get("name")?.as_array()?;
+        let column = |key: &str| msg.get(key).and_then(Json::as_array);
+        let (position, velocity, effort) =
+            (column("position")?, column("velocity"), column("effort"));
+        let at = |values: Option<&Vec<Json>>, i: usize| values?.get(i)?.as_f64();
+        Some(
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| Joint {
+                    name: name.as_str().unwrap_or("?").to_string(),
+                    position: at(Some(position), i),
+                    velocity: at(velocity, i),
+                    effort: at(effort, i),
+                })
+                .collect(),
+        )
+    }
+
+    // A DiagnosticArray's statuses. A status's level is a byte, which bridges
+    // send as a number or as a one-character string.
+    fn statuses(msg: &Json) -> Option<Vec<Status>> {
+        msg.get("status")?
+            .as_array()?
+            .iter()
+            .map(|status| {
+                let level = match status.get("level")? {
+                    Json::Number(n) => n.as_u64()?,
+                    Json::String(s) if s.chars().count() == 1 => {
+                        let c = s.chars().next()?;
+                        c.to_digit(10).map_or(u64::from(c), u64::from)
+                    }
+                    _ => return None,
+                };
+                let text = |key: &str| status.get(key).and_then(Json::as_str).unwrap_or("");
+                Some(Status {
+                    name: text("name").to_string(),
+                    level: level.min(3) as u8,
+                    message: text("message").to_string(),
+                })
+            })
+            .collect()
+    }
+
+    // A message for the log: without its header, cut at MAX_LOGGED
+    fn compact(msg: &Json) -> String {
+        let mut msg = msg.clone();
+        if let Some(fields) = msg.as_object_mut() {
+            fields.remove("header");
+        }
+        let text = msg.to_string();
+        match text.char_indices().nth(MAX_LOGGED) {
+            Some((at, _)) => format!("{}…", &text[..at]),
+            None => text,
+        }
+    }
+}
+
+#[cfg(not(feature = "ros2"))]
+mod sys {
+    use std::sync::mpsc::Receiver;
+
+    use super::{Ros2Spec, Update};
+    use crate::{datasource::Sample, ingest::IngestQueue, reconnect::Links};
+
+    pub fn subscribe(
+        _spec: &Ros2Spec,
+        _links: &Links,
+        _ingest: &IngestQueue,
+    ) -> Result<(Receiver<Update>, Receiver<Vec<Sample>>), String> {
+        Err("this build doesn't include it; rebuild with `--features ros2`".to_string())
+    }
+}
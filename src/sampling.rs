@@ -0,0 +1,356 @@
+use std::{collections::VecDeque, time::Duration};
+
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::{
+    layout::{fit, sanitize, sanitize_lines},
+    payload::Popup,
+    redact::{self, Rule},
+    tokens::span,
+    ui::centered,
+};
+
+// Finished requests kept for the viewer
+pub const KEPT: usize = 64;
+// Bytes of a prompt or response held while it's still coming in
+pub const MAX_CAPTURE: usize = 64 << 10;
+// Characters of a prompt or response shown, after redaction
+const MAX_TEXT: usize = 4000;
+// The detail popup's text width
+const WRAP: usize = 76;
+
+// `[llm-requests]` in config.toml: what share of the requests through
+// `--llm-proxy` keep their prompt and response for the viewer, and what's
+// masked in them first
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SamplingSpec {
+    #[serde(default = "everything", deserialize_with = "share")]
+    pub sample: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact: Vec<Rule>,
+}
+
+impl Default for SamplingSpec {
+    fn default() -> Self {
+        Self {
+            sample: everything(),
+            redact: Vec::new(),
+        }
+    }
+}
+
+fn everything() -> f64 {
+    1.0
+}
+
+fn share<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    let sample = f64::deserialize(d)?;
+    if !(0.0..=1.0).contains(&sample) {
+        return Err(de::Error::custom(format!(
+            "sample is a share of requests, 0 to 1, got {sample}"
+        )));
+    }
+    Ok(sample)
+}
+
+impl SamplingSpec {
+    // Whether the next request keeps its text
+    pub fn sampled(&self) -> bool {
+        self.sample >= 1.0 || rand::random::<f64>() < self.sample
+    }
+
+    // Redacted first, so a cut can't leave half a secret showing
+    pub fn clean(&self, text: &str) -> String {
+        let text = redact::apply(&self.redact, text);
+        let text = sanitize_lines(text.trim());
+        match text.char_indices().nth(MAX_TEXT) {
+            Some((at, _)) => format!(
+                "{}… ({} more characters)",
+                &text[..at],
+                text[at..].chars().count()
+            ),
+            None => text.into_owned(),
+        }
+    }
+}
+
+// One request that went through the proxy, streamed or not
+pub struct Exchange {
+    // when it finished, on the app's clock
+    pub at: Duration,
+    pub method: String,
+    pub path: String,
+    // the model asked for, or the path
+    pub label: String,
+    // the server's; None if it never answered
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub latency: Duration,
+    pub ttft: Option<Duration>,
+    pub tokens: Option<u64>,
+    // sampled requests only, redacted and cut short
+    pub text: Option<(String, String)>,
+}
+
+// Appends to a prompt or response being captured, up to MAX_CAPTURE
+pub fn keep(text: &mut String, more: &str) {
+    let room = MAX_CAPTURE.saturating_sub(text.len());
+    if more.len() <= room {
+        text.push_str(more);
+        return;
+    }
+    let mut end = room;
+    while !more.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.push_str(&more[..end]);
+}
+
+// A chat request's messages as `role: content` lines, a completion's
+// prompt, or the body itself
+pub fn prompt(body: &[u8]) -> String {
+    let Ok(request) = serde_json::from_slice::<Value>(body) else {
+        return String::from_utf8_lossy(body).into_owned();
+    };
+    if let Some(messages) = request.get("messages").and_then(Value::as_array) {
+        let lines: Vec<String> = messages
+            .iter()
+            .map(|m| {
+                let role = m.get("role").and_then(Value::as_str).unwrap_or("?");
+                format!("{role}: {}", content(m.get("content")))
+            })
+            .collect();
+        return lines.join("\n");
+    }
+    match request.get("prompt").or_else(|| request.get("input")) {
+        Some(prompt) => content(Some(prompt)),
+        None => String::from_utf8_lossy(body).into_owned(),
+    }
+}
+
+// A string, or the text parts of a list of them
+fn content(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.as_str().or_else(|| part.get("text")?.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+// The text a streamed chunk adds to the response
+pub fn delta(event: &Value) -> String {
+    let Some(choices) = event.get("choices").and_then(Value::as_array) else {
+        return String::new();
+    };
+    choices
+        .iter()
+        .flat_map(|choice| {
+            ["/delta/reasoning_content", "/delta/content", "/text"]
+                .into_iter()
+                .filter_map(|pointer| choice.pointer(pointer)?.as_str())
+        })
+        .collect()
+}
+
+// A non-streamed response's text and completion tokens
+pub fn reply(body: &[u8]) -> (Option<String>, Option<u64>) {
+    let Ok(response) = serde_json::from_slice::<Value>(body) else {
+        return (None, None);
+    };
+    let tokens = response
+        .pointer("/usage/completion_tokens")
+        .and_then(Value::as_u64);
+    let text: Vec<String> = response
+        .get("choices")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|choice| {
+            let message = choice.get("message");
+            match message.and_then(|m| m.get("tool_calls")) {
+                Some(calls) if message.and_then(|m| m.get("content")).is_none() => {
+                    Some(calls.to_string())
+                }
+                _ => choice
+                    .pointer("/message/content")
+                    .or_else(|| choice.get("text"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+            }
+        })
+        .collect();
+    ((!text.is_empty()).then(|| text.join("\n")), tokens)
+}
+
+// `12s`, `3m`, or `2h` since `at`
+fn ago(now: Duration, at: Duration) -> String {
+    match now.saturating_sub(at).as_secs() {
+        s if s < 60 => format!("{s}s"),
+        s if s < 3600 => format!("{}m", s / 60),
+        s => format!("{}h", s / 3600),
+    }
+}
+
+fn status_style(exchange: &Exchange) -> Style {
+    match exchange.status {
+        Some(200..=299) if exchange.error.is_none() => Style::default().fg(Color::Green),
+        Some(400..=499) => Style::default().fg(Color::Yellow),
+        _ => Style::default().fg(Color::Red),
+    }
+}
+
+// `requests`: the kept requests, newest first, with the picked one
+// highlighted; Enter opens it
+pub fn draw(
+    buf: &mut Buffer,
+    area: Rect,
+    requests: &VecDeque<Exchange>,
+    pick: usize,
+    now: Duration,
+    sample: f64,
+) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let rect = centered(
+        area,
+        area.width.saturating_sub(8).min(120),
+        requests.len().max(1) as u16 + 3,
+    );
+    let mut title = format!("llm requests • {} kept", requests.len());
+    if sample < 1.0 {
+        title += &format!(" • {:.0}% sampled", sample * 100.0);
+    }
+    title += " • ↑↓ pick • Enter opens • Esc closes";
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(Span::styled(
+            title,
+            Style::default()
+                .fg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD),
+        ));
+    let inner = block.inner(rect);
+    Clear.render(rect, buf);
+    block.render(rect, buf);
+
+    let header = format!(
+        "{:>4} {:>6} {:>7} {:>6} {:>6}  {:<20} prompt",
+        "ago", "status", "latency", "ttft", "tokens", "model"
+    );
+    let mut lines = vec![Line::styled(header, dim)];
+    if requests.is_empty() {
+        lines.push(Line::styled("no requests yet", dim));
+    }
+    // the picked row stays on screen
+    let rows = (inner.height as usize).saturating_sub(1).max(1);
+    let skip = pick.saturating_sub(rows - 1);
+    for (i, exchange) in requests.iter().enumerate().skip(skip).take(rows) {
+        let status = exchange.status.map_or("-".to_string(), |s| s.to_string());
+        let ttft = exchange.ttft.map_or("-".to_string(), span);
+        let tokens = exchange.tokens.map_or("-".to_string(), |n| n.to_string());
+        let label = sanitize(&exchange.label);
+        let (label, pad) = fit(&label, 20);
+        let preview = match &exchange.text {
+            Some((prompt, _)) => prompt.lines().last().unwrap_or("").to_string(),
+            None => "(not sampled)".to_string(),
+        };
+        let mut spans = vec![
+            Span::styled(format!("{:>4} ", ago(now, exchange.at)), dim),
+            Span::styled(format!("{status:>6} "), status_style(exchange)),
+            Span::raw(format!(
+                "{:>7} {ttft:>6} {tokens:>6}  ",
+                span(exchange.latency)
+            )),
+            Span::styled(format!("{label}{pad} "), Style::default().fg(Color::White)),
+            Span::styled(preview, dim),
+        ];
+        if i == pick {
+            for span in &mut spans {
+                span.style = span.style.add_modifier(Modifier::REVERSED);
+            }
+        }
+        lines.push(Line::from(spans));
+    }
+    Paragraph::new(lines).render(inner, buf);
+}
+
+// The detail popup for one request: its outcome, then the prompt and the
+// response, wrapped
+pub fn popup(exchange: &Exchange) -> Popup {
+    let outcome = match (exchange.status, &exchange.error) {
+        (Some(status), None) => status.to_string(),
+        (Some(status), Some(error)) => format!("{status}, then {error}"),
+        (None, Some(error)) => error.clone(),
+        (None, None) => "no answer".to_string(),
+    };
+    let mut lines = vec![format!(
+        "{} {} → {outcome} in {}",
+        exchange.method,
+        sanitize(&exchange.path),
+        span(exchange.latency)
+    )];
+    let mut stats = Vec::new();
+    if let Some(ttft) = exchange.ttft {
+        stats.push(format!("ttft {}", span(ttft)));
+    }
+    if let Some(tokens) = exchange.tokens {
+        stats.push(format!("{tokens} tokens"));
+    }
+    if !stats.is_empty() {
+        lines.push(stats.join(" • "));
+    }
+    match &exchange.text {
+        None => {
+            lines.push(String::new());
+            lines
+                .push("prompt and response weren't sampled; see [llm-requests] sample".to_string());
+        }
+        Some((prompt, response)) => {
+            for (heading, text) in [("prompt", prompt), ("response", response)] {
+                lines.push(String::new());
+                lines.push(heading.to_string());
+                if text.is_empty() {
+                    lines.push("  (empty)".to_string());
+                }
+                lines.extend(text.lines().flat_map(wrap).map(|l| format!("  {l}")));
+            }
+        }
+    }
+    Popup {
+        title: format!("{} • ↑↓ scroll • Esc closes", sanitize(&exchange.label)),
+        lines,
+        scroll: 0,
+    }
+}
+
+// One line cut into WRAP-wide pieces, at spaces where there are some
+fn wrap(line: &str) -> Vec<String> {
+    let mut rest = line;
+    let mut out = Vec::new();
+    loop {
+        let (head, _) = fit(rest, WRAP);
+        if head.len() == rest.len() {
+            out.push(rest.to_string());
+            return out;
+        }
+        let cut = match head.rfind(' ') {
+            Some(space) if space > 0 => space + 1,
+            // a wide glyph wider than the popup still goes somewhere
+            _ if head.is_empty() => rest.chars().next().map_or(1, char::len_utf8),
+            _ => head.len(),
+        };
+        out.push(rest[..cut].trim_end().to_string());
+        rest = &rest[cut..];
+    }
+}
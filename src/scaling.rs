@@ -0,0 +1,341 @@
+use std::{
+    collections::VecDeque,
+    io::{self, BufReader, Read},
+    process::{Child, ChildStdout, Stdio},
+    sync::mpsc::{self, Receiver, Sender},
+    time::Duration,
+};
+
+use ratatui::{prelude::*, widgets::Paragraph};
+use serde_json::Value;
+
+use crate::{
+    chrome::Chrome,
+    detail,
+    glyphs::GlyphSet,
+    ingest::{IngestQueue, SourceSender},
+    layout::{fit, sanitize},
+    logs::Level,
+    programs::Programs,
+    reconnect::{Connector, Links},
+    timebase::Timebase,
+};
+
+// What events are logged under, and the link's name
+pub const NAME: &str = "kubernetes";
+pub const QUEUE: usize = 256;
+// `--kube-events` with no namespace watches all of them
+pub const ALL: &str = "all";
+// How far back the timeline reaches
+pub const WINDOW: Duration = Duration::from_secs(5 * 60);
+// Events kept for the list and the timeline
+const RECENT: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    // replicas, pods, or nodes added
+    Up,
+    // and removed
+    Down,
+    Scheduled,
+    // a pod the scheduler found no room for
+    Unschedulable,
+}
+
+impl Kind {
+    fn glyph(self, ascii: bool) -> char {
+        match (self, ascii) {
+            (Kind::Up, false) => '▲',
+            (Kind::Down, false) => '▼',
+            (Kind::Scheduled, false) => '●',
+            (Kind::Unschedulable, false) => '✖',
+            (Kind::Up, true) => '^',
+            (Kind::Down, true) => 'v',
+            (Kind::Scheduled, true) => 'o',
+            (Kind::Unschedulable, true) => 'x',
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Kind::Up => Color::Green,
+            Kind::Down => Color::Cyan,
+            Kind::Scheduled => Color::DarkGray,
+            Kind::Unschedulable => Color::Red,
+        }
+    }
+
+    // Replica counts changing, as opposed to pods finding a node
+    pub fn scales(self) -> bool {
+        matches!(self, Kind::Up | Kind::Down)
+    }
+}
+
+pub struct Event {
+    pub at: Duration,
+    pub kind: Kind,
+    // `deployment/web`
+    pub object: String,
+    pub message: String,
+}
+
+// --kube-events: scaling and scheduling events from `kubectl get events
+// --watch`, logged as they come and kept for cloud mode's timeline
+pub struct Scaling {
+    // None for every namespace
+    pub namespace: Option<String>,
+    pub recent: VecDeque<Event>,
+    rx: Receiver<(Kind, String, String)>,
+}
+
+impl Scaling {
+    pub fn start(
+        namespace: &str,
+        programs: &Programs,
+        links: &Links,
+        ingest: &IngestQueue,
+    ) -> Self {
+        let namespace = Some(namespace.to_string()).filter(|ns| ns != ALL);
+        let (events, rx) = mpsc::channel();
+        let connector = Kubectl {
+            namespace: namespace.clone(),
+            events,
+            programs: programs.clone(),
+        };
+        links.spawn(NAME, connector, ingest.sender(NAME, QUEUE));
+        Self {
+            namespace,
+            recent: VecDeque::new(),
+            rx,
+        }
+    }
+
+    // Events are stamped when they arrive, so they line up with the metrics
+    // drawn for the same moment
+    pub fn drain(&mut self, now: Duration) {
+        while let Ok((kind, object, message)) = self.rx.try_recv() {
+            if self.recent.len() == RECENT {
+                self.recent.pop_back();
+            }
+            self.recent.push_front(Event {
+                at: now,
+                kind,
+                object,
+                message,
+            });
+        }
+    }
+
+    // Events between `start` and `end`
+    pub fn between(&self, start: Duration, end: Duration) -> impl Iterator<Item = &Event> {
+        self.recent
+            .iter()
+            .filter(move |e| (start..=end).contains(&e.at))
+    }
+}
+
+struct Kubectl {
+    namespace: Option<String>,
+    events: Sender<(Kind, String, String)>,
+    programs: Programs,
+}
+
+impl Connector for Kubectl {
+    type Conn = (Child, ChildStdout);
+
+    fn connect(&mut self) -> io::Result<Self::Conn> {
+        let mut kubectl = self.programs.command("kubectl");
+        kubectl.args(["get", "events", "--watch-only", "--output", "json"]);
+        match &self.namespace {
+            Some(ns) => kubectl.arg("--namespace").arg(ns),
+            None => kubectl.arg("--all-namespaces"),
+        };
+        // stderr is read once kubectl exits, for why it did
+        let mut child = kubectl
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::NotFound => io::Error::other("kubectl isn't on PATH"),
+                _ => e,
+            })?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+        Ok((child, stdout))
+    }
+
+    fn run(&mut self, (mut child, stdout): Self::Conn, tx: &SourceSender) -> io::Result<()> {
+        // one pretty-printed object after another, not one per line
+        let objects = serde_json::Deserializer::from_reader(BufReader::new(stdout));
+        for object in objects.into_iter::<Value>() {
+            let object = object.map_err(io::Error::other)?;
+            let Some((kind, name, message)) = classify(&object) else {
+                continue;
+            };
+            let level = match kind {
+                Kind::Unschedulable => Level::Warn,
+                _ => Level::Info,
+            };
+            tx.send(level, format!("{name}: {message}"));
+            if self.events.send((kind, name, message)).is_err() {
+                // the app is gone
+                let _ = child.kill();
+                return Ok(());
+            }
+        }
+        let status = child.wait()?;
+        if status.success() {
+            return Ok(());
+        }
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            let _ = pipe.read_to_string(&mut stderr);
+        }
+        Err(io::Error::other(match stderr.lines().last() {
+            Some(why) => format!("kubectl exited with {status}: {}", why.trim()),
+            None => format!("kubectl exited with {status}"),
+        }))
+    }
+}
+
+// The events that move capacity, by reason: deployments and autoscalers
+// changing replica counts, controllers adding and removing pods, the
+// cluster autoscaler adding and removing nodes, and the scheduler placing
+// pods. Everything else is skipped.
+fn classify(event: &Value) -> Option<(Kind, String, String)> {
+    let reason = event.get("reason")?.as_str()?;
+    let message = event.get("message").and_then(Value::as_str).unwrap_or("");
+    let kind = match reason {
+        // `Scaled up replica set web-5d4f to 4`
+        "ScalingReplicaSet" if message.contains("Scaled down") => Kind::Down,
+        "ScalingReplicaSet" => Kind::Up,
+        // `New size: 4; reason: cpu resource utilization above target`
+        "SuccessfulRescale" if message.contains("below target") => Kind::Down,
+        "SuccessfulRescale" => Kind::Up,
+        "SuccessfulCreate" | "TriggeredScaleUp" | "RegisteredNode" => Kind::Up,
+        "SuccessfulDelete" | "ScaleDown" | "RemovingNode" => Kind::Down,
+        "Scheduled" => Kind::Scheduled,
+        "FailedScheduling" => Kind::Unschedulable,
+        _ => return None,
+    };
+    let object = event.get("involvedObject")?;
+    let name = object.get("name")?.as_str()?;
+    let name = match object.get("kind").and_then(Value::as_str) {
+        Some(kind) => format!("{}/{name}", kind.to_lowercase()),
+        None => name.to_string(),
+    };
+    Some((kind, name, message.trim().to_string()))
+}
+
+// Cloud mode's strip above the logs: the latency row's bar fill across
+// WINDOW, sampled once per column like an expanded row's sparkline, with
+// scaling events as rules through it and every event marked on the row
+// below; the newest events are listed beside it
+#[allow(clippy::too_many_arguments)]
+pub fn draw(
+    buf: &mut Buffer,
+    area: Rect,
+    scaling: &Scaling,
+    // the latency bar's fill at a moment, None in a gap
+    latency: &dyn Fn(Duration) -> Option<f32>,
+    end: Duration,
+    timebase: &Timebase,
+    glyphs: GlyphSet,
+    chrome: Chrome,
+) {
+    let ascii = glyphs == GlyphSet::Ascii;
+    let title = match &scaling.namespace {
+        Some(ns) => format!("scaling • latency p95 • {ns}"),
+        None => "scaling • latency p95 • all namespaces".to_string(),
+    };
+    let block = chrome
+        .block()
+        .border_style(Style::default().fg(Color::DarkGray))
+        .title(Span::styled(
+            title,
+            Style::default()
+                .fg(Color::LightMagenta)
+                .add_modifier(Modifier::BOLD),
+        ));
+    let inner = block.inner(area);
+    block.render(area, buf);
+    let split = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Min(0)])
+        .split(inner);
+    let (chart, list) = (split[0], split[1]);
+
+    let start = end.saturating_sub(WINDOW);
+    let width = chart.width as usize;
+    let last = width.saturating_sub(1).max(1);
+    let fills: Vec<Option<f32>> = (0..width)
+        .map(|i| latency(start + (end - start).mul_f64(i as f64 / last as f64)))
+        .collect();
+    let column = |at: Duration| {
+        let frac = (at - start).as_secs_f64() / (end - start).as_secs_f64().max(f64::EPSILON);
+        (frac * last as f64).round() as usize
+    };
+    let events: Vec<_> = scaling.between(start, end).collect();
+    let marks: Vec<usize> = events
+        .iter()
+        .filter(|e| e.kind.scales())
+        .map(|e| column(e.at))
+        .collect();
+    let mut lines = detail::sparkline(&fills, &marks, glyphs, Color::LightMagenta);
+    // the newest event wins a shared column
+    let mut row: Vec<Option<Kind>> = vec![None; width];
+    for event in events.iter().rev() {
+        if let Some(slot) = row.get_mut(column(event.at)) {
+            *slot = Some(event.kind);
+        }
+    }
+    lines.push(Line::from(
+        row.iter()
+            .map(|kind| match kind {
+                Some(kind) => Span::styled(
+                    kind.glyph(ascii).to_string(),
+                    Style::default().fg(kind.color()),
+                ),
+                None => Span::raw(" "),
+            })
+            .collect::<Vec<_>>(),
+    ));
+    let ago = format!("-{}m", WINDOW.as_secs() / 60);
+    let gap = width.saturating_sub(ago.len() + "now".len());
+    lines.push(Line::styled(
+        format!("{ago}{}now", " ".repeat(gap)),
+        Style::default().fg(Color::DarkGray),
+    ));
+    Paragraph::new(lines).render(chart, buf);
+
+    let width = list.width.saturating_sub(1) as usize;
+    let mut lines: Vec<Line> = scaling
+        .recent
+        .iter()
+        .filter(|e| e.at <= end)
+        .take(list.height as usize)
+        .map(|event| {
+            let text = sanitize(&event.object).into_owned() + " " + &sanitize(&event.message);
+            let (text, _) = fit(&text, width.saturating_sub(11));
+            Line::from(vec![
+                Span::raw(" "),
+                Span::styled(
+                    timebase.local_at(event.at).format("%H:%M:%S ").to_string(),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!("{} ", event.kind.glyph(ascii)),
+                    Style::default().fg(event.kind.color()),
+                ),
+                Span::raw(text.to_string()),
+            ])
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::styled(
+            " no scaling events yet",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    Paragraph::new(lines).render(list, buf);
+}
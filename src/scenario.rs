@@ -0,0 +1,134 @@
+use std::{fmt, fs, path::Path, time::Duration};
+
+use clap::ValueEnum;
+
+use crate::{app::Mode, logs::Level, parse::level_from_name};
+
+// One scripted beat of a kiosk demo
+#[derive(Clone, Debug)]
+pub enum Action {
+    Mode(Mode),
+    // a command-bar line, exactly as typed after `:`
+    Run(String),
+    // a synthetic log line from a named source
+    Log {
+        level: Level,
+        source: String,
+        message: String,
+    },
+}
+
+#[derive(Debug)]
+pub struct ScenarioError {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// A timed script of mode switches, commands, and synthetic events, replayed on
+// loop. One step per line, each waiting DELAY after the previous one:
+//
+//   # comments and blank lines are ignored
+//   0s    mode ai
+//   5s    run grep drift
+//   2s    log warn AI[core] latency spike p95=910ms
+//   500ms log error GPU[0] ECC error rate rising
+//   10s   run grep
+pub struct Scenario {
+    steps: Vec<(Duration, Action)>,
+    next: usize,
+    // when the next step fires, on the app clock
+    due: Duration,
+    // one full pass through the steps
+    period: Duration,
+}
+
+fn parse_step(line: &str) -> Result<(Duration, Action), String> {
+    let (delay, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let delay =
+        humantime::parse_duration(delay).map_err(|e| format!("bad delay `{delay}`: {e}"))?;
+    let rest = rest.trim();
+    let (verb, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let args = args.trim();
+    let action = match verb {
+        "mode" => Mode::from_str(args, true)
+            .map(Action::Mode)
+            .map_err(|_| format!("unknown mode `{args}`"))?,
+        "run" if !args.is_empty() => Action::Run(args.to_string()),
+        "log" => {
+            let mut parts = args.splitn(3, char::is_whitespace);
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(level), Some(source), Some(message)) => Action::Log {
+                    level: level_from_name(level),
+                    source: source.to_string(),
+                    message: message.trim().to_string(),
+                },
+                _ => return Err("expected `log LEVEL SOURCE MESSAGE`".to_string()),
+            }
+        }
+        _ => return Err(format!("expected `mode`, `run`, or `log`, got `{rest}`")),
+    };
+    Ok((delay, action))
+}
+
+impl Scenario {
+    pub fn parse(text: &str) -> Result<Self, ScenarioError> {
+        let mut steps = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let step = parse_step(line).map_err(|message| ScenarioError {
+                line: i + 1,
+                message,
+            })?;
+            steps.push(step);
+        }
+        if steps.is_empty() {
+            return Err(ScenarioError {
+                line: 0,
+                message: "no steps".to_string(),
+            });
+        }
+        // a loop with no delay anywhere would spin forever within one tick
+        if steps.iter().all(|(delay, _)| delay.is_zero()) {
+            return Err(ScenarioError {
+                line: 0,
+                message: "every delay is zero".to_string(),
+            });
+        }
+        Ok(Self {
+            due: steps[0].0,
+            period: steps.iter().map(|(delay, _)| *delay).sum(),
+            steps,
+            next: 0,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Self::parse(&text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    // Next action that has come due by `now`, if any; wraps around at the end
+    pub fn poll(&mut self, now: Duration) -> Option<Action> {
+        if now < self.due {
+            return None;
+        }
+        // after a long stall (suspend, debugger) resume from here rather than
+        // replaying every missed loop in one tick
+        if now - self.due > self.period {
+            self.due = now;
+        }
+        let action = self.steps[self.next].1.clone();
+        self.next = (self.next + 1) % self.steps.len();
+        self.due += self.steps[self.next].0;
+        Some(action)
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+type Trigram = [u8; 3];
+
+// Trigram index over the retained log lines, keyed by each line's sequence
+// number. Posting lists are append-only and sorted, so evicted lines are
+// trimmed lazily from the front instead of on every eviction.
+#[derive(Default)]
+pub struct SearchIndex {
+    postings: HashMap<Trigram, Vec<u64>>,
+    // lines below this sequence number have been evicted
+    floor: u64,
+    evicted_since_sweep: usize,
+    // entries across all posting lists, evicted ones included until a sweep
+    held: usize,
+}
+
+fn trigrams(text: &[u8]) -> impl Iterator<Item = Trigram> + '_ {
+    text.windows(3).map(|w| [w[0], w[1], w[2]])
+}
+
+impl SearchIndex {
+    pub fn insert(&mut self, seq: u64, text: &str) {
+        let lower = text.to_ascii_lowercase();
+        for gram in trigrams(lower.as_bytes()) {
+            let list = self.postings.entry(gram).or_default();
+            // a line repeating the same trigram is only listed once
+            if list.last() != Some(&seq) {
+                list.push(seq);
+                self.held += 1;
+            }
+        }
+    }
+
+    // Everything below `floor` is gone; sweep once as many lines have been
+    // evicted as are retained, so dead entries never outgrow the live ones
+    pub fn evict_below(&mut self, floor: u64, retained: usize) {
+        self.evicted_since_sweep += floor.saturating_sub(self.floor) as usize;
+        self.floor = floor;
+        if self.evicted_since_sweep > retained {
+            self.sweep();
+        }
+    }
+
+    fn sweep(&mut self) {
+        let floor = self.floor;
+        self.postings.retain(|_, list| {
+            let cut = list.partition_point(|&s| s < floor);
+            list.drain(..cut);
+            !list.is_empty()
+        });
+        self.held = self.postings.values().map(Vec::len).sum();
+        self.evicted_since_sweep = 0;
+    }
+
+    pub fn clear(&mut self, floor: u64) {
+        self.postings.clear();
+        self.floor = floor;
+        self.evicted_since_sweep = 0;
+        self.held = 0;
+    }
+
+    // Approximate bytes held: a key and list header per trigram, plus its entries
+    pub fn bytes(&self) -> usize {
+        self.postings.len() * std::mem::size_of::<(Trigram, Vec<u64>)>()
+            + self.held * std::mem::size_of::<u64>()
+    }
+
+    // Candidate sequence numbers (ascending) that contain every trigram of the
+    // query. None means the query is too short to use the index.
+    pub fn candidates(&self, query: &str) -> Option<Vec<u64>> {
+        let lower = query.to_ascii_lowercase();
+        if lower.len() < 3 {
+            return None;
+        }
+
+        let mut lists: Vec<&[u64]> = Vec::new();
+        for gram in trigrams(lower.as_bytes()) {
+            match self.postings.get(&gram) {
+                Some(list) => {
+                    let start = list.partition_point(|&s| s < self.floor);
+                    lists.push(&list[start..]);
+                }
+                None => return Some(Vec::new()),
+            }
+        }
+        // intersect starting from the rarest trigram
+        lists.sort_by_key(|l| l.len());
+        let (first, rest) = lists.split_first()?;
+        Some(
+            first
+                .iter()
+                .copied()
+                .filter(|seq| rest.iter().all(|l| l.binary_search(seq).is_ok()))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    datasource::{DataSource, Sample, Value, METRICS},
+    ingest::{IngestQueue, SourceSender},
+    logs::Level,
+    parse::parse_line,
+    push::row,
+    reconnect::{Connector, Links},
+};
+
+pub const QUEUE: usize = 1024;
+// Line speeds `stty` and `mode` both take
+const BAUDS: [u32; 12] = [
+    1200, 2400, 4800, 9600, 19200, 38400, 57600, 115200, 230400, 460800, 500000, 921600,
+];
+// Longest a line gets before it's logged without its end
+const MAX_LINE: usize = 4096;
+// How long a quiet read waits, so a closed port's thread notices soon
+const POLL: Duration = Duration::from_millis(100);
+
+// `serial open`: a port whose lines go to the logs, under the device's name,
+// and whose `key=value` numbers fill robotics mode's rows
+pub struct Port {
+    pub path: PathBuf,
+    pub baud: u32,
+    pub name: String,
+    open: Arc<AtomicBool>,
+}
+
+// The robotics panel feed: numbers read since the last tick
+pub struct Readings {
+    path: PathBuf,
+    baud: u32,
+    rx: Receiver<Vec<Sample>>,
+}
+
+impl Port {
+    // Checks the speed, then reads on its own reconnecting link, so an
+    // adapter unplugged and plugged back in picks up where it left off
+    pub fn open(
+        path: &Path,
+        baud: u32,
+        links: &Links,
+        ingest: &IngestQueue,
+    ) -> Result<(Self, Readings), String> {
+        if !BAUDS.contains(&baud) {
+            return Err(format!(
+                "{baud} isn't a line speed; try one of {}",
+                BAUDS.map(|b| b.to_string()).join(", ")
+            ));
+        }
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        let open = Arc::new(AtomicBool::new(true));
+        let (samples, rx) = mpsc::channel();
+        let reader = Reader {
+            path: path.to_path_buf(),
+            baud,
+            open: open.clone(),
+            samples,
+            mentioned: HashSet::new(),
+        };
+        links.spawn(&name, reader, ingest.sender(&name, QUEUE));
+        let port = Self {
+            path: path.to_path_buf(),
+            baud,
+            name,
+            open,
+        };
+        let readings = Readings {
+            path: path.to_path_buf(),
+            baud,
+            rx,
+        };
+        Ok((port, readings))
+    }
+
+    pub fn close(&self, links: &Links) {
+        self.open.store(false, Ordering::Relaxed);
+        links.close(&self.name);
+    }
+}
+
+impl DataSource for Readings {
+    fn poll(&mut self) -> Vec<Sample> {
+        self.rx.try_iter().flatten().collect()
+    }
+
+    fn describe(&self) -> String {
+        format!("serial {} at {} baud", self.path.display(), self.baud)
+    }
+}
+
+struct Reader {
+    path: PathBuf,
+    baud: u32,
+    open: Arc<AtomicBool>,
+    samples: Sender<Vec<Sample>>,
+    // keys that fill no row, mentioned once
+    mentioned: HashSet<String>,
+}
+
+impl Connector for Reader {
+    type Conn = File;
+
+    fn connect(&mut self) -> io::Result<File> {
+        configure(&self.path, self.baud)?;
+        File::open(device(&self.path))
+    }
+
+    fn run(&mut self, mut port: File, tx: &SourceSender) -> io::Result<()> {
+        let (mut partial, mut buf) = (Vec::new(), [0; 4096]);
+        while self.open.load(Ordering::Relaxed) {
+            let n = match port.read(&mut buf) {
+                // nothing yet: a quiet port, or the end of a file for now
+                Ok(0) => {
+                    thread::sleep(POLL);
+                    continue;
+                }
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            partial.extend_from_slice(&buf[..n]);
+            let mut lines = Vec::new();
+            while let Some(end) = partial.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = partial.drain(..=end).collect();
+                lines.push(String::from_utf8_lossy(&line).into_owned());
+            }
+            if partial.len() > MAX_LINE {
+                lines.push(String::from_utf8_lossy(&partial).into_owned());
+                partial.clear();
+            }
+            self.lines(&lines, tx);
+        }
+        Ok(())
+    }
+}
+
+impl Reader {
+    // Every line is logged; its numbers fill the rows they're named after
+    fn lines(&mut self, lines: &[String], tx: &SourceSender) {
+        let mut logged = Vec::new();
+        let mut samples = Vec::new();
+        for line in lines {
+            let Some(parsed) = parse_line(line) else {
+                continue;
+            };
+            for (key, value) in pairs(&parsed.message) {
+                let value = Value::Gauge(value);
+                match row(key, &value) {
+                    Some(metric) => samples.push(Sample { metric, value }),
+                    None if self.mentioned.insert(key.to_string()) => logged.push((
+                        Level::Warn,
+                        format!("`{key}` fills no row; rows are {}", METRICS.join(", ")),
+                    )),
+                    None => {}
+                }
+            }
+            logged.push((parsed.level, parsed.message));
+        }
+        if !logged.is_empty() {
+            tx.send_batch(logged);
+        }
+        if !samples.is_empty() {
+            // the app is gone if this fails; the link goes with it
+            let _ = self.samples.send(samples);
+        }
+    }
+}
+
+// `key=value` pairs with a number for a value, split on spaces, commas, and
+// semicolons; a unit after the number is ignored: `temp=41.5C rpm=1200`
+fn pairs(line: &str) -> Vec<(&str, f64)> {
+    line.split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .filter_map(|token| {
+            let (key, value) = token.split_once('=')?;
+            let number = value.trim_end_matches(|c: char| c.is_alphabetic() || c == '%');
+            let value = number.parse::<f64>().ok().filter(|v| v.is_finite())?;
+            (!key.is_empty()).then_some((key, value))
+        })
+        .collect()
+}
+
+// The line speed and raw mode, through `stty`; reads wait at most a tenth
+// of a second. Anything that isn't a terminal device, like a FIFO or a
+// capture being replayed, is read as it is.
+#[cfg(unix)]
+fn configure(path: &Path, baud: u32) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if !path.metadata()?.file_type().is_char_device() {
+        return Ok(());
+    }
+    let flag = if cfg!(target_os = "linux") {
+        "-F"
+    } else {
+        "-f"
+    };
+    let out = Command::new("stty")
+        .arg(flag)
+        .arg(path)
+        .args([&baud.to_string(), "raw", "-echo", "min", "0", "time", "1"])
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("can't run stty: {e}")))?;
+    match out.status.success() {
+        true => Ok(()),
+        false => Err(io::Error::other(format!(
+            "stty: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ))),
+    }
+}
+
+// The line speed, through `mode`, for COM ports
+#[cfg(windows)]
+fn configure(path: &Path, baud: u32) -> io::Result<()> {
+    let port = path.display().to_string();
+    if !port.to_ascii_uppercase().starts_with("COM") {
+        return Ok(());
+    }
+    let out = Command::new("mode")
+        .arg(format!("{port}:"))
+        .args([
+            format!("BAUD={baud}"),
+            "PARITY=N".to_string(),
+            "DATA=8".to_string(),
+            "STOP=1".to_string(),
+        ])
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("can't run mode: {e}")))?;
+    match out.status.success() {
+        true => Ok(()),
+        false => Err(io::Error::other(format!(
+            "mode: {}",
+            String::from_utf8_lossy(&out.stdout).trim()
+        ))),
+    }
+}
+
+// COM10 and up only open by their device path
+fn device(path: &Path) -> PathBuf {
+    let port = path.display().to_string();
+    match cfg!(windows) && port.to_ascii_uppercase().starts_with("COM") {
+        true => PathBuf::from(format!(r"\\.\{port}")),
+        false => path.to_path_buf(),
+    }
+}
@@ -0,0 +1,69 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+// Termination requests (SIGTERM/SIGHUP, or console close on Windows) recorded by
+// the handler and picked up by the main loop, so shutdown goes through the
+// normal path: restore the terminal, flush reports, then exit. An external
+// SIGTSTP is likewise deferred to the loop so the terminal can be released
+// before the process stops.
+pub struct Signals {
+    received: Arc<AtomicUsize>,
+    suspend: Arc<AtomicBool>,
+}
+
+// reported like SIGTERM so the exit status matches Unix
+#[cfg(windows)]
+const SIGTERM: usize = 15;
+
+impl Signals {
+    pub fn install() -> io::Result<Self> {
+        let received = Arc::new(AtomicUsize::new(0));
+        let suspend = Arc::new(AtomicBool::new(false));
+
+        #[cfg(unix)]
+        {
+            for sig in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGHUP] {
+                signal_hook::flag::register_usize(sig, received.clone(), sig as usize)?;
+            }
+            signal_hook::flag::register(signal_hook::consts::SIGTSTP, suspend.clone())?;
+        }
+
+        #[cfg(windows)]
+        {
+            let flag = received.clone();
+            ctrlc::set_handler(move || flag.store(SIGTERM, Ordering::SeqCst))
+                .map_err(io::Error::other)?;
+        }
+
+        Ok(Self { received, suspend })
+    }
+
+    pub fn take_suspend(&self) -> bool {
+        self.suspend.swap(false, Ordering::SeqCst)
+    }
+
+    // Signal number of the first pending termination request, if any
+    pub fn pending(&self) -> Option<i32> {
+        match self.received.load(Ordering::SeqCst) {
+            0 => None,
+            sig => Some(sig as i32),
+        }
+    }
+
+    // Conventional shell exit status for death-by-signal
+    pub fn exit_code(sig: i32) -> i32 {
+        128 + sig
+    }
+}
+
+// Stops the process the way an unhandled SIGTSTP would; returns once a SIGCONT
+// resumes it
+#[cfg(unix)]
+pub fn stop_self() -> io::Result<()> {
+    signal_hook::low_level::emulate_default_handler(signal_hook::consts::SIGTSTP)
+}
@@ -0,0 +1,188 @@
+use std::time::Instant;
+
+use ratatui::style::Color;
+
+use crate::{
+    app::{MetricRow, Mode},
+    datasource::{DataSource, Sample, Value, METRICS},
+};
+
+// The built-in demo data for a mode
+pub struct Synthetic {
+    mode: Mode,
+    start: Instant,
+}
+
+impl Synthetic {
+    pub fn new(mode: Mode) -> Self {
+        Self {
+            mode,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl DataSource for Synthetic {
+    fn poll(&mut self) -> Vec<Sample> {
+        self.at(self.start.elapsed().as_secs_f32())
+            .unwrap_or_default()
+    }
+
+    fn describe(&self) -> String {
+        format!("synthetic {} generator", self.mode.name())
+    }
+
+    fn at(&self, t: f32) -> Option<Vec<Sample>> {
+        let values = metrics(self.mode, t);
+        Some(
+            METRICS
+                .iter()
+                .zip(values)
+                .map(|(metric, value)| Sample {
+                    metric: metric.to_string(),
+                    value: Value::Gauge(value.into()),
+                })
+                .collect(),
+        )
+    }
+}
+
+// The sine-wave generator: latency, service load, tokens/min, errors/min,
+// queue depth, jitter, and trust for `mode` at `t` seconds
+pub fn metrics(mode: Mode, t: f32) -> [f32; 7] {
+    // Per-mode base shapes + light mode-specific accents via value ranges
+    match mode {
+        Mode::AiObservability => [
+            220.0 + 90.0 * (t * 0.33).sin(), // latency ms
+            0.18 + 0.12 * (t * 0.27).cos(),  // service load
+            13_000.0 + 5_000.0 * (t * 0.19).sin(),
+            0.5 + 0.8 * (t * 0.41).sin().abs(),
+            0.45 + 0.25 * (t * 0.23).cos(),
+            7.0 + 3.0 * (t * 0.51).sin().abs(),
+            0.92 - 0.08 * (t * 0.17).sin().abs(),
+        ],
+        Mode::Robotics => [
+            80.0 + 40.0 * (t * 0.55).sin(),
+            0.35 + 0.18 * (t * 0.37).cos(),
+            4_800.0 + 1_800.0 * (t * 0.29).sin(),
+            0.2 + 0.5 * (t * 0.63).sin().abs(),
+            0.35 + 0.22 * (t * 0.33).cos(),
+            4.0 + 2.5 * (t * 0.72).sin().abs(),
+            0.89 - 0.10 * (t * 0.27).sin().abs(),
+        ],
+        Mode::Cloud => [
+            260.0 + 110.0 * (t * 0.29).sin(),
+            0.42 + 0.22 * (t * 0.31).cos(),
+            19_000.0 + 7_000.0 * (t * 0.21).sin(),
+            1.0 + 1.2 * (t * 0.45).sin().abs(),
+            0.62 + 0.28 * (t * 0.26).cos(),
+            5.5 + 3.5 * (t * 0.54).sin().abs(),
+            0.87 - 0.12 * (t * 0.23).sin().abs(),
+        ],
+        Mode::DataForensics => [
+            180.0 + 70.0 * (t * 0.39).sin(),
+            0.24 + 0.15 * (t * 0.22).cos(),
+            9_500.0 + 3_000.0 * (t * 0.18).sin(),
+            0.3 + 0.9 * (t * 0.58).sin().abs(),
+            0.28 + 0.18 * (t * 0.44).cos(),
+            6.5 + 4.0 * (t * 0.63).sin().abs(),
+            0.93 - 0.06 * (t * 0.31).sin().abs(),
+        ],
+        Mode::Sandbox => [
+            150.0 + 120.0 * (t * 0.41).sin(),
+            0.30 + 0.30 * (t * 0.36).cos(),
+            7_000.0 + 9_000.0 * (t * 0.27).sin(),
+            0.1 + 1.5 * (t * 0.49).sin().abs(),
+            0.5 + 0.3 * (t * 0.38).cos(),
+            8.0 + 5.0 * (t * 0.69).sin().abs(),
+            0.80 - 0.18 * (t * 0.42).sin().abs(),
+        ],
+    }
+}
+
+// The --simulate system panel's rows at time `t`
+pub fn system_rows(t: f32) -> [MetricRow; 4] {
+    let cpu = 0.40 + 0.25 * (t * 0.41).sin().abs();
+    let mem = 0.55 + 0.20 * (t * 0.27).cos().abs();
+    let disk = 0.30 + 0.35 * (t * 0.31).sin().abs();
+    let net = 0.20 + 0.40 * (t * 0.22).cos().abs();
+
+    [
+        (
+            "cpu load",
+            cpu,
+            |v| format!("{:.0}%", v * 100.0),
+            cpu,
+            Color::LightGreen,
+        ),
+        (
+            "memory",
+            mem,
+            |v| format!("{:.0}%", v * 100.0),
+            mem,
+            Color::LightMagenta,
+        ),
+        (
+            "disk io",
+            disk,
+            |v| format!("{:.0}%", v * 100.0),
+            disk,
+            Color::Cyan,
+        ),
+        (
+            "net jitter",
+            net,
+            |v| format!("{:.0}%", v * 100.0),
+            net,
+            Color::Yellow,
+        ),
+    ]
+}
+
+// A log line of the kind `mode`'s service would write at `t` seconds: the
+// source's name and the message
+pub fn log_line(mode: Mode, t: f32) -> (&'static str, String) {
+    match mode {
+        Mode::AiObservability => (
+            "AI[core]",
+            format!(
+                "step={} temp={:.2} drift={:.3}",
+                (t * 12.0) as i32,
+                0.9 + 0.1 * (t * 0.3).sin(),
+                (t * 0.17).cos()
+            ),
+        ),
+        Mode::Robotics => (
+            "ROB[path]",
+            format!(
+                "jitter={:.1}ms torque={:.1}Nm",
+                4.0 + 3.0 * (t * 0.4).sin(),
+                18.0 + 2.0 * (t * 0.6).cos()
+            ),
+        ),
+        Mode::Cloud => (
+            "CLD[node]",
+            format!(
+                "p95={:.0}ms q_depth={:.2}",
+                210.0 + 85.0 * (t * 0.33).sin(),
+                0.4 + 0.3 * (t * 0.21).cos()
+            ),
+        ),
+        Mode::DataForensics => (
+            "DFX[trace]",
+            format!(
+                "anomalies={:.2} hash_shift={:.2}",
+                0.2 + 0.6 * (t * 0.27).sin().abs(),
+                0.1 + 0.4 * (t * 0.36).cos().abs()
+            ),
+        ),
+        Mode::Sandbox => (
+            "SBX[synth]",
+            format!(
+                "pattern={:.2} entropy={:.2}",
+                (t * 0.19).sin(),
+                (t * 0.23).cos().abs()
+            ),
+        ),
+    }
+}
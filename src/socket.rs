@@ -0,0 +1,171 @@
+use std::{
+    env, io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+use crate::logs::Level;
+
+// Where `--socket` listens without a PATH: in $XDG_RUNTIME_DIR, or the
+// state directory where there's none (macOS)
+const FILE: &str = "ai-intui.sock";
+// A command line longer than this ends the connection
+#[cfg_attr(not(unix), allow(dead_code))]
+const MAX_LINE: u64 = 4096;
+
+pub fn default_path(state: &Path) -> PathBuf {
+    env::var_os("XDG_RUNTIME_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(|| state.to_path_buf(), PathBuf::from)
+        .join(FILE)
+}
+
+// One command line from a client, waiting on what the app makes of it
+pub struct Request {
+    pub line: String,
+    reply: Sender<Vec<String>>,
+}
+
+impl Request {
+    // What the command logged, warnings and errors marked as such; `ok` if
+    // it said nothing
+    pub fn answer(self, said: Vec<(Level, String)>) {
+        let mut lines: Vec<String> = said
+            .into_iter()
+            .map(|(level, line)| match level {
+                Level::Warn => format!("warn: {line}"),
+                Level::Error => format!("error: {line}"),
+                Level::Debug | Level::Info => line,
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push("ok".to_string());
+        }
+        // the client may have hung up; nothing to do about it
+        let _ = self.reply.send(lines);
+    }
+}
+
+// `--socket`: a Unix socket that takes the same commands as the `:` prompt,
+// one per line, so scripts and other terminals can drive a running instance:
+// `echo 'set mode cloud' | nc -U $XDG_RUNTIME_DIR/ai-intui.sock`. Each
+// command is answered with what it logged, then an empty line. Commands come
+// out of `requests` and the app runs them on its next tick.
+pub struct ControlSocket {
+    pub path: PathBuf,
+    rx: Receiver<Request>,
+}
+
+impl ControlSocket {
+    pub fn bind(path: PathBuf) -> io::Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        sys::bind(&path, tx)?;
+        Ok(Self { path, rx })
+    }
+
+    pub fn requests(&self) -> impl Iterator<Item = Request> + '_ {
+        self.rx.try_iter()
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::{
+        fs,
+        io::{self, BufRead, BufReader, Read, Write},
+        os::unix::{
+            fs::{FileTypeExt, PermissionsExt},
+            net::{UnixListener, UnixStream},
+        },
+        path::Path,
+        sync::mpsc::{self, Sender},
+        thread,
+    };
+
+    use super::{Request, MAX_LINE};
+
+    pub fn bind(path: &Path, tx: Sender<Request>) -> io::Result<()> {
+        // a socket left behind by an instance that didn't exit cleanly is
+        // replaced; one that still answers is someone else's
+        if let Ok(meta) = fs::symlink_metadata(path) {
+            if !meta.file_type().is_socket() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "something other than a socket is there",
+                ));
+            }
+            if UnixStream::connect(path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    "another instance is listening there",
+                ));
+            }
+            fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        // commands can clear logs and move robots; keep them to this user
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let tx = tx.clone();
+                // a client waiting on its reply shouldn't hold up the others
+                thread::spawn(move || {
+                    let _ = serve(&stream, &tx);
+                });
+            }
+        });
+        Ok(())
+    }
+
+    fn serve(stream: &UnixStream, tx: &Sender<Request>) -> io::Result<()> {
+        let mut reader = BufReader::new(stream);
+        let mut writer = stream;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if (&mut reader).take(MAX_LINE).read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let command = line.trim();
+            if command.is_empty() {
+                continue;
+            }
+            let (reply, answer) = mpsc::channel();
+            let request = Request {
+                line: command.to_string(),
+                reply,
+            };
+            // the app is gone
+            if tx.send(request).is_err() {
+                return Ok(());
+            }
+            let Ok(lines) = answer.recv() else {
+                return Ok(());
+            };
+            for line in lines {
+                writeln!(writer, "{line}")?;
+            }
+            writeln!(writer)?;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod sys {
+    use std::{io, path::Path, sync::mpsc::Sender};
+
+    use super::Request;
+
+    pub fn bind(_path: &Path, _tx: Sender<Request>) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "control sockets are only supported on Unix",
+        ))
+    }
+}
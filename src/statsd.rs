@@ -0,0 +1,286 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io,
+    net::{SocketAddr, UdpSocket},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    datasource::{DataSource, Sample, Value, METRICS},
+    histogram,
+    ingest::SourceSender,
+    logs::Level,
+};
+
+// What listener notes are logged under
+pub const NAME: &str = "statsd";
+pub const QUEUE: usize = 64;
+// Largest datagram read; clients usually stay under the MTU but may batch
+const MAX_PACKET: usize = 65_535;
+// Counts and timings older than this no longer count toward their row
+const WINDOW: Duration = Duration::from_secs(60);
+// Timings kept per row; a busy timer keeps the newest
+const MAX_TIMINGS: usize = 100_000;
+// Names mentioned as matching no row, so each is mentioned once
+const MAX_NOTES: usize = 256;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Counter,
+    // `+N` and `-N` move the last value instead of replacing it
+    Gauge { relative: bool },
+    // `ms`, and `h` and `d` which read the same
+    Timer,
+    Set,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Counter => "counter",
+            Kind::Gauge { .. } => "gauge",
+            Kind::Timer => "timer",
+            Kind::Set => "set",
+        }
+    }
+}
+
+// One `name:value|type|@rate|#tags` line
+struct Line<'a> {
+    name: &'a str,
+    value: f64,
+    kind: Kind,
+    // a counter or timing sampled at 0.1 stands for ten
+    rate: f64,
+}
+
+// A line matched to the row it fills
+struct Reading {
+    row: String,
+    value: f64,
+    kind: Kind,
+    rate: f64,
+}
+
+// `--statsd`: a StatsD listener in place of the AI observability panel's
+// synthetic numbers. Gauges fill the row they're named after, counters the
+// matching `/min` row with their rate over the last minute, and timers the
+// latency row.
+pub struct Statsd {
+    addr: SocketAddr,
+    rx: Receiver<(Instant, Vec<Reading>)>,
+    gauges: HashMap<String, f64>,
+    // kept for rows that were counted once, so a stopped counter falls to 0
+    counts: HashMap<String, VecDeque<(Instant, f64)>>,
+    // (received, ms, weight)
+    timings: HashMap<String, VecDeque<(Instant, f64, f64)>>,
+}
+
+impl Statsd {
+    pub fn listen(addr: SocketAddr, log: SourceSender) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        let addr = socket.local_addr()?;
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || receive(&socket, &tx, &log));
+        Ok(Self {
+            addr,
+            rx,
+            gauges: HashMap::new(),
+            counts: HashMap::new(),
+            timings: HashMap::new(),
+        })
+    }
+}
+
+impl DataSource for Statsd {
+    fn poll(&mut self) -> Vec<Sample> {
+        let mut samples = Vec::new();
+        let packets: Vec<_> = self.rx.try_iter().collect();
+        for (at, readings) in packets {
+            for reading in readings {
+                match reading.kind {
+                    Kind::Gauge { relative } => {
+                        let value = self.gauges.entry(reading.row.clone()).or_default();
+                        *value = match relative {
+                            true => *value + reading.value,
+                            false => reading.value,
+                        };
+                        samples.push(Sample {
+                            metric: reading.row,
+                            value: Value::Gauge(*value),
+                        });
+                    }
+                    Kind::Counter => self
+                        .counts
+                        .entry(reading.row)
+                        .or_default()
+                        .push_back((at, reading.value / reading.rate)),
+                    Kind::Timer => {
+                        let timings = self.timings.entry(reading.row).or_default();
+                        if timings.len() == MAX_TIMINGS {
+                            timings.pop_front();
+                        }
+                        timings.push_back((at, reading.value, 1.0 / reading.rate));
+                    }
+                    Kind::Set => {}
+                }
+            }
+        }
+
+        let now = Instant::now();
+        for (row, counts) in &mut self.counts {
+            while counts
+                .front()
+                .is_some_and(|c| now.duration_since(c.0) > WINDOW)
+            {
+                counts.pop_front();
+            }
+            let total: f64 = counts.iter().map(|c| c.1).sum();
+            samples.push(Sample {
+                metric: row.clone(),
+                value: Value::Gauge(total * 60.0 / WINDOW.as_secs_f64()),
+            });
+        }
+        for (row, timings) in &mut self.timings {
+            while timings
+                .front()
+                .is_some_and(|t| now.duration_since(t.0) > WINDOW)
+            {
+                timings.pop_front();
+            }
+            let observed: Vec<_> = timings.iter().map(|t| (t.1, t.2)).collect();
+            samples.extend(histogram::observed(&observed).map(|h| Sample {
+                metric: row.clone(),
+                value: Value::Histogram(h),
+            }));
+        }
+        samples
+    }
+
+    fn describe(&self) -> String {
+        format!("StatsD listener on udp://{}", self.addr)
+    }
+}
+
+fn receive(socket: &UdpSocket, tx: &Sender<(Instant, Vec<Reading>)>, log: &SourceSender) {
+    let mut buf = vec![0; MAX_PACKET];
+    // what was already mentioned, so a client sending every second doesn't
+    // repeat it
+    let mut said: HashSet<String> = HashSet::new();
+    let mut note = |key: String, level: Level, message: String| {
+        if said.len() < MAX_NOTES && said.insert(key) {
+            log.send(level, message);
+        }
+    };
+    loop {
+        // Windows reports an earlier send's ICMP port unreachable here;
+        // nothing is lost by reading on
+        let Ok(n) = socket.recv(&mut buf) else {
+            continue;
+        };
+        let text = String::from_utf8_lossy(&buf[..n]);
+        let mut readings = Vec::new();
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let line = match parse(line) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    note(
+                        "malformed".to_string(),
+                        Level::Warn,
+                        format!("ignoring malformed lines such as `{line}`: {e}"),
+                    );
+                    continue;
+                }
+            };
+            if line.kind == Kind::Set {
+                note(
+                    "|s".to_string(),
+                    Level::Info,
+                    format!("sets such as `{}` aren't shown", line.name),
+                );
+                continue;
+            }
+            match row(line.name, line.kind) {
+                Some(row) => readings.push(Reading {
+                    row,
+                    value: line.value,
+                    kind: line.kind,
+                    rate: line.rate,
+                }),
+                None => note(
+                    format!("`{}`", line.name),
+                    Level::Info,
+                    format!("{} `{}` fills no row", line.kind.name(), line.name),
+                ),
+            }
+        }
+        if !readings.is_empty() && tx.send((Instant::now(), readings)).is_err() {
+            // the app is gone
+            return;
+        }
+    }
+}
+
+fn parse(line: &str) -> Result<Line<'_>, String> {
+    let (name, rest) = line.split_once(':').ok_or("no `:value`")?;
+    let mut fields = rest.split('|');
+    let value = fields.next().unwrap_or("");
+    let kind = match fields.next().ok_or("no `|type`")? {
+        "c" => Kind::Counter,
+        "g" => Kind::Gauge {
+            relative: value.starts_with(['+', '-']),
+        },
+        "ms" | "h" | "d" => Kind::Timer,
+        "s" => Kind::Set,
+        other => return Err(format!("unknown type `{other}`")),
+    };
+    let value = match kind {
+        // a set's members needn't be numbers
+        Kind::Set => 0.0,
+        _ => value
+            .parse()
+            .ok()
+            .filter(|v: &f64| v.is_finite())
+            .ok_or_else(|| format!("`{value}` isn't a number"))?,
+    };
+    // tags and anything newer are skipped
+    let rate = match fields.find_map(|f| f.strip_prefix('@')) {
+        Some(rate) => rate
+            .parse()
+            .ok()
+            .filter(|r| *r > 0.0 && *r <= 1.0)
+            .ok_or_else(|| format!("sample rate `{rate}` isn't in (0, 1]"))?,
+        None => 1.0,
+    };
+    if name.is_empty() {
+        return Err("no name".to_string());
+    }
+    Ok(Line {
+        name,
+        value,
+        kind,
+        rate,
+    })
+}
+
+// The row a metric fills, by name the way `--otlp` matches them: a gauge
+// `queue.depth` fills queue depth, a counter `tokens` fills tokens/min, and a
+// timer `latency` fills latency p95
+fn row(name: &str, kind: Kind) -> Option<String> {
+    let spelled = name.to_lowercase().replace(['.', '_'], " ");
+    let row = match kind {
+        Kind::Timer => {
+            return METRICS
+                .iter()
+                .map(|m| histogram::base(m))
+                .find(|base| *base == spelled && !METRICS.contains(base))
+                .map(str::to_string)
+        }
+        Kind::Counter if spelled.ends_with("/min") => spelled,
+        Kind::Counter => format!("{spelled}/min"),
+        Kind::Gauge { .. } | Kind::Set => spelled,
+    };
+    METRICS.contains(&row.as_str()).then_some(row)
+}
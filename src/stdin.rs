@@ -0,0 +1,47 @@
+use std::{
+    io::{self, BufRead, BufReader, IsTerminal},
+    thread,
+};
+
+use crate::{ingest::SourceSender, logs::Level, parse::parse_line};
+
+// What piped lines are shown under
+pub const NAME: &str = "stdin";
+// Lines buffered before the oldest are dropped; a pipe can outrun the UI
+pub const QUEUE: usize = 4096;
+// Lines handed over at once while more are already waiting in the pipe
+const BATCH: usize = 256;
+
+// `my-server | ai-intui`: stdin is someone's output, not the keyboard
+pub fn piped() -> bool {
+    !io::stdin().is_terminal()
+}
+
+// Reads stdin to its end from a background thread, sending each line parsed
+// like any other source's, then says the input ended
+pub fn spawn(tx: SourceSender) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(io::stdin().lock());
+        let mut raw = Vec::new();
+        let mut batch = Vec::new();
+        loop {
+            raw.clear();
+            match reader.read_until(b'\n', &mut raw) {
+                Ok(0) => break,
+                Ok(_) => batch.extend(parse_line(&String::from_utf8_lossy(&raw))),
+                Err(e) => {
+                    tx.send_batch(batch.drain(..).map(|p| (p.level, p.message)).collect());
+                    tx.send(Level::Warn, format!("can't read stdin: {e}"));
+                    return;
+                }
+            }
+            // send once the pipe is drained, so a trickle shows up line by
+            // line and a flood in batches
+            if batch.len() >= BATCH || reader.buffer().is_empty() {
+                tx.send_batch(batch.drain(..).map(|p| (p.level, p.message)).collect());
+            }
+        }
+        tx.send_batch(batch.drain(..).map(|p| (p.level, p.message)).collect());
+        tx.send(Level::Info, "end of input".to_string());
+    });
+}
@@ -0,0 +1,277 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Read},
+    net::{IpAddr, SocketAddr, TcpListener, TcpStream, UdpSocket},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crate::{
+    ingest::{IngestQueue, SourceSender},
+    logs::Level,
+    parse::{level_from_syslog, ParserPool},
+};
+
+// What messages from senders past MAX_TAGS are logged under
+pub const NAME: &str = "syslog";
+pub const QUEUE: usize = 1024;
+// Largest datagram read, and longest frame taken over TCP
+const MAX_MESSAGE: usize = 64 << 10;
+// Devices shown under their own name; past this many, the rest share NAME
+const MAX_TAGS: usize = 256;
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// `--syslog`: a syslog receiver on UDP and TCP at the same address, for
+// network devices and containers. Messages are logged under the host they
+// name, or the sender's IP, as `app[pid]: message` at their severity.
+// Datagrams all arrive on one socket, so they're parsed on a pool; a TCP
+// sender has a thread of its own already. Returns the address bound, which
+// differs from `addr` for port 0.
+pub fn spawn(addr: SocketAddr, ingest: IngestQueue) -> io::Result<SocketAddr> {
+    let udp = UdpSocket::bind(addr)?;
+    let addr = udp.local_addr()?;
+    let tcp = TcpListener::bind(addr)?;
+    let senders = Senders {
+        ingest,
+        tags: Arc::default(),
+    };
+    let datagrams = senders.clone();
+    let mut pool = ParserPool::new(
+        |(raw, peer): (String, IpAddr)| Some((parse(&raw)?, peer)),
+        move |messages| {
+            for (message, peer) in messages {
+                datagrams.deliver(message, peer);
+            }
+        },
+    );
+    thread::spawn(move || {
+        let mut buf = vec![0; MAX_MESSAGE];
+        while let Ok((n, peer)) = udp.recv_from(&mut buf) {
+            // some senders end the datagram with a newline, some batch a few
+            let lines = String::from_utf8_lossy(&buf[..n])
+                .lines()
+                .map(|line| (line.to_string(), peer.ip()))
+                .collect();
+            pool.feed(lines);
+        }
+    });
+    thread::spawn(move || {
+        for stream in tcp.incoming().flatten() {
+            let Ok(peer) = stream.peer_addr() else {
+                continue;
+            };
+            let senders = senders.clone();
+            thread::spawn(move || read(stream, peer.ip(), &senders));
+        }
+    });
+    Ok(addr)
+}
+
+// A sender per device, shared by both listeners
+#[derive(Clone)]
+struct Senders {
+    ingest: IngestQueue,
+    tags: Arc<Mutex<HashMap<String, SourceSender>>>,
+}
+
+impl Senders {
+    fn send(&self, raw: &str, peer: IpAddr) {
+        if let Some(message) = parse(raw) {
+            self.deliver(message, peer);
+        }
+    }
+
+    fn deliver(&self, message: Message, peer: IpAddr) {
+        let tag = message.host.clone().unwrap_or_else(|| peer.to_string());
+        let mut tags = self.tags.lock().unwrap();
+        let tag = match tags.len() < MAX_TAGS || tags.contains_key(&tag) {
+            true => tag,
+            false => NAME.to_string(),
+        };
+        let tx = tags
+            .entry(tag)
+            .or_insert_with_key(|tag| self.ingest.sender(tag, QUEUE));
+        tx.send(message.level, message.line());
+    }
+}
+
+// Frames over TCP are either counted, `LEN MESSAGE` as RFC 6587 has it, or
+// end at a newline; senders pick one and stick to it
+fn read(stream: TcpStream, peer: IpAddr, senders: &Senders) {
+    let mut reader = BufReader::new(stream);
+    let mut frame = Vec::new();
+    loop {
+        frame.clear();
+        let counted = match reader.fill_buf() {
+            Ok([]) | Err(_) => return,
+            Ok(buf) => buf[0].is_ascii_digit(),
+        };
+        let read = match counted {
+            true => counted_frame(&mut reader, &mut frame),
+            false => (&mut reader)
+                .take(MAX_MESSAGE as u64)
+                .read_until(b'\n', &mut frame)
+                .map(|_| ()),
+        };
+        if read.is_err() {
+            return;
+        }
+        senders.send(&String::from_utf8_lossy(&frame), peer);
+    }
+}
+
+fn counted_frame(reader: &mut BufReader<TcpStream>, frame: &mut Vec<u8>) -> io::Result<()> {
+    let mut len = Vec::new();
+    reader.take(8).read_until(b' ', &mut len)?;
+    let len = std::str::from_utf8(&len)
+        .ok()
+        .and_then(|len| len.trim_end().parse::<usize>().ok())
+        .filter(|len| *len <= MAX_MESSAGE)
+        .ok_or_else(|| io::Error::other("bad frame length"))?;
+    frame.resize(len, 0);
+    reader.read_exact(frame)
+}
+
+// One message, whichever RFC it was written to
+struct Message {
+    level: Level,
+    host: Option<String>,
+    app: Option<String>,
+    pid: Option<String>,
+    text: String,
+}
+
+impl Message {
+    // As the journal's lines read: `sshd[812]: Accepted publickey for ops`
+    fn line(&self) -> String {
+        match (&self.app, &self.pid) {
+            (Some(app), Some(pid)) => format!("{app}[{pid}]: {}", self.text),
+            (Some(app), None) => format!("{app}: {}", self.text),
+            _ => self.text.clone(),
+        }
+    }
+}
+
+// `<PRI>1 TIMESTAMP HOST APP PID MSGID [SD] MSG` is RFC 5424;
+// `<PRI>Mmm dd hh:mm:ss HOST TAG[PID]: MSG` is RFC 3164, where senders
+// often leave out the time or the host. A message without `<PRI>` is a
+// notice, as RFC 3164 says. Blank ones are dropped.
+fn parse(raw: &str) -> Option<Message> {
+    let raw = raw.trim_end_matches(['\r', '\n', '\0']);
+    if raw.trim().is_empty() {
+        return None;
+    }
+    let (severity, rest) = raw
+        .strip_prefix('<')
+        .and_then(|rest| rest.split_once('>'))
+        .and_then(|(pri, rest)| Some((pri.parse::<u8>().ok().filter(|p| *p < 192)? % 8, rest)))
+        .unwrap_or((5, raw));
+    let level = level_from_syslog(severity);
+    Some(match rest.strip_prefix("1 ") {
+        Some(rest) => rfc5424(level, rest),
+        None => rfc3164(level, rest),
+    })
+}
+
+fn rfc5424(level: Level, rest: &str) -> Message {
+    let fields: Vec<&str> = rest.splitn(6, ' ').collect();
+    let field = |i: usize| {
+        fields
+            .get(i)
+            .filter(|f| **f != "-" && !f.is_empty())
+            .map(|f| f.to_string())
+    };
+    let rest = fields.get(5).copied().unwrap_or("");
+    let text = structured_data(rest).unwrap_or(rest);
+    Message {
+        level,
+        host: field(1),
+        app: field(2),
+        pid: field(3),
+        text: text.trim_start_matches('\u{feff}').to_string(),
+    }
+}
+
+// What follows the structured data, `-` or `[id key="value" ...]...`, which
+// is dropped; None if it isn't well formed
+fn structured_data(rest: &str) -> Option<&str> {
+    let mut rest = match rest.strip_prefix('-') {
+        Some(text) => return Some(text.strip_prefix(' ').unwrap_or(text)),
+        None => rest,
+    };
+    while rest.starts_with('[') {
+        let (mut quoted, mut escaped) = (false, false);
+        let end = rest.char_indices().find_map(|(i, c)| {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if quoted => escaped = true,
+                '"' => quoted = !quoted,
+                ']' if !quoted => return Some(i),
+                _ => {}
+            }
+            None
+        })?;
+        rest = &rest[end + 1..];
+    }
+    match rest {
+        "" => Some(rest),
+        _ => rest.strip_prefix(' '),
+    }
+}
+
+fn rfc3164(level: Level, rest: &str) -> Message {
+    let (mut host, mut rest) = (None, rest.trim_start());
+    if let Some(after) = timestamp(rest) {
+        rest = after;
+        // a host comes next unless what's there is already the tag
+        if let Some((word, after)) = rest.split_once(' ') {
+            if !word.ends_with(':') && !word.contains('[') {
+                host = Some(word.to_string());
+                rest = after;
+            }
+        }
+    }
+    let (app, pid, text) = tag(rest);
+    Message {
+        level,
+        host,
+        app,
+        pid,
+        text,
+    }
+}
+
+// What follows an `Oct 16 22:14:15 ` timestamp, if one is there
+fn timestamp(rest: &str) -> Option<&str> {
+    let month = rest.get(..3)?;
+    let day = rest.get(3..7)?;
+    let time = rest.get(7..16)?;
+    let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    let ok = MONTHS.contains(&month)
+        && day.starts_with(' ')
+        && day.ends_with(' ')
+        && digits(day.trim())
+        && time
+            .trim_end()
+            .split(':')
+            .all(|p| p.len() == 2 && digits(p))
+        && time.ends_with(' ');
+    ok.then(|| rest[16..].trim_start())
+}
+
+// `app[pid]: text` or `app: text`; anything else is all text
+fn tag(rest: &str) -> (Option<String>, Option<String>, String) {
+    let Some((tag, text)) = rest.split_once(": ") else {
+        return (None, None, rest.to_string());
+    };
+    if tag.is_empty() || tag.contains(' ') {
+        return (None, None, rest.to_string());
+    }
+    let (app, pid) = match tag.strip_suffix(']').and_then(|t| t.split_once('[')) {
+        Some((app, pid)) => (app, Some(pid.to_string())),
+        None => (tag, None),
+    };
+    (Some(app.to_string()), pid, text.to_string())
+}
@@ -0,0 +1,189 @@
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+use ratatui::style::Color;
+use sysinfo::{DiskRefreshKind, Disks, Networks, System};
+
+use crate::app::MetricRow;
+
+// How often the sampler thread reads the machine
+pub const INTERVAL: Duration = Duration::from_secs(1);
+// Readings kept for time travel, expanded rows, and the report: six hours at INTERVAL
+const MAX_HISTORY: usize = 6 * 60 * 60;
+
+// This machine at one moment; rates are bytes per second since the last reading
+#[derive(Clone, Copy, Default)]
+pub struct Reading {
+    // 0..1
+    pub cpu: f32,
+    pub memory: f32,
+    pub disk: f32,
+    pub network: f32,
+}
+
+pub struct Sampler {
+    system: System,
+    disks: Disks,
+    networks: Networks,
+    last: Instant,
+}
+
+impl Sampler {
+    pub fn new() -> Self {
+        let mut system = System::new();
+        system.refresh_cpu_usage();
+        Self {
+            system,
+            disks: Disks::new_with_refreshed_list_specifics(
+                DiskRefreshKind::nothing().with_io_usage(),
+            ),
+            networks: Networks::new_with_refreshed_list(),
+            last: Instant::now(),
+        }
+    }
+
+    pub fn read(&mut self) -> Reading {
+        self.system.refresh_cpu_usage();
+        self.system.refresh_memory();
+        for disk in self.disks.list_mut() {
+            disk.refresh_specifics(DiskRefreshKind::nothing().with_io_usage());
+        }
+        self.networks.refresh(true);
+        let secs = self.last.elapsed().as_secs_f32().max(0.001);
+        self.last = Instant::now();
+
+        let disk: u64 = self
+            .disks
+            .list()
+            .iter()
+            .map(|d| d.usage().read_bytes + d.usage().written_bytes)
+            .sum();
+        // loopback traffic never leaves the machine
+        let network: u64 = self
+            .networks
+            .list()
+            .iter()
+            .filter(|(name, _)| !name.to_ascii_lowercase().starts_with("lo"))
+            .map(|(_, data)| data.received() + data.transmitted())
+            .sum();
+        let total = self.system.total_memory().max(1);
+        Reading {
+            cpu: self.system.global_cpu_usage() / 100.0,
+            memory: self.system.used_memory() as f32 / total as f32,
+            disk: disk as f32 / secs,
+            network: network as f32 / secs,
+        }
+    }
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Readings every INTERVAL from a background thread, which stops once the
+// receiver is dropped
+fn spawn() -> Receiver<Reading> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut sampler = Sampler::new();
+        loop {
+            thread::sleep(INTERVAL);
+            if tx.send(sampler.read()).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+// The system panel's feed: readings on the app clock, oldest first
+pub struct Machine {
+    pub name: Option<String>,
+    rx: Receiver<Reading>,
+    readings: VecDeque<(Duration, Reading)>,
+}
+
+impl Machine {
+    pub fn start() -> Self {
+        Self {
+            name: System::host_name(),
+            rx: spawn(),
+            readings: VecDeque::new(),
+        }
+    }
+
+    // Stamps whatever the sampler sent since the last tick with `now`
+    pub fn drain(&mut self, now: Duration) {
+        while let Ok(reading) = self.rx.try_recv() {
+            if self.readings.len() == MAX_HISTORY {
+                self.readings.pop_front();
+            }
+            self.readings.push_back((now, reading));
+        }
+    }
+
+    // The latest reading taken by `at`; before the first, the first (or
+    // zeros until there is one)
+    pub fn at(&self, at: Duration) -> Reading {
+        let i = self.readings.partition_point(|(t, _)| *t <= at);
+        self.readings
+            .get(i.saturating_sub(1))
+            .map(|(_, r)| *r)
+            .unwrap_or_default()
+    }
+}
+
+// `850K/s`, `12M/s`: six cells at most, the system panel's value column
+pub(crate) fn rate(bytes: f32) -> String {
+    match bytes {
+        b if b < 1e3 => format!("{b:.0}B/s"),
+        b if b < 1e6 => format!("{:.0}K/s", b / 1e3),
+        b if b < 1e7 => format!("{:.1}M/s", b / 1e6),
+        b if b < 1e9 => format!("{:.0}M/s", b / 1e6),
+        b => format!("{:.1}G/s", b / 1e9),
+    }
+}
+
+// What a full bar stands for: a busy SSD, and a saturated gigabit link
+const DISK_FULL: f32 = 500e6;
+const NETWORK_FULL: f32 = 125e6;
+
+// The system panel's rows for a reading
+pub fn rows(r: Reading) -> [MetricRow; 4] {
+    [
+        (
+            "cpu load",
+            r.cpu,
+            |v| format!("{:.0}%", v * 100.0),
+            r.cpu.clamp(0.0, 1.0),
+            Color::LightGreen,
+        ),
+        (
+            "memory",
+            r.memory,
+            |v| format!("{:.0}%", v * 100.0),
+            r.memory.clamp(0.0, 1.0),
+            Color::LightMagenta,
+        ),
+        (
+            "disk io",
+            r.disk,
+            rate,
+            (r.disk / DISK_FULL).clamp(0.0, 1.0),
+            Color::Cyan,
+        ),
+        (
+            "network",
+            r.network,
+            rate,
+            (r.network / NETWORK_FULL).clamp(0.0, 1.0),
+            Color::Yellow,
+        ),
+    ]
+}
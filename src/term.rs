@@ -0,0 +1,291 @@
+use std::{io, time::Duration};
+
+use crossterm::{
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, EventStream,
+    },
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::CrosstermBackend, Terminal, TerminalOptions, Viewport};
+use tokio::{
+    runtime,
+    sync::{mpsc, watch},
+    time,
+};
+use tokio_stream::StreamExt;
+
+use crate::{
+    app::{handle_event, AppState, Control},
+    cli::Cli,
+    config,
+    ingest::IngestQueue,
+    layout::PanelLayout,
+    marks,
+    profiler::FrameProfiler,
+    scenario::Scenario,
+    signals::Signals,
+    stdin,
+    sysmetrics::Machine,
+    ui::draw_frame,
+    wizard::Wizard,
+};
+
+// How often the render task checks for termination and suspend requests
+const SIGNAL_POLL: Duration = Duration::from_millis(50);
+// Shortest gap between two ingest wakeups, about a frame at 60Hz
+const INGEST_BATCH: Duration = Duration::from_millis(16);
+
+// The dashboard on the real terminal, until a quit or a signal
+pub fn run(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(dashboard(cli))
+}
+
+async fn dashboard(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let signals = Signals::install()?;
+
+    let scenario = match &cli.scenario {
+        Some(path) => match Scenario::load(path) {
+            Ok(scenario) => Some(scenario),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let inline = cli.inline.is_some();
+    let mut app = AppState::new(cli);
+    if let Some(addr) = cli.mark_listen {
+        if let Err(e) = marks::listen(addr, app.mark_tx.clone()) {
+            eprintln!("--mark-listen {addr}: {e}");
+            std::process::exit(1);
+        }
+    }
+    app.machine = (!cli.simulate).then(Machine::start);
+    // `my-server | ai-intui`: keys still arrive, since crossterm reads them
+    // from the terminal itself (/dev/tty, CONIN$) when stdin isn't one
+    if stdin::piped() {
+        app.read_stdin();
+    }
+    // a kiosk runs unattended, so nobody would be there to answer the wizard
+    let unattended = cli.read_only || scenario.is_some();
+    app.scenario = scenario;
+    let unconfigured = config::layers(cli).iter().all(|l| !l.path.is_file());
+    if unconfigured && cli.config.is_none() && !unattended {
+        app.wizard = Some(Wizard::new(
+            &app.dirs.data.display().to_string(),
+            cli.simulate,
+        ));
+    }
+    let mut mouse = app.mouse_capture;
+    enter_terminal(inline, mouse)?;
+    let backend = CrosstermBackend::new(io::stdout());
+    let viewport = match cli.inline {
+        Some(rows) => Viewport::Inline(rows),
+        None => Viewport::Fullscreen,
+    };
+    let mut terminal = Terminal::with_options(backend, TerminalOptions { viewport })?;
+
+    let mut panels = PanelLayout::default();
+    let mut profiler = FrameProfiler::new(cli.profile_frames.clone());
+
+    // input, ticks, and ingestion each get a task; this one owns the app and
+    // the terminal, and redraws whenever one of them changed something
+    let (input_tx, mut input_rx) = mpsc::unbounded_channel();
+    tokio::spawn(read_input(input_tx));
+    let (interval_tx, interval_rx) = watch::channel(app.tick_interval());
+    let (tick_tx, mut tick_rx) = mpsc::channel(1);
+    tokio::spawn(ticks(interval_rx, tick_tx));
+    let (ingest_tx, mut ingest_rx) = mpsc::channel(1);
+    tokio::spawn(watch_ingest(app.ingest.clone(), ingest_tx));
+    let mut signal_poll = time::interval(SIGNAL_POLL);
+    // sources faster than the tick are sampled in between, without a redraw;
+    // the next tick draws each one's mean
+    let mut sample_every = app.sample_interval();
+    let mut sampler = sample_timer(sample_every);
+
+    let mut exit_signal = None;
+
+    'main: loop {
+        if let Some(sig) = signals.pending() {
+            exit_signal = Some(sig);
+            break;
+        }
+        if signals.take_suspend() {
+            suspend(&mut terminal, inline, mouse)?;
+            app.mark_dirty();
+        }
+
+        if app.dirty {
+            draw_frame(&mut terminal, &mut app, &mut panels, &mut profiler)?;
+        }
+        if *interval_tx.borrow() != app.tick_interval() {
+            interval_tx.send_replace(app.tick_interval());
+        }
+        if app.sample_interval() != sample_every {
+            sample_every = app.sample_interval();
+            sampler = sample_timer(sample_every);
+        }
+
+        tokio::select! {
+            Some(ev) = input_rx.recv() => {
+                // everything that queued up since the last frame, before redrawing
+                let mut next = Some(ev);
+                while let Some(ev) = next {
+                    let ev = ev?;
+                    match profiler.time("events", || handle_event(&mut app, ev)) {
+                        Control::Continue => {}
+                        Control::Quit => break 'main,
+                        Control::Suspend => {
+                            suspend(&mut terminal, inline, mouse)?;
+                            app.mark_dirty();
+                        }
+                    }
+                    next = input_rx.try_recv().ok();
+                }
+            }
+            Some(()) = tick_rx.recv() => profiler.time("tick", || app.tick()),
+            Some(()) = ingest_rx.recv() => app.drain_ingest(),
+            _ = sampler.tick(), if sample_every.is_some() => profiler.time("sample", || app.sample()),
+            _ = signal_poll.tick() => {}
+        }
+
+        if app.mouse_capture != mouse {
+            mouse = app.mouse_capture;
+            set_mouse_capture(mouse)?;
+        }
+    }
+
+    leave_terminal(inline, mouse)?;
+    if inline {
+        // leave the last frame in scrollback and put the prompt below it
+        println!();
+    }
+    terminal.show_cursor()?;
+    profiler.write_report()?;
+    if let Some(bench) = &app.bench {
+        let dropped = app.ingest.dropped().iter().map(|(_, n)| n).sum();
+        println!("{}", bench.report(app.ingested, dropped));
+    }
+
+    if let Some(sig) = exit_signal {
+        std::process::exit(Signals::exit_code(sig));
+    }
+    Ok(())
+}
+
+// Terminal events as they arrive; stops with the dashboard or at the first error
+async fn read_input(tx: mpsc::UnboundedSender<io::Result<Event>>) {
+    let mut events = EventStream::new();
+    while let Some(ev) = events.next().await {
+        let failed = ev.is_err();
+        if tx.send(ev).is_err() || failed {
+            break;
+        }
+    }
+}
+
+// One message per tick; the interval follows the app's (idle slows it down)
+async fn ticks(mut interval: watch::Receiver<Duration>, tx: mpsc::Sender<()>) {
+    loop {
+        let every = *interval.borrow_and_update();
+        tokio::select! {
+            _ = time::sleep(every) => {
+                if tx.send(()).await.is_err() {
+                    break;
+                }
+            }
+            changed = interval.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// Missed samples are skipped: a busy frame shouldn't be followed by a burst
+// of them
+fn sample_timer(every: Option<Duration>) -> time::Interval {
+    let mut timer = time::interval(every.unwrap_or(SIGNAL_POLL));
+    timer.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+    timer
+}
+
+// Wakes the render task when sources sent lines, at most once per INGEST_BATCH
+// so a flood is drawn in batches rather than a frame per line
+async fn watch_ingest(queue: IngestQueue, tx: mpsc::Sender<()>) {
+    loop {
+        queue.ready().await;
+        if tx.send(()).await.is_err() {
+            break;
+        }
+        time::sleep(INGEST_BATCH).await;
+    }
+}
+
+// inline mode draws a fixed-height region in the normal buffer, keeping shell scrollback
+fn enter_terminal(inline: bool, mouse: bool) -> io::Result<()> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    if !inline {
+        execute!(stdout, EnterAlternateScreen)?;
+    }
+    // best effort: legacy Windows consoles can't do bracketed paste
+    let _ = execute!(stdout, EnableBracketedPaste);
+    if mouse {
+        set_mouse_capture(true)?;
+    }
+    Ok(())
+}
+
+fn set_mouse_capture(on: bool) -> io::Result<()> {
+    if on {
+        execute!(io::stdout(), EnableMouseCapture)
+    } else {
+        execute!(io::stdout(), DisableMouseCapture)
+    }
+}
+
+fn leave_terminal(inline: bool, mouse: bool) -> io::Result<()> {
+    disable_raw_mode()?;
+    let mut stdout = io::stdout();
+    let _ = execute!(stdout, DisableBracketedPaste);
+    if mouse {
+        set_mouse_capture(false)?;
+    }
+    if !inline {
+        execute!(stdout, LeaveAlternateScreen)?;
+    }
+    Ok(())
+}
+
+// Job control: hand the terminal back to the shell, stop, and on SIGCONT take
+// it over again with a full redraw
+#[cfg(unix)]
+fn suspend(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    inline: bool,
+    mouse: bool,
+) -> io::Result<()> {
+    leave_terminal(inline, mouse)?;
+    terminal.show_cursor()?;
+    crate::signals::stop_self()?;
+    enter_terminal(inline, mouse)?;
+    terminal.clear()
+}
+
+#[cfg(not(unix))]
+fn suspend(
+    _terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    _inline: bool,
+    _mouse: bool,
+) -> io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, FixedOffset, Local};
+
+// Wall clock may drift this far from the monotonic projection before we rebase
+const MAX_DRIFT: Duration = Duration::from_secs(2);
+
+// Pairs the monotonic clock (ordering, intervals, synthetic curves) with wall
+// time (what users and exports see). Everything is stored as a monotonic
+// offset from start and converted to wall time through a single anchor; if the
+// system clock jumps (NTP step, suspend) the anchor is rebased.
+//
+// A simulated timebase ignores real time entirely: it only moves when
+// `advance` is called and maps onto wall time from the Unix epoch in UTC, so
+// runs are reproducible byte for byte.
+pub struct Timebase {
+    start: Instant,
+    anchor_mono: Instant,
+    anchor_wall: SystemTime,
+    virtual_now: Option<Duration>,
+}
+
+impl Timebase {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            anchor_mono: now,
+            anchor_wall: SystemTime::now(),
+            virtual_now: None,
+        }
+    }
+
+    pub fn simulated() -> Self {
+        Self {
+            anchor_wall: UNIX_EPOCH,
+            virtual_now: Some(Duration::ZERO),
+            ..Self::new()
+        }
+    }
+
+    pub fn advance(&mut self, step: Duration) {
+        if let Some(now) = &mut self.virtual_now {
+            *now += step;
+        }
+    }
+
+    // Monotonic (or virtual) offset since start
+    pub fn elapsed(&self) -> Duration {
+        self.virtual_now.unwrap_or_else(|| self.start.elapsed())
+    }
+
+    pub fn wall_at(&self, at: Duration) -> SystemTime {
+        if self.virtual_now.is_some() {
+            return UNIX_EPOCH + at;
+        }
+        let mono = self.start + at;
+        if mono >= self.anchor_mono {
+            self.anchor_wall + (mono - self.anchor_mono)
+        } else {
+            self.anchor_wall - (self.anchor_mono - mono)
+        }
+    }
+
+    // Local time for display; UTC when simulated so output doesn't depend on the host
+    pub fn local_at(&self, at: Duration) -> DateTime<FixedOffset> {
+        let wall = self.wall_at(at);
+        if self.virtual_now.is_some() {
+            DateTime::<chrono::Utc>::from(wall).fixed_offset()
+        } else {
+            DateTime::<Local>::from(wall).fixed_offset()
+        }
+    }
+
+    // Re-anchors when the wall clock has moved away from the monotonic
+    // projection; returns the size of the jump and whether it went forward
+    pub fn check_drift(&mut self) -> Option<(Duration, bool)> {
+        if self.virtual_now.is_some() {
+            return None;
+        }
+        let now_mono = Instant::now();
+        let now_wall = SystemTime::now();
+        let projected = self.anchor_wall + (now_mono - self.anchor_mono);
+        let (jump, forward) = match now_wall.duration_since(projected) {
+            Ok(ahead) => (ahead, true),
+            Err(behind) => (behind.duration(), false),
+        };
+        if jump <= MAX_DRIFT {
+            return None;
+        }
+        self.anchor_mono = now_mono;
+        self.anchor_wall = now_wall;
+        Some((jump, forward))
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use ratatui::prelude::*;
+
+// Room for an HH:MM:SS label and a space at either end of the track
+const LABEL_W: u16 = 9;
+
+// Where the scrubber sits: the last row inside the logs panel's border
+pub fn rect(logs: Rect) -> Rect {
+    if logs.height < 4 || logs.width < 2 + 2 * LABEL_W + 4 {
+        return Rect::default();
+    }
+    Rect::new(logs.x + 1, logs.bottom() - 2, logs.width - 2, 1)
+}
+
+fn track(rect: Rect) -> Rect {
+    Rect::new(rect.x + LABEL_W, rect.y, rect.width - 2 * LABEL_W, 1)
+}
+
+// The moment under `column`, for clicks and drags on the timeline
+pub fn position(rect: Rect, column: u16, start: Duration, end: Duration) -> Option<Duration> {
+    if rect.is_empty() {
+        return None;
+    }
+    let track = track(rect);
+    let last = track.width.saturating_sub(1).max(1);
+    let offset = column.clamp(track.x, track.right() - 1) - track.x;
+    Some(start + (end.saturating_sub(start)).mul_f64(f64::from(offset) / f64::from(last)))
+}
+
+// Retained history from `start` to `end` (now) with a marker at `at` and a
+// tick at each `mark`; `label` formats the two ends
+pub fn draw(
+    buf: &mut Buffer,
+    rect: Rect,
+    (start, end, at): (Duration, Duration, Duration),
+    marks: &[Duration],
+    label: impl Fn(Duration) -> String,
+    ascii: bool,
+) {
+    if rect.is_empty() {
+        return;
+    }
+    let track = track(rect);
+    let (line, marker, tick) = if ascii {
+        ('-', 'O', '|')
+    } else {
+        ('─', '●', '┼')
+    };
+    let span = end.saturating_sub(start).as_secs_f64();
+    let column = |t: Duration| {
+        let frac = if span > 0.0 {
+            (t.saturating_sub(start).as_secs_f64() / span).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        (frac * f64::from(track.width.saturating_sub(1))).round() as u16
+    };
+    let pos = column(at);
+    let bar: String = (0..track.width)
+        .map(|i| if i == pos { marker } else { line })
+        .collect();
+
+    let dim = Style::default().fg(Color::DarkGray);
+    buf.set_string(rect.x, rect.y, label(start), dim);
+    buf.set_string(track.x, track.y, bar, Style::default().fg(Color::Yellow));
+    for &mark in marks.iter().filter(|&&m| m >= start && m <= end) {
+        let x = track.x + column(mark);
+        if x != track.x + pos {
+            buf.get_mut(x, track.y)
+                .set_char(tick)
+                .set_fg(Color::LightYellow);
+        }
+    }
+    buf.set_string(track.right() + 1, rect.y, label(end), dim);
+}
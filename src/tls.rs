@@ -0,0 +1,102 @@
+// TLS for https://, wss://, and mqtts:// sources: the roots a server's
+// certificate is checked against, and the client certificate shown to one
+// that asks. Only in builds with the feature; without it, a TLS URL is
+// refused when its source starts.
+pub use sys::Tls;
+
+#[cfg(feature = "tls")]
+mod sys {
+    use std::{io, net::TcpStream, path::Path, sync::Arc, time::Duration};
+
+    use rustls::{
+        pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer, ServerName},
+        ClientConfig, ClientConnection, RootCertStore, StreamOwned,
+    };
+
+    use crate::{auth::AuthSpec, net::Stream};
+
+    pub struct Tls(Arc<ClientConfig>);
+
+    impl Tls {
+        // The files `auth` names are read now, so a bad one is reported at
+        // start rather than on every reconnect
+        pub fn new(auth: &AuthSpec) -> Result<Self, String> {
+            let mut roots = RootCertStore::empty();
+            match &auth.ca {
+                Some(path) => {
+                    for cert in certs(path)? {
+                        roots
+                            .add(cert)
+                            .map_err(|e| format!("{}: {e}", path.display()))?;
+                    }
+                }
+                None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+            }
+            let provider = Arc::new(rustls::crypto::ring::default_provider());
+            let builder = ClientConfig::builder_with_provider(provider)
+                .with_safe_default_protocol_versions()
+                .map_err(|e| e.to_string())?
+                .with_root_certificates(roots);
+            let config = match (&auth.cert, &auth.key) {
+                (Some(cert), Some(key)) => {
+                    let der = PrivateKeyDer::from_pem_file(key)
+                        .map_err(|e| format!("{}: {e}", key.display()))?;
+                    builder
+                        .with_client_auth_cert(certs(cert)?, der)
+                        .map_err(|e| format!("{}: {e}", key.display()))?
+                }
+                _ => builder.with_no_client_auth(),
+            };
+            Ok(Self(Arc::new(config)))
+        }
+
+        // `tcp` once the handshake with `host` is done; it's given up on
+        // after `timeout` without a word from the server
+        pub fn wrap(
+            &self,
+            host: &str,
+            mut tcp: TcpStream,
+            timeout: Duration,
+        ) -> io::Result<Stream> {
+            let name = ServerName::try_from(host.to_string())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let mut conn = ClientConnection::new(self.0.clone(), name).map_err(io::Error::other)?;
+            tcp.set_read_timeout(Some(timeout))?;
+            tcp.set_write_timeout(Some(timeout))?;
+            while conn.is_handshaking() {
+                conn.complete_io(&mut tcp)?;
+            }
+            Ok(Stream::Tls(Box::new(StreamOwned::new(conn, tcp))))
+        }
+    }
+
+    // Every certificate in a PEM file; none is an error
+    fn certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+        let certs = CertificateDer::pem_file_iter(path)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        if certs.is_empty() {
+            return Err(format!("{}: no certificates in it", path.display()));
+        }
+        Ok(certs)
+    }
+}
+
+#[cfg(not(feature = "tls"))]
+mod sys {
+    use std::{io, net::TcpStream, time::Duration};
+
+    use crate::{auth::AuthSpec, net::Stream};
+
+    pub enum Tls {}
+
+    impl Tls {
+        pub fn new(_auth: &AuthSpec) -> Result<Self, String> {
+            Err("no TLS: this build doesn't include it; rebuild with `--features tls`".to_string())
+        }
+
+        pub fn wrap(&self, _host: &str, _tcp: TcpStream, _timeout: Duration) -> io::Result<Stream> {
+            match *self {}
+        }
+    }
+}
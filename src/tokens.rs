@@ -0,0 +1,792 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    time::{Duration, Instant},
+};
+
+use ratatui::{prelude::*, widgets::Paragraph};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::{
+    chrome::Chrome,
+    cli::parse_http_addr,
+    detail::LEVELS,
+    glyphs::GlyphSet,
+    http::{self, Chunks, Request},
+    ingest::SourceSender,
+    layout::{fit, sanitize},
+    logs::Level,
+    net::{authority, Authority},
+    sampling::{self, Exchange, SamplingSpec},
+};
+
+// What finished streams are logged under
+pub const NAME: &str = "llm";
+pub const QUEUE: usize = 256;
+// A gap this long between tokens is a stall
+pub const STALL: Duration = Duration::from_secs(1);
+// Streams kept for the strip
+const RECENT: usize = 32;
+// Columns of token counts kept per stream, one a second
+const COLUMNS: usize = 512;
+// Largest request body forwarded
+const MAX_BODY: usize = 8 << 20;
+// Reading the client's request, and connecting to the server
+const TIMEOUT: Duration = Duration::from_secs(10);
+// Silence from the server, before its headers or mid-stream, that ends a
+// request; a long non-streamed completion has to fit in it
+const GIVE_UP: Duration = Duration::from_secs(300);
+// A non-streamed response body read for its text and usage, up to this much
+const MAX_REPLY: usize = 1 << 20;
+// The strip's columns for each stream's name, TTFT, and rate
+const LABEL_W: usize = 16;
+
+// One `--llm-proxy PORT=URL`: where clients connect, and the
+// OpenAI-compatible server their requests go on to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProxySpec {
+    pub listen: SocketAddr,
+    pub upstream: Upstream,
+}
+
+// Plain http only, like `[prometheus]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Upstream {
+    url: String,
+    authority: Authority,
+    // put in front of every forwarded path, without a trailing slash
+    prefix: String,
+}
+
+impl FromStr for Upstream {
+    type Err = String;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        if !url.starts_with("http://") {
+            return Err(match url {
+                u if u.starts_with("https://") => {
+                    "https isn't supported; proxy a server reachable over http".to_string()
+                }
+                u => format!("expected an http:// URL, got `{u}`"),
+            });
+        }
+        let (authority, prefix) = authority(url, 80)?;
+        Ok(Self {
+            url: url.to_string(),
+            authority,
+            prefix: prefix.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Upstream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.url)
+    }
+}
+
+impl Upstream {
+    fn connect(&self) -> io::Result<TcpStream> {
+        self.authority.dial(TIMEOUT)
+    }
+}
+
+impl FromStr for ProxySpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (listen, url) = s.split_once('=').ok_or_else(|| {
+            format!("expected PORT=http://HOST:PORT, e.g. 8001=http://127.0.0.1:8000, got `{s}`")
+        })?;
+        Ok(Self {
+            listen: parse_http_addr(listen)?,
+            upstream: url.trim().parse()?,
+        })
+    }
+}
+
+impl fmt::Display for ProxySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.listen, self.upstream)
+    }
+}
+
+impl<'de> Deserialize<'de> for ProxySpec {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        String::deserialize(d)?.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for ProxySpec {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+// What the relay threads report about the streams going through them
+enum Update {
+    Start {
+        id: u64,
+        label: String,
+    },
+    // `ttft` comes with the first tokens only
+    Tokens {
+        id: u64,
+        count: u32,
+        ttft: Option<Duration>,
+    },
+    // STALL without a token, after the first
+    Stall {
+        id: u64,
+    },
+    End {
+        id: u64,
+        error: Option<String>,
+    },
+    // any request, streamed or not, once it's over
+    Finished(Box<Exchange>),
+}
+
+// One streamed response, stamped on the app's clock
+pub struct Stream {
+    pub id: u64,
+    // the model asked for, or the path
+    pub label: String,
+    pub started: Duration,
+    pub ttft: Option<Duration>,
+    pub tokens: u64,
+    // (second on the app's clock, tokens that arrived in it)
+    counts: VecDeque<(u64, u32)>,
+    // (from, to); an open one is still going
+    stalls: Vec<(Duration, Option<Duration>)>,
+    pub ended: Option<Duration>,
+    pub error: Option<String>,
+}
+
+impl Stream {
+    fn count(&self, second: u64) -> u32 {
+        self.counts
+            .iter()
+            .find(|(s, _)| *s == second)
+            .map_or(0, |(_, n)| *n)
+    }
+
+    fn stalled(&self, from: Duration, to: Duration) -> bool {
+        self.stalls
+            .iter()
+            .any(|&(start, end)| start < to && end.unwrap_or(Duration::MAX) > from)
+    }
+
+    pub fn stalling(&self) -> bool {
+        self.stalls.last().is_some_and(|(_, end)| end.is_none())
+    }
+
+    // Tokens a second since the first, up to `now` or the end
+    fn rate(&self, now: Duration) -> Option<f64> {
+        let first = self.started + self.ttft?;
+        let secs = self
+            .ended
+            .unwrap_or(now)
+            .saturating_sub(first)
+            .as_secs_f64();
+        (secs > 0.0).then(|| self.tokens as f64 / secs)
+    }
+
+    fn close_stall(&mut self, now: Duration) {
+        if let Some((_, end @ None)) = self.stalls.last_mut() {
+            *end = Some(now);
+        }
+    }
+}
+
+// --llm-proxy: a pass-through HTTP proxy in front of an OpenAI-compatible
+// server (vLLM, llama.cpp, Ollama's /v1, ...). Every request goes on
+// untouched; responses streamed as server-sent events are timed on the way
+// back, for AI observability mode's strip of token rates, TTFT, and stalls.
+pub struct TokenStreams {
+    pub listen: SocketAddr,
+    pub upstream: String,
+    pub streams: VecDeque<Stream>,
+    // every request, newest first, for `requests`
+    pub requests: VecDeque<Exchange>,
+    pub sample: f64,
+    rx: Receiver<Update>,
+}
+
+impl TokenStreams {
+    pub fn start(spec: &ProxySpec, sampling: SamplingSpec, tx: SourceSender) -> io::Result<Self> {
+        let listener = TcpListener::bind(spec.listen)?;
+        let listen = listener.local_addr()?;
+        let (updates, rx) = mpsc::channel();
+        let sample = sampling.sample;
+        let relay_state = Relay {
+            upstream: spec.upstream.clone(),
+            sampling,
+            updates,
+            tx,
+        };
+        let next = AtomicU64::new(0);
+        http::serve(listener, TIMEOUT, move |client, reader| {
+            let id = next.fetch_add(1, Ordering::Relaxed);
+            relay(client, reader, id, &relay_state)
+        });
+        Ok(Self {
+            listen,
+            upstream: spec.upstream.to_string(),
+            streams: VecDeque::new(),
+            requests: VecDeque::new(),
+            sample,
+            rx,
+        })
+    }
+
+    pub fn drain(&mut self, now: Duration) {
+        while let Ok(update) = self.rx.try_recv() {
+            if let Update::Finished(mut exchange) = update {
+                if self.requests.len() == sampling::KEPT {
+                    self.requests.pop_back();
+                }
+                exchange.at = now;
+                self.requests.push_front(*exchange);
+                continue;
+            }
+            if let Update::Start { id, label } = update {
+                if self.streams.len() == RECENT {
+                    self.streams.pop_back();
+                }
+                self.streams.push_front(Stream {
+                    id,
+                    label,
+                    started: now,
+                    ttft: None,
+                    tokens: 0,
+                    counts: VecDeque::new(),
+                    stalls: Vec::new(),
+                    ended: None,
+                    error: None,
+                });
+                continue;
+            }
+            let id = match &update {
+                Update::Start { id, .. }
+                | Update::Tokens { id, .. }
+                | Update::Stall { id }
+                | Update::End { id, .. } => *id,
+                Update::Finished(_) => continue,
+            };
+            // dropped from RECENT while still going
+            let Some(stream) = self.streams.iter_mut().find(|s| s.id == id) else {
+                continue;
+            };
+            match update {
+                Update::Start { .. } | Update::Finished(_) => {}
+                Update::Tokens { count, ttft, .. } => {
+                    stream.close_stall(now);
+                    if ttft.is_some() {
+                        stream.ttft = ttft;
+                    }
+                    stream.tokens += u64::from(count);
+                    let second = now.as_secs();
+                    match stream.counts.back_mut() {
+                        Some((s, n)) if *s == second => *n += count,
+                        _ => {
+                            if stream.counts.len() == COLUMNS {
+                                stream.counts.pop_front();
+                            }
+                            stream.counts.push_back((second, count));
+                        }
+                    }
+                }
+                // the silence started STALL ago
+                Update::Stall { .. } => stream.stalls.push((now.saturating_sub(STALL), None)),
+                Update::End { error, .. } => {
+                    stream.close_stall(now);
+                    stream.ended = Some(now);
+                    stream.error = error;
+                }
+            }
+        }
+    }
+
+    // The median time to first token over the kept streams
+    pub fn ttft_p50(&self) -> Option<Duration> {
+        let mut ttfts: Vec<Duration> = self.streams.iter().filter_map(|s| s.ttft).collect();
+        ttfts.sort();
+        ttfts.get(ttfts.len().saturating_sub(1) / 2).copied()
+    }
+}
+
+// What the relay threads share
+struct Relay {
+    upstream: Upstream,
+    sampling: SamplingSpec,
+    updates: Sender<Update>,
+    tx: SourceSender,
+}
+
+impl Relay {
+    fn send(&self, update: Update) {
+        // the app is gone; the client still gets its tokens
+        let _ = self.updates.send(update);
+    }
+
+    // Hands a finished request to the viewer, with its text if it was sampled
+    fn finish(
+        &self,
+        mut exchange: Exchange,
+        started: Instant,
+        prompt: Option<String>,
+        response: Option<String>,
+    ) {
+        exchange.latency = started.elapsed();
+        exchange.text = prompt.map(|prompt| {
+            let response = response.unwrap_or_default();
+            (self.sampling.clean(&prompt), self.sampling.clean(&response))
+        });
+        self.send(Update::Finished(Box::new(exchange)));
+    }
+}
+
+// Forwards one client connection's request and copies the response back,
+// timing it if it's an event stream
+fn relay(
+    mut client: TcpStream,
+    mut reader: BufReader<TcpStream>,
+    id: u64,
+    relay: &Relay,
+) -> io::Result<()> {
+    let (upstream, tx) = (&relay.upstream, &relay.tx);
+    let request = match Request::read(&mut reader)? {
+        Ok(request) => request,
+        Err(reply) => return http::reply_text(&mut client, reply),
+    };
+    let Some(body) = request.body(&mut reader, MAX_BODY)? else {
+        return http::reply_text(
+            &mut client,
+            (
+                "413 Payload Too Large",
+                "request body too large".to_string(),
+            ),
+        );
+    };
+    // one request per connection, to a different host, with a body that
+    // goes on whole
+    let headers = request.headers.iter().filter(|(name, _)| {
+        !matches!(
+            name.to_ascii_lowercase().as_str(),
+            "host"
+                | "connection"
+                | "keep-alive"
+                | "proxy-connection"
+                | "content-length"
+                | "transfer-encoding"
+        )
+    });
+
+    let method = match request.method.as_str() {
+        "" => "GET",
+        method => method,
+    };
+    let target = match request.target.as_str() {
+        "" => "/",
+        target => target,
+    };
+    let label = serde_json::from_slice::<Value>(&body)
+        .ok()
+        .and_then(|v| v.get("model")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| target.split('?').next().unwrap_or(target).to_string());
+    let started = Instant::now();
+    let prompt = relay.sampling.sampled().then(|| sampling::prompt(&body));
+    let mut exchange = Exchange {
+        at: Duration::ZERO,
+        method: method.to_string(),
+        path: target.to_string(),
+        label: label.clone(),
+        status: None,
+        error: None,
+        latency: Duration::ZERO,
+        ttft: None,
+        tokens: None,
+        text: None,
+    };
+    let mut server = match upstream.connect() {
+        Ok(server) => server,
+        Err(e) => {
+            tx.send(Level::Warn, format!("can't reach {upstream}: {e}"));
+            exchange.error = Some(format!("can't reach {upstream}: {e}"));
+            relay.finish(exchange, started, prompt, None);
+            return http::reply_text(&mut client, ("502 Bad Gateway", e.to_string()));
+        }
+    };
+    let mut head = format!(
+        "{method} {}{target} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n",
+        upstream.prefix, upstream.authority.written
+    );
+    for (name, value) in headers {
+        head += &format!("{name}: {value}\r\n");
+    }
+    head += &format!("Content-Length: {}\r\n\r\n", body.len());
+    server.write_all(head.as_bytes())?;
+    server.write_all(&body)?;
+    let sent = Instant::now();
+
+    // the head comes back as is; the body is looked at on its way through
+    server.set_read_timeout(Some(GIVE_UP))?;
+    let mut response = BufReader::new(server);
+    let (mut stream, mut chunked) = (false, false);
+    loop {
+        let mut header = String::new();
+        if response.read_line(&mut header)? == 0 {
+            exchange.error = Some("closed without answering".to_string());
+            relay.finish(exchange, started, prompt, None);
+            return Ok(());
+        }
+        client.write_all(header.as_bytes())?;
+        if header.trim().is_empty() {
+            break;
+        }
+        if exchange.status.is_none() {
+            exchange.status = header
+                .split_whitespace()
+                .nth(1)
+                .and_then(|s| s.parse().ok());
+        }
+        let lower = header.to_ascii_lowercase();
+        stream |= lower.starts_with("content-type:") && lower.contains("text/event-stream");
+        chunked |= lower.starts_with("transfer-encoding:") && lower.contains("chunked");
+    }
+    if !stream {
+        // kept, up to MAX_REPLY, for its text and usage
+        let mut chunks = chunked.then(Chunks::default);
+        let mut kept = Vec::new();
+        let mut buf = [0; 8192];
+        loop {
+            let n = match response.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    exchange.error = Some(e.to_string());
+                    break;
+                }
+            };
+            if let Err(e) = client.write_all(&buf[..n]) {
+                exchange.error = Some(format!("client went away: {e}"));
+                break;
+            }
+            if kept.len() < MAX_REPLY {
+                match &mut chunks {
+                    Some(chunks) => kept.extend(chunks.feed(&buf[..n])),
+                    None => kept.extend_from_slice(&buf[..n]),
+                }
+            }
+        }
+        let (text, tokens) = sampling::reply(&kept);
+        exchange.tokens = tokens;
+        relay.finish(exchange, started, prompt, text);
+        return Ok(());
+    }
+
+    relay.send(Update::Start {
+        id,
+        label: label.clone(),
+    });
+    // short reads, so a stall shows while it's happening
+    response.get_ref().set_read_timeout(Some(STALL))?;
+    let mut events = Events::new(chunked, prompt.is_some());
+    let (mut first, mut last) = (None::<Instant>, sent);
+    let (mut tokens, mut usage, mut stalls, mut longest) = (0, None, 0, Duration::ZERO);
+    let mut stalling = false;
+    let mut buf = [0; 8192];
+    let error = loop {
+        match response.read(&mut buf) {
+            Ok(0) => break None,
+            Ok(n) => {
+                if let Err(e) = client.write_all(&buf[..n]) {
+                    break Some(format!("client went away: {e}"));
+                }
+                let (count, total) = events.feed(&buf[..n]);
+                usage = total.or(usage);
+                if count == 0 {
+                    continue;
+                }
+                let now = Instant::now();
+                if first.is_some() {
+                    longest = longest.max(now - last);
+                }
+                let ttft = first.is_none().then(|| now - sent);
+                first.get_or_insert(now);
+                last = now;
+                stalling = false;
+                tokens += u64::from(count);
+                relay.send(Update::Tokens { id, count, ttft });
+            }
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                if last.elapsed() >= GIVE_UP {
+                    break Some(format!("nothing for {} s", GIVE_UP.as_secs()));
+                }
+                if first.is_some() && !stalling {
+                    stalling = true;
+                    stalls += 1;
+                    relay.send(Update::Stall { id });
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => break Some(e.to_string()),
+        }
+    };
+    relay.send(Update::End {
+        id,
+        error: error.clone(),
+    });
+
+    // a final `usage` has the exact count
+    let tokens = usage.unwrap_or(tokens);
+    exchange.ttft = first.map(|first| first - sent);
+    exchange.tokens = Some(tokens);
+    exchange.error = error.clone();
+    relay.finish(exchange, started, prompt, events.text);
+    let label = sanitize(&label);
+    if let Some(error) = error {
+        tx.send(
+            Level::Warn,
+            format!("{label}: stream cut off after {tokens} tokens: {error}"),
+        );
+        return Ok(());
+    }
+    let mut line = format!("{label}: {tokens} tokens in {}", span(sent.elapsed()));
+    if let Some(first) = first {
+        let rate = tokens as f64 / last.duration_since(first).as_secs_f64().max(0.001);
+        line += &format!(", ttft {}, {rate:.0} tok/s", span(first - sent));
+    }
+    if stalls == 0 {
+        tx.send(Level::Info, line);
+    } else {
+        line += &format!(
+            ", {stalls} stall{} (longest {})",
+            if stalls == 1 { "" } else { "s" },
+            span(longest)
+        );
+        tx.send(Level::Warn, line);
+    }
+    Ok(())
+}
+
+// `240ms`, or `8.4s`
+pub(crate) fn span(d: Duration) -> String {
+    match d.as_millis() {
+        ms if ms < 1000 => format!("{ms}ms"),
+        _ => format!("{:.1}s", d.as_secs_f64()),
+    }
+}
+
+// A `text/event-stream` body read as it arrives, chunked or not, for the
+// tokens in its `data:` events
+struct Events {
+    chunks: Option<Chunks>,
+    line: Vec<u8>,
+    // the response so far, when it's being sampled
+    text: Option<String>,
+}
+
+impl Events {
+    fn new(chunked: bool, sampled: bool) -> Self {
+        Self {
+            chunks: chunked.then(Chunks::default),
+            line: Vec::new(),
+            text: sampled.then(String::new),
+        }
+    }
+
+    // (tokens in the events `bytes` completed, a final `usage` count if one
+    // was among them)
+    fn feed(&mut self, bytes: &[u8]) -> (u32, Option<u64>) {
+        let data = match &mut self.chunks {
+            Some(chunks) => chunks.feed(bytes),
+            None => bytes.to_vec(),
+        };
+        let (mut tokens, mut usage) = (0, None);
+        for byte in data {
+            if byte != b'\n' {
+                self.line.push(byte);
+                continue;
+            }
+            let line = std::mem::take(&mut self.line);
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim_end_matches('\r').strip_prefix("data:") else {
+                continue;
+            };
+            // `[DONE]` ends OpenAI's streams, and isn't JSON
+            let Ok(event) = serde_json::from_str::<Value>(data.trim()) else {
+                continue;
+            };
+            tokens += count(&event);
+            if let Some(text) = &mut self.text {
+                sampling::keep(text, &sampling::delta(&event));
+            }
+            if let Some(total) = event.pointer("/usage/completion_tokens") {
+                usage = total.as_u64().or(usage);
+            }
+        }
+        (tokens, usage)
+    }
+}
+
+// Tokens in one streamed chunk. OpenAI-style servers send about one a
+// chunk: `choices[].delta.content` (or a reasoning model's
+// `reasoning_content`, or a tool call) for chat, `choices[].text` for
+// completions. Role-only and finishing chunks carry none.
+fn count(event: &Value) -> u32 {
+    let Some(choices) = event.get("choices").and_then(Value::as_array) else {
+        return 0;
+    };
+    let carries = |choice: &&Value| {
+        let text = |pointer| {
+            choice
+                .pointer(pointer)
+                .and_then(Value::as_str)
+                .is_some_and(|t| !t.is_empty())
+        };
+        text("/delta/content")
+            || text("/delta/reasoning_content")
+            || text("/text")
+            || choice.pointer("/delta/tool_calls").is_some()
+    };
+    choices.iter().filter(carries).count() as u32
+}
+
+// The AI observability strip: a row per stream, newest first, with its
+// tokens per second across the last seconds, one column each. Stalls are
+// marked `!`, and the wait for the first token is dotted; a TTFT over twice
+// the median is highlighted.
+pub fn draw(
+    buf: &mut Buffer,
+    area: Rect,
+    tokens: &TokenStreams,
+    now: Duration,
+    glyphs: GlyphSet,
+    chrome: Chrome,
+) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let ascii = glyphs == GlyphSet::Ascii;
+    let streaming = tokens.streams.iter().filter(|s| s.ended.is_none()).count();
+    let stalling = tokens.streams.iter().filter(|s| s.stalling()).count();
+    let p50 = tokens.ttft_p50();
+    let mut title = format!("token stream • {streaming} streaming");
+    if let Some(p50) = p50 {
+        title += &format!(" • ttft p50 {}", span(p50));
+    }
+    if stalling > 0 {
+        title += &format!(" • {stalling} stalled");
+    }
+    let block = chrome.block().border_style(dim).title(Span::styled(
+        title,
+        Style::default()
+            .fg(Color::LightGreen)
+            .add_modifier(Modifier::BOLD),
+    ));
+    let inner = block.inner(area);
+    block.render(area, buf);
+
+    if tokens.streams.is_empty() {
+        let hint = format!(
+            "no streams yet; point clients at http://{} for {}",
+            tokens.listen, tokens.upstream
+        );
+        Paragraph::new(Line::styled(hint, dim)).render(inner, buf);
+        return;
+    }
+    // the name, TTFT, and rate, then the chart
+    let fixed = LABEL_W + 1 + 6 + 1 + 8 + 1;
+    let width = (inner.width as usize).saturating_sub(fixed);
+    let shown: Vec<&Stream> = tokens.streams.iter().take(inner.height as usize).collect();
+    // the seconds on screen, oldest first; None before the app started
+    let end = now.as_secs() + 1;
+    let seconds = || (0..width as u64).map(move |i| (end + i).checked_sub(width as u64));
+    // rates are drawn against the busiest second on screen
+    let busiest = shown
+        .iter()
+        .flat_map(|stream| seconds().flatten().map(|second| stream.count(second)))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let slow = |ttft: Duration| p50.is_some_and(|p50| tokens.streams.len() >= 3 && ttft > p50 * 2);
+
+    let lines: Vec<Line> = shown
+        .iter()
+        .map(|stream| {
+            let name = sanitize(&stream.label);
+            let (name, pad) = fit(&name, LABEL_W);
+            let name_style = match (&stream.error, stream.ended) {
+                (Some(_), _) => Style::default().fg(Color::Red),
+                (None, None) => Style::default().fg(Color::White),
+                (None, Some(_)) => dim,
+            };
+            let ttft = stream.ttft.map_or("-".to_string(), span);
+            let ttft_style = match stream.ttft {
+                Some(t) if slow(t) => Style::default().fg(Color::Yellow),
+                _ => dim,
+            };
+            let rate = stream
+                .rate(now)
+                .map_or("-".to_string(), |r| format!("{r:.0} t/s"));
+            let mut spans = vec![
+                Span::styled(format!("{name}{pad} "), name_style),
+                Span::styled(format!("{ttft:>6} "), ttft_style),
+                Span::styled(
+                    format!("{rate:>8} "),
+                    Style::default().fg(Color::LightGreen),
+                ),
+            ];
+            for second in seconds() {
+                let Some(second) = second else {
+                    spans.push(Span::raw(" "));
+                    continue;
+                };
+                let from = Duration::from_secs(second);
+                let to = from + Duration::from_secs(1);
+                let first = stream.ttft.map(|t| stream.started + t);
+                let n = stream.count(second);
+                // a second with tokens in it shows them, even if it stalled too
+                let (glyph, style) =
+                    if to <= stream.started || stream.ended.is_some_and(|end| from >= end) {
+                        (' ', Style::default())
+                    } else if n == 0 && stream.stalled(from, to) {
+                        (
+                            '!',
+                            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                        )
+                    } else if to <= first.unwrap_or(Duration::MAX) {
+                        (if ascii { '.' } else { '·' }, dim)
+                    } else {
+                        let level = (n as usize * 8).div_ceil(busiest as usize).min(8);
+                        let glyph = match glyphs {
+                            GlyphSet::Unicode => LEVELS[level],
+                            GlyphSet::Blocks if level >= 4 => '█',
+                            GlyphSet::Blocks if level > 0 => '▄',
+                            GlyphSet::Ascii if level >= 4 => '#',
+                            GlyphSet::Ascii if level > 0 => ':',
+                            _ => ' ',
+                        };
+                        (glyph, Style::default().fg(Color::Green))
+                    };
+                spans.push(Span::styled(glyph.to_string(), style));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    Paragraph::new(lines).render(inner, buf);
+}
@@ -0,0 +1,1229 @@
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use humantime::format_duration;
+use ratatui::{
+    layout::{Alignment, Rect},
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Terminal,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    app::{AppState, MetricRow, Mode, TREND_SECS, TREND_W},
+    capture, control,
+    datasource::METRICS,
+    detail::Panel,
+    docker,
+    explain::Catalog,
+    focus::{self, Focus},
+    glyphs::GlyphSet,
+    gpu, health,
+    hosts::{self, HostView},
+    idle, integrity, kafka, kube,
+    layout::{fit, PanelLayout, MIN_H, MIN_W},
+    logs::{Level, LogEntry, SourceId},
+    payload::{self, Payload},
+    profiler::FrameProfiler,
+    reconnect::LinkState,
+    ros2, sampling, scaling,
+    sysmetrics::Machine,
+    timeline, tokens,
+    watchdog::{Degrade, DEGRADED_LOG_LINES},
+    watchlist::{self, Status},
+};
+
+// One frame, plus the bookkeeping that depends on where things landed
+pub fn draw_frame<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut AppState,
+    panels: &mut PanelLayout,
+    profiler: &mut FrameProfiler,
+) -> io::Result<()> {
+    let frame_start = Instant::now();
+    let area = terminal
+        .draw(|f| {
+            ui(f, app, panels, profiler);
+            app.caps.degrade(f.buffer_mut());
+        })?
+        .area;
+    app.clamp_pan(area);
+    app.timeline = match app.host_view {
+        Some(_) => Rect::default(),
+        None => timeline::rect(panels.logs),
+    };
+    let took = frame_start.elapsed();
+    profiler.record("frame", took);
+    app.frame_drawn(took);
+    app.dirty = false;
+    Ok(())
+}
+
+pub(crate) fn ui(
+    f: &mut Frame,
+    app: &AppState,
+    panels: &mut PanelLayout,
+    prof: &mut FrameProfiler,
+) {
+    let size = f.size();
+    if size.is_empty() {
+        return;
+    }
+    if app.idle.active() {
+        draw_ambient(f.buffer_mut(), size, app);
+        return;
+    }
+
+    // Undersized terminals get the dashboard drawn offscreen at its minimum
+    // size, showing the window of it the user has panned to with the arrow keys
+    if size.width < MIN_W || size.height < MIN_H {
+        let full = Rect::new(0, 0, size.width.max(MIN_W), size.height.max(MIN_H));
+        let mut offscreen = Buffer::empty(full);
+        let cursor = draw_dashboard(&mut offscreen, full, app, panels, prof);
+
+        let px = app.pan.0.min(full.width - size.width);
+        let py = app.pan.1.min(full.height - size.height);
+        let buf = f.buffer_mut();
+        for y in 0..size.height {
+            for x in 0..size.width {
+                *buf.get_mut(size.x + x, size.y + y) = offscreen.get(px + x, py + y).clone();
+            }
+        }
+        if let Some((cx, cy)) = cursor {
+            let (cx, cy) = (cx.wrapping_sub(px), cy.wrapping_sub(py));
+            if cx < size.width && cy < size.height {
+                f.set_cursor(size.x + cx, size.y + cy);
+            }
+        }
+
+        let hint = format!(" arrows pan {MIN_W}x{MIN_H} ");
+        let row = Rect::new(size.x, size.bottom() - 1, size.width, 1);
+        Paragraph::new(hint)
+            .alignment(Alignment::Right)
+            .style(Style::default().fg(Color::Black).bg(Color::DarkGray))
+            .render(row, f.buffer_mut());
+        return;
+    }
+
+    if let Some((x, y)) = draw_dashboard(f.buffer_mut(), size, app, panels, prof) {
+        f.set_cursor(x, y);
+    }
+}
+
+// Data forensics mode's tables above the logs: integrity changes and the
+// capture summary, side by side when there are both
+fn draw_forensics(buf: &mut Buffer, area: Rect, app: &AppState) {
+    let ascii = app.caps.glyphs == GlyphSet::Ascii;
+    let (left, right) = match (&app.integrity, &app.capture) {
+        (Some(_), Some(_)) => {
+            let split = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .split(area);
+            (split[0], split[1])
+        }
+        _ => (area, area),
+    };
+    if let Some(integrity) = &app.integrity {
+        integrity::draw(buf, left, integrity, &app.timebase, ascii, app.chrome.strip);
+    }
+    if let Some(capture) = &app.capture {
+        capture::draw(buf, right, capture, app.chrome.strip);
+    }
+}
+
+// AI observability mode's strip: streamed token rates and GPU processes,
+// side by side when there are both
+fn draw_ai_strip(buf: &mut Buffer, area: Rect, app: &AppState) {
+    let (left, right) = match (&app.tokens, &app.gpu) {
+        (Some(_), Some(_)) => {
+            let split = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .split(area);
+            (split[0], split[1])
+        }
+        _ => (area, area),
+    };
+    if let Some(streams) = &app.tokens {
+        tokens::draw(
+            buf,
+            left,
+            streams,
+            app.view_time(),
+            app.caps.glyphs,
+            app.chrome.strip,
+        );
+    }
+    if let Some(gpu) = &app.gpu {
+        gpu::draw(buf, right, gpu, app.caps.glyphs, app.chrome.strip);
+    }
+}
+
+// Cloud mode's scaling timeline, under the latency it lines up with, and
+// the Kafka lag beside it
+fn draw_cloud_strip(buf: &mut Buffer, area: Rect, app: &AppState) {
+    let (left, right) = match (&app.scaling, &app.kafka) {
+        (Some(_), Some(_)) => {
+            let split = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .split(area);
+            (split[0], split[1])
+        }
+        _ => (area, area),
+    };
+    if let Some(kafka) = &app.kafka {
+        kafka::draw(buf, right, kafka, app.caps.glyphs, app.chrome.strip);
+    }
+    if let Some(scaling) = &app.scaling {
+        // a gap where the source had gone quiet
+        let latency = |at: Duration| {
+            let t = at.as_secs_f32();
+            let fill = app.panel_rows(Mode::Cloud, t)[0].3;
+            app.stale(Mode::Cloud, 0, t).is_none().then_some(fill)
+        };
+        let end = app.view_time();
+        scaling::draw(
+            buf,
+            left,
+            scaling,
+            &latency,
+            end,
+            &app.timebase,
+            app.caps.glyphs,
+            app.chrome.strip,
+        );
+    }
+}
+
+// Cloud mode's pods and containers, in the system panel's place, one above
+// the other when there are both
+fn draw_cloud_system(buf: &mut Buffer, area: Rect, app: &AppState) {
+    let docker = app.docker.as_ref().filter(|d| d.available());
+    let (top, bottom) = match (&app.pods, docker) {
+        (Some(_), Some(_)) => {
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(area);
+            (split[0], split[1])
+        }
+        _ => (area, area),
+    };
+    if let Some(pods) = &app.pods {
+        kube::draw(buf, top, pods, app.chrome.system);
+    }
+    if let Some(docker) = docker {
+        docker::draw(buf, bottom, docker, app.chrome.system);
+    }
+}
+
+// Draws every panel into `area`; returns the command-bar cursor position, if any
+fn draw_dashboard(
+    buf: &mut Buffer,
+    area: Rect,
+    app: &AppState,
+    panels: &mut PanelLayout,
+    prof: &mut FrameProfiler,
+) -> Option<(u16, u16)> {
+    let emphasis = app.emphasis().map(|sel| sel.panel);
+    let strip = match app.mode {
+        Mode::DataForensics => app.integrity.is_some() || app.capture.is_some(),
+        Mode::Cloud => app.scaling.is_some() || app.kafka.is_some(),
+        Mode::AiObservability => app.gpu.is_some() || app.tokens.is_some(),
+        Mode::Robotics => app.ros2.is_some(),
+        _ => false,
+    };
+    prof.time("layout", || {
+        panels.update(area, !app.watchlist.is_empty(), emphasis, strip)
+    });
+
+    // collapsed panels come back as empty rects and are skipped entirely
+    prof.time("banner", || draw_banner(buf, panels.banner, app));
+    if !app.pins.is_empty() {
+        prof.time("pins", || draw_pins(buf, panels.banner[0], area, app));
+    }
+    if let Some(view) = app.host_view {
+        // the host view takes over everything between the banner and command bar
+        let top = panels.banner[0].bottom();
+        let body = Rect::new(
+            area.x,
+            top,
+            area.width,
+            panels.command.y.saturating_sub(top),
+        );
+        prof.time("hosts", || draw_hosts(buf, body, app, view));
+    } else {
+        if !panels.ai_metrics.is_empty() {
+            prof.time("ai metrics", || {
+                draw_ai_metrics(buf, panels.ai_metrics, app)
+            });
+        }
+        if !panels.system.is_empty() {
+            let docker = app.docker.as_ref().is_some_and(|d| d.available());
+            if app.mode == Mode::Cloud && (app.pods.is_some() || docker) {
+                prof.time("containers", || draw_cloud_system(buf, panels.system, app));
+            } else {
+                prof.time("system", || draw_system_panel(buf, panels.system, app));
+            }
+        }
+        if !panels.logs.is_empty() {
+            prof.time("logs", || draw_logs(buf, panels.logs, app));
+        }
+        if !panels.strip.is_empty() {
+            match app.mode {
+                Mode::Cloud => {
+                    prof.time("cloud strip", || draw_cloud_strip(buf, panels.strip, app))
+                }
+                Mode::AiObservability => {
+                    prof.time("ai strip", || draw_ai_strip(buf, panels.strip, app))
+                }
+                Mode::Robotics => prof.time("ros2", || {
+                    if let Some(ros2) = &app.ros2 {
+                        ros2::draw(buf, panels.strip, ros2, app.caps.glyphs, app.chrome.strip)
+                    }
+                }),
+                _ => prof.time("forensics", || draw_forensics(buf, panels.strip, app)),
+            }
+        }
+        if !panels.watchlist.is_empty() {
+            prof.time("watchlist", || {
+                let ascii = app.caps.glyphs == GlyphSet::Ascii;
+                watchlist::draw(
+                    buf,
+                    panels.watchlist,
+                    &app.watch_rows(),
+                    ascii,
+                    app.chrome.watchlist,
+                )
+            });
+        }
+    }
+    let cursor = prof.time("command", || draw_command(buf, panels.command, app));
+
+    if let Some(comparison) = &app.comparison {
+        comparison.draw(buf, area, app.caps.glyphs == GlyphSet::Unicode);
+    }
+    if app.show_health {
+        let t = app.view_time().as_secs_f32();
+        if let (Some(parts), Some(spec)) = (app.health_parts(app.mode, t), &app.health) {
+            health::draw(buf, area, &parts, spec.alert_below);
+        }
+    }
+    if let (Some(pick), Some(tokens)) = (app.request_pick, &app.tokens) {
+        sampling::draw(
+            buf,
+            area,
+            &tokens.requests,
+            pick,
+            app.uptime(),
+            tokens.sample,
+        );
+    }
+    if let Some(popup) = &app.log_popup {
+        popup.draw(buf, area);
+    }
+    if let (Some(request), Some(control)) = (&app.confirm, &app.control) {
+        control::draw(buf, area, request, &control.spec.url);
+    }
+    if app.show_diag {
+        draw_diagnostics(buf, area, app, prof);
+    }
+    if let Some(wizard) = &app.wizard {
+        return wizard.draw(buf, area);
+    }
+    cursor
+}
+
+// Pinned metrics on the banner's spare middle row, whatever the mode
+fn draw_pins(buf: &mut Buffer, banner: Rect, area: Rect, app: &AppState) {
+    if banner.height < 3 {
+        return;
+    }
+    let mut spans = vec![Span::styled("★ ", Style::default().fg(Color::Yellow))];
+    for (i, pin) in app.pins.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled("  │  ", Style::default().fg(Color::DarkGray)));
+        }
+        let value = app.pin_value(pin).unwrap_or_else(|| "—".to_string());
+        spans.extend([
+            Span::styled(
+                format!("{} {} ", pin.scope_name(), pin.label),
+                Style::default().fg(Color::Gray),
+            ),
+            Span::styled(
+                value,
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]);
+    }
+    let row = Rect::new(area.x + 1, banner.y + 1, area.width.saturating_sub(2), 1);
+    Clear.render(row, buf);
+    Paragraph::new(Line::from(spans)).render(row, buf);
+}
+
+fn draw_banner(buf: &mut Buffer, cols: [Rect; 3], app: &AppState) {
+    // LEFT: [1–5] mode badges, colored by each mode's worst watched status
+    // with its firing alert count; as much of the hint as fits
+    let left = {
+        let badges: Vec<_> = Mode::value_variants()
+            .iter()
+            .enumerate()
+            .map(|(i, &mode)| {
+                let (status, firing) = app.mode_status(mode);
+                let color = match status {
+                    Status::Crit => Color::Red,
+                    Status::Warn => Color::Yellow,
+                    Status::Ok => Color::Green,
+                    Status::Unknown => Color::Gray,
+                };
+                let mut style = Style::default().fg(color);
+                if mode == app.mode {
+                    style = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+                }
+                let alerts = if firing > 0 {
+                    format!("!{firing}")
+                } else {
+                    String::new()
+                };
+                (i + 1, mode.short(), alerts, style)
+            })
+            .collect();
+        let line = |numbers: bool, gap: &str, tail: &str| {
+            let mut spans = Vec::new();
+            for (n, short, alerts, style) in &badges {
+                if !spans.is_empty() {
+                    spans.push(Span::raw(gap.to_string()));
+                }
+                if numbers {
+                    spans.push(Span::raw(format!("[{n}] ")));
+                }
+                spans.push(Span::styled(*short, *style));
+                spans.push(Span::styled(alerts.clone(), *style));
+            }
+            spans.push(Span::raw(tail.to_string()));
+            Line::from(spans)
+        };
+        let hint = [
+            line(true, "  ", "  |  : command"),
+            line(true, "  ", ""),
+            line(true, " ", ""),
+        ]
+        .into_iter()
+        .find(|l| l.width() <= cols[0].width as usize)
+        .unwrap_or_else(|| line(false, " ", ""));
+        Paragraph::new(hint).alignment(Alignment::Left).block(
+            app.chrome
+                .banner
+                .rule()
+                .border_style(focus::border(app.focused(Focus::Banner))),
+        )
+    };
+
+    // CENTER: Ai-inTUI + mode centered
+    let center_line = Line::from(vec![
+        Span::styled(
+            "Ai-inTUI",
+            Style::default()
+                .fg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(" • "),
+        Span::styled(
+            app.mode.name(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ]);
+    let mid = Paragraph::new(center_line)
+        .alignment(Alignment::Center)
+        .block(
+            app.chrome
+                .banner
+                .rule()
+                .border_style(focus::border(app.focused(Focus::Banner))),
+        );
+
+    // RIGHT: network source health (when there are any) + uptime; no mode, so
+    // it never pushes center/hints
+    let right = {
+        let uptime = format_duration(app.uptime()).to_string();
+        let mut spans = Vec::new();
+        let links = app.links.states();
+        if !links.is_empty() {
+            let up = links
+                .iter()
+                .filter(|(_, s)| *s == LinkState::Connected)
+                .count();
+            let color = if up == links.len() {
+                Color::Green
+            } else {
+                Color::Yellow
+            };
+            spans.push(Span::styled(
+                format!("links {up}/{}  ", links.len()),
+                Style::default().fg(color),
+            ));
+        }
+        spans.extend([
+            Span::styled("uptime ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                uptime,
+                Style::default()
+                    .fg(Color::LightBlue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]);
+        let line = Line::from(spans);
+        Paragraph::new(line).alignment(Alignment::Right).block(
+            app.chrome
+                .banner
+                .rule()
+                .border_style(focus::border(app.focused(Focus::Banner))),
+        )
+    };
+
+    left.render(cols[0], buf);
+    mid.render(cols[1], buf);
+    right.render(cols[2], buf);
+}
+
+// The AI panel's rows for a mode's values, in METRICS order
+pub(crate) fn ai_rows(values: [f32; 7]) -> [MetricRow; 7] {
+    let [lat, gpu, tpm, err, q, jitter, trust] = values;
+
+    // Normalized for bars (keeps alignment)
+    let lat_norm = (lat / 400.0).clamp(0.0, 1.0);
+    let gpu_norm = gpu.clamp(0.0, 1.0);
+    let tpm_norm = (tpm / 25_000.0).clamp(0.0, 1.0);
+    let err_norm = (err / 3.0).clamp(0.0, 1.0);
+    let q_norm = q.clamp(0.0, 1.0);
+    let jitter_norm = (jitter / 20.0).clamp(0.0, 1.0);
+    let trust_norm = trust.clamp(0.0, 1.0);
+
+    [
+        (
+            METRICS[0],
+            lat,
+            |v| format!("{v:.0} ms"),
+            lat_norm,
+            Color::LightGreen,
+        ),
+        (
+            METRICS[1],
+            gpu,
+            |v| format!("{:.0}%", v * 100.0),
+            gpu_norm,
+            Color::LightMagenta,
+        ),
+        (
+            METRICS[2],
+            tpm,
+            |v| format!("{v:.0}"),
+            tpm_norm,
+            Color::Cyan,
+        ),
+        (METRICS[3], err, |v| format!("{v:.2}"), err_norm, Color::Red),
+        (METRICS[4], q, |v| format!("{v:.2}"), q_norm, Color::Yellow),
+        (
+            METRICS[5],
+            jitter,
+            |v| format!("{v:.1} ms"),
+            jitter_norm,
+            Color::LightBlue,
+        ),
+        (
+            METRICS[6],
+            trust,
+            |v| format!("{:.0}%", v * 100.0),
+            trust_norm,
+            Color::Green,
+        ),
+    ]
+}
+
+fn draw_ai_metrics(buf: &mut Buffer, area: Rect, app: &AppState) {
+    let t = app.view_time().as_secs_f32();
+
+    let label_width = 15;
+    let value_width = 8;
+    let trend_width = if app.deltas { TREND_W } else { 0 };
+    // bars give up width first when the panel is narrow
+    let bar_len = (area.width as usize)
+        .saturating_sub(2 + label_width + 2 + value_width + trend_width + 2)
+        .min(22);
+
+    #[allow(clippy::too_many_arguments)]
+    fn metric_line(
+        label: &'static str,
+        value: String,
+        trend: Option<Span<'static>>,
+        bar: (&'static str, &'static str, &'static str),
+        color: Color,
+        stale: Option<Duration>,
+        label_width: usize,
+        value_width: usize,
+    ) -> Line<'static> {
+        let (label, label_pad) = fit(label, label_width);
+        let (value, value_pad) = fit(&value, value_width);
+        let value_padded = format!("{value_pad}{value}");
+        let (full, partial, empty) = bar;
+
+        let mut spans = vec![
+            Span::styled(label, Style::default().fg(Color::Gray)),
+            Span::raw(label_pad),
+            Span::raw("  "),
+        ];
+        // a value the source stopped updating is dimmed, with how long ago
+        // in place of the bar it would otherwise freeze
+        match stale {
+            Some(age) => spans.extend([
+                Span::styled(value_padded, Style::default().fg(Color::DarkGray)),
+                trend.unwrap_or_default(),
+                Span::raw("  "),
+                Span::styled(
+                    format!("stale {}", ago(age)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]),
+            None => spans.extend([
+                Span::styled(value_padded, Style::default().fg(Color::White)),
+                trend.unwrap_or_default(),
+                Span::raw("  "),
+                Span::styled(full, Style::default().fg(color)),
+                Span::styled(partial, Style::default().fg(color)),
+                Span::raw(empty),
+            ]),
+        }
+        Line::from(spans)
+    }
+
+    // subtle accent: title color depends on mode, but same layout
+    let title_color = match app.mode {
+        Mode::AiObservability => Color::Cyan,
+        Mode::Robotics => Color::LightYellow,
+        Mode::Cloud => Color::LightMagenta,
+        Mode::DataForensics => Color::LightGreen,
+        Mode::Sandbox => Color::LightBlue,
+    };
+
+    let title = format!("AI metrics • {}", app.mode.name());
+
+    // Only rows that fit inside the block get formatted
+    let rows = app.panel_rows(app.mode, t);
+    let before = app
+        .deltas
+        .then(|| app.panel_rows(app.mode, (t - TREND_SECS).max(0.0)));
+    // inner height minus the padding row
+    let visible = (area.height.saturating_sub(2) as usize).saturating_sub(1);
+
+    let mut lines: Vec<Line> = vec![Line::from("")]; // small padding
+                                                     // in educator mode every row is followed by its explanation
+    let explain = app.explanations();
+    let per_row = if explain.is_some() { 2 } else { 1 };
+    for (i, &(label, value, fmt, norm, color)) in rows
+        .iter()
+        .enumerate()
+        .skip(app.first_row(Panel::Ai))
+        .take(visible.div_ceil(per_row))
+    {
+        let (value, norm) = app.shown(label, value, norm);
+        let trend = before.as_ref().map(|rows| app.trend(value, rows[i].1, fmt));
+        let mut line = metric_line(
+            label,
+            fmt(value),
+            trend,
+            app.bar_glyphs().bar(norm, bar_len),
+            color,
+            app.stale(app.mode, i, t),
+            label_width,
+            value_width,
+        );
+        app.highlight(Panel::Ai, i, &mut line);
+        lines.push(line);
+        if let Some(catalog) = explain {
+            lines.push(explain_line(catalog, label, area.width.saturating_sub(2)));
+        }
+        lines.extend(app.expansion(Panel::Ai, i, area, color));
+    }
+
+    let block = app
+        .chrome
+        .metrics
+        .block()
+        .border_style(focus::border(app.focused(Focus::Metrics)))
+        .title(Span::styled(
+            title,
+            Style::default()
+                .fg(title_color)
+                .add_modifier(Modifier::BOLD),
+        ));
+
+    let para = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    para.render(area, buf);
+}
+
+// Screensaver: clock and a handful of headline numbers, dimmed
+fn draw_ambient(buf: &mut Buffer, area: Rect, app: &AppState) {
+    let t = app.uptime().as_secs_f32();
+    let rows = app.panel_rows(app.mode, t);
+    let [lat, err, q] = [rows[0].1, rows[3].1, rows[4].1];
+    let (score_label, score) = (rows[6].0, rows[6].1);
+    let time = app
+        .timebase
+        .local_at(app.uptime())
+        .format("%H:%M")
+        .to_string();
+    let fill = match app.caps.glyphs {
+        GlyphSet::Ascii => '#',
+        _ => '█',
+    };
+    let dim = Style::default().fg(Color::Gray);
+    let headline = [
+        Line::from(Span::styled(
+            app.mode.name(),
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(Span::styled(
+            format!(
+                "p95 {lat:.0} ms   errors {err:.2}/min   queue {q:.2}   {score_label} {:.0}%",
+                score * 100.0
+            ),
+            dim,
+        )),
+    ];
+    idle::draw(buf, area, &time, fill, &headline);
+}
+
+// Dim, indented, and cut to the panel so it never wraps into the next row
+pub(crate) fn explain_line(catalog: &Catalog, label: &str, width: u16) -> Line<'static> {
+    let text = catalog.get(label).unwrap_or("(not in the catalog)");
+    let (text, _) = fit(text, (width as usize).saturating_sub(2));
+    Line::from(Span::styled(
+        format!("  {text}"),
+        Style::default().fg(Color::DarkGray),
+    ))
+}
+
+fn draw_system_panel(buf: &mut Buffer, area: Rect, app: &AppState) {
+    // Use app uptime so system panel "breathes" with the rest of the dashboard
+    let t = app.view_time().as_secs_f32();
+
+    let label_width = 12;
+    let value_width = 6;
+    let trend_width = if app.deltas { TREND_W } else { 0 };
+    let bar_len = (area.width as usize)
+        .saturating_sub(2 + label_width + 1 + value_width + trend_width + 1)
+        .min(16);
+
+    fn sys_line(
+        label: &'static str,
+        value: String,
+        trend: Option<Span<'static>>,
+        bar: (&'static str, &'static str, &'static str),
+        color: Color,
+        label_width: usize,
+        value_width: usize,
+    ) -> Line<'static> {
+        let (label, label_pad) = fit(label, label_width);
+        let (value, value_pad) = fit(&value, value_width);
+        let value_padded = format!("{value_pad}{value}");
+        let (full, partial, empty) = bar;
+
+        Line::from(vec![
+            Span::styled(label, Style::default().fg(Color::Gray)),
+            Span::raw(label_pad),
+            Span::raw(" "),
+            Span::styled(value_padded, Style::default().fg(Color::White)),
+            trend.unwrap_or_default(),
+            Span::raw(" "),
+            Span::styled(full, Style::default().fg(color)),
+            Span::styled(partial, Style::default().fg(color)),
+            Span::raw(empty),
+        ])
+    }
+
+    let title = match &app.machine {
+        Some(Machine {
+            name: Some(name), ..
+        }) => format!("system • {name}"),
+        Some(_) => "system".to_string(),
+        None => "system panel (simulated)".to_string(),
+    };
+
+    // Only rows that fit inside the block get formatted
+    let rows = app.system_rows(t);
+    let before = app
+        .deltas
+        .then(|| app.system_rows((t - TREND_SECS).max(0.0)));
+    let visible = (area.height.saturating_sub(2) as usize).saturating_sub(1);
+
+    let mut lines: Vec<Line> = vec![Line::from("")];
+    // in educator mode every row is followed by its explanation
+    let explain = app.explanations();
+    let per_row = if explain.is_some() { 2 } else { 1 };
+    for (i, &(label, value, fmt, norm, color)) in rows
+        .iter()
+        .enumerate()
+        .skip(app.first_row(Panel::System))
+        .take(visible.div_ceil(per_row))
+    {
+        let (value, norm) = app.shown(label, value, norm);
+        let trend = before.as_ref().map(|rows| app.trend(value, rows[i].1, fmt));
+        let mut line = sys_line(
+            label,
+            fmt(value),
+            trend,
+            app.bar_glyphs().bar(norm, bar_len),
+            color,
+            label_width,
+            value_width,
+        );
+        app.highlight(Panel::System, i, &mut line);
+        lines.push(line);
+        if let Some(catalog) = explain {
+            lines.push(explain_line(catalog, label, area.width.saturating_sub(2)));
+        }
+        lines.extend(app.expansion(Panel::System, i, area, color));
+    }
+
+    let block = app
+        .chrome
+        .system
+        .block()
+        .border_style(focus::border(app.focused(Focus::System)))
+        .title(Span::styled(
+            title,
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD),
+        ));
+
+    let para = Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    para.render(area, buf);
+}
+
+// Stable per name, so a source keeps its color across runs; red and yellow are
+// left to warnings and errors
+pub(crate) fn source_color(name: &str) -> Color {
+    const PALETTE: [Color; 8] = [
+        Color::Cyan,
+        Color::Magenta,
+        Color::Green,
+        Color::Blue,
+        Color::LightCyan,
+        Color::LightMagenta,
+        Color::LightGreen,
+        Color::LightBlue,
+    ];
+    // FNV-1a; std's hasher is seeded per process
+    let hash = name.bytes().fold(0x811c_9dc5_u32, |h, b| {
+        (h ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    });
+    PALETTE[hash as usize % PALETTE.len()]
+}
+
+// ` ×12` after a line that stands for twelve; nothing for a single line
+fn repeat_count(entry: &LogEntry, ascii: bool) -> String {
+    match entry.repeats {
+        0 | 1 => String::new(),
+        n if ascii => format!(" x{n}"),
+        n => format!(" ×{n}"),
+    }
+}
+
+fn draw_logs(buf: &mut Buffer, area: Rect, app: &AppState) {
+    let mut title = format!("logs • {}", app.mode.short());
+    if let Some(at) = app.scrub {
+        let behind = app.uptime().saturating_sub(at);
+        title.push_str(&format!(
+            " • time travel -{}",
+            format_duration(Duration::from_secs(behind.as_secs()))
+        ));
+    }
+    for (source, n) in &app.dropped {
+        title.push_str(&format!(" • {source} dropped {n} lines"));
+    }
+    let mut matches = app.search.as_deref().map(|q| app.logs.search(q));
+    if let (Some(q), Some(m)) = (&app.search, &matches) {
+        title.push_str(&format!(" • grep \"{q}\" ({})", m.len()));
+    }
+    if let Some(only) = app.log_source {
+        let shown: Vec<&LogEntry> = match matches {
+            Some(m) => m.into_iter().filter(|e| e.source == only).collect(),
+            None => app.logs.iter().filter(|e| e.source == only).collect(),
+        };
+        title.push_str(&format!(
+            " • only {} ({})",
+            app.logs.source_name(only),
+            shown.len()
+        ));
+        matches = Some(shown);
+    }
+    // focused: the number keys' legend, e.g. `1 syslog 2 AI[core] 0 all`
+    if app.focused(Focus::Logs) {
+        title.push_str(" •");
+        for (i, id) in app.logs.active_sources().into_iter().take(9).enumerate() {
+            title.push_str(&format!(" {} {}", i + 1, app.logs.source_name(id)));
+        }
+        title.push_str(" 0 all");
+    }
+
+    let block = app
+        .chrome
+        .logs
+        .block()
+        .border_style(focus::border(app.focused(Focus::Logs)))
+        .title(Span::styled(
+            title,
+            Style::default()
+                .fg(Color::LightBlue)
+                .add_modifier(Modifier::BOLD),
+        ));
+
+    let inner = block.inner(area);
+
+    // Keep only the last N entries that fit, then build their lines
+    let mut max_visible = inner.height.saturating_sub(1) as usize;
+    if app.watchdog.level() == Degrade::Minimal {
+        max_visible = max_visible.min(DEGRADED_LOG_LINES);
+    }
+    let total = matches.as_ref().map_or(app.logs.len(), Vec::len);
+    let skip = if max_visible > 0 {
+        total.saturating_sub(max_visible)
+    } else {
+        0
+    };
+    let visible: Vec<&LogEntry> = match (&matches, app.scrub) {
+        (Some(m), None) => m[skip..].to_vec(),
+        (None, None) => app.logs.iter().skip(skip).collect(),
+        // time travel: the last lines that had arrived by then
+        (_, Some(at)) => {
+            let arrived = |e: &&LogEntry| e.at <= at;
+            let mut shown: Vec<&LogEntry> = match &matches {
+                Some(m) => m
+                    .iter()
+                    .rev()
+                    .copied()
+                    .filter(arrived)
+                    .take(max_visible)
+                    .collect(),
+                None => app
+                    .logs
+                    .iter()
+                    .rev()
+                    .filter(arrived)
+                    .take(max_visible)
+                    .collect(),
+            };
+            shown.reverse();
+            shown
+        }
+    };
+
+    let payloads = app.log_pick.map(|_| app.payload_entries());
+    let picked = app
+        .log_pick
+        .zip(payloads.as_ref())
+        .and_then(|(i, entries)| entries.get(i).copied());
+    let lines: Vec<Line> = visible
+        .into_iter()
+        .map(|entry| {
+            let style = match entry.level {
+                Level::Error => Style::default().fg(Color::Red),
+                Level::Warn => Style::default().fg(Color::Yellow),
+                Level::Info | Level::Debug => Style::default(),
+            };
+            let count = repeat_count(entry, app.caps.glyphs == GlyphSet::Ascii);
+            if app.watchdog.level() > Degrade::Full {
+                let text = app.logs.render(entry);
+                let first = text.lines().next().unwrap_or("").to_string();
+                return Line::from(Span::styled(first + &count, style));
+            }
+            let stamp = app.timebase.local_at(entry.at).format("%H:%M:%S ");
+            let mut spans = vec![Span::styled(
+                stamp.to_string(),
+                Style::default().fg(Color::DarkGray),
+            )];
+            // app messages stay bare; every other line leads with its source
+            let mut room = (inner.width as usize).saturating_sub(9);
+            if entry.source != SourceId::APP {
+                let name = app.logs.source_name(entry.source);
+                room = room.saturating_sub(name.width() + 1);
+                spans.push(Span::styled(
+                    name.to_string(),
+                    Style::default()
+                        .fg(source_color(name))
+                        .add_modifier(Modifier::BOLD),
+                ));
+                spans.push(Span::raw(" "));
+            }
+            match Payload::detect(&entry.message) {
+                // one line with a hint instead of a wrapped blob; `l` then
+                // ↑/↓ and Enter open it
+                Some(kind) => {
+                    let hint = kind.hint(app.caps.glyphs == GlyphSet::Ascii);
+                    let text = payload::collapsed(
+                        &entry.message,
+                        room.saturating_sub(hint.width() + count.width()),
+                    );
+                    let style = if picked.is_some_and(|p| std::ptr::eq(p, entry)) {
+                        style.add_modifier(Modifier::REVERSED)
+                    } else {
+                        style
+                    };
+                    spans.push(Span::styled(text.to_string(), style));
+                    spans.push(Span::styled(hint, Style::default().fg(Color::DarkGray)));
+                }
+                None => spans.push(Span::styled(entry.message.to_string(), style)),
+            }
+            if !count.is_empty() {
+                spans.push(Span::styled(count, style.add_modifier(Modifier::BOLD)));
+            }
+            if entry.source == SourceId::MARK {
+                let marked = Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::LightYellow)
+                    .add_modifier(Modifier::BOLD);
+                spans.iter_mut().for_each(|span| span.style = marked);
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    let para = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    para.render(area, buf);
+
+    if let Some(at) = app.scrub {
+        timeline::draw(
+            buf,
+            timeline::rect(area),
+            (app.history_start(), app.uptime(), at),
+            &app.markers.iter().map(|m| m.1).collect::<Vec<_>>(),
+            |d| app.timebase.local_at(d).format("%H:%M:%S").to_string(),
+            app.caps.glyphs == GlyphSet::Ascii,
+        );
+    }
+}
+
+fn draw_hosts(buf: &mut Buffer, area: Rect, app: &AppState, view: HostView) {
+    let states = app.links.states();
+    let Some(host) = app.fleet.hosts.get(view.focus) else {
+        return;
+    };
+    if !view.detail {
+        hosts::draw_grid(
+            buf,
+            area,
+            &app.fleet,
+            &states,
+            &|name, width| {
+                app.availability
+                    .line(name, app.uptime(), width, app.bar_glyphs())
+            },
+            view,
+            app.bar_glyphs(),
+        );
+        return;
+    }
+    let state = states.iter().find(|(n, _)| *n == host.name).map(|s| s.1);
+    // only as many of the host's lines as could possibly be on screen
+    let logs: Vec<Line> = app
+        .logs
+        .iter()
+        .rev()
+        .filter(|entry| app.logs.source_name(entry.source) == &*host.name)
+        .take(area.height as usize)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .map(|entry| {
+            let style = match entry.level {
+                Level::Error => Style::default().fg(Color::Red),
+                Level::Warn => Style::default().fg(Color::Yellow),
+                Level::Info | Level::Debug => Style::default(),
+            };
+            let stamp = app.timebase.local_at(entry.at).format("%H:%M:%S ");
+            Line::from(vec![
+                Span::styled(stamp.to_string(), Style::default().fg(Color::DarkGray)),
+                Span::styled(entry.message.to_string(), style),
+            ])
+        })
+        .collect();
+    hosts::draw_detail(
+        buf,
+        area,
+        host,
+        state,
+        app.availability.line(
+            &host.name,
+            app.uptime(),
+            area.width.saturating_sub(2) as usize,
+            app.bar_glyphs(),
+        ),
+        app.bar_glyphs(),
+        logs,
+    );
+}
+
+// Returns where the real cursor goes while the command bar is active
+fn draw_command(buf: &mut Buffer, area: Rect, app: &AppState) -> Option<(u16, u16)> {
+    let mut title = vec![Span::styled(
+        "command",
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    )];
+    if app.read_only {
+        title.push(Span::styled(
+            " · read-only ",
+            Style::default().fg(Color::LightRed),
+        ));
+    }
+    // mouse released for native selection: say so, or clicks look broken
+    if !app.mouse_capture {
+        title.push(Span::styled(
+            " · mouse off (m) ",
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    let block = app
+        .chrome
+        .command
+        .block()
+        .border_style(focus::border(app.focused(Focus::Command)))
+        .title(Line::from(title));
+    let inner = block.inner(area);
+
+    // the input scrolls sideways inside the bar, after the `:> ` prompt
+    let room = inner.width.saturating_sub(3) as usize;
+    let input = app.cmd_input.view(room);
+    let line: Line = if app.cmd_active {
+        // Active command mode: show prompt + current input, and the hint while
+        // there's room for it
+        let hint =
+            "  (help / ? / mode / set mode ai|robotics|cloud|forensics|sandbox • Esc to cancel)";
+        let mut spans = vec![Span::styled(
+            format!(":> {}", input.text),
+            Style::default().fg(Color::White),
+        )];
+        if input.text.width() + 1 + hint.width() <= room {
+            spans.push(Span::styled(hint, Style::default().fg(Color::DarkGray)));
+        }
+        Line::from(spans)
+    } else {
+        // Idle: show a subtle hint (the focused panel's keys, if any), keep bar visible
+        let hint = match app.focus {
+            Some(panel) => panel.hint(),
+            None => {
+                "press : for command mode • 1–5 to switch modes • Tab to focus a panel • q to quit"
+            }
+        };
+        Line::from(vec![Span::styled(
+            hint,
+            Style::default().fg(Color::DarkGray),
+        )])
+    };
+
+    let para = Paragraph::new(line).block(block).wrap(Wrap { trim: true });
+
+    // render on full area so text is visible
+    para.render(area, buf);
+
+    // real terminal cursor, placed by display width so wide glyphs line up
+    if !app.cmd_active {
+        return None;
+    }
+    let width = u16::try_from(input.cursor).unwrap_or(u16::MAX);
+    let x = (inner.x + 3).saturating_add(width);
+    (x < inner.right()).then_some((x, inner.y))
+}
+
+// `32s`, `5m`, `2h`: how long ago, in the unit that matters
+fn ago(d: Duration) -> String {
+    match d.as_secs() {
+        s if s < 60 => format!("{s}s"),
+        s if s < 60 * 60 => format!("{}m", s / 60),
+        s => format!("{}h", s / 3600),
+    }
+}
+
+// Centered rect of at most width x height inside area
+pub(crate) fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let w = width.min(area.width);
+    let h = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - w) / 2,
+        area.y + (area.height - h) / 2,
+        w,
+        h,
+    )
+}
+
+fn draw_diagnostics(buf: &mut Buffer, area: Rect, app: &AppState, prof: &FrameProfiler) {
+    let kib = |b: usize| format!("{:.1} KiB", b as f64 / 1024.0);
+    let budget = if app.logs.budget() > 0 {
+        kib(app.logs.budget())
+    } else {
+        "unbounded".to_string()
+    };
+    let dropped: u64 = app.dropped.iter().map(|(_, n)| n).sum();
+
+    let mut lines = vec![
+        Line::from(format!(
+            "log lines   {} / {}",
+            app.logs.len(),
+            app.logs.capacity()
+        )),
+        Line::from(format!(
+            "log memory  ~{} / {}",
+            kib(app.logs.bytes()),
+            budget
+        )),
+        Line::from(format!("  index     ~{}", kib(app.logs.index_bytes()))),
+        Line::from(format!("dropped     {dropped} lines")),
+        Line::from(format!("rendering   {}", app.watchdog.level().label())),
+    ];
+
+    // live per-section breakdown while --profile-frames is recording
+    if prof.enabled() {
+        lines.push(Line::from(""));
+        for (section, avg, max) in prof.summary() {
+            lines.push(Line::from(format!(
+                "{section:<12}{avg:>7} µs avg{max:>8} µs max"
+            )));
+        }
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray))
+        .title(Span::styled(
+            "diagnostics",
+            Style::default()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+
+    let rect = centered(area, 48, u16::try_from(lines.len() + 2).unwrap_or(u16::MAX));
+    Clear.render(rect, buf);
+    Paragraph::new(lines).block(block).render(rect, buf);
+}
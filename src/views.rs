@@ -0,0 +1,109 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::Mode,
+    config::{value_enum, value_name, ConfigError},
+};
+
+// An investigation as `view save` found it, for `view load` to put back:
+// the mode, the log filters, the picked metric row, the `compare` chart,
+// the moment time travel was at, and how far an undersized terminal was
+// panned. Anything missing is off when loaded, save the mode, which stays
+// as it is. The log pane isn't kept where it was: a picked line is counted
+// back from the newest, so it would be another line by the time it loads.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct View {
+    #[serde(deserialize_with = "value_enum", serialize_with = "value_name")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<Mode>,
+    // `grep`'s text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grep: Option<String>,
+    // the one source the log pane shows, by name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    // the metric row picked, as `pin` names it, and whether it's expanded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metric: Option<String>,
+    #[serde(skip_serializing_if = "is_false")]
+    pub expanded: bool,
+    // `compare`'s arguments, drawn again from the time of loading
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compare: Option<String>,
+    // the moment time travel showed, RFC 3339
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub at: Option<String>,
+    // columns and rows the dashboard was panned by; clamped to the terminal
+    // it's loaded in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pan: Option<(u16, u16)>,
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
+}
+
+impl View {
+    // `Cloud • grep "timeout" • only kafka • cloud:latency p95 expanded`
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(mode) = self.mode {
+            parts.push(mode.name().to_string());
+        }
+        if let Some(grep) = &self.grep {
+            parts.push(format!("grep \"{grep}\""));
+        }
+        if let Some(source) = &self.source {
+            parts.push(format!("only {source}"));
+        }
+        if let Some(metric) = &self.metric {
+            let expanded = if self.expanded { " expanded" } else { "" };
+            parts.push(format!("{metric}{expanded}"));
+        }
+        if let Some(args) = &self.compare {
+            parts.push(format!("compare {args}"));
+        }
+        if let Some(at) = &self.at {
+            parts.push(format!("at {at}"));
+        }
+        if let Some((x, y)) = self.pan {
+            parts.push(format!("panned {x},{y}"));
+        }
+        parts.join(" • ")
+    }
+}
+
+// Saved views by name, kept in the state directory across sessions
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Views(pub BTreeMap<String, View>);
+
+impl Views {
+    // A missing file is no views
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(ConfigError::Io(path.to_path_buf(), e)),
+        };
+        toml::from_str(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    // A file that's there but doesn't load is left alone, so the views in it
+    // aren't lost to the ones this session has
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if Self::load(path).is_err() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "it doesn't load; fix it or move it aside first",
+            ));
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self).map_err(io::Error::other)?)
+    }
+}
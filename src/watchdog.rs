@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+// Consecutive slow frames before shedding another level of work
+const SLOW_FRAMES: u32 = 10;
+// Consecutive fast frames (under half the budget) before restoring one
+const FAST_FRAMES: u32 = 150;
+// Log lines drawn at most once degraded to `Degrade::Minimal`
+pub const DEGRADED_LOG_LINES: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Degrade {
+    Full,
+    // whole-cell bars, no log timestamps
+    Reduced,
+    // plus a short log tail
+    Minimal,
+}
+
+impl Degrade {
+    pub fn label(self) -> &'static str {
+        match self {
+            Degrade::Full => "full",
+            Degrade::Reduced => "reduced (coarse bars, no stamps)",
+            Degrade::Minimal => "minimal (+ short log tail)",
+        }
+    }
+}
+
+// Watches draw times and sheds rendering work when frames keep blowing the
+// budget (slow SSH links, huge terminals), then gives it back once frames are
+// comfortably fast again. Hysteresis keeps it from flapping on one slow frame.
+pub struct FrameWatchdog {
+    budget: Option<Duration>,
+    level: Degrade,
+    slow: u32,
+    fast: u32,
+}
+
+impl FrameWatchdog {
+    // A zero budget disables the watchdog
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget: (!budget.is_zero()).then_some(budget),
+            level: Degrade::Full,
+            slow: 0,
+            fast: 0,
+        }
+    }
+
+    pub fn level(&self) -> Degrade {
+        self.level
+    }
+
+    pub fn budget(&self) -> Option<Duration> {
+        self.budget
+    }
+
+    // Feeds one frame time; returns the new level when it changes
+    pub fn observe(&mut self, frame: Duration) -> Option<Degrade> {
+        let budget = self.budget?;
+        if frame > budget {
+            self.fast = 0;
+            self.slow += 1;
+            if self.slow >= SLOW_FRAMES && self.level < Degrade::Minimal {
+                self.slow = 0;
+                self.level = match self.level {
+                    Degrade::Full => Degrade::Reduced,
+                    _ => Degrade::Minimal,
+                };
+                return Some(self.level);
+            }
+        } else {
+            self.slow = 0;
+            if frame < budget / 2 {
+                self.fast += 1;
+            }
+            if self.fast >= FAST_FRAMES && self.level > Degrade::Full {
+                self.fast = 0;
+                self.level = match self.level {
+                    Degrade::Minimal => Degrade::Reduced,
+                    _ => Degrade::Full,
+                };
+                return Some(self.level);
+            }
+        }
+        None
+    }
+}
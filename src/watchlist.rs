@@ -0,0 +1,237 @@
+use std::{fs, io, path::Path};
+
+use ratatui::{prelude::*, widgets::Paragraph};
+use serde::{Deserialize, Serialize};
+
+use crate::{alerts::Hushed, chrome::Chrome, config::ConfigError, layout::fit};
+
+// Where an item stands against its thresholds; declared best to worst so
+// sorting descending puts trouble on top
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    // no reading (a host that hasn't reported it yet)
+    Unknown,
+    Ok,
+    Warn,
+    Crit,
+}
+
+// One watched metric. `metric` is a pin spec (`cloud:latency p95`,
+// `web1:queue depth`); thresholds are in the metric's own units, and when
+// `crit` is below `warn` lower values are the bad ones.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Item {
+    pub metric: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warn: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crit: Option<f64>,
+}
+
+impl Item {
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.metric)
+    }
+
+    pub fn status(&self, value: Option<f64>) -> Status {
+        let Some(value) = value else {
+            return Status::Unknown;
+        };
+        let lower_is_bad = matches!((self.warn, self.crit), (Some(w), Some(c)) if c < w);
+        let past = |limit: Option<f64>| {
+            limit.is_some_and(|l| if lower_is_bad { value <= l } else { value >= l })
+        };
+        if past(self.crit) {
+            Status::Crit
+        } else if past(self.warn) {
+            Status::Warn
+        } else {
+            Status::Ok
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Order {
+    // crit, then warn, then the rest; ties keep the order they were added in
+    #[default]
+    Worst,
+    Added,
+}
+
+// The triage list, kept in the state directory across sessions
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Watchlist {
+    pub sort: Order,
+    #[serde(rename = "item")]
+    pub items: Vec<Item>,
+}
+
+impl Watchlist {
+    // A missing file is an empty list
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(ConfigError::Io(path.to_path_buf(), e)),
+        };
+        toml::from_str(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    // Like views, a file that's there but doesn't load is left alone
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if Self::load(path).is_err() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "it doesn't load; fix it or move it aside first",
+            ));
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self).map_err(io::Error::other)?)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // Adds `item`, or updates the one watching the same metric
+    pub fn upsert(&mut self, item: Item) -> bool {
+        match self.items.iter_mut().find(|i| i.metric == item.metric) {
+            Some(existing) => {
+                *existing = item;
+                false
+            }
+            None => {
+                self.items.push(item);
+                true
+            }
+        }
+    }
+
+    // By metric spec or display name; how many went
+    pub fn remove(&mut self, what: &str) -> usize {
+        let before = self.items.len();
+        self.items
+            .retain(|i| i.metric != what && i.name.as_deref() != Some(what));
+        before - self.items.len()
+    }
+}
+
+// `watch METRIC [as NAME] [warn N] [crit N]`: the metric runs up to the first
+// keyword. Percentages may be written as `80%`.
+pub fn parse(args: &str) -> Result<Item, String> {
+    let mut metric = Vec::new();
+    let mut name = Vec::new();
+    let (mut warn, mut crit) = (None, None);
+    let mut words = args.split_whitespace();
+    let mut field = "metric";
+    while let Some(word) = words.next() {
+        match word {
+            "as" => field = "name",
+            "warn" | "crit" => {
+                let raw = words
+                    .next()
+                    .ok_or_else(|| format!("`{word}` needs a value"))?;
+                let value = threshold(raw)?;
+                if word == "warn" {
+                    warn = Some(value);
+                } else {
+                    crit = Some(value);
+                }
+                field = "";
+            }
+            _ if field == "metric" => metric.push(word),
+            _ if field == "name" => name.push(word),
+            _ => return Err(format!("unexpected `{word}`")),
+        }
+    }
+    if metric.is_empty() {
+        return Err("usage: watch METRIC [as NAME] [warn N] [crit N]".to_string());
+    }
+    Ok(Item {
+        metric: metric.join(" "),
+        name: (!name.is_empty()).then(|| name.join(" ")),
+        warn,
+        crit,
+    })
+}
+
+fn threshold(raw: &str) -> Result<f64, String> {
+    let (number, scale) = match raw.strip_suffix('%') {
+        Some(n) => (n, 0.01),
+        None => (raw, 1.0),
+    };
+    number
+        .parse::<f64>()
+        .map(|n| n * scale)
+        .map_err(|_| format!("bad threshold `{raw}`"))
+}
+
+// (name, formatted value, status, hushed) per item, already in display
+// order; an acknowledged alert is marked with a tick and a silenced or held
+// one with a tilde, in place of its status mark
+pub fn draw(
+    buf: &mut Buffer,
+    area: Rect,
+    rows: &[(String, String, Status, Option<Hushed>)],
+    ascii: bool,
+    chrome: Chrome,
+) {
+    let inner = area.width.saturating_sub(2) as usize;
+    let value_w = 9.min(inner);
+    let name_w = inner.saturating_sub(value_w + 3);
+    let lines: Vec<Line> = rows
+        .iter()
+        .map(|(name, value, status, hushed)| {
+            let (mark, color) = match (status, ascii) {
+                (Status::Crit, false) => ("●", Color::Red),
+                (Status::Crit, true) => ("X", Color::Red),
+                (Status::Warn, false) => ("▲", Color::Yellow),
+                (Status::Warn, true) => ("!", Color::Yellow),
+                (Status::Ok, false) => ("·", Color::Green),
+                (Status::Ok, true) => (".", Color::Green),
+                (Status::Unknown, _) => ("?", Color::DarkGray),
+            };
+            let mark = match (hushed, ascii) {
+                (Some(Hushed::Acked), false) => "✓",
+                (Some(Hushed::Acked), true) => "v",
+                (Some(Hushed::Silenced | Hushed::Maintenance), _) => "~",
+                (None, _) => mark,
+            };
+            let (name, name_pad) = fit(name, name_w);
+            let (value, value_pad) = fit(value, value_w);
+            Line::from(vec![
+                Span::styled(mark, Style::default().fg(color)),
+                Span::raw(" "),
+                Span::styled(name.to_string(), Style::default().fg(Color::Gray)),
+                Span::raw(name_pad),
+                Span::raw(" "),
+                Span::raw(value_pad),
+                Span::styled(
+                    value.to_string(),
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                ),
+            ])
+        })
+        .collect();
+    Paragraph::new(lines)
+        .block(
+            chrome
+                .block()
+                .border_style(Style::default().fg(Color::DarkGray))
+                .title(Span::styled(
+                    "watchlist",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )),
+        )
+        .render(area, buf);
+}
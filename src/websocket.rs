@@ -0,0 +1,419 @@
+use std::{
+    collections::HashSet,
+    io::{self, BufRead, BufReader, Write},
+    sync::mpsc::{self, Receiver, Sender},
+    time::Duration,
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value as Json;
+
+use crate::{
+    auth::{self, base64, AuthSpec},
+    datasource::{DataSource, Sample, METRICS},
+    hosts::parse_metric,
+    ingest::{IngestQueue, SourceSender},
+    logs::Level,
+    net::{authority, Remote, Stream},
+    parse::parse_line,
+    push::row,
+    reconnect::{Connector, Links},
+};
+
+// What the link and the streamed log lines are shown under
+pub const NAME: &str = "websocket";
+const QUEUE: usize = 1024;
+// Connecting, and the handshake
+const TIMEOUT: Duration = Duration::from_secs(10);
+// Quiet this long and the server is pinged; quiet as long again after that
+// and the connection is given up on
+const PING: Duration = Duration::from_secs(30);
+// Largest message read, across its fragments
+const MAX_MESSAGE: usize = 16 << 20;
+
+// `[websocket]` in config.toml: a gateway streaming JSON events, and the
+// messages that subscribe to them. Checked as it's read, like `[prometheus]`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct WebSocketSpec {
+    #[serde(deserialize_with = "url")]
+    pub url: String,
+    // sent once connected, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subscribe: Vec<Message>,
+    // where each message keeps its events, as a JSON pointer like `/data`;
+    // messages without it (acks, heartbeats) are skipped
+    #[serde(default, deserialize_with = "pointer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events: Option<String>,
+    // `[websocket.auth]`: for a gateway that wants credentials
+    #[serde(default, deserialize_with = "auth::http")]
+    #[serde(skip_serializing_if = "AuthSpec::is_empty")]
+    pub auth: AuthSpec,
+}
+
+// A subscribe message: a string goes as written, a table as JSON
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Message {
+    Text(String),
+    Json(Json),
+}
+
+impl Message {
+    fn text(&self) -> String {
+        match self {
+            Message::Text(text) => text.clone(),
+            Message::Json(json) => json.to_string(),
+        }
+    }
+}
+
+fn url<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    let url = String::deserialize(d)?;
+    Endpoint::parse(&url, &AuthSpec::default()).map_err(de::Error::custom)?;
+    Ok(url)
+}
+
+fn pointer<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    let pointer = String::deserialize(d)?;
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return Err(de::Error::custom(format!(
+            "`{pointer}` isn't a JSON pointer; write it like `/data` or `/payload/events`"
+        )));
+    }
+    Ok(Some(pointer))
+}
+
+// The panel feed: metric events since the last tick. Everything else is a
+// log line, sent straight to the logs.
+pub struct WebSocket {
+    url: String,
+    rx: Receiver<Vec<Sample>>,
+}
+
+impl WebSocket {
+    // Checks the spec, then connects on its own reconnecting link
+    pub fn start(
+        spec: &WebSocketSpec,
+        links: &Links,
+        ingest: &IngestQueue,
+    ) -> Result<Self, String> {
+        let endpoint = Endpoint::parse(&spec.url, &spec.auth)?;
+        let (samples, rx) = mpsc::channel();
+        let client = Client {
+            endpoint,
+            subscribe: spec.subscribe.iter().map(Message::text).collect(),
+            events: spec.events.clone(),
+            samples,
+            mentioned: HashSet::new(),
+        };
+        links.spawn(NAME, client, ingest.sender(NAME, QUEUE));
+        Ok(Self {
+            url: spec.url.clone(),
+            rx,
+        })
+    }
+}
+
+impl DataSource for WebSocket {
+    fn poll(&mut self) -> Vec<Sample> {
+        self.rx.try_iter().flatten().collect()
+    }
+
+    fn describe(&self) -> String {
+        format!("WebSocket {}", self.url)
+    }
+}
+
+struct Client {
+    endpoint: Endpoint,
+    subscribe: Vec<String>,
+    events: Option<String>,
+    samples: Sender<Vec<Sample>>,
+    // metric names that fill no row, and malformed samples, mentioned once
+    mentioned: HashSet<String>,
+}
+
+impl Connector for Client {
+    type Conn = BufReader<Stream>;
+
+    fn connect(&mut self) -> io::Result<Self::Conn> {
+        self.endpoint.handshake()
+    }
+
+    fn run(&mut self, mut conn: Self::Conn, tx: &SourceSender) -> io::Result<()> {
+        for message in &self.subscribe {
+            frame::write(conn.get_mut(), frame::TEXT, message.as_bytes())?;
+        }
+        messages(&mut conn, |text| self.message(text, tx))
+    }
+}
+
+// Reads whole text messages into `each` until the server closes the
+// connection, answering pings and pinging a quiet server
+pub(crate) fn messages(conn: &mut BufReader<Stream>, mut each: impl FnMut(&str)) -> io::Result<()> {
+    conn.get_ref().set_read_timeout(Some(PING))?;
+    let (mut message, mut pinged) = (Vec::new(), false);
+    loop {
+        let (fin, opcode, payload) = match frame::read(conn) {
+            Ok(frame) => frame,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                if pinged {
+                    return Err(io::Error::other(format!(
+                        "no answer to a ping in {} s",
+                        PING.as_secs()
+                    )));
+                }
+                frame::write(conn.get_mut(), frame::PING, b"")?;
+                pinged = true;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        pinged = false;
+        match opcode {
+            frame::CONTINUATION | frame::TEXT | frame::BINARY => {
+                message.extend_from_slice(&payload);
+                if message.len() > MAX_MESSAGE {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("message over {MAX_MESSAGE} bytes"),
+                    ));
+                }
+                if fin {
+                    let text = String::from_utf8_lossy(&message).into_owned();
+                    message.clear();
+                    each(&text);
+                }
+            }
+            frame::CLOSE => {
+                // the status code comes back, as the close handshake asks
+                let _ = frame::write(
+                    conn.get_mut(),
+                    frame::CLOSE,
+                    payload.get(..2).unwrap_or(&[]),
+                );
+                let code = payload.get(..2).map(|c| u16::from_be_bytes([c[0], c[1]]));
+                let reason = String::from_utf8_lossy(payload.get(2..).unwrap_or(&[]));
+                return match code {
+                    None | Some(1000) => Ok(()),
+                    Some(code) => Err(io::Error::other(format!(
+                        "server closed the connection: {code} {}",
+                        reason.trim()
+                    ))),
+                };
+            }
+            frame::PING => frame::write(conn.get_mut(), frame::PONG, &payload)?,
+            frame::PONG => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown frame opcode {opcode}"),
+                ))
+            }
+        }
+    }
+}
+
+impl Client {
+    // A message's events: metric samples for the panel, anything else for
+    // the logs. A message that isn't JSON is taken as log lines.
+    fn message(&mut self, text: &str, tx: &SourceSender) {
+        let Ok(json) = serde_json::from_str::<Json>(text) else {
+            let lines = text.lines().filter_map(parse_line);
+            tx.send_batch(lines.map(|p| (p.level, p.message)).collect());
+            return;
+        };
+        let events = match &self.events {
+            Some(pointer) => match json.pointer(pointer) {
+                Some(events) => events,
+                None => return,
+            },
+            None => &json,
+        };
+        let events = match events {
+            Json::Array(events) => events.iter().collect(),
+            event => vec![event],
+        };
+        let (mut lines, mut samples) = (Vec::new(), Vec::new());
+        for event in events {
+            if event.get("metric").is_none() {
+                let line = match event {
+                    Json::String(line) => line.clone(),
+                    event => event.to_string(),
+                };
+                lines.extend(parse_line(&line).map(|p| (p.level, p.message)));
+                continue;
+            }
+            let sample = parse_metric(&event.to_string());
+            match sample {
+                Some((name, value)) => match row(&name, &value) {
+                    Some(metric) => samples.push(Sample { metric, value }),
+                    None if self.mentioned.insert(name.clone()) => lines.push((
+                        Level::Warn,
+                        format!("`{name}` fills no row; rows are {}", METRICS.join(", ")),
+                    )),
+                    None => {}
+                },
+                None if self.mentioned.insert(String::new()) => lines.push((
+                    Level::Warn,
+                    format!(
+                        "skipping malformed metric events like {event}; expected \
+                         {{\"metric\": NAME, \"value\": N}} or \
+                         {{\"metric\": NAME, \"buckets\": [[LE, COUNT], ...]}}"
+                    ),
+                )),
+                None => {}
+            }
+        }
+        if !lines.is_empty() {
+            tx.send_batch(lines);
+        }
+        if !samples.is_empty() {
+            // the app is gone if this fails; the link goes with it
+            let _ = self.samples.send(samples);
+        }
+    }
+}
+
+// Where to connect, and the headers that go with the handshake
+pub(crate) struct Endpoint {
+    remote: Remote,
+    path: String,
+    headers: String,
+}
+
+impl Endpoint {
+    // wss only in builds with TLS
+    pub(crate) fn parse(url: &str, auth: &AuthSpec) -> Result<Self, String> {
+        let (secure, port) = match url {
+            u if u.starts_with("ws://") => (false, 80),
+            u if u.starts_with("wss://") => (true, 443),
+            u => return Err(format!("expected a ws:// or wss:// URL, got `{u}`")),
+        };
+        let (authority, path) = authority(url, port)?;
+        Ok(Self {
+            remote: Remote::new(authority, secure, auth)?,
+            path: match path {
+                "" => "/".to_string(),
+                path => path.to_string(),
+            },
+            headers: auth.header_lines(),
+        })
+    }
+
+    // The opening handshake; the connection, buffered for reading and
+    // written through `get_mut`, once the server has switched protocols. Its accept hash isn't checked: that only
+    // guards against servers that don't speak WebSocket, and those don't
+    // answer 101 either.
+    pub(crate) fn handshake(&self) -> io::Result<BufReader<Stream>> {
+        let mut stream = self.remote.dial(TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\nUser-Agent: ai-intui/{}\r\n{}\r\n",
+            self.path,
+            self.remote.authority.written,
+            base64(&rand::random::<[u8; 16]>()),
+            env!("CARGO_PKG_VERSION"),
+            self.headers
+        )?;
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status)?;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+                break;
+            }
+        }
+        if status.split_whitespace().nth(1) != Some("101") {
+            return Err(io::Error::other(match status.trim() {
+                "" => "the server hung up during the handshake".to_string(),
+                status => format!("handshake answered `{status}`"),
+            }));
+        }
+        Ok(reader)
+    }
+}
+
+// Just enough of RFC 6455's framing for a client: masked frames out,
+// unmasked frames in
+pub(crate) mod frame {
+    use std::io::{self, Read, Write};
+
+    use super::MAX_MESSAGE;
+
+    pub const CONTINUATION: u8 = 0x0;
+    pub const TEXT: u8 = 0x1;
+    pub const BINARY: u8 = 0x2;
+    pub const CLOSE: u8 = 0x8;
+    pub const PING: u8 = 0x9;
+    pub const PONG: u8 = 0xa;
+
+    // One whole, unfragmented frame
+    pub fn write(stream: &mut impl Write, opcode: u8, payload: &[u8]) -> io::Result<()> {
+        let mut frame = vec![0x80 | opcode];
+        match payload.len() {
+            n if n < 126 => frame.push(0x80 | n as u8),
+            n if n <= 0xffff => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+            n => {
+                frame.push(0x80 | 127);
+                frame.extend_from_slice(&(n as u64).to_be_bytes());
+            }
+        }
+        let mask: [u8; 4] = rand::random();
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().zip(mask.iter().cycle()).map(|(b, m)| b ^ m));
+        stream.write_all(&frame)
+    }
+
+    // (last fragment, opcode, payload)
+    pub fn read(stream: &mut impl Read) -> io::Result<(bool, u8, Vec<u8>)> {
+        let mut head = [0; 2];
+        stream.read_exact(&mut head)?;
+        let len = match head[1] & 0x7f {
+            126 => {
+                let mut len = [0; 2];
+                stream.read_exact(&mut len)?;
+                u64::from(u16::from_be_bytes(len))
+            }
+            127 => {
+                let mut len = [0; 8];
+                stream.read_exact(&mut len)?;
+                u64::from_be_bytes(len)
+            }
+            len => u64::from(len),
+        };
+        if len > MAX_MESSAGE as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("message over {MAX_MESSAGE} bytes"),
+            ));
+        }
+        // servers shouldn't mask, but a masked frame is still readable
+        let mut mask = [0; 4];
+        let masked = head[1] & 0x80 != 0;
+        if masked {
+            stream.read_exact(&mut mask)?;
+        }
+        let mut payload = vec![0; len as usize];
+        stream.read_exact(&mut payload)?;
+        if masked {
+            for (b, m) in payload.iter_mut().zip(mask.iter().cycle()) {
+                *b ^= m;
+            }
+        }
+        Ok((head[0] & 0x80 != 0, head[0] & 0x0f, payload))
+    }
+}
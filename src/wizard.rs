@@ -0,0 +1,234 @@
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::{
+    app::Mode,
+    chrome::{BorderStyle, Chrome},
+    input::LineInput,
+    profile::Profile,
+    ui::centered,
+};
+
+// the panel frames stand in for a theme; the highlighted one frames the box
+const LOOK_CHOICES: [(BorderStyle, &str); 5] = [
+    (BorderStyle::Plain, "plain frames"),
+    (BorderStyle::Rounded, "rounded frames"),
+    (BorderStyle::Double, "double frames"),
+    (BorderStyle::Thick, "thick frames"),
+    (BorderStyle::Hidden, "no frames (minimal)"),
+];
+
+// whether the system panel reads this machine or --simulate's made-up numbers
+const SYSTEM_CHOICES: [(bool, &str); 2] = [
+    (false, "this machine's CPU, memory, disk, and network"),
+    (true, "simulated numbers (--simulate)"),
+];
+
+const DATA_CHOICES: [(Profile, &str); 2] = [
+    (Profile::Demo, "synthetic demo data"),
+    (Profile::Ops, "quiet: no synthetic logs (ops)"),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Mode,
+    Look,
+    System,
+    Data,
+    DataDir,
+}
+
+// What the user picked; written out as the initial settings file
+pub struct Choices {
+    pub mode: Mode,
+    pub borders: BorderStyle,
+    pub simulate: bool,
+    pub profile: Profile,
+    pub data_dir: PathBuf,
+}
+
+pub enum Outcome {
+    Continue,
+    Done(Choices),
+    // Esc: keep the defaults
+    Skip,
+}
+
+// First-run setup shown over the dashboard when no settings file exists yet,
+// so nobody mistakes the synthetic demo for their own systems
+pub struct Wizard {
+    step: Step,
+    mode: usize,
+    look: usize,
+    system: usize,
+    data: usize,
+    data_dir: LineInput,
+}
+
+impl Wizard {
+    // starts on whatever the command line already asked for
+    pub fn new(data_dir: &str, simulate: bool) -> Self {
+        let mut input = LineInput::default();
+        input.insert_str(data_dir);
+        Self {
+            step: Step::Mode,
+            mode: 0,
+            look: 0,
+            system: usize::from(simulate),
+            data: 0,
+            data_dir: input,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        let modes = Mode::value_variants().len();
+        let looks = LOOK_CHOICES.len();
+        match (self.step, key.code) {
+            (_, KeyCode::Esc) => return Outcome::Skip,
+            (Step::Mode, KeyCode::Up) => self.mode = (self.mode + modes - 1) % modes,
+            (Step::Mode, KeyCode::Down) => self.mode = (self.mode + 1) % modes,
+            (Step::Mode, KeyCode::Enter) => self.step = Step::Look,
+            (Step::Look, KeyCode::Up) => self.look = (self.look + looks - 1) % looks,
+            (Step::Look, KeyCode::Down) => self.look = (self.look + 1) % looks,
+            (Step::Look, KeyCode::Enter) => self.step = Step::System,
+            (Step::System, KeyCode::Up | KeyCode::Down) => self.system = 1 - self.system,
+            (Step::System, KeyCode::Enter) => self.step = Step::Data,
+            (Step::Data, KeyCode::Up | KeyCode::Down) => self.data = 1 - self.data,
+            (Step::Data, KeyCode::Enter) => self.step = Step::DataDir,
+            (Step::DataDir, KeyCode::Enter) => {
+                return Outcome::Done(Choices {
+                    mode: Mode::value_variants()[self.mode],
+                    borders: LOOK_CHOICES[self.look].0,
+                    simulate: SYSTEM_CHOICES[self.system].0,
+                    profile: DATA_CHOICES[self.data].0,
+                    data_dir: PathBuf::from(self.data_dir.as_str().trim()),
+                })
+            }
+            (Step::DataDir, KeyCode::Backspace) => self.data_dir.backspace(),
+            (Step::DataDir, KeyCode::Delete) => self.data_dir.delete(),
+            (Step::DataDir, KeyCode::Left) => self.data_dir.left(),
+            (Step::DataDir, KeyCode::Right) => self.data_dir.right(),
+            (Step::DataDir, KeyCode::Home) => self.data_dir.home(),
+            (Step::DataDir, KeyCode::End) => self.data_dir.end(),
+            (Step::DataDir, KeyCode::Char(c)) => self.data_dir.insert_char(c),
+            _ => {}
+        }
+        Outcome::Continue
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        if self.step == Step::DataDir {
+            self.data_dir.insert_str(text);
+        }
+    }
+
+    // Returns the cursor position while the data directory is being edited
+    pub fn draw(&self, buf: &mut Buffer, area: Rect) -> Option<(u16, u16)> {
+        let dim = Style::default().fg(Color::DarkGray);
+        // the path scrolls inside the box, between its borders and a space each side
+        let path = self
+            .data_dir
+            .view(area.width.min(60).saturating_sub(4) as usize);
+        let picked = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let option = |selected: bool, label: &str| {
+            if selected {
+                Line::from(Span::styled(format!(" › {label}"), picked))
+            } else {
+                Line::from(format!("   {label}"))
+            }
+        };
+
+        let (title, mut lines) = match self.step {
+            Step::Mode => (
+                "1/5 start mode",
+                Mode::value_variants()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, m)| option(i == self.mode, m.name()))
+                    .collect::<Vec<_>>(),
+            ),
+            Step::Look => (
+                "2/5 look",
+                LOOK_CHOICES
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, label))| option(i == self.look, label))
+                    .collect(),
+            ),
+            Step::System => (
+                "3/5 system metrics",
+                SYSTEM_CHOICES
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, label))| option(i == self.system, label))
+                    .collect(),
+            ),
+            Step::Data => (
+                "4/5 logs",
+                DATA_CHOICES
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, label))| option(i == self.data, label))
+                    .collect(),
+            ),
+            Step::DataDir => (
+                "5/5 data directory (history, recordings)",
+                vec![Line::from(format!(" {}", path.text))],
+            ),
+        };
+        lines.insert(
+            0,
+            Line::from(Span::styled(
+                "No settings yet. Pick a few defaults:",
+                Style::default().fg(Color::White),
+            )),
+        );
+        lines.insert(1, Line::from(""));
+        lines.push(Line::from(""));
+        let keys = if self.step == Step::DataDir {
+            "Enter save • Esc skip"
+        } else {
+            "↑/↓ choose • Enter next • Esc skip"
+        };
+        lines.push(Line::from(Span::styled(keys, dim)));
+
+        // the look step frames itself in the highlighted style, keeping a
+        // frame for "none" so the box still reads as one
+        let block = match LOOK_CHOICES[self.look].0 {
+            borders if self.step == Step::Look && borders != BorderStyle::Hidden => Chrome {
+                borders,
+                ..Chrome::default()
+            }
+            .block(),
+            _ => Block::default().borders(Borders::ALL),
+        };
+        let block = block
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(Span::styled(
+                format!("setup • {title}"),
+                Style::default()
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        let rect = centered(area, 60, u16::try_from(lines.len() + 2).unwrap_or(u16::MAX));
+        Clear.render(rect, buf);
+        Paragraph::new(lines).block(block).render(rect, buf);
+
+        if self.step != Step::DataDir {
+            return None;
+        }
+        // below the border, intro line, and blank line; one space of indent
+        let width = u16::try_from(path.cursor).unwrap_or(u16::MAX);
+        let x = (rect.x + 2).saturating_add(width);
+        let y = rect.y + 3;
+        (x < rect.right().saturating_sub(1) && y < rect.bottom()).then_some((x, y))
+    }
+}
@@ -0,0 +1,77 @@
+// Replays tests/e2e/*.script through the hidden `drive` subcommand; run with
+// `cargo test --features e2e`
+#![cfg(feature = "e2e")]
+
+use std::{env, fs, path::PathBuf, process::Command};
+
+fn drive(name: &str) {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // scratch directories so the user's config, history, and watchlist stay out of it
+    let home = env::temp_dir().join(format!("ai-intui-e2e-{name}-{}", std::process::id()));
+    fs::create_dir_all(&home).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-intui"))
+        .arg("--config-dir")
+        .arg(home.join("config"))
+        .arg("--data-dir")
+        .arg(home.join("data"))
+        .arg("drive")
+        .arg(root.join("tests/e2e").join(format!("{name}.script")))
+        .output()
+        .unwrap();
+    let _ = fs::remove_dir_all(&home);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn modes() {
+    drive("modes");
+}
+
+#[test]
+fn commands() {
+    drive("commands");
+}
+
+#[test]
+fn rows() {
+    drive("rows");
+}
+
+#[test]
+fn logs() {
+    drive("logs");
+}
+
+#[test]
+fn marks() {
+    drive("marks");
+}
+
+#[test]
+fn payloads() {
+    drive("payloads");
+}
+
+#[test]
+fn deltas() {
+    drive("deltas");
+}
+
+#[test]
+fn input() {
+    drive("input");
+}
+
+#[test]
+fn badges() {
+    drive("badges");
+}
+
+#[test]
+fn dedup() {
+    drive("dedup");
+}